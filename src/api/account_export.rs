@@ -0,0 +1,133 @@
+use actix_web::{delete, get, http::header, post, rt, web, HttpResponse, Scope};
+use uuid::Uuid;
+
+use crate::{
+    dto::auth::ClaimsDto, error::service::ServiceResult, server::State,
+    service::account_export::AccountExportService,
+};
+
+#[utoipa::path(
+    path = "/export",
+    responses(
+        (status = 202, body = AccountExportReadDto),
+        (status = 409, body = ErrorDto)
+    )
+)]
+#[post("")]
+pub async fn create_account_export_handler(
+    state: web::Data<State>,
+    claims: ClaimsDto,
+) -> ServiceResult<HttpResponse> {
+    let schema = AccountExportService::create(&state.postgres, claims.sub).await?;
+
+    // Small accounts are usually ready by the time the client polls, the
+    // export job picks up whatever this misses
+    let (db, storage, expire) = (
+        state.postgres.clone(),
+        state.storage.clone(),
+        state.config.export.expire,
+    );
+    rt::spawn(async move {
+        if let Err(err) = AccountExportService::process_next(&db, &storage, expire).await {
+            log::error!("Building export failed: {}", err);
+        }
+    });
+
+    Ok(HttpResponse::Accepted().json(schema))
+}
+
+#[utoipa::path(
+    path = "/export",
+    responses(
+        (status = 200, body = [AccountExportReadDto])
+    )
+)]
+#[get("")]
+pub async fn get_account_export_handler(
+    state: web::Data<State>,
+    claims: ClaimsDto,
+) -> ServiceResult<HttpResponse> {
+    Ok(HttpResponse::Ok().json(AccountExportService::list(&state.postgres, claims.sub).await?))
+}
+
+#[utoipa::path(
+    path = "/export/{id}",
+    responses(
+        (status = 200, body = AccountExportReadDto),
+        (status = 403, body = ErrorDto),
+        (status = 404, body = ErrorDto)
+    )
+)]
+#[get("/{id}")]
+pub async fn get_account_export_by_id_handler(
+    state: web::Data<State>,
+    claims: ClaimsDto,
+    path: web::Path<Uuid>,
+) -> ServiceResult<HttpResponse> {
+    let id: Uuid = path.into_inner();
+
+    Ok(HttpResponse::Ok()
+        .json(AccountExportService::get_by_id(&state.postgres, claims.sub, id).await?))
+}
+
+#[utoipa::path(
+    path = "/export/download/{token}",
+    params(
+        ("token" = String, Path, description = "Download token of a ready export")
+    ),
+    responses(
+        (status = 200, body = Vec<u8>, content_type = "application/zip"),
+        (status = 401, body = ErrorDto)
+    ),
+    security()
+)]
+#[get("/download/{token}")]
+pub async fn download_account_export_handler(
+    state: web::Data<State>,
+    path: web::Path<String>,
+) -> ServiceResult<HttpResponse> {
+    let (body, size, created_at) =
+        AccountExportService::download(&state.postgres, &state.storage, path.into_inner()).await?;
+
+    Ok(HttpResponse::Ok()
+        .content_type("application/zip")
+        .insert_header((
+            header::CONTENT_DISPOSITION,
+            format!(
+                "attachment; filename=\"task-flow-export-{}.zip\"",
+                created_at.format("%Y%m%d")
+            ),
+        ))
+        .no_chunking(size)
+        .streaming(body))
+}
+
+#[utoipa::path(
+    path = "/export/{id}",
+    responses(
+        (status = 204),
+        (status = 403, body = ErrorDto),
+        (status = 404, body = ErrorDto)
+    )
+)]
+#[delete("/{id}")]
+pub async fn delete_account_export_handler(
+    state: web::Data<State>,
+    claims: ClaimsDto,
+    path: web::Path<Uuid>,
+) -> ServiceResult<HttpResponse> {
+    let id: Uuid = path.into_inner();
+
+    AccountExportService::delete(&state.postgres, claims.sub, id).await?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+pub fn get_scope() -> Scope {
+    web::scope("/export")
+        .service(create_account_export_handler)
+        .service(get_account_export_handler)
+        .service(download_account_export_handler)
+        .service(get_account_export_by_id_handler)
+        .service(delete_account_export_handler)
+}
@@ -0,0 +1,73 @@
+use actix_web::{delete, get, post, web, HttpResponse, Scope};
+use garde::Validate;
+use uuid::Uuid;
+
+use crate::{
+    dto::{app_password::AppPasswordCreateDto, auth::ClaimsDto},
+    error::service::ServiceResult,
+    server::State,
+    service::app_password::AppPasswordService,
+};
+
+#[utoipa::path(
+    path = "/app_password",
+    request_body = AppPasswordCreateDto,
+    responses(
+        (status = 201, body = AppPasswordReadDto),
+        (status = 409, body = ErrorDto),
+        (status = 422, body = [ValidateItemErrorDto])
+    )
+)]
+#[post("")]
+pub async fn create_app_password_handler(
+    state: web::Data<State>,
+    claims: ClaimsDto,
+    body: web::Json<AppPasswordCreateDto>,
+) -> ServiceResult<HttpResponse> {
+    body.validate()?;
+
+    Ok(HttpResponse::Created()
+        .json(AppPasswordService::create(&state.postgres, claims.sub, body.into_inner()).await?))
+}
+
+#[utoipa::path(
+    path = "/app_password",
+    responses(
+        (status = 200, body = [AppPasswordReadDto])
+    )
+)]
+#[get("")]
+pub async fn get_app_password_handler(
+    state: web::Data<State>,
+    claims: ClaimsDto,
+) -> ServiceResult<HttpResponse> {
+    Ok(HttpResponse::Ok().json(AppPasswordService::list(&state.postgres, claims.sub).await?))
+}
+
+#[utoipa::path(
+    path = "/app_password/{id}",
+    responses(
+        (status = 204),
+        (status = 403, body = ErrorDto),
+        (status = 404, body = ErrorDto)
+    )
+)]
+#[delete("/{id}")]
+pub async fn delete_app_password_handler(
+    state: web::Data<State>,
+    claims: ClaimsDto,
+    path: web::Path<Uuid>,
+) -> ServiceResult<HttpResponse> {
+    let id: Uuid = path.into_inner();
+
+    AppPasswordService::delete(&state.postgres, claims.sub, id).await?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+pub fn get_scope() -> Scope {
+    web::scope("/app_password")
+        .service(create_app_password_handler)
+        .service(get_app_password_handler)
+        .service(delete_app_password_handler)
+}
@@ -0,0 +1,196 @@
+use actix_web::{
+    http::{
+        header::{self, HeaderValue},
+        StatusCode,
+    },
+    web, FromRequest, HttpRequest, HttpResponse, Resource, ResponseError, Scope,
+};
+use base64::{prelude::BASE64_STANDARD, Engine};
+use uuid::Uuid;
+
+use crate::{
+    constants,
+    dto::{
+        auth::ClaimsDto,
+        caldav::{DavDepth, DavPropsDto, DavReportDto, DavResource},
+        precondition::{etag, IfMatchDto, IfNoneMatchDto},
+    },
+    error::service::{ServiceError, ServiceResult},
+    server::State,
+    service::{
+        app_password::AppPasswordService,
+        caldav::{CalDavPut, CalDavService},
+    },
+};
+
+const ALLOW: &str = "OPTIONS, GET, HEAD, PUT, DELETE, PROPFIND, REPORT";
+
+const XML_CONTENT_TYPE: &str = "application/xml; charset=utf-8";
+
+const CALENDAR_CONTENT_TYPE: &str = "text/calendar; charset=utf-8";
+
+/// Serves every method under the DAV root. CalDAV verbs are not routable
+/// by method, so they are dispatched here.
+pub async fn dav_handler(
+    req: HttpRequest,
+    state: web::Data<State>,
+    body: web::Bytes,
+) -> ServiceResult<HttpResponse> {
+    // Clients probe capabilities before they authenticate
+    if req.method().as_str() == "OPTIONS" {
+        return Ok(HttpResponse::Ok()
+            .insert_header(("DAV", "1, 3, calendar-access"))
+            .insert_header((header::ALLOW, ALLOW))
+            .finish());
+    }
+
+    match dispatch(&req, &state, &body).await {
+        Err(err @ ServiceError::InvalidCredentials(_)) => {
+            let mut response: HttpResponse = err.error_response();
+            response.headers_mut().insert(
+                header::WWW_AUTHENTICATE,
+                HeaderValue::from_static("Basic realm=\"task-flow\""),
+            );
+
+            Ok(response)
+        }
+        result => result,
+    }
+}
+
+/// Points clients doing service discovery at the DAV root.
+pub async fn well_known_caldav_handler() -> HttpResponse {
+    HttpResponse::MovedPermanently()
+        .insert_header((header::LOCATION, DavResource::Root.href()))
+        .finish()
+}
+
+async fn dispatch(req: &HttpRequest, state: &State, body: &[u8]) -> ServiceResult<HttpResponse> {
+    let user_id: Uuid = authenticate(req, state).await?;
+
+    let resource: DavResource = match req
+        .path()
+        .strip_prefix(constants::DAV_PATH)
+        .and_then(DavResource::from_path)
+    {
+        Some(value) => value,
+        None => return Ok(HttpResponse::NotFound().finish()),
+    };
+
+    let body: &str = std::str::from_utf8(body)
+        .map_err(|_| ServiceError::BadRequest("Body is not valid UTF-8".to_string()))?;
+
+    match (req.method().as_str(), resource) {
+        ("PROPFIND", resource) => {
+            let depth: DavDepth = DavDepth::from(
+                req.headers()
+                    .get("Depth")
+                    .and_then(|value| value.to_str().ok()),
+            );
+            let props: DavPropsDto = body.parse()?;
+
+            match CalDavService::propfind(&state.postgres, user_id, resource, depth, props).await? {
+                Some(value) => Ok(multistatus(value)),
+                None => Ok(HttpResponse::NotFound().finish()),
+            }
+        }
+        ("REPORT", DavResource::Calendar) => {
+            let report: DavReportDto = body.parse()?;
+
+            Ok(multistatus(
+                CalDavService::report(&state.postgres, user_id, report).await?,
+            ))
+        }
+        ("REPORT", _) => Err(ServiceError::DavPrecondition(
+            "D:supported-report".to_string(),
+        )),
+        ("GET" | "HEAD", DavResource::Object(name)) => {
+            match CalDavService::get(&state.postgres, user_id, &name).await? {
+                Some((content, version)) => Ok(HttpResponse::Ok()
+                    .content_type(CALENDAR_CONTENT_TYPE)
+                    .insert_header(etag(version))
+                    .body(content)),
+                None => Ok(HttpResponse::NotFound().finish()),
+            }
+        }
+        ("PUT", DavResource::Object(name)) => {
+            let if_match: IfMatchDto = IfMatchDto::extract(req).await?;
+            let if_none_match: IfNoneMatchDto = IfNoneMatchDto::extract(req).await?;
+
+            let result: CalDavPut = CalDavService::put(
+                &state.postgres,
+                user_id,
+                name,
+                body,
+                if_match,
+                if_none_match,
+                state.config.task.enforce_blockers,
+            )
+            .await?;
+
+            Ok(match result.created {
+                true => HttpResponse::Created(),
+                false => HttpResponse::NoContent(),
+            }
+            .insert_header(etag(result.version))
+            .finish())
+        }
+        ("DELETE", DavResource::Object(name)) => {
+            let if_match: IfMatchDto = IfMatchDto::extract(req).await?;
+
+            match CalDavService::delete(&state.postgres, user_id, &name, if_match).await? {
+                true => Ok(HttpResponse::NoContent().finish()),
+                false => Ok(HttpResponse::NotFound().finish()),
+            }
+        }
+        _ => Ok(HttpResponse::MethodNotAllowed()
+            .insert_header((header::ALLOW, ALLOW))
+            .finish()),
+    }
+}
+
+/// Accepts an app password over basic authentication, which is all most
+/// CalDAV clients support, or an access token.
+async fn authenticate(req: &HttpRequest, state: &State) -> ServiceResult<Uuid> {
+    let header: &str = req
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default();
+
+    match header.strip_prefix("Basic ") {
+        Some(value) => {
+            let credentials: String = BASE64_STANDARD
+                .decode(value.trim())
+                .ok()
+                .and_then(|value| String::from_utf8(value).ok())
+                .ok_or(ServiceError::InvalidCredentials(
+                    "Invalid basic credentials".to_string(),
+                ))?;
+
+            match credentials.split_once(':') {
+                Some((login, password)) => {
+                    AppPasswordService::authenticate(&state.postgres, login, password).await
+                }
+                None => Err(ServiceError::InvalidCredentials(
+                    "Invalid basic credentials".to_string(),
+                )),
+            }
+        }
+        None => Ok(ClaimsDto::extract(req).await?.sub),
+    }
+}
+
+fn multistatus(body: String) -> HttpResponse {
+    HttpResponse::build(StatusCode::MULTI_STATUS)
+        .content_type(XML_CONTENT_TYPE)
+        .body(body)
+}
+
+pub fn get_scope() -> Scope {
+    web::scope(constants::DAV_PATH).default_service(web::to(dav_handler))
+}
+
+pub fn get_well_known() -> Resource {
+    web::resource("/.well-known/caldav").to(well_known_caldav_handler)
+}
@@ -0,0 +1,112 @@
+use actix_web::{delete, get, http::header, post, web, HttpResponse, Scope};
+
+use crate::{
+    dto::{auth::ClaimsDto, calendar::CalendarQuery},
+    error::service::ServiceResult,
+    server::State,
+    service::calendar::CalendarService,
+};
+
+const CONTENT_TYPE: &str = "text/calendar; charset=utf-8";
+
+#[utoipa::path(
+    path = "/calendar/export.ics",
+    params(
+        ("status" = Option<TaskStatus>, Query, description = "Task status category"),
+        ("status_id" = Option<Uuid>, Query, description = "Task status, takes precedence over status"),
+        ("priority" = Option<TaskPriority>, Query, description = "Task priority"),
+        ("actionable" = Option<bool>, Query, description = "Only tasks without unfinished blockers"),
+        ("field_id" = Option<Uuid>, Query, description = "Only tasks with this custom field set"),
+        ("field_value" = Option<String>, Query, description = "Value of the field_id custom field"),
+        ("component" = Option<CalendarComponent>, Query, description = "Export tasks as to-dos or events")
+    ),
+    responses(
+        (status = 200, body = String, content_type = "text/calendar"),
+        (status = 400, body = ErrorDto),
+        (status = 403, body = ErrorDto),
+        (status = 404, body = ErrorDto)
+    )
+)]
+#[get("/export.ics")]
+pub async fn export_calendar_handler(
+    state: web::Data<State>,
+    claims: ClaimsDto,
+    query: web::Query<CalendarQuery>,
+) -> ServiceResult<HttpResponse> {
+    Ok(HttpResponse::Ok()
+        .content_type(CONTENT_TYPE)
+        .insert_header((
+            header::CONTENT_DISPOSITION,
+            "attachment; filename=\"tasks.ics\"",
+        ))
+        .body(CalendarService::export(&state.postgres, claims.sub, query.into_inner()).await?))
+}
+
+#[utoipa::path(
+    path = "/calendar/feed/{token}.ics",
+    params(
+        ("token" = String, Path, description = "Secret feed token"),
+        ("status" = Option<TaskStatus>, Query, description = "Task status category"),
+        ("status_id" = Option<Uuid>, Query, description = "Task status, takes precedence over status"),
+        ("priority" = Option<TaskPriority>, Query, description = "Task priority"),
+        ("actionable" = Option<bool>, Query, description = "Only tasks without unfinished blockers"),
+        ("field_id" = Option<Uuid>, Query, description = "Only tasks with this custom field set"),
+        ("field_value" = Option<String>, Query, description = "Value of the field_id custom field"),
+        ("component" = Option<CalendarComponent>, Query, description = "Export tasks as to-dos or events")
+    ),
+    responses(
+        (status = 200, body = String, content_type = "text/calendar"),
+        (status = 400, body = ErrorDto),
+        (status = 401, body = ErrorDto)
+    ),
+    security()
+)]
+#[get("/feed/{token}.ics")]
+pub async fn get_calendar_feed_handler(
+    state: web::Data<State>,
+    path: web::Path<String>,
+    query: web::Query<CalendarQuery>,
+) -> ServiceResult<HttpResponse> {
+    Ok(HttpResponse::Ok()
+        .content_type(CONTENT_TYPE)
+        .body(CalendarService::feed(&state.postgres, path.into_inner(), query.into_inner()).await?))
+}
+
+#[utoipa::path(
+    path = "/calendar/token",
+    responses(
+        (status = 201, body = CalendarTokenReadDto)
+    )
+)]
+#[post("/token")]
+pub async fn regenerate_calendar_token_handler(
+    state: web::Data<State>,
+    claims: ClaimsDto,
+) -> ServiceResult<HttpResponse> {
+    Ok(HttpResponse::Created()
+        .json(CalendarService::regenerate_token(&state.postgres, claims.sub).await?))
+}
+
+#[utoipa::path(
+    path = "/calendar/token",
+    responses(
+        (status = 204)
+    )
+)]
+#[delete("/token")]
+pub async fn revoke_calendar_token_handler(
+    state: web::Data<State>,
+    claims: ClaimsDto,
+) -> ServiceResult<HttpResponse> {
+    CalendarService::revoke_token(&state.postgres, claims.sub).await?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+pub fn get_scope() -> Scope {
+    web::scope("/calendar")
+        .service(export_calendar_handler)
+        .service(get_calendar_feed_handler)
+        .service(regenerate_calendar_token_handler)
+        .service(revoke_calendar_token_handler)
+}
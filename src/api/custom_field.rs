@@ -0,0 +1,106 @@
+use actix_web::{delete, get, patch, post, web, HttpResponse, Scope};
+use garde::Validate;
+use uuid::Uuid;
+
+use crate::{
+    dto::{
+        auth::ClaimsDto,
+        custom_field::{CustomFieldCreateDto, CustomFieldUpdateDto},
+    },
+    error::service::ServiceResult,
+    server::State,
+    service::custom_field::CustomFieldService,
+};
+
+#[utoipa::path(
+    path = "/field",
+    request_body = CustomFieldCreateDto,
+    responses(
+        (status = 201, body = CustomFieldReadDto),
+        (status = 400, body = ErrorDto),
+        (status = 409, body = ErrorDto),
+        (status = 422, body = [ValidateItemErrorDto])
+    )
+)]
+#[post("")]
+pub async fn create_custom_field_handler(
+    state: web::Data<State>,
+    claims: ClaimsDto,
+    body: web::Json<CustomFieldCreateDto>,
+) -> ServiceResult<HttpResponse> {
+    body.validate()?;
+
+    Ok(HttpResponse::Created()
+        .json(CustomFieldService::create(&state.postgres, claims.sub, body.into_inner()).await?))
+}
+
+#[utoipa::path(
+    path = "/field",
+    responses(
+        (status = 200, body = [CustomFieldReadDto])
+    )
+)]
+#[get("")]
+pub async fn get_custom_field_handler(
+    state: web::Data<State>,
+    claims: ClaimsDto,
+) -> ServiceResult<HttpResponse> {
+    Ok(HttpResponse::Ok().json(CustomFieldService::list(&state.postgres, claims.sub).await?))
+}
+
+#[utoipa::path(
+    path = "/field/{id}",
+    request_body = CustomFieldUpdateDto,
+    responses(
+        (status = 200, body = CustomFieldReadDto),
+        (status = 400, body = ErrorDto),
+        (status = 403, body = ErrorDto),
+        (status = 404, body = ErrorDto),
+        (status = 409, body = ErrorDto),
+        (status = 422, body = [ValidateItemErrorDto])
+    )
+)]
+#[patch("/{id}")]
+pub async fn update_custom_field_handler(
+    state: web::Data<State>,
+    claims: ClaimsDto,
+    path: web::Path<Uuid>,
+    body: web::Json<CustomFieldUpdateDto>,
+) -> ServiceResult<HttpResponse> {
+    body.validate()?;
+
+    let id: Uuid = path.into_inner();
+
+    Ok(HttpResponse::Ok().json(
+        CustomFieldService::update(&state.postgres, claims.sub, id, body.into_inner()).await?,
+    ))
+}
+
+#[utoipa::path(
+    path = "/field/{id}",
+    responses(
+        (status = 204),
+        (status = 403, body = ErrorDto),
+        (status = 404, body = ErrorDto)
+    )
+)]
+#[delete("/{id}")]
+pub async fn delete_custom_field_handler(
+    state: web::Data<State>,
+    claims: ClaimsDto,
+    path: web::Path<Uuid>,
+) -> ServiceResult<HttpResponse> {
+    let id: Uuid = path.into_inner();
+
+    CustomFieldService::delete(&state.postgres, claims.sub, id).await?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+pub fn get_scope() -> Scope {
+    web::scope("/field")
+        .service(create_custom_field_handler)
+        .service(get_custom_field_handler)
+        .service(update_custom_field_handler)
+        .service(delete_custom_field_handler)
+}
@@ -3,15 +3,34 @@ use openapi::ApiDoc;
 use utoipa::OpenApi;
 use utoipa_swagger_ui::SwaggerUi;
 
+pub mod account_export;
+pub mod app_password;
 pub mod auth;
+pub mod caldav;
+pub mod calendar;
+pub mod custom_field;
 pub mod openapi;
+pub mod saved_filter;
+pub mod sync;
 pub mod task;
+pub mod task_import;
 pub mod user;
+pub mod workflow_status;
 
 pub fn service_configure(config: &mut ServiceConfig) {
     config
         .service(user::get_scope())
         .service(auth::get_scope())
         .service(task::get_scope())
+        .service(workflow_status::get_scope())
+        .service(custom_field::get_scope())
+        .service(saved_filter::get_scope())
+        .service(calendar::get_scope())
+        .service(caldav::get_scope())
+        .service(caldav::get_well_known())
+        .service(app_password::get_scope())
+        .service(account_export::get_scope())
+        .service(task_import::get_scope())
+        .service(sync::get_scope())
         .service(SwaggerUi::new("/docs/{_:.*}").url("/docs/openapi.json", ApiDoc::openapi()));
 }
@@ -5,15 +5,46 @@ use utoipa::{
 
 use crate::{
     dto::{
+        account_deletion::{AccountDeletionCreateDto, AccountDeletionReadDto},
+        account_export::AccountExportReadDto,
+        app_password::{AppPasswordCreateDto, AppPasswordReadDto},
+        attachment::{AttachmentReadDto, AttachmentUploadDto},
         auth::{SignInDto, TokenDto},
+        calendar::{CalendarComponent, CalendarQuery, CalendarTokenReadDto},
+        custom_field::{CustomFieldCreateDto, CustomFieldReadDto, CustomFieldUpdateDto},
         error::{ErrorDto, ValidateItemErrorDto},
+        saved_filter::{
+            SavedFilterCreateDto, SavedFilterReadDto, SavedFilterRunQuery, SavedFilterUpdateDto,
+        },
+        sync::{
+            SyncMutationDto, SyncMutationResultDto, SyncPullDto, SyncPullQuery, SyncPushDto,
+            TombstoneReadDto,
+        },
         task::{
-            TaskCommentCreateDto, TaskCommentGetQuery, TaskCommentReadDto, TaskCommentUpdateDto,
-            TaskCreateDto, TaskGetQuery, TaskReadDto, TaskUpdateDto,
+            TaskBulkDto, TaskBulkOperationDto, TaskBulkResultDto, TaskCommentCreateDto,
+            TaskCommentGetQuery, TaskCommentReadDto, TaskCommentUpdateDto, TaskCreateDto,
+            TaskDeadlineDto, TaskDependencyCreateDto, TaskDependencyReadDto, TaskEventGetQuery,
+            TaskEventReadDto, TaskGetQuery, TaskKanbanColumnDto, TaskKanbanQuery, TaskMoveDto,
+            TaskQuickDto, TaskQuickReadDto, TaskReadDto, TaskTrashGetQuery, TaskUpdateDto,
+        },
+        task_import::{
+            CsvMappingDto, TaskImportCreateDto, TaskImportReadDto, TaskImportRowDto,
+            TaskImportRowStatus,
         },
-        user::{UserAvatarUploadDto, UserCreateDto, UserReadDto, UserUpdateDto},
+        user::{
+            UserAvatarCropQuery, UserAvatarGetQuery, UserAvatarUploadDto, UserCreateDto,
+            UserProfileReadDto, UserReadDto, UserUpdateDto,
+        },
+        user_preference::{UserPreferenceReadDto, UserPreferenceUpdateDto},
+        workflow_status::{
+            WorkflowStatusCreateDto, WorkflowStatusDeleteQuery, WorkflowStatusReadDto,
+            WorkflowStatusUpdateDto,
+        },
+    },
+    entity::sea_orm_active_enums::{
+        AvatarFormat, CustomFieldKind, DateFormat, DeadlineKind, ExportStatus, ImportFormat,
+        ImportStatus, SyncEntity, TaskEventAction, TaskPriority, TaskSort, TaskStatus, WeekDay,
     },
-    entity::sea_orm_active_enums::{TaskPriority, TaskStatus},
 };
 
 #[derive(OpenApi)]
@@ -25,42 +56,172 @@ use crate::{
         crate::api::user::get_user_by_id_handler,
         crate::api::user::search_user_handler,
         crate::api::user::update_user_handler,
-        crate::api::user::delete_user_handler,
+        crate::api::user::create_deletion_handler,
+        crate::api::user::delete_deletion_handler,
         crate::api::user::create_avatar_handler,
         crate::api::user::get_avatar_handler,
         crate::api::user::delete_avatar_handler,
         crate::api::user::get_avatar_by_user_id_handler,
+        crate::api::user::get_preferences_handler,
+        crate::api::user::update_preferences_handler,
         // Auth
         crate::api::auth::sign_in_handler,
         // Task
         crate::api::task::create_task_handler,
+        crate::api::task::quick_task_handler,
+        crate::api::task::bulk_task_handler,
         crate::api::task::get_task_handler,
+        crate::api::task::get_task_by_id_handler,
+        crate::api::task::get_task_history_handler,
         crate::api::task::update_task_handler,
+        crate::api::task::move_task_handler,
+        crate::api::task::get_task_kanban_handler,
         crate::api::task::delete_task_handler,
         crate::api::task::create_task_comment_handler,
         crate::api::task::get_task_comment_handler,
+        crate::api::task::get_task_comment_by_id_handler,
         crate::api::task::update_task_comment_handler,
         crate::api::task::delete_task_comment_handler,
+        crate::api::task::create_task_blocker_handler,
+        crate::api::task::get_task_blocker_handler,
+        crate::api::task::get_task_dependent_handler,
+        crate::api::task::delete_task_blocker_handler,
+        crate::api::task::get_task_trash_handler,
+        crate::api::task::get_task_comment_trash_handler,
+        crate::api::task::restore_task_handler,
+        crate::api::task::restore_task_comment_handler,
+        crate::api::task::purge_task_handler,
+        crate::api::task::purge_task_comment_handler,
+        // Attachment
+        crate::api::task::create_task_attachment_handler,
+        crate::api::task::create_task_comment_attachment_handler,
+        crate::api::task::get_task_attachment_handler,
+        crate::api::task::get_task_attachment_by_id_handler,
+        crate::api::task::get_task_attachment_content_handler,
+        crate::api::task::delete_task_attachment_handler,
+        // Workflow status
+        crate::api::workflow_status::create_workflow_status_handler,
+        crate::api::workflow_status::get_workflow_status_handler,
+        crate::api::workflow_status::update_workflow_status_handler,
+        crate::api::workflow_status::delete_workflow_status_handler,
+        // Custom field
+        crate::api::custom_field::create_custom_field_handler,
+        crate::api::custom_field::get_custom_field_handler,
+        crate::api::custom_field::update_custom_field_handler,
+        crate::api::custom_field::delete_custom_field_handler,
+        // Saved filter
+        crate::api::saved_filter::create_saved_filter_handler,
+        crate::api::saved_filter::get_saved_filter_handler,
+        crate::api::saved_filter::get_saved_filter_by_id_handler,
+        crate::api::saved_filter::run_saved_filter_handler,
+        crate::api::saved_filter::update_saved_filter_handler,
+        crate::api::saved_filter::delete_saved_filter_handler,
+        // Calendar
+        crate::api::calendar::export_calendar_handler,
+        crate::api::calendar::get_calendar_feed_handler,
+        crate::api::calendar::regenerate_calendar_token_handler,
+        crate::api::calendar::revoke_calendar_token_handler,
+        // App password
+        crate::api::app_password::create_app_password_handler,
+        crate::api::app_password::get_app_password_handler,
+        crate::api::app_password::delete_app_password_handler,
+        // Export
+        crate::api::account_export::create_account_export_handler,
+        crate::api::account_export::get_account_export_handler,
+        crate::api::account_export::get_account_export_by_id_handler,
+        crate::api::account_export::download_account_export_handler,
+        crate::api::account_export::delete_account_export_handler,
+        // Import
+        crate::api::task_import::create_task_import_handler,
+        crate::api::task_import::apply_task_import_handler,
+        crate::api::task_import::get_task_import_handler,
+        crate::api::task_import::get_task_import_by_id_handler,
+        crate::api::task_import::delete_task_import_handler,
+        // Sync
+        crate::api::sync::pull_sync_handler,
+        crate::api::sync::push_sync_handler,
     ),
     components(schemas(
         UserCreateDto,
         UserReadDto,
+        UserProfileReadDto,
         UserUpdateDto,
+        AccountDeletionCreateDto,
+        AccountDeletionReadDto,
         ErrorDto,
         ValidateItemErrorDto,
         SignInDto,
         TokenDto,
         UserAvatarUploadDto,
+        UserAvatarCropQuery,
+        UserAvatarGetQuery,
+        AvatarFormat,
+        UserPreferenceReadDto,
+        UserPreferenceUpdateDto,
+        WeekDay,
+        DateFormat,
+        TaskSort,
         TaskReadDto,
         TaskCreateDto,
+        TaskDeadlineDto,
+        DeadlineKind,
         TaskStatus,
         TaskPriority,
         TaskGetQuery,
         TaskUpdateDto,
+        TaskMoveDto,
+        TaskKanbanQuery,
+        TaskKanbanColumnDto,
+        TaskQuickDto,
+        TaskQuickReadDto,
+        TaskBulkOperationDto,
+        TaskBulkDto,
+        TaskBulkResultDto,
         TaskCommentCreateDto,
         TaskCommentReadDto,
         TaskCommentGetQuery,
         TaskCommentUpdateDto,
+        TaskDependencyCreateDto,
+        TaskDependencyReadDto,
+        TaskEventAction,
+        TaskEventReadDto,
+        TaskEventGetQuery,
+        TaskTrashGetQuery,
+        AttachmentUploadDto,
+        AttachmentReadDto,
+        WorkflowStatusCreateDto,
+        WorkflowStatusUpdateDto,
+        WorkflowStatusDeleteQuery,
+        WorkflowStatusReadDto,
+        CustomFieldKind,
+        CustomFieldCreateDto,
+        CustomFieldUpdateDto,
+        CustomFieldReadDto,
+        SavedFilterCreateDto,
+        SavedFilterUpdateDto,
+        SavedFilterRunQuery,
+        SavedFilterReadDto,
+        CalendarComponent,
+        CalendarQuery,
+        CalendarTokenReadDto,
+        AppPasswordCreateDto,
+        AppPasswordReadDto,
+        ExportStatus,
+        AccountExportReadDto,
+        ImportFormat,
+        ImportStatus,
+        CsvMappingDto,
+        TaskImportCreateDto,
+        TaskImportRowStatus,
+        TaskImportRowDto,
+        TaskImportReadDto,
+        SyncEntity,
+        SyncPullQuery,
+        SyncPullDto,
+        TombstoneReadDto,
+        SyncMutationDto,
+        SyncPushDto,
+        SyncMutationResultDto,
     )),
     security(("JWT token" = [])),
     modifiers(&BearerAuth)
@@ -0,0 +1,154 @@
+use actix_web::{delete, get, patch, post, web, HttpResponse, Scope};
+use garde::Validate;
+use uuid::Uuid;
+
+use crate::{
+    dto::{
+        auth::ClaimsDto,
+        saved_filter::{SavedFilterCreateDto, SavedFilterRunQuery, SavedFilterUpdateDto},
+    },
+    error::service::ServiceResult,
+    server::State,
+    service::saved_filter::SavedFilterService,
+};
+
+#[utoipa::path(
+    path = "/filter",
+    request_body = SavedFilterCreateDto,
+    responses(
+        (status = 201, body = SavedFilterReadDto),
+        (status = 400, body = ErrorDto),
+        (status = 409, body = ErrorDto),
+        (status = 422, body = [ValidateItemErrorDto])
+    )
+)]
+#[post("")]
+pub async fn create_saved_filter_handler(
+    state: web::Data<State>,
+    claims: ClaimsDto,
+    body: web::Json<SavedFilterCreateDto>,
+) -> ServiceResult<HttpResponse> {
+    body.validate()?;
+
+    Ok(HttpResponse::Created()
+        .json(SavedFilterService::create(&state.postgres, claims.sub, body.into_inner()).await?))
+}
+
+#[utoipa::path(
+    path = "/filter",
+    responses(
+        (status = 200, body = [SavedFilterReadDto])
+    )
+)]
+#[get("")]
+pub async fn get_saved_filter_handler(
+    state: web::Data<State>,
+    claims: ClaimsDto,
+) -> ServiceResult<HttpResponse> {
+    Ok(HttpResponse::Ok().json(SavedFilterService::list(&state.postgres, claims.sub).await?))
+}
+
+#[utoipa::path(
+    path = "/filter/{id}",
+    responses(
+        (status = 200, body = SavedFilterReadDto),
+        (status = 403, body = ErrorDto),
+        (status = 404, body = ErrorDto)
+    )
+)]
+#[get("/{id}")]
+pub async fn get_saved_filter_by_id_handler(
+    state: web::Data<State>,
+    claims: ClaimsDto,
+    path: web::Path<Uuid>,
+) -> ServiceResult<HttpResponse> {
+    let id: Uuid = path.into_inner();
+
+    Ok(HttpResponse::Ok()
+        .json(SavedFilterService::get_by_id(&state.postgres, claims.sub, id).await?))
+}
+
+#[utoipa::path(
+    path = "/filter/{id}/task",
+    params(
+        ("limit" = u64, Query, description = "Limit of tasks"),
+        ("offset" = u64, Query, description = "Offset of tasks")
+    ),
+    responses(
+        (status = 200, body = [TaskReadDto]),
+        (status = 400, body = ErrorDto),
+        (status = 403, body = ErrorDto),
+        (status = 404, body = ErrorDto)
+    )
+)]
+#[get("/{id}/task")]
+pub async fn run_saved_filter_handler(
+    state: web::Data<State>,
+    claims: ClaimsDto,
+    path: web::Path<Uuid>,
+    query: web::Query<SavedFilterRunQuery>,
+) -> ServiceResult<HttpResponse> {
+    let id: Uuid = path.into_inner();
+
+    Ok(HttpResponse::Ok()
+        .json(SavedFilterService::run(&state.postgres, claims.sub, id, query.into_inner()).await?))
+}
+
+#[utoipa::path(
+    path = "/filter/{id}",
+    request_body = SavedFilterUpdateDto,
+    responses(
+        (status = 200, body = SavedFilterReadDto),
+        (status = 400, body = ErrorDto),
+        (status = 403, body = ErrorDto),
+        (status = 404, body = ErrorDto),
+        (status = 409, body = ErrorDto),
+        (status = 422, body = [ValidateItemErrorDto])
+    )
+)]
+#[patch("/{id}")]
+pub async fn update_saved_filter_handler(
+    state: web::Data<State>,
+    claims: ClaimsDto,
+    path: web::Path<Uuid>,
+    body: web::Json<SavedFilterUpdateDto>,
+) -> ServiceResult<HttpResponse> {
+    body.validate()?;
+
+    let id: Uuid = path.into_inner();
+
+    Ok(HttpResponse::Ok().json(
+        SavedFilterService::update(&state.postgres, claims.sub, id, body.into_inner()).await?,
+    ))
+}
+
+#[utoipa::path(
+    path = "/filter/{id}",
+    responses(
+        (status = 204),
+        (status = 403, body = ErrorDto),
+        (status = 404, body = ErrorDto)
+    )
+)]
+#[delete("/{id}")]
+pub async fn delete_saved_filter_handler(
+    state: web::Data<State>,
+    claims: ClaimsDto,
+    path: web::Path<Uuid>,
+) -> ServiceResult<HttpResponse> {
+    let id: Uuid = path.into_inner();
+
+    SavedFilterService::delete(&state.postgres, claims.sub, id).await?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+pub fn get_scope() -> Scope {
+    web::scope("/filter")
+        .service(create_saved_filter_handler)
+        .service(get_saved_filter_handler)
+        .service(get_saved_filter_by_id_handler)
+        .service(run_saved_filter_handler)
+        .service(update_saved_filter_handler)
+        .service(delete_saved_filter_handler)
+}
@@ -0,0 +1,68 @@
+use actix_web::{get, post, web, HttpResponse, Scope};
+use garde::Validate;
+
+use crate::{
+    dto::{
+        auth::ClaimsDto,
+        sync::{SyncPullQuery, SyncPushDto},
+    },
+    error::service::ServiceResult,
+    server::State,
+    service::sync::SyncService,
+};
+
+#[utoipa::path(
+    path = "/sync",
+    params(
+        ("token" = Option<String>, Query, description = "Sync token from the previous pull"),
+        ("limit" = u64, Query, description = "Limit of changes, at most 1000"),
+    ),
+    responses(
+        (status = 200, body = SyncPullDto),
+        (status = 400, body = ErrorDto),
+        (status = 422, body = [ValidateItemErrorDto])
+    )
+)]
+#[get("")]
+pub async fn pull_sync_handler(
+    state: web::Data<State>,
+    claims: ClaimsDto,
+    query: web::Query<SyncPullQuery>,
+) -> ServiceResult<HttpResponse> {
+    query.validate()?;
+
+    let query: SyncPullQuery = query.into_inner();
+
+    Ok(HttpResponse::Ok()
+        .json(SyncService::pull(&state.postgres, claims.sub, query.token, query.limit).await?))
+}
+
+#[utoipa::path(
+    path = "/sync",
+    request_body = SyncPushDto,
+    responses(
+        (status = 200, body = [SyncMutationResultDto])
+    )
+)]
+#[post("")]
+pub async fn push_sync_handler(
+    state: web::Data<State>,
+    claims: ClaimsDto,
+    body: web::Json<SyncPushDto>,
+) -> ServiceResult<HttpResponse> {
+    Ok(HttpResponse::Ok().json(
+        SyncService::push(
+            &state.postgres,
+            claims.sub,
+            body.into_inner(),
+            state.config.task.enforce_blockers,
+        )
+        .await?,
+    ))
+}
+
+pub fn get_scope() -> Scope {
+    web::scope("/sync")
+        .service(pull_sync_handler)
+        .service(push_sync_handler)
+}
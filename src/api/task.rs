@@ -1,18 +1,29 @@
-use actix_web::{delete, get, patch, post, web, HttpResponse, Scope};
+use actix_multipart::Multipart;
+use actix_web::{delete, get, http::header, patch, post, web, HttpResponse, Scope};
 use garde::Validate;
 use uuid::Uuid;
 
 use crate::{
     dto::{
+        attachment::RangeDto,
         auth::ClaimsDto,
+        precondition::{etag, IfMatchDto, IfNoneMatchDto},
         task::{
-            TaskCommentCreateDto, TaskCommentGetQuery, TaskCommentUpdateDto, TaskCreateDto,
-            TaskGetQuery, TaskUpdateDto,
+            TaskBulkDto, TaskCommentCreateDto, TaskCommentGetQuery, TaskCommentReadDto,
+            TaskCommentUpdateDto, TaskCreateDto, TaskDependencyCreateDto, TaskEventGetQuery,
+            TaskGetQuery, TaskKanbanQuery, TaskMoveDto, TaskQuickDto, TaskQuickReadDto,
+            TaskReadDto, TaskTrashGetQuery, TaskUpdateDto,
         },
     },
     error::service::ServiceResult,
     server::State,
-    service::{task::TaskService, task_comment::TaskCommentService},
+    service::{
+        attachment::{AttachmentContent, AttachmentService},
+        task::TaskService,
+        task_comment::TaskCommentService,
+        task_dependency::TaskDependencyService,
+        task_event::TaskEventService,
+    },
 };
 
 #[utoipa::path(
@@ -20,6 +31,7 @@ use crate::{
     request_body = TaskCreateDto,
     responses(
         (status = 201, body = TaskReadDto),
+        (status = 400, body = ErrorDto),
         (status = 422, body = [ValidateItemErrorDto])
     )
 )]
@@ -66,11 +78,21 @@ pub async fn create_task_comment_handler(
     params(
         ("limit" = u64, Query, description = "Limit of tasks"),
         ("offset" = u64, Query, description = "Offset of tasks"),
-        ("status" = Option<TaskStatus>, Query, description = "Task status"),
-        ("priority" = Option<TaskPriority>, Query, description = "Task priority")
+        ("status" = Option<TaskStatus>, Query, description = "Task status category"),
+        ("status_id" = Option<Uuid>, Query, description = "Task status, takes precedence over status"),
+        ("priority" = Option<TaskPriority>, Query, description = "Task priority"),
+        ("actionable" = Option<bool>, Query, description = "Only tasks without unfinished blockers"),
+        ("field_id" = Option<Uuid>, Query, description = "Only tasks with this custom field set"),
+        ("field_value" = Option<String>, Query, description = "Value of the field_id custom field"),
+        ("sort_field_id" = Option<Uuid>, Query, description = "Custom field to sort tasks by, takes precedence over sort"),
+        ("sort" = Option<TaskSort>, Query, description = "Order of tasks, the preferred one by default"),
+        ("descending" = Option<bool>, Query, description = "Sort in descending order")
     ),
     responses(
-        (status = 200, body = [TaskReadDto])
+        (status = 200, body = [TaskReadDto]),
+        (status = 400, body = ErrorDto),
+        (status = 403, body = ErrorDto),
+        (status = 404, body = ErrorDto)
     )
 )]
 #[get("/me")]
@@ -79,17 +101,8 @@ pub async fn get_task_handler(
     claims: ClaimsDto,
     query: web::Query<TaskGetQuery>,
 ) -> ServiceResult<HttpResponse> {
-    Ok(HttpResponse::Ok().json(
-        TaskService::list(
-            &state.postgres,
-            claims.sub,
-            query.limit,
-            query.offset,
-            query.status.clone(),
-            query.priority.clone(),
-        )
-        .await?,
-    ))
+    Ok(HttpResponse::Ok()
+        .json(TaskService::list(&state.postgres, claims.sub, query.into_inner()).await?))
 }
 
 #[utoipa::path(
@@ -125,13 +138,185 @@ pub async fn get_task_comment_handler(
     ))
 }
 
+#[utoipa::path(
+    path = "/task/{id}",
+    params(
+        ("If-None-Match" = Option<String>, Header, description = "ETag of the cached task"),
+    ),
+    responses(
+        (status = 200, body = TaskReadDto),
+        (status = 304),
+        (status = 403, body = ErrorDto),
+        (status = 404, body = ErrorDto),
+    )
+)]
+#[get("/{id}")]
+pub async fn get_task_by_id_handler(
+    state: web::Data<State>,
+    claims: ClaimsDto,
+    path: web::Path<Uuid>,
+    if_none_match: IfNoneMatchDto,
+) -> ServiceResult<HttpResponse> {
+    let id: Uuid = path.into_inner();
+
+    Ok(if_none_match.respond(
+        HttpResponse::Ok(),
+        TaskService::get_by_id(&state.postgres, claims.sub, id).await?,
+    ))
+}
+
+#[utoipa::path(
+    path = "/task/{task_id}/comment/{id}",
+    params(
+        ("If-None-Match" = Option<String>, Header, description = "ETag of the cached comment"),
+    ),
+    responses(
+        (status = 200, body = TaskCommentReadDto),
+        (status = 304),
+        (status = 403, body = ErrorDto),
+        (status = 404, body = ErrorDto),
+    )
+)]
+#[get("/{task_id}/comment/{id}")]
+pub async fn get_task_comment_by_id_handler(
+    state: web::Data<State>,
+    claims: ClaimsDto,
+    path: web::Path<(Uuid, Uuid)>,
+    if_none_match: IfNoneMatchDto,
+) -> ServiceResult<HttpResponse> {
+    let (task_id, id) = path.into_inner();
+
+    Ok(if_none_match.respond(
+        HttpResponse::Ok(),
+        TaskCommentService::get_by_id(&state.postgres, claims.sub, task_id, id).await?,
+    ))
+}
+
+#[utoipa::path(
+    path = "/task/{id}/history",
+    params(
+        ("limit" = u64, Query, description = "Limit of events"),
+        ("offset" = u64, Query, description = "Offset of events"),
+    ),
+    responses(
+        (status = 200, body = [TaskEventReadDto]),
+        (status = 403, body = ErrorDto),
+        (status = 404, body = ErrorDto),
+    )
+)]
+#[get("/{id}/history")]
+pub async fn get_task_history_handler(
+    state: web::Data<State>,
+    claims: ClaimsDto,
+    path: web::Path<Uuid>,
+    query: web::Query<TaskEventGetQuery>,
+) -> ServiceResult<HttpResponse> {
+    let id: Uuid = path.into_inner();
+
+    Ok(HttpResponse::Ok().json(
+        TaskEventService::list(&state.postgres, claims.sub, id, query.limit, query.offset).await?,
+    ))
+}
+
+#[utoipa::path(
+    path = "/task/{id}/blocker",
+    request_body = TaskDependencyCreateDto,
+    responses(
+        (status = 201, body = TaskDependencyReadDto),
+        (status = 400, body = ErrorDto),
+        (status = 403, body = ErrorDto),
+        (status = 404, body = ErrorDto),
+        (status = 409, body = ErrorDto)
+    )
+)]
+#[post("/{id}/blocker")]
+pub async fn create_task_blocker_handler(
+    state: web::Data<State>,
+    claims: ClaimsDto,
+    path: web::Path<Uuid>,
+    body: web::Json<TaskDependencyCreateDto>,
+) -> ServiceResult<HttpResponse> {
+    let task_id: Uuid = path.into_inner();
+
+    Ok(HttpResponse::Created().json(
+        TaskDependencyService::create(&state.postgres, claims.sub, task_id, body.into_inner())
+            .await?,
+    ))
+}
+
+#[utoipa::path(
+    path = "/task/{id}/blocker",
+    responses(
+        (status = 200, body = [TaskReadDto]),
+        (status = 403, body = ErrorDto),
+        (status = 404, body = ErrorDto)
+    )
+)]
+#[get("/{id}/blocker")]
+pub async fn get_task_blocker_handler(
+    state: web::Data<State>,
+    claims: ClaimsDto,
+    path: web::Path<Uuid>,
+) -> ServiceResult<HttpResponse> {
+    let task_id: Uuid = path.into_inner();
+
+    Ok(HttpResponse::Ok()
+        .json(TaskDependencyService::list_blockers(&state.postgres, claims.sub, task_id).await?))
+}
+
+#[utoipa::path(
+    path = "/task/{id}/dependent",
+    responses(
+        (status = 200, body = [TaskReadDto]),
+        (status = 403, body = ErrorDto),
+        (status = 404, body = ErrorDto)
+    )
+)]
+#[get("/{id}/dependent")]
+pub async fn get_task_dependent_handler(
+    state: web::Data<State>,
+    claims: ClaimsDto,
+    path: web::Path<Uuid>,
+) -> ServiceResult<HttpResponse> {
+    let task_id: Uuid = path.into_inner();
+
+    Ok(HttpResponse::Ok()
+        .json(TaskDependencyService::list_dependents(&state.postgres, claims.sub, task_id).await?))
+}
+
+#[utoipa::path(
+    path = "/task/{task_id}/blocker/{id}",
+    responses(
+        (status = 204),
+        (status = 403, body = ErrorDto),
+        (status = 404, body = ErrorDto)
+    )
+)]
+#[delete("/{task_id}/blocker/{id}")]
+pub async fn delete_task_blocker_handler(
+    state: web::Data<State>,
+    claims: ClaimsDto,
+    path: web::Path<(Uuid, Uuid)>,
+) -> ServiceResult<HttpResponse> {
+    let (task_id, id) = path.into_inner();
+
+    TaskDependencyService::delete(&state.postgres, claims.sub, task_id, id).await?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
 #[utoipa::path(
     path = "/task/{id}",
     request_body = TaskUpdateDto,
+    params(
+        ("If-Match" = Option<String>, Header, description = "Expected ETag of the task"),
+    ),
     responses(
         (status = 200, body = TaskReadDto),
+        (status = 400, body = ErrorDto),
         (status = 403, body = ErrorDto),
         (status = 404, body = ErrorDto),
+        (status = 412, body = ErrorDto),
         (status = 422, body = [ValidateItemErrorDto]),
     )
 )]
@@ -140,23 +325,39 @@ pub async fn update_task_handler(
     state: web::Data<State>,
     claims: ClaimsDto,
     path: web::Path<Uuid>,
+    if_match: IfMatchDto,
     body: web::Json<TaskUpdateDto>,
 ) -> ServiceResult<HttpResponse> {
     body.validate()?;
 
     let id: Uuid = path.into_inner();
 
+    let schema: TaskReadDto = TaskService::update(
+        &state.postgres,
+        claims.sub,
+        id,
+        if_match,
+        body.into_inner(),
+        state.config.task.enforce_blockers,
+    )
+    .await?;
+
     Ok(HttpResponse::Ok()
-        .json(TaskService::update(&state.postgres, claims.sub, id, body.into_inner()).await?))
+        .insert_header(etag(schema.version))
+        .json(schema))
 }
 
 #[utoipa::path(
     path = "/task/{task_id}/comment/{id}",
     request_body = TaskCommentUpdateDto,
+    params(
+        ("If-Match" = Option<String>, Header, description = "Expected ETag of the comment"),
+    ),
     responses(
         (status = 200, body = TaskCommentReadDto),
         (status = 403, body = ErrorDto),
         (status = 404, body = ErrorDto),
+        (status = 412, body = ErrorDto),
         (status = 422, body = [ValidateItemErrorDto])
     )
 )]
@@ -165,24 +366,38 @@ pub async fn update_task_comment_handler(
     state: web::Data<State>,
     claims: ClaimsDto,
     path: web::Path<(Uuid, Uuid)>,
+    if_match: IfMatchDto,
     body: web::Json<TaskCommentUpdateDto>,
 ) -> ServiceResult<HttpResponse> {
     body.validate()?;
 
     let (task_id, id) = path.into_inner();
 
-    Ok(HttpResponse::Ok().json(
-        TaskCommentService::update(&state.postgres, claims.sub, task_id, id, body.into_inner())
-            .await?,
-    ))
+    let schema: TaskCommentReadDto = TaskCommentService::update(
+        &state.postgres,
+        claims.sub,
+        task_id,
+        id,
+        if_match,
+        body.into_inner(),
+    )
+    .await?;
+
+    Ok(HttpResponse::Ok()
+        .insert_header(etag(schema.version))
+        .json(schema))
 }
 
 #[utoipa::path(
     path = "/task/{id}",
+    params(
+        ("If-Match" = Option<String>, Header, description = "Expected ETag of the task"),
+    ),
     responses(
         (status = 204),
         (status = 403, body = ErrorDto),
-        (status = 404, body = ErrorDto)
+        (status = 404, body = ErrorDto),
+        (status = 412, body = ErrorDto)
     )
 )]
 #[delete("/{id}")]
@@ -190,20 +405,25 @@ pub async fn delete_task_handler(
     state: web::Data<State>,
     claims: ClaimsDto,
     path: web::Path<Uuid>,
+    if_match: IfMatchDto,
 ) -> ServiceResult<HttpResponse> {
     let id: Uuid = path.into_inner();
 
-    TaskService::delete(&state.postgres, claims.sub, id).await?;
+    TaskService::delete(&state.postgres, claims.sub, id, if_match).await?;
 
     Ok(HttpResponse::NoContent().finish())
 }
 
 #[utoipa::path(
     path = "/task/{task_id}/comment/{id}",
+    params(
+        ("If-Match" = Option<String>, Header, description = "Expected ETag of the comment"),
+    ),
     responses(
         (status = 204),
         (status = 403, body = ErrorDto),
-        (status = 404, body = ErrorDto)
+        (status = 404, body = ErrorDto),
+        (status = 412, body = ErrorDto)
     )
 )]
 #[delete("/{task_id}/comment/{id}")]
@@ -211,22 +431,525 @@ pub async fn delete_task_comment_handler(
     state: web::Data<State>,
     claims: ClaimsDto,
     path: web::Path<(Uuid, Uuid)>,
+    if_match: IfMatchDto,
+) -> ServiceResult<HttpResponse> {
+    let (task_id, id) = path.into_inner();
+
+    TaskCommentService::delete(&state.postgres, claims.sub, task_id, id, if_match).await?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+#[utoipa::path(
+    path = "/task/quick",
+    request_body = TaskQuickDto,
+    responses(
+        (status = 200, body = TaskQuickReadDto, description = "Dry run"),
+        (status = 201, body = TaskQuickReadDto),
+        (status = 400, body = ErrorDto),
+        (status = 422, body = [ValidateItemErrorDto])
+    )
+)]
+#[post("/quick")]
+pub async fn quick_task_handler(
+    state: web::Data<State>,
+    claims: ClaimsDto,
+    body: web::Json<TaskQuickDto>,
+) -> ServiceResult<HttpResponse> {
+    body.validate()?;
+
+    let schema: TaskQuickReadDto =
+        TaskService::quick(&state.postgres, claims.sub, body.into_inner()).await?;
+
+    Ok(match schema.task {
+        Some(_) => HttpResponse::Created().json(schema),
+        None => HttpResponse::Ok().json(schema),
+    })
+}
+
+#[utoipa::path(
+    path = "/task/bulk",
+    request_body = TaskBulkDto,
+    responses(
+        (status = 200, body = [TaskBulkResultDto]),
+        (status = 400, body = ErrorDto),
+        (status = 422, body = [ValidateItemErrorDto])
+    )
+)]
+#[post("/bulk")]
+pub async fn bulk_task_handler(
+    state: web::Data<State>,
+    claims: ClaimsDto,
+    body: web::Json<TaskBulkDto>,
+) -> ServiceResult<HttpResponse> {
+    body.validate()?;
+
+    Ok(HttpResponse::Ok().json(
+        TaskService::bulk(
+            &state.postgres,
+            claims.sub,
+            body.into_inner(),
+            state.config.task.enforce_blockers,
+        )
+        .await?,
+    ))
+}
+
+#[utoipa::path(
+    path = "/task/kanban",
+    params(
+        ("limit" = u64, Query, description = "Limit of tasks in each column"),
+    ),
+    responses(
+        (status = 200, body = [TaskKanbanColumnDto])
+    )
+)]
+#[get("/kanban")]
+pub async fn get_task_kanban_handler(
+    state: web::Data<State>,
+    claims: ClaimsDto,
+    query: web::Query<TaskKanbanQuery>,
+) -> ServiceResult<HttpResponse> {
+    Ok(HttpResponse::Ok()
+        .json(TaskService::kanban(&state.postgres, claims.sub, query.limit).await?))
+}
+
+#[utoipa::path(
+    path = "/task/{id}/move",
+    request_body = TaskMoveDto,
+    params(
+        ("If-Match" = Option<String>, Header, description = "Expected ETag of the task"),
+    ),
+    responses(
+        (status = 200, body = TaskReadDto),
+        (status = 400, body = ErrorDto),
+        (status = 403, body = ErrorDto),
+        (status = 404, body = ErrorDto),
+        (status = 409, body = ErrorDto),
+        (status = 412, body = ErrorDto)
+    )
+)]
+#[post("/{id}/move")]
+pub async fn move_task_handler(
+    state: web::Data<State>,
+    claims: ClaimsDto,
+    path: web::Path<Uuid>,
+    if_match: IfMatchDto,
+    body: web::Json<TaskMoveDto>,
+) -> ServiceResult<HttpResponse> {
+    let id: Uuid = path.into_inner();
+
+    let schema: TaskReadDto = TaskService::reposition(
+        &state.postgres,
+        claims.sub,
+        id,
+        if_match,
+        body.into_inner(),
+        state.config.task.enforce_blockers,
+    )
+    .await?;
+
+    Ok(HttpResponse::Ok()
+        .insert_header(etag(schema.version))
+        .json(schema))
+}
+
+#[utoipa::path(
+    path = "/task/trash",
+    params(
+        ("limit" = u64, Query, description = "Limit of tasks"),
+        ("offset" = u64, Query, description = "Offset of tasks"),
+    ),
+    responses(
+        (status = 200, body = [TaskReadDto])
+    )
+)]
+#[get("/trash")]
+pub async fn get_task_trash_handler(
+    state: web::Data<State>,
+    claims: ClaimsDto,
+    query: web::Query<TaskTrashGetQuery>,
+) -> ServiceResult<HttpResponse> {
+    Ok(HttpResponse::Ok().json(
+        TaskService::list_trash(&state.postgres, claims.sub, query.limit, query.offset).await?,
+    ))
+}
+
+#[utoipa::path(
+    path = "/task/trash/comment",
+    params(
+        ("limit" = u64, Query, description = "Limit of comments"),
+        ("offset" = u64, Query, description = "Offset of comments"),
+    ),
+    responses(
+        (status = 200, body = [TaskCommentReadDto])
+    )
+)]
+#[get("/trash/comment")]
+pub async fn get_task_comment_trash_handler(
+    state: web::Data<State>,
+    claims: ClaimsDto,
+    query: web::Query<TaskTrashGetQuery>,
+) -> ServiceResult<HttpResponse> {
+    Ok(HttpResponse::Ok().json(
+        TaskCommentService::list_trash(&state.postgres, claims.sub, query.limit, query.offset)
+            .await?,
+    ))
+}
+
+#[utoipa::path(
+    path = "/task/trash/{id}/restore",
+    params(
+        ("If-Match" = Option<String>, Header, description = "Expected ETag of the task"),
+    ),
+    responses(
+        (status = 200, body = TaskReadDto),
+        (status = 403, body = ErrorDto),
+        (status = 404, body = ErrorDto),
+        (status = 412, body = ErrorDto)
+    )
+)]
+#[post("/trash/{id}/restore")]
+pub async fn restore_task_handler(
+    state: web::Data<State>,
+    claims: ClaimsDto,
+    path: web::Path<Uuid>,
+    if_match: IfMatchDto,
+) -> ServiceResult<HttpResponse> {
+    let id: Uuid = path.into_inner();
+
+    let schema: TaskReadDto =
+        TaskService::restore(&state.postgres, claims.sub, id, if_match).await?;
+
+    Ok(HttpResponse::Ok()
+        .insert_header(etag(schema.version))
+        .json(schema))
+}
+
+#[utoipa::path(
+    path = "/task/trash/comment/{id}/restore",
+    params(
+        ("If-Match" = Option<String>, Header, description = "Expected ETag of the comment"),
+    ),
+    responses(
+        (status = 200, body = TaskCommentReadDto),
+        (status = 403, body = ErrorDto),
+        (status = 404, body = ErrorDto),
+        (status = 412, body = ErrorDto)
+    )
+)]
+#[post("/trash/comment/{id}/restore")]
+pub async fn restore_task_comment_handler(
+    state: web::Data<State>,
+    claims: ClaimsDto,
+    path: web::Path<Uuid>,
+    if_match: IfMatchDto,
+) -> ServiceResult<HttpResponse> {
+    let id: Uuid = path.into_inner();
+
+    let schema: TaskCommentReadDto =
+        TaskCommentService::restore(&state.postgres, claims.sub, id, if_match).await?;
+
+    Ok(HttpResponse::Ok()
+        .insert_header(etag(schema.version))
+        .json(schema))
+}
+
+#[utoipa::path(
+    path = "/task/trash/{id}",
+    params(
+        ("If-Match" = Option<String>, Header, description = "Expected ETag of the task"),
+    ),
+    responses(
+        (status = 204),
+        (status = 403, body = ErrorDto),
+        (status = 404, body = ErrorDto),
+        (status = 412, body = ErrorDto)
+    )
+)]
+#[delete("/trash/{id}")]
+pub async fn purge_task_handler(
+    state: web::Data<State>,
+    claims: ClaimsDto,
+    path: web::Path<Uuid>,
+    if_match: IfMatchDto,
+) -> ServiceResult<HttpResponse> {
+    let id: Uuid = path.into_inner();
+
+    TaskService::purge(&state.postgres, claims.sub, id, if_match).await?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+#[utoipa::path(
+    path = "/task/trash/comment/{id}",
+    params(
+        ("If-Match" = Option<String>, Header, description = "Expected ETag of the comment"),
+    ),
+    responses(
+        (status = 204),
+        (status = 403, body = ErrorDto),
+        (status = 404, body = ErrorDto),
+        (status = 412, body = ErrorDto)
+    )
+)]
+#[delete("/trash/comment/{id}")]
+pub async fn purge_task_comment_handler(
+    state: web::Data<State>,
+    claims: ClaimsDto,
+    path: web::Path<Uuid>,
+    if_match: IfMatchDto,
+) -> ServiceResult<HttpResponse> {
+    let id: Uuid = path.into_inner();
+
+    TaskCommentService::purge(&state.postgres, claims.sub, id, if_match).await?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+#[utoipa::path(
+    path = "/task/{id}/attachment",
+    request_body(content = AttachmentUploadDto, content_type = "multipart/form-data"),
+    responses(
+        (status = 201, body = AttachmentReadDto),
+        (status = 403, body = ErrorDto),
+        (status = 404, body = ErrorDto),
+        (status = 413, body = ErrorDto),
+        (status = 415, body = ErrorDto)
+    )
+)]
+#[post("/{id}/attachment")]
+pub async fn create_task_attachment_handler(
+    state: web::Data<State>,
+    claims: ClaimsDto,
+    path: web::Path<Uuid>,
+    body: Multipart,
+) -> ServiceResult<HttpResponse> {
+    let id: Uuid = path.into_inner();
+
+    Ok(HttpResponse::Created().json(
+        AttachmentService::create(
+            &state.postgres,
+            &state.storage,
+            &state.config.attachment,
+            claims.sub,
+            id,
+            None,
+            body,
+        )
+        .await?,
+    ))
+}
+
+#[utoipa::path(
+    path = "/task/{task_id}/comment/{id}/attachment",
+    request_body(content = AttachmentUploadDto, content_type = "multipart/form-data"),
+    responses(
+        (status = 201, body = AttachmentReadDto),
+        (status = 403, body = ErrorDto),
+        (status = 404, body = ErrorDto),
+        (status = 413, body = ErrorDto),
+        (status = 415, body = ErrorDto)
+    )
+)]
+#[post("/{task_id}/comment/{id}/attachment")]
+pub async fn create_task_comment_attachment_handler(
+    state: web::Data<State>,
+    claims: ClaimsDto,
+    path: web::Path<(Uuid, Uuid)>,
+    body: Multipart,
+) -> ServiceResult<HttpResponse> {
+    let (task_id, id) = path.into_inner();
+
+    Ok(HttpResponse::Created().json(
+        AttachmentService::create(
+            &state.postgres,
+            &state.storage,
+            &state.config.attachment,
+            claims.sub,
+            task_id,
+            Some(id),
+            body,
+        )
+        .await?,
+    ))
+}
+
+#[utoipa::path(
+    path = "/task/{id}/attachment",
+    responses(
+        (status = 200, body = [AttachmentReadDto]),
+        (status = 403, body = ErrorDto),
+        (status = 404, body = ErrorDto)
+    )
+)]
+#[get("/{id}/attachment")]
+pub async fn get_task_attachment_handler(
+    state: web::Data<State>,
+    claims: ClaimsDto,
+    path: web::Path<Uuid>,
+) -> ServiceResult<HttpResponse> {
+    let id: Uuid = path.into_inner();
+
+    Ok(HttpResponse::Ok().json(AttachmentService::list(&state.postgres, claims.sub, id).await?))
+}
+
+#[utoipa::path(
+    path = "/task/{task_id}/attachment/{id}",
+    responses(
+        (status = 200, body = AttachmentReadDto),
+        (status = 403, body = ErrorDto),
+        (status = 404, body = ErrorDto)
+    )
+)]
+#[get("/{task_id}/attachment/{id}")]
+pub async fn get_task_attachment_by_id_handler(
+    state: web::Data<State>,
+    claims: ClaimsDto,
+    path: web::Path<(Uuid, Uuid)>,
+) -> ServiceResult<HttpResponse> {
+    let (task_id, id) = path.into_inner();
+
+    Ok(HttpResponse::Ok()
+        .json(AttachmentService::get_by_id(&state.postgres, claims.sub, task_id, id).await?))
+}
+
+#[utoipa::path(
+    path = "/task/{task_id}/attachment/{id}/content",
+    params(
+        ("Range" = Option<String>, Header, description = "Single byte range to send"),
+    ),
+    responses(
+        (status = 200, body = [u8], content_type = "application/octet-stream"),
+        (status = 206, body = [u8], content_type = "application/octet-stream"),
+        (status = 403, body = ErrorDto),
+        (status = 404, body = ErrorDto),
+        (status = 416, body = ErrorDto)
+    )
+)]
+#[get("/{task_id}/attachment/{id}/content")]
+pub async fn get_task_attachment_content_handler(
+    state: web::Data<State>,
+    claims: ClaimsDto,
+    path: web::Path<(Uuid, Uuid)>,
+    range: RangeDto,
 ) -> ServiceResult<HttpResponse> {
     let (task_id, id) = path.into_inner();
 
-    TaskCommentService::delete(&state.postgres, claims.sub, task_id, id).await?;
+    let content: AttachmentContent = AttachmentService::content(
+        &state.postgres,
+        &state.storage,
+        claims.sub,
+        task_id,
+        id,
+        range,
+    )
+    .await?;
+
+    let (mut response, length) = match content.range {
+        Some((start, end, size)) => {
+            let mut response = HttpResponse::PartialContent();
+            response.insert_header((header::CONTENT_RANGE, format!("bytes {start}-{end}/{size}")));
+            (response, end - start + 1)
+        }
+        None => (HttpResponse::Ok(), content.attachment.size as u64),
+    };
+
+    // Uploads are untrusted, browsers must neither sniff nor run them
+    Ok(response
+        .content_type(content.attachment.content_type.as_str())
+        .insert_header(content_disposition(
+            &content.attachment.name,
+            &content.attachment.content_type,
+        ))
+        .insert_header((header::X_CONTENT_TYPE_OPTIONS, "nosniff"))
+        .insert_header((header::CONTENT_SECURITY_POLICY, "sandbox"))
+        .insert_header((header::ACCEPT_RANGES, "bytes"))
+        .insert_header(header::ETag(header::EntityTag::new_strong(
+            content.attachment.sha256,
+        )))
+        .no_chunking(length)
+        .streaming(content.body))
+}
+
+#[utoipa::path(
+    path = "/task/{task_id}/attachment/{id}",
+    responses(
+        (status = 204),
+        (status = 403, body = ErrorDto),
+        (status = 404, body = ErrorDto)
+    )
+)]
+#[delete("/{task_id}/attachment/{id}")]
+pub async fn delete_task_attachment_handler(
+    state: web::Data<State>,
+    claims: ClaimsDto,
+    path: web::Path<(Uuid, Uuid)>,
+) -> ServiceResult<HttpResponse> {
+    let (task_id, id) = path.into_inner();
+
+    AttachmentService::delete(&state.postgres, claims.sub, task_id, id).await?;
 
     Ok(HttpResponse::NoContent().finish())
 }
 
+/// Shows images, PDFs and plain text in the browser, everything else is
+/// downloaded.
+fn content_disposition(name: &str, content_type: &str) -> header::ContentDisposition {
+    let disposition: header::DispositionType = match content_type {
+        "image/png" | "image/jpeg" | "image/gif" | "image/webp" | "application/pdf"
+        | "text/plain" => header::DispositionType::Inline,
+        _ => header::DispositionType::Attachment,
+    };
+
+    let parameter: header::DispositionParam = if name.is_ascii() {
+        header::DispositionParam::Filename(name.to_string())
+    } else {
+        header::DispositionParam::FilenameExt(header::ExtendedValue {
+            charset: header::Charset::Ext("UTF-8".to_string()),
+            language_tag: None,
+            value: name.as_bytes().to_vec(),
+        })
+    };
+
+    header::ContentDisposition {
+        disposition,
+        parameters: vec![parameter],
+    }
+}
+
 pub fn get_scope() -> Scope {
+    // Static routes go first so their names are not taken for a task id
     web::scope("/task")
+        .service(get_task_kanban_handler)
+        .service(get_task_trash_handler)
+        .service(get_task_comment_trash_handler)
+        .service(restore_task_handler)
+        .service(restore_task_comment_handler)
+        .service(purge_task_handler)
+        .service(purge_task_comment_handler)
         .service(create_task_handler)
+        .service(quick_task_handler)
+        .service(bulk_task_handler)
         .service(get_task_handler)
+        .service(get_task_by_id_handler)
+        .service(get_task_history_handler)
         .service(update_task_handler)
+        .service(move_task_handler)
         .service(delete_task_handler)
         .service(create_task_comment_handler)
         .service(get_task_comment_handler)
+        .service(get_task_comment_by_id_handler)
         .service(update_task_comment_handler)
         .service(delete_task_comment_handler)
+        .service(create_task_blocker_handler)
+        .service(get_task_blocker_handler)
+        .service(get_task_dependent_handler)
+        .service(delete_task_blocker_handler)
+        .service(create_task_attachment_handler)
+        .service(create_task_comment_attachment_handler)
+        .service(get_task_attachment_handler)
+        .service(get_task_attachment_by_id_handler)
+        .service(get_task_attachment_content_handler)
+        .service(delete_task_attachment_handler)
 }
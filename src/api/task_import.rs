@@ -0,0 +1,132 @@
+use actix_web::{delete, get, post, rt, web, HttpResponse, Scope};
+use garde::Validate;
+use uuid::Uuid;
+
+use crate::{
+    dto::{auth::ClaimsDto, task_import::TaskImportCreateDto},
+    error::service::ServiceResult,
+    server::State,
+    service::task_import::TaskImportService,
+};
+
+#[utoipa::path(
+    path = "/import",
+    request_body = TaskImportCreateDto,
+    responses(
+        (status = 202, body = TaskImportReadDto),
+        (status = 422, body = [ValidateItemErrorDto])
+    )
+)]
+#[post("")]
+pub async fn create_task_import_handler(
+    state: web::Data<State>,
+    claims: ClaimsDto,
+    body: web::Json<TaskImportCreateDto>,
+) -> ServiceResult<HttpResponse> {
+    body.validate()?;
+
+    let schema = TaskImportService::create(&state.postgres, claims.sub, body.into_inner()).await?;
+
+    spawn_process(&state);
+
+    Ok(HttpResponse::Accepted().json(schema))
+}
+
+#[utoipa::path(
+    path = "/import/{id}/apply",
+    responses(
+        (status = 202, body = TaskImportReadDto),
+        (status = 400, body = ErrorDto),
+        (status = 403, body = ErrorDto),
+        (status = 404, body = ErrorDto)
+    )
+)]
+#[post("/{id}/apply")]
+pub async fn apply_task_import_handler(
+    state: web::Data<State>,
+    claims: ClaimsDto,
+    path: web::Path<Uuid>,
+) -> ServiceResult<HttpResponse> {
+    let id: Uuid = path.into_inner();
+
+    let schema = TaskImportService::apply(&state.postgres, claims.sub, id).await?;
+
+    spawn_process(&state);
+
+    Ok(HttpResponse::Accepted().json(schema))
+}
+
+#[utoipa::path(
+    path = "/import",
+    responses(
+        (status = 200, body = [TaskImportReadDto])
+    )
+)]
+#[get("")]
+pub async fn get_task_import_handler(
+    state: web::Data<State>,
+    claims: ClaimsDto,
+) -> ServiceResult<HttpResponse> {
+    Ok(HttpResponse::Ok().json(TaskImportService::list(&state.postgres, claims.sub).await?))
+}
+
+#[utoipa::path(
+    path = "/import/{id}",
+    responses(
+        (status = 200, body = TaskImportReadDto),
+        (status = 403, body = ErrorDto),
+        (status = 404, body = ErrorDto)
+    )
+)]
+#[get("/{id}")]
+pub async fn get_task_import_by_id_handler(
+    state: web::Data<State>,
+    claims: ClaimsDto,
+    path: web::Path<Uuid>,
+) -> ServiceResult<HttpResponse> {
+    let id: Uuid = path.into_inner();
+
+    Ok(HttpResponse::Ok()
+        .json(TaskImportService::get_by_id(&state.postgres, claims.sub, id).await?))
+}
+
+#[utoipa::path(
+    path = "/import/{id}",
+    responses(
+        (status = 204),
+        (status = 403, body = ErrorDto),
+        (status = 404, body = ErrorDto)
+    )
+)]
+#[delete("/{id}")]
+pub async fn delete_task_import_handler(
+    state: web::Data<State>,
+    claims: ClaimsDto,
+    path: web::Path<Uuid>,
+) -> ServiceResult<HttpResponse> {
+    let id: Uuid = path.into_inner();
+
+    TaskImportService::delete(&state.postgres, claims.sub, id).await?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+/// Small files are usually imported by the time the client polls, the
+/// import job picks up whatever this misses.
+fn spawn_process(state: &State) {
+    let db = state.postgres.clone();
+    rt::spawn(async move {
+        if let Err(err) = TaskImportService::process_next(&db).await {
+            log::error!("Running import failed: {}", err);
+        }
+    });
+}
+
+pub fn get_scope() -> Scope {
+    web::scope("/import")
+        .service(create_task_import_handler)
+        .service(get_task_import_handler)
+        .service(apply_task_import_handler)
+        .service(get_task_import_by_id_handler)
+        .service(delete_task_import_handler)
+}
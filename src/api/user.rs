@@ -1,16 +1,30 @@
 use actix_multipart::Multipart;
-use actix_web::{delete, get, patch, post, web, HttpResponse, Scope};
+use actix_web::{
+    delete, get, http::header, patch, post, web, HttpResponse, HttpResponseBuilder, Scope,
+};
 use garde::Validate;
 use uuid::Uuid;
 
 use crate::{
     dto::{
+        account_deletion::{AccountDeletionCreateDto, AccountDeletionReadDto},
         auth::ClaimsDto,
-        user::{UserCreateDto, UserSearchQuery, UserUpdateDto},
+        precondition::{etag, IfMatchDto, IfNoneMatchDto, IfNoneMatchTagDto},
+        user::{
+            AvatarFormatDto, UserAvatarCropQuery, UserAvatarGetQuery, UserCreateDto, UserReadDto,
+            UserSearchQuery, UserUpdateDto,
+        },
+        user_preference::{UserPreferenceReadDto, UserPreferenceUpdateDto},
     },
+    entity::sea_orm_active_enums::AvatarFormat,
     error::service::ServiceResult,
     server::State,
-    service::{user::UserService, user_avatar::UserAvatarService},
+    service::{
+        account_deletion::AccountDeletionService,
+        user::UserService,
+        user_avatar::{UserAvatarContent, UserAvatarService},
+        user_preference::UserPreferenceService,
+    },
 };
 
 #[utoipa::path(
@@ -38,10 +52,18 @@ pub async fn create_user_handler(
 
 #[utoipa::path(
     path = "/user/me/avatar",
+    params(
+        ("x" = Option<u32>, Query, description = "Left edge of the crop box"),
+        ("y" = Option<u32>, Query, description = "Top edge of the crop box"),
+        ("width" = Option<u32>, Query, description = "Width of the crop box"),
+        ("height" = Option<u32>, Query, description = "Height of the crop box"),
+    ),
     request_body(content = UserAvatarUploadDto, content_type = "multipart/form-data"),
     responses(
         (status = 201, body = [u8], content_type = "image/png"),
-        (status = 409, body = ErrorDto),
+        (status = 201, body = [u8], content_type = "image/webp"),
+        (status = 400, body = ErrorDto),
+        (status = 415, body = ErrorDto),
         (status = 422, body = ErrorDto)
     )
 )]
@@ -49,17 +71,34 @@ pub async fn create_user_handler(
 pub async fn create_avatar_handler(
     state: web::Data<State>,
     claims: ClaimsDto,
+    query: web::Query<UserAvatarCropQuery>,
+    format: AvatarFormatDto,
     body: Multipart,
 ) -> ServiceResult<HttpResponse> {
-    Ok(HttpResponse::Created()
-        .content_type("image/png")
-        .body(UserAvatarService::set(&state.postgres, claims.sub, body).await?))
+    Ok(avatar_response(
+        HttpResponse::Created(),
+        UserAvatarService::set(
+            &state.postgres,
+            &state.storage,
+            claims.sub,
+            body,
+            query.into_inner(),
+            format.0,
+        )
+        .await?,
+    ))
 }
 
 #[utoipa::path(
     path = "/user/me/avatar",
+    params(
+        ("size" = Option<u32>, Query, description = "Side in pixels, 64, 128 or 512"),
+        ("If-None-Match" = Option<String>, Header, description = "ETag of the cached avatar"),
+    ),
     responses(
         (status = 200, body = [u8], content_type = "image/png"),
+        (status = 200, body = [u8], content_type = "image/webp"),
+        (status = 304),
         (status = 404, body = ErrorDto)
     )
 )]
@@ -67,16 +106,35 @@ pub async fn create_avatar_handler(
 pub async fn get_avatar_handler(
     state: web::Data<State>,
     claims: ClaimsDto,
+    query: web::Query<UserAvatarGetQuery>,
+    format: AvatarFormatDto,
+    if_none_match: IfNoneMatchTagDto,
 ) -> ServiceResult<HttpResponse> {
-    Ok(HttpResponse::Ok()
-        .content_type("image/png")
-        .body(UserAvatarService::get_by_user_id(&state.postgres, claims.sub).await?))
+    Ok(avatar_response(
+        HttpResponse::Ok(),
+        UserAvatarService::get_by_user_id(
+            &state.postgres,
+            &state.storage,
+            claims.sub,
+            claims.sub,
+            query.size,
+            format.0,
+            &if_none_match,
+        )
+        .await?,
+    ))
 }
 
 #[utoipa::path(
     path = "/user/{id}/avatar",
+    params(
+        ("size" = Option<u32>, Query, description = "Side in pixels, 64, 128 or 512"),
+        ("If-None-Match" = Option<String>, Header, description = "ETag of the cached avatar"),
+    ),
     responses(
         (status = 200, body = [u8], content_type = "image/png"),
+        (status = 200, body = [u8], content_type = "image/webp"),
+        (status = 304),
         (status = 404, body = ErrorDto)
     )
 )]
@@ -84,13 +142,26 @@ pub async fn get_avatar_handler(
 pub async fn get_avatar_by_user_id_handler(
     state: web::Data<State>,
     path: web::Path<Uuid>,
-    _: ClaimsDto,
+    claims: ClaimsDto,
+    query: web::Query<UserAvatarGetQuery>,
+    format: AvatarFormatDto,
+    if_none_match: IfNoneMatchTagDto,
 ) -> ServiceResult<HttpResponse> {
     let id: Uuid = path.into_inner();
 
-    Ok(HttpResponse::Ok()
-        .content_type("image/png")
-        .body(UserAvatarService::get_by_user_id(&state.postgres, id).await?))
+    Ok(avatar_response(
+        HttpResponse::Ok(),
+        UserAvatarService::get_by_user_id(
+            &state.postgres,
+            &state.storage,
+            id,
+            claims.sub,
+            query.size,
+            format.0,
+            &if_none_match,
+        )
+        .await?,
+    ))
 }
 
 #[utoipa::path(
@@ -112,8 +183,12 @@ pub async fn delete_avatar_handler(
 
 #[utoipa::path(
     path = "/user/me",
+    params(
+        ("If-None-Match" = Option<String>, Header, description = "ETag of the cached user"),
+    ),
     responses(
         (status = 200, body = UserReadDto),
+        (status = 304),
         (status = 404, body = ErrorDto),
     ),
 )]
@@ -121,14 +196,22 @@ pub async fn delete_avatar_handler(
 pub async fn get_user_handler(
     state: web::Data<State>,
     claims: ClaimsDto,
+    if_none_match: IfNoneMatchDto,
 ) -> ServiceResult<HttpResponse> {
-    Ok(HttpResponse::Ok().json(UserService::get_by_id(&state.postgres, claims.sub).await?))
+    Ok(if_none_match.respond(
+        HttpResponse::Ok(),
+        UserService::get_by_id(&state.postgres, claims.sub).await?,
+    ))
 }
 
 #[utoipa::path(
     path = "/user/{id}",
+    params(
+        ("If-None-Match" = Option<String>, Header, description = "ETag of the cached user"),
+    ),
     responses(
-        (status = 200, body = UserReadDto),
+        (status = 200, body = UserProfileReadDto),
+        (status = 304),
         (status = 404, body = ErrorDto),
     ),
 )]
@@ -137,19 +220,28 @@ pub async fn get_user_by_id_handler(
     state: web::Data<State>,
     path: web::Path<Uuid>,
     _: ClaimsDto,
+    if_none_match: IfNoneMatchDto,
 ) -> ServiceResult<HttpResponse> {
     let id = path.into_inner();
 
-    Ok(HttpResponse::Ok().json(UserService::get_by_id(&state.postgres, id).await?))
+    Ok(if_none_match.respond(
+        HttpResponse::Ok(),
+        UserService::get_profile_by_id(&state.postgres, id).await?,
+    ))
 }
 
+/// Finds users that let others find them by name or display name, tolerating
+/// typos. Names starting with the query come first. Needs a token unless
+/// public search is on.
 #[utoipa::path(
     path = "/user",
     responses(
-        (status = 200, body = [UserReadDto])
+        (status = 200, body = [UserProfileReadDto]),
+        (status = 401, body = ErrorDto),
+        (status = 422, body = [ValidateItemErrorDto])
     ),
     params(
-        ("name" = String, Query, description = "User name"),
+        ("name" = String, Query, description = "Part of the name or display name, at least 2 characters"),
         ("limit" = u64, Query, description = "Limit of users"),
         ("offset" = u64, Query, description = "Offset of users"),
     ),
@@ -157,8 +249,14 @@ pub async fn get_user_by_id_handler(
 #[get("")]
 pub async fn search_user_handler(
     state: web::Data<State>,
+    claims: ServiceResult<ClaimsDto>,
     query: web::Query<UserSearchQuery>,
 ) -> ServiceResult<HttpResponse> {
+    if !state.config.user.public_search {
+        claims?;
+    }
+    query.validate()?;
+
     let name: String = query.name.clone();
     let limit: u64 = query.limit;
     let offset: u64 = query.offset;
@@ -167,13 +265,71 @@ pub async fn search_user_handler(
         .json(UserService::search_by_name(&state.postgres, name, limit, offset).await?))
 }
 
+#[utoipa::path(
+    path = "/user/me/preferences",
+    params(
+        ("If-None-Match" = Option<String>, Header, description = "ETag of the cached preferences"),
+    ),
+    responses(
+        (status = 200, body = UserPreferenceReadDto),
+        (status = 304),
+        (status = 404, body = ErrorDto),
+    ),
+)]
+#[get("/me/preferences")]
+pub async fn get_preferences_handler(
+    state: web::Data<State>,
+    claims: ClaimsDto,
+    if_none_match: IfNoneMatchDto,
+) -> ServiceResult<HttpResponse> {
+    Ok(if_none_match.respond(
+        HttpResponse::Ok(),
+        UserPreferenceService::get(&state.postgres, claims.sub).await?,
+    ))
+}
+
+#[utoipa::path(
+    path = "/user/me/preferences",
+    request_body = UserPreferenceUpdateDto,
+    params(
+        ("If-Match" = Option<String>, Header, description = "Expected ETag of the preferences"),
+    ),
+    responses(
+        (status = 200, body = UserPreferenceReadDto),
+        (status = 404, body = ErrorDto),
+        (status = 412, body = ErrorDto),
+        (status = 422, body = [ValidateItemErrorDto])
+    )
+)]
+#[patch("/me/preferences")]
+pub async fn update_preferences_handler(
+    state: web::Data<State>,
+    body: web::Json<UserPreferenceUpdateDto>,
+    claims: ClaimsDto,
+    if_match: IfMatchDto,
+) -> ServiceResult<HttpResponse> {
+    body.validate()?;
+
+    let schema: UserPreferenceReadDto =
+        UserPreferenceService::update(&state.postgres, claims.sub, if_match, body.into_inner())
+            .await?;
+
+    Ok(HttpResponse::Ok()
+        .insert_header(etag(schema.version))
+        .json(schema))
+}
+
 #[utoipa::path(
     path = "/user/me",
     request_body = UserUpdateDto,
+    params(
+        ("If-Match" = Option<String>, Header, description = "Expected ETag of the user"),
+    ),
     responses(
         (status = 200, body = UserReadDto),
         (status = 404, body = ErrorDto),
         (status = 409, body = ErrorDto),
+        (status = 412, body = ErrorDto),
         (status = 422, body = [ValidateItemErrorDto])
     )
 )]
@@ -182,38 +338,106 @@ pub async fn update_user_handler(
     state: web::Data<State>,
     body: web::Json<UserUpdateDto>,
     claims: ClaimsDto,
+    if_match: IfMatchDto,
 ) -> ServiceResult<HttpResponse> {
     body.validate()?;
 
+    let schema: UserReadDto =
+        UserService::update(&state.postgres, claims.sub, if_match, body.into_inner()).await?;
+
     Ok(HttpResponse::Ok()
-        .json(UserService::update(&state.postgres, claims.sub, body.into_inner()).await?))
+        .insert_header(etag(schema.version))
+        .json(schema))
 }
 
+/// Schedules deletion of the account after the grace period and mails the
+/// user about it. The account stays usable until then.
 #[utoipa::path(
-    path = "/user/me",
+    path = "/user/me/deletion",
+    request_body = AccountDeletionCreateDto,
+    responses(
+        (status = 202, body = AccountDeletionReadDto),
+        (status = 401, body = ErrorDto),
+        (status = 409, body = ErrorDto),
+        (status = 422, body = [ValidateItemErrorDto])
+    )
+)]
+#[post("/me/deletion")]
+pub async fn create_deletion_handler(
+    state: web::Data<State>,
+    claims: ClaimsDto,
+    body: web::Json<AccountDeletionCreateDto>,
+) -> ServiceResult<HttpResponse> {
+    body.validate()?;
+
+    let schema: AccountDeletionReadDto = AccountDeletionService::request(
+        &state.postgres,
+        &state.mail,
+        claims.sub,
+        body.into_inner(),
+        state.config.user.deletion_grace,
+    )
+    .await?;
+
+    Ok(HttpResponse::Accepted().json(schema))
+}
+
+#[utoipa::path(
+    path = "/user/me/deletion",
     responses(
         (status = 204),
         (status = 404, body = ErrorDto)
     )
 )]
-#[delete("/me")]
-pub async fn delete_user_handler(
+#[delete("/me/deletion")]
+pub async fn delete_deletion_handler(
     state: web::Data<State>,
     claims: ClaimsDto,
 ) -> ServiceResult<HttpResponse> {
-    UserService::delete(&state.postgres, claims.sub).await?;
+    AccountDeletionService::cancel(&state.postgres, &state.mail, claims.sub).await?;
 
     Ok(HttpResponse::NoContent().finish())
 }
 
+/// Tags the avatar by its contents. Clients may keep it but must ask again
+/// before use, as the address stays when the avatar changes. The format
+/// follows `Accept`, which caches must take into account.
+fn avatar_response(builder: HttpResponseBuilder, content: UserAvatarContent) -> HttpResponse {
+    let mut builder: HttpResponseBuilder = match content.body {
+        Some(_) => builder,
+        None => HttpResponse::NotModified(),
+    };
+
+    builder
+        .insert_header(header::ETag(header::EntityTag::new_strong(content.sha256)))
+        .insert_header(header::CacheControl(vec![
+            header::CacheDirective::Private,
+            header::CacheDirective::NoCache,
+        ]))
+        .insert_header((header::VARY, "Accept"));
+
+    match content.body {
+        Some(body) => builder
+            .content_type(match content.format {
+                AvatarFormat::Png => "image/png",
+                AvatarFormat::Webp => "image/webp",
+            })
+            .streaming(body),
+        None => builder.finish(),
+    }
+}
+
 pub fn get_scope() -> Scope {
     web::scope("/user")
         .service(create_user_handler)
         .service(get_user_handler)
+        .service(get_preferences_handler)
+        .service(update_preferences_handler)
         .service(get_user_by_id_handler)
         .service(search_user_handler)
         .service(update_user_handler)
-        .service(delete_user_handler)
+        .service(create_deletion_handler)
+        .service(delete_deletion_handler)
         .service(create_avatar_handler)
         .service(get_avatar_handler)
         .service(delete_avatar_handler)
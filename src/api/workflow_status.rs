@@ -0,0 +1,112 @@
+use actix_web::{delete, get, patch, post, web, HttpResponse, Scope};
+use garde::Validate;
+use uuid::Uuid;
+
+use crate::{
+    dto::{
+        auth::ClaimsDto,
+        workflow_status::{
+            WorkflowStatusCreateDto, WorkflowStatusDeleteQuery, WorkflowStatusUpdateDto,
+        },
+    },
+    error::service::ServiceResult,
+    server::State,
+    service::workflow_status::WorkflowStatusService,
+};
+
+#[utoipa::path(
+    path = "/status",
+    request_body = WorkflowStatusCreateDto,
+    responses(
+        (status = 201, body = WorkflowStatusReadDto),
+        (status = 409, body = ErrorDto),
+        (status = 422, body = [ValidateItemErrorDto])
+    )
+)]
+#[post("")]
+pub async fn create_workflow_status_handler(
+    state: web::Data<State>,
+    claims: ClaimsDto,
+    body: web::Json<WorkflowStatusCreateDto>,
+) -> ServiceResult<HttpResponse> {
+    body.validate()?;
+
+    Ok(HttpResponse::Created()
+        .json(WorkflowStatusService::create(&state.postgres, claims.sub, body.into_inner()).await?))
+}
+
+#[utoipa::path(
+    path = "/status",
+    responses(
+        (status = 200, body = [WorkflowStatusReadDto])
+    )
+)]
+#[get("")]
+pub async fn get_workflow_status_handler(
+    state: web::Data<State>,
+    claims: ClaimsDto,
+) -> ServiceResult<HttpResponse> {
+    Ok(HttpResponse::Ok().json(WorkflowStatusService::list(&state.postgres, claims.sub).await?))
+}
+
+#[utoipa::path(
+    path = "/status/{id}",
+    request_body = WorkflowStatusUpdateDto,
+    responses(
+        (status = 200, body = WorkflowStatusReadDto),
+        (status = 400, body = ErrorDto),
+        (status = 403, body = ErrorDto),
+        (status = 404, body = ErrorDto),
+        (status = 409, body = ErrorDto),
+        (status = 422, body = [ValidateItemErrorDto])
+    )
+)]
+#[patch("/{id}")]
+pub async fn update_workflow_status_handler(
+    state: web::Data<State>,
+    claims: ClaimsDto,
+    path: web::Path<Uuid>,
+    body: web::Json<WorkflowStatusUpdateDto>,
+) -> ServiceResult<HttpResponse> {
+    body.validate()?;
+
+    let id: Uuid = path.into_inner();
+
+    Ok(HttpResponse::Ok().json(
+        WorkflowStatusService::update(&state.postgres, claims.sub, id, body.into_inner()).await?,
+    ))
+}
+
+#[utoipa::path(
+    path = "/status/{id}",
+    params(
+        ("replacement_id" = Option<Uuid>, Query, description = "Status that takes over the tasks")
+    ),
+    responses(
+        (status = 204),
+        (status = 400, body = ErrorDto),
+        (status = 403, body = ErrorDto),
+        (status = 404, body = ErrorDto)
+    )
+)]
+#[delete("/{id}")]
+pub async fn delete_workflow_status_handler(
+    state: web::Data<State>,
+    claims: ClaimsDto,
+    path: web::Path<Uuid>,
+    query: web::Query<WorkflowStatusDeleteQuery>,
+) -> ServiceResult<HttpResponse> {
+    let id: Uuid = path.into_inner();
+
+    WorkflowStatusService::delete(&state.postgres, claims.sub, id, query.replacement_id).await?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+pub fn get_scope() -> Scope {
+    web::scope("/status")
+        .service(create_workflow_status_handler)
+        .service(get_workflow_status_handler)
+        .service(update_workflow_status_handler)
+        .service(delete_workflow_status_handler)
+}
@@ -0,0 +1,35 @@
+use lettre::message::Mailbox;
+
+use crate::error::mail::MailResult;
+
+use super::{Mail, Mailer};
+
+/// Logs mails instead of sending them, so flows needing one can be followed
+/// without a relay.
+#[derive(Debug)]
+pub struct LogMailer {
+    from: Mailbox,
+}
+
+impl LogMailer {
+    pub fn new(from: Mailbox) -> Self {
+        Self { from }
+    }
+}
+
+#[async_trait::async_trait]
+impl Mailer for LogMailer {
+    async fn send(&self, mail: Mail) -> MailResult {
+        let to: Mailbox = mail.to.parse()?;
+
+        log::info!(
+            "Mail from {} to {}: {}\n{}",
+            self.from,
+            to,
+            mail.subject,
+            mail.body
+        );
+
+        Ok(())
+    }
+}
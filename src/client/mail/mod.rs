@@ -0,0 +1,72 @@
+pub mod logger;
+pub mod smtp;
+
+use std::{fmt::Debug, sync::Arc};
+
+use lettre::message::Mailbox;
+
+use crate::{
+    config::{mail::MailBackend, Config},
+    error::{
+        client::{ClientError, ClientResult},
+        mail::MailResult,
+    },
+};
+
+use super::ClientBuilder;
+use logger::LogMailer;
+use smtp::SmtpMailer;
+
+/// Plain text mail to a single recipient.
+#[derive(Debug, Clone)]
+pub struct Mail {
+    pub to: String,
+    pub subject: String,
+    pub body: String,
+}
+
+/// Delivers mails to users, all from the configured sender.
+#[async_trait::async_trait]
+pub trait Mailer: Debug + Send + Sync {
+    /// Sends `mail`, returning once the relay accepted it.
+    async fn send(&self, mail: Mail) -> MailResult;
+}
+
+pub type MailClient = Arc<dyn Mailer>;
+
+#[async_trait::async_trait]
+impl ClientBuilder for MailClient {
+    async fn from_config(config: &Config) -> ClientResult<Self> {
+        let missing = |name: &str| ClientError::Mail(format!("mail.{name} is not set"));
+        let mail = &config.mail;
+
+        let from: Mailbox = mail
+            .from
+            .parse()
+            .map_err(|err: lettre::address::AddressError| ClientError::Mail(err.to_string()))?;
+
+        match mail.backend {
+            MailBackend::Log => Ok(Arc::new(LogMailer::new(from))),
+            MailBackend::Smtp => {
+                let credentials: Option<(String, String)> =
+                    match (mail.username.clone(), mail.password.clone()) {
+                        (Some(username), Some(password)) => Some((username, password)),
+                        (None, None) => None,
+                        (Some(_), None) => return Err(missing("password")),
+                        (None, Some(_)) => return Err(missing("username")),
+                    };
+
+                Ok(Arc::new(
+                    SmtpMailer::new(
+                        from,
+                        mail.host.as_ref().ok_or(missing("host"))?,
+                        mail.port,
+                        mail.starttls.unwrap_or(true),
+                        credentials,
+                    )
+                    .map_err(|err| ClientError::Mail(err.to_string()))?,
+                ))
+            }
+        }
+    }
+}
@@ -1,4 +1,6 @@
+pub mod mail;
 pub mod postgres;
+pub mod storage;
 
 use crate::{config::Config, error::client::ClientResult};
 
@@ -0,0 +1,116 @@
+use std::{
+    io::{Error, ErrorKind, SeekFrom},
+    ops::Range,
+    path::{Component, Path, PathBuf},
+};
+
+use futures::{StreamExt, TryStreamExt};
+use tokio::{
+    fs::{self, File},
+    io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt},
+};
+use tokio_util::io::ReaderStream;
+
+use crate::error::storage::{StorageError, StorageResult};
+
+use super::{ByteStream, Storage};
+
+/// Files under a directory, keys are paths relative to it.
+#[derive(Debug)]
+pub struct LocalStorage {
+    root: PathBuf,
+}
+
+impl LocalStorage {
+    pub async fn new(root: impl AsRef<Path>) -> std::io::Result<Self> {
+        fs::create_dir_all(root.as_ref()).await?;
+
+        Ok(Self {
+            root: root.as_ref().to_path_buf(),
+        })
+    }
+
+    /// Path of `key`, keys leaving the root are refused.
+    fn path(&self, key: &str) -> StorageResult<PathBuf> {
+        let relative: &Path = Path::new(key);
+
+        if !relative
+            .components()
+            .all(|component| matches!(component, Component::Normal(_)))
+        {
+            return Err(StorageError::Io(Error::new(
+                ErrorKind::InvalidInput,
+                format!("Invalid key {key}"),
+            )));
+        }
+
+        Ok(self.root.join(relative))
+    }
+}
+
+#[async_trait::async_trait]
+impl Storage for LocalStorage {
+    async fn put(&self, key: &str, mut body: ByteStream, size: u64) -> StorageResult {
+        let path: PathBuf = self.path(key)?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).await?;
+        }
+
+        // Written aside and moved in place, so readers never see half a file
+        let part: PathBuf = path.with_extension("part");
+        let mut file: File = File::create(&part).await?;
+        let mut written: u64 = 0;
+
+        let result: std::io::Result<()> = async {
+            while let Some(chunk) = body.try_next().await? {
+                written += chunk.len() as u64;
+                file.write_all(&chunk).await?;
+            }
+            file.flush().await?;
+
+            match written == size {
+                true => Ok(()),
+                false => Err(Error::new(
+                    ErrorKind::InvalidData,
+                    format!("Expected {size} bytes, got {written}"),
+                )),
+            }
+        }
+        .await;
+
+        match result {
+            Ok(()) => Ok(fs::rename(&part, &path).await?),
+            Err(err) => {
+                let _ = fs::remove_file(&part).await;
+                Err(StorageError::Io(err))
+            }
+        }
+    }
+
+    async fn get(&self, key: &str, range: Option<Range<u64>>) -> StorageResult<ByteStream> {
+        let mut file: File = match File::open(self.path(key)?).await {
+            Ok(value) => value,
+            Err(err) if err.kind() == ErrorKind::NotFound => {
+                return Err(StorageError::NotFound(key.to_string()))
+            }
+            Err(err) => return Err(StorageError::Io(err)),
+        };
+
+        match range {
+            Some(range) => {
+                file.seek(SeekFrom::Start(range.start)).await?;
+
+                Ok(ReaderStream::new(file.take(range.end - range.start)).boxed())
+            }
+            None => Ok(ReaderStream::new(file).boxed()),
+        }
+    }
+
+    async fn delete(&self, key: &str) -> StorageResult {
+        match fs::remove_file(self.path(key)?).await {
+            Ok(()) => Ok(()),
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(()),
+            Err(err) => Err(StorageError::Io(err)),
+        }
+    }
+}
@@ -0,0 +1,105 @@
+pub mod local;
+pub mod s3;
+
+use std::{fmt::Debug, ops::Range, sync::Arc};
+
+use bytes::Bytes;
+use futures::{
+    future,
+    stream::{self, BoxStream},
+    StreamExt, TryStreamExt,
+};
+
+use crate::{
+    config::{storage::StorageBackend, Config},
+    error::{
+        client::{ClientError, ClientResult},
+        storage::StorageResult,
+    },
+};
+
+use super::ClientBuilder;
+use local::LocalStorage;
+use s3::S3Storage;
+
+/// Contents of a file, read or written a chunk at a time.
+pub type ByteStream = BoxStream<'static, Result<Bytes, std::io::Error>>;
+
+/// Keeps file contents by key, what they belong to is recorded in Postgres.
+#[async_trait::async_trait]
+pub trait Storage: Debug + Send + Sync {
+    /// Writes the `size` bytes of `body` under `key`, replacing what was
+    /// there. A body of another length fails and leaves nothing behind.
+    async fn put(&self, key: &str, body: ByteStream, size: u64) -> StorageResult;
+
+    /// Reads `range` of the object, or all of it.
+    async fn get(&self, key: &str, range: Option<Range<u64>>) -> StorageResult<ByteStream>;
+
+    /// Removes the object, a missing one is not an error.
+    async fn delete(&self, key: &str) -> StorageResult;
+
+    /// Writes contents already held in memory under `key`.
+    async fn put_bytes(&self, key: &str, body: Vec<u8>) -> StorageResult {
+        let size: u64 = body.len() as u64;
+
+        self.put(
+            key,
+            stream::once(future::ready(Ok(Bytes::from(body)))).boxed(),
+            size,
+        )
+        .await
+    }
+
+    /// Reads all of the object into memory.
+    async fn read(&self, key: &str) -> StorageResult<Vec<u8>> {
+        let mut body: ByteStream = self.get(key, None).await?;
+        let mut content: Vec<u8> = Vec::new();
+
+        while let Some(chunk) = body.try_next().await? {
+            content.extend_from_slice(&chunk);
+        }
+
+        Ok(content)
+    }
+}
+
+pub type StorageClient = Arc<dyn Storage>;
+
+#[async_trait::async_trait]
+impl ClientBuilder for StorageClient {
+    async fn from_config(config: &Config) -> ClientResult<Self> {
+        let missing = |name: &str| ClientError::Storage(format!("storage.{name} is not set"));
+        let storage = &config.storage;
+
+        match storage.backend {
+            StorageBackend::Local => {
+                let path: &String = storage.path.as_ref().ok_or(missing("path"))?;
+
+                Ok(Arc::new(
+                    LocalStorage::new(path)
+                        .await
+                        .map_err(|err| ClientError::Storage(err.to_string()))?,
+                ))
+            }
+            StorageBackend::S3 => {
+                let client: S3Storage = S3Storage::new(
+                    storage.endpoint.clone().ok_or(missing("endpoint"))?,
+                    storage.bucket.clone().ok_or(missing("bucket"))?,
+                    storage
+                        .region
+                        .clone()
+                        .unwrap_or_else(|| "us-east-1".to_string()),
+                    storage.access_key.clone().ok_or(missing("access_key"))?,
+                    storage.secret_key.clone().ok_or(missing("secret_key"))?,
+                );
+
+                client
+                    .create_bucket()
+                    .await
+                    .map_err(|err| ClientError::Storage(err.to_string()))?;
+
+                Ok(Arc::new(client))
+            }
+        }
+    }
+}
@@ -0,0 +1,195 @@
+use std::ops::Range;
+
+use chrono::{DateTime, Utc};
+use futures::{StreamExt, TryStreamExt};
+use hmac::{Hmac, Mac};
+use percent_encoding::{utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use reqwest::{header, Body, Client, Method, RequestBuilder, Response, StatusCode, Url};
+use sha2::{Digest, Sha256};
+
+use crate::error::storage::{StorageError, StorageResult};
+
+use super::{ByteStream, Storage};
+
+/// Bodies are sent as they stream in, so they are left out of signatures.
+const UNSIGNED_PAYLOAD: &str = "UNSIGNED-PAYLOAD";
+
+/// Characters of a key that stay as they are in a path, as S3 signs it.
+const KEY: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'_')
+    .remove(b'.')
+    .remove(b'~')
+    .remove(b'/');
+
+/// Objects of one bucket of an S3-compatible service, signed with AWS
+/// Signature Version 4. Buckets are addressed by path, which every
+/// S3-compatible service understands.
+#[derive(Debug)]
+pub struct S3Storage {
+    client: Client,
+    endpoint: String,
+    bucket: String,
+    region: String,
+    access_key: String,
+    secret_key: String,
+}
+
+impl S3Storage {
+    pub fn new(
+        endpoint: String,
+        bucket: String,
+        region: String,
+        access_key: String,
+        secret_key: String,
+    ) -> Self {
+        Self {
+            client: Client::new(),
+            endpoint: endpoint.trim_end_matches('/').to_string(),
+            bucket,
+            region,
+            access_key,
+            secret_key,
+        }
+    }
+
+    /// Creates the bucket unless it exists.
+    pub async fn create_bucket(&self) -> StorageResult {
+        let response: Response = self.request(Method::HEAD, None)?.send().await?;
+
+        if response.status() != StatusCode::NOT_FOUND {
+            return check(response).await.map(|_| ());
+        }
+
+        check(self.request(Method::PUT, None)?.send().await?)
+            .await
+            .map(|_| ())
+    }
+
+    /// Signed request for the bucket or one of its objects.
+    fn request(&self, method: Method, key: Option<&str>) -> StorageResult<RequestBuilder> {
+        let path: String = match key {
+            Some(key) => format!("/{}/{}", self.bucket, utf8_percent_encode(key, KEY)),
+            None => format!("/{}", self.bucket),
+        };
+        let url: Url = Url::parse(&format!("{}{}", self.endpoint, path)).map_err(|err| {
+            StorageError::Io(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                err.to_string(),
+            ))
+        })?;
+
+        let host: String = match (url.host_str(), url.port()) {
+            (Some(host), Some(port)) => format!("{host}:{port}"),
+            (Some(host), None) => host.to_string(),
+            (None, _) => String::new(),
+        };
+        let now: DateTime<Utc> = Utc::now();
+        let date: String = now.format("%Y%m%d").to_string();
+        let time: String = now.format("%Y%m%dT%H%M%SZ").to_string();
+
+        let signed_headers: &str = "host;x-amz-content-sha256;x-amz-date";
+        let canonical_request: String = format!(
+            "{method}\n{path}\n\nhost:{host}\nx-amz-content-sha256:{UNSIGNED_PAYLOAD}\nx-amz-date:{time}\n\n{signed_headers}\n{UNSIGNED_PAYLOAD}",
+            method = method.as_str(),
+            path = url.path(),
+        );
+        let scope: String = format!("{date}/{}/s3/aws4_request", self.region);
+        let string_to_sign: String = format!(
+            "AWS4-HMAC-SHA256\n{time}\n{scope}\n{}",
+            hex(&Sha256::digest(canonical_request.as_bytes()))
+        );
+
+        let key: Vec<u8> = [date.as_str(), self.region.as_str(), "s3", "aws4_request"]
+            .iter()
+            .fold(
+                format!("AWS4{}", self.secret_key).into_bytes(),
+                |key, value| hmac(&key, value.as_bytes()),
+            );
+        let signature: String = hex(&hmac(&key, string_to_sign.as_bytes()));
+
+        Ok(self
+            .client
+            .request(method, url)
+            .header("x-amz-content-sha256", UNSIGNED_PAYLOAD)
+            .header("x-amz-date", time)
+            .header(
+                header::AUTHORIZATION,
+                format!(
+                    "AWS4-HMAC-SHA256 Credential={}/{scope}, SignedHeaders={signed_headers}, Signature={signature}",
+                    self.access_key
+                ),
+            ))
+    }
+}
+
+#[async_trait::async_trait]
+impl Storage for S3Storage {
+    async fn put(&self, key: &str, body: ByteStream, size: u64) -> StorageResult {
+        let response: Response = self
+            .request(Method::PUT, Some(key))?
+            .header(header::CONTENT_LENGTH, size)
+            .body(Body::wrap_stream(body))
+            .send()
+            .await?;
+
+        check(response).await.map(|_| ())
+    }
+
+    async fn get(&self, key: &str, range: Option<Range<u64>>) -> StorageResult<ByteStream> {
+        let mut request: RequestBuilder = self.request(Method::GET, Some(key))?;
+        if let Some(range) = range {
+            request = request.header(
+                header::RANGE,
+                format!("bytes={}-{}", range.start, range.end - 1),
+            );
+        }
+
+        let response: Response = request.send().await?;
+        if response.status() == StatusCode::NOT_FOUND {
+            return Err(StorageError::NotFound(key.to_string()));
+        }
+
+        Ok(check(response)
+            .await?
+            .bytes_stream()
+            .map_err(std::io::Error::other)
+            .boxed())
+    }
+
+    async fn delete(&self, key: &str) -> StorageResult {
+        let response: Response = self.request(Method::DELETE, Some(key))?.send().await?;
+
+        match response.status() {
+            StatusCode::NOT_FOUND => Ok(()),
+            _ => check(response).await.map(|_| ()),
+        }
+    }
+}
+
+/// Passes a successful response on, turns others into an error.
+async fn check(response: Response) -> StorageResult<Response> {
+    let status: StatusCode = response.status();
+
+    match status.is_success() {
+        true => Ok(response),
+        false => Err(StorageError::Response {
+            status: status.as_u16(),
+            body: response.text().await.unwrap_or_default(),
+        }),
+    }
+}
+
+fn hmac(key: &[u8], value: &[u8]) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC takes keys of any size");
+    mac.update(value);
+
+    mac.finalize().into_bytes().to_vec()
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect::<String>()
+}
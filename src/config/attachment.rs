@@ -0,0 +1,9 @@
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct AttachmentConfig {
+    /// Largest file in bytes
+    pub max_size: u64,
+    /// Accepted MIME types, `type/*` accepts a whole type
+    pub content_types: Vec<String>,
+}
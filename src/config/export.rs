@@ -0,0 +1,9 @@
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct ExportConfig {
+    /// Seconds a finished export can be downloaded
+    pub expire: u64,
+    /// Seconds between runs building waiting exports and removing expired ones
+    pub interval: u64,
+}
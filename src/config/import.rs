@@ -0,0 +1,7 @@
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct ImportConfig {
+    /// Seconds between runs of imports still waiting
+    pub interval: u64,
+}
@@ -0,0 +1,24 @@
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum MailBackend {
+    /// Writes mails to the log instead of sending them, for development
+    Log,
+    Smtp,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct MailConfig {
+    pub backend: MailBackend,
+    /// Sender of every mail, such as `Task Flow <noreply@example.com>`
+    pub from: String,
+    /// Host of the SMTP relay
+    pub host: Option<String>,
+    /// Defaults to 587, or 25 without STARTTLS
+    pub port: Option<u16>,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    /// Whether to upgrade the connection with STARTTLS, on unless set
+    pub starttls: Option<bool>,
+}
@@ -1,15 +1,39 @@
+pub mod attachment;
 pub mod auth;
+pub mod export;
+pub mod import;
+pub mod mail;
 pub mod postgres;
 pub mod server;
+pub mod storage;
+pub mod task;
+pub mod trash;
+pub mod user;
 
+use attachment::AttachmentConfig;
 use auth::AuthConfig;
+use export::ExportConfig;
+use import::ImportConfig;
+use mail::MailConfig;
 use postgres::PostgresConfig;
 use serde::Deserialize;
 use server::ServerConfig;
+use storage::StorageConfig;
+use task::TaskConfig;
+use trash::TrashConfig;
+use user::UserConfig;
 
 #[derive(Debug, Deserialize, Clone)]
 pub struct Config {
+    pub attachment: AttachmentConfig,
     pub auth: AuthConfig,
+    pub export: ExportConfig,
+    pub import: ImportConfig,
+    pub mail: MailConfig,
     pub postgres: PostgresConfig,
     pub server: ServerConfig,
+    pub storage: StorageConfig,
+    pub task: TaskConfig,
+    pub trash: TrashConfig,
+    pub user: UserConfig,
 }
@@ -0,0 +1,24 @@
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum StorageBackend {
+    Local,
+    /// Any S3-compatible service, such as MinIO
+    S3,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct StorageConfig {
+    pub backend: StorageBackend,
+    /// Directory files are kept in by the local backend
+    pub path: Option<String>,
+    /// Base URL of the S3 service, buckets are addressed by path
+    pub endpoint: Option<String>,
+    pub bucket: Option<String>,
+    pub region: Option<String>,
+    pub access_key: Option<String>,
+    pub secret_key: Option<String>,
+    /// Seconds between runs removing files whose records are gone
+    pub cleanup_interval: u64,
+}
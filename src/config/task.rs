@@ -0,0 +1,9 @@
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct TaskConfig {
+    /// Refuse to start or finish a task while one of its blockers is unfinished
+    pub enforce_blockers: bool,
+    /// Seconds between position rebalancing runs
+    pub rebalance_interval: u64,
+}
@@ -0,0 +1,9 @@
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct TrashConfig {
+    /// Seconds a trashed item is kept before it is purged
+    pub retention: u64,
+    /// Seconds between purge runs
+    pub purge_interval: u64,
+}
@@ -0,0 +1,12 @@
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct UserConfig {
+    /// Let anyone search users by name, signed in or not
+    pub public_search: bool,
+    /// Seconds between requesting deletion of an account and its purge, the
+    /// request can be cancelled meanwhile
+    pub deletion_grace: u64,
+    /// Seconds between runs purging accounts whose grace period ended
+    pub deletion_interval: u64,
+}
@@ -7,8 +7,20 @@ pub const EMAIL_MAX_LENGTH: usize = 320;
 pub const PASSWORD_MIN_LENGTH: usize = 8;
 pub const PASSWORD_MAX_LENGTH: usize = 128;
 
+pub const USER_SEARCH_MIN_LENGTH: usize = 2;
+pub const USER_SEARCH_MAX_LENGTH: usize = 64;
+
+pub const DISPLAY_NAME_MIN_LENGTH: usize = 1;
+pub const DISPLAY_NAME_MAX_LENGTH: usize = 64;
+
+pub const BIO_MAX_LENGTH: usize = 1024;
+
+pub const LOCALE_MAX_LENGTH: usize = 35;
+pub const LOCALE_PATTERN: &str = r"^[a-zA-Z]{2,3}(-[a-zA-Z0-9]{2,8})*$";
+
 pub const AVATAR_MAX_SIZE: usize = 5 * 1024 * 1024;
-pub const AVATAR_SIDE_SIZE: usize = 518;
+pub const AVATAR_SIZES: [u32; 3] = [64, 128, 512];
+pub const AVATAR_MAX_DIMENSION: u32 = 8192;
 
 pub const TASK_NAME_MIN_LENGTH: usize = 4;
 pub const TASK_NAME_MAX_LENGTH: usize = 512;
@@ -16,5 +28,49 @@ pub const TASK_NAME_MAX_LENGTH: usize = 512;
 pub const TASK_DESCRIPTION_MIN_LENGTH: usize = 4;
 pub const TASK_DESCRIPTION_MAX_LENGTH: usize = 4096;
 
+pub const TASK_BULK_MAX_SIZE: usize = 500;
+
+pub const TASK_QUICK_TEXT_MAX_LENGTH: usize = 4096;
+
+pub const TASK_POSITION_MAX_LENGTH: usize = 16;
+
 pub const TASK_COMMENT_TEXT_MIN_LENGTH: usize = 4;
 pub const TASK_COMMENT_TEXT_MAX_LENGTH: usize = 4096;
+
+pub const TRASH_PURGE_BATCH_SIZE: u64 = 500;
+
+pub const SYNC_PULL_MAX_LIMIT: u64 = 1000;
+
+pub const WORKFLOW_STATUS_NAME_MIN_LENGTH: usize = 1;
+pub const WORKFLOW_STATUS_NAME_MAX_LENGTH: usize = 32;
+
+pub const CUSTOM_FIELD_NAME_MIN_LENGTH: usize = 1;
+pub const CUSTOM_FIELD_NAME_MAX_LENGTH: usize = 32;
+
+pub const CUSTOM_FIELD_OPTIONS_MAX_COUNT: usize = 64;
+pub const CUSTOM_FIELD_OPTION_MAX_LENGTH: usize = 64;
+
+pub const CUSTOM_FIELD_TEXT_MAX_LENGTH: usize = 1024;
+pub const CUSTOM_FIELD_URL_MAX_LENGTH: usize = 2048;
+
+pub const SAVED_FILTER_NAME_MIN_LENGTH: usize = 1;
+pub const SAVED_FILTER_NAME_MAX_LENGTH: usize = 64;
+pub const SAVED_FILTER_EXPRESSION_MAX_LENGTH: usize = 1024;
+
+pub const CALENDAR_TOKEN_SIZE: usize = 32;
+pub const CALENDAR_MAX_TASKS: u64 = 5000;
+
+pub const APP_PASSWORD_NAME_MIN_LENGTH: usize = 1;
+pub const APP_PASSWORD_NAME_MAX_LENGTH: usize = 64;
+pub const APP_PASSWORD_SIZE: usize = 24;
+
+pub const DAV_PATH: &str = "/dav";
+pub const DAV_SYNC_TOKEN_PREFIX: &str = "data:,task-flow-sync/";
+
+pub const EXPORT_TOKEN_SIZE: usize = 32;
+
+pub const TASK_IMPORT_CONTENT_MAX_LENGTH: usize = 2 * 1024 * 1024;
+pub const TASK_IMPORT_MAX_ROWS: usize = 5000;
+
+pub const ATTACHMENT_NAME_MAX_LENGTH: usize = 255;
+pub const STORAGE_CLEANUP_BATCH_SIZE: u64 = 100;
@@ -0,0 +1,38 @@
+use garde::Validate;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::{constants, entity::prelude::UserModel};
+
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct AccountDeletionCreateDto {
+    /// Current password, asked again as the deletion can't be undone once
+    /// the grace period ends
+    #[garde(length(min = constants::PASSWORD_MIN_LENGTH, max = constants::PASSWORD_MAX_LENGTH))]
+    #[schema(example = "some_password12345")]
+    pub password: String,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct AccountDeletionReadDto {
+    #[schema(example = "2024-05-15T15:36:21.434500+03:00")]
+    pub requested_at: String,
+
+    /// When the account and everything it owns is purged, it can be
+    /// cancelled until then
+    #[schema(example = "2024-05-22T15:36:21.434500+03:00")]
+    pub scheduled_at: String,
+}
+
+impl AccountDeletionReadDto {
+    /// Deletion pending on `model`, if any.
+    pub fn new(model: &UserModel) -> Option<Self> {
+        match (model.deletion_requested_at, model.deletion_scheduled_at) {
+            (Some(requested_at), Some(scheduled_at)) => Some(Self {
+                requested_at: requested_at.to_rfc3339(),
+                scheduled_at: scheduled_at.to_rfc3339(),
+            }),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,42 @@
+use serde::Serialize;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::entity::{prelude::AccountExportModel, sea_orm_active_enums::ExportStatus};
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct AccountExportReadDto {
+    pub id: Uuid,
+
+    #[schema(example = "Ready")]
+    pub status: ExportStatus,
+
+    /// Download link of a ready export, it needs no authentication
+    #[schema(example = "/export/download/3f1c9a0e5b7d42c8a6e1f0b9d8c7a6e5")]
+    pub path: Option<String>,
+
+    /// Archive size in bytes
+    pub size: Option<usize>,
+
+    /// Why the export failed
+    pub error: Option<String>,
+
+    pub expires_at: Option<String>,
+    pub updated_at: String,
+    pub created_at: String,
+}
+
+impl From<AccountExportModel> for AccountExportReadDto {
+    fn from(value: AccountExportModel) -> Self {
+        Self {
+            id: value.id,
+            status: value.status,
+            path: value.token.map(|token| format!("/export/download/{token}")),
+            size: value.size.map(|size| size as usize),
+            error: value.error,
+            expires_at: value.expires_at.map(|value| value.to_rfc3339()),
+            updated_at: value.updated_at.to_rfc3339(),
+            created_at: value.created_at.to_rfc3339(),
+        }
+    }
+}
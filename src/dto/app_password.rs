@@ -0,0 +1,41 @@
+use garde::Validate;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::constants;
+use crate::entity::prelude::AppPasswordModel;
+
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct AppPasswordCreateDto {
+    #[garde(length(min = constants::APP_PASSWORD_NAME_MIN_LENGTH, max = constants::APP_PASSWORD_NAME_MAX_LENGTH))]
+    #[schema(example = "Phone reminders")]
+    pub name: String,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct AppPasswordReadDto {
+    pub id: Uuid,
+
+    #[schema(example = "Phone reminders")]
+    pub name: String,
+
+    /// Only returned when the password is created, it is not stored
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub password: Option<String>,
+
+    pub last_used_at: Option<String>,
+    pub created_at: String,
+}
+
+impl From<AppPasswordModel> for AppPasswordReadDto {
+    fn from(value: AppPasswordModel) -> Self {
+        Self {
+            id: value.id,
+            name: value.name,
+            password: None,
+            last_used_at: value.last_used_at.map(|value| value.to_rfc3339()),
+            created_at: value.created_at.to_rfc3339(),
+        }
+    }
+}
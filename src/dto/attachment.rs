@@ -0,0 +1,96 @@
+use std::future::{ready, Ready};
+use std::ops::Range as ByteRange;
+
+use actix_multipart::form::{tempfile::TempFile, MultipartForm};
+use actix_web::http::header::{ByteRangeSpec, Header, Range};
+use actix_web::FromRequest;
+use serde::Serialize;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::entity::prelude::AttachmentModel;
+use crate::error::service::{ServiceError, ServiceResult};
+
+#[derive(Debug, MultipartForm, ToSchema)]
+pub struct AttachmentUploadDto {
+    #[schema(value_type = String, format = Binary)]
+    pub file: TempFile,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct AttachmentReadDto {
+    pub id: Uuid,
+    pub task_id: Uuid,
+
+    /// Comment the file is attached to, `null` for files of the task itself
+    pub comment_id: Option<Uuid>,
+
+    #[schema(example = "report.pdf")]
+    pub name: String,
+
+    #[schema(example = "application/pdf")]
+    pub content_type: String,
+
+    /// Size in bytes
+    pub size: i64,
+
+    /// SHA-256 of the contents in hex, also the `ETag` of downloads
+    pub sha256: String,
+
+    /// Download link, it honours `Range` requests
+    #[schema(
+        example = "/task/4a0c1f9e-7d2b-4c8e-9f1a-3b5d6e7f8a90/attachment/9b2d1c3e-7f4a-4e8b-a1d2-3c4b5a6f7e80/content"
+    )]
+    pub path: String,
+
+    pub created_at: String,
+}
+
+/// Byte range asked for by `Range`. Only a single range is served, a list of
+/// them gets the whole file as the header allows.
+#[derive(Debug, Clone, Default)]
+pub struct RangeDto(Option<ByteRangeSpec>);
+
+impl From<AttachmentModel> for AttachmentReadDto {
+    fn from(value: AttachmentModel) -> Self {
+        Self {
+            path: format!("/task/{}/attachment/{}/content", value.task_id, value.id),
+            id: value.id,
+            task_id: value.task_id,
+            comment_id: value.comment_id,
+            name: value.name,
+            content_type: value.content_type,
+            size: value.size,
+            sha256: value.sha256,
+            created_at: value.created_at.to_rfc3339(),
+        }
+    }
+}
+
+impl RangeDto {
+    /// Bytes to send of a file of `size`, `None` for all of them.
+    pub fn resolve(&self, size: u64) -> ServiceResult<Option<ByteRange<u64>>> {
+        match &self.0 {
+            Some(spec) => match spec.to_satisfiable_range(size) {
+                Some((start, end)) => Ok(Some(start..end + 1)),
+                None => Err(ServiceError::RangeNotSatisfiable(size)),
+            },
+            None => Ok(None),
+        }
+    }
+}
+
+impl FromRequest for RangeDto {
+    type Error = ServiceError;
+    type Future = Ready<ServiceResult<Self>>;
+
+    fn from_request(req: &actix_web::HttpRequest, _: &mut actix_web::dev::Payload) -> Self::Future {
+        // Malformed and non-byte ranges are ignored, as if the header was absent
+        match Range::parse(req) {
+            Ok(Range::Bytes(specs)) if specs.len() == 1 => {
+                ready(Ok(Self(specs.into_iter().next())))
+            }
+            _ => ready(Ok(Self(None))),
+        }
+    }
+}
@@ -0,0 +1,251 @@
+use std::str::FromStr;
+
+use percent_encoding::{percent_decode_str, utf8_percent_encode, AsciiSet, CONTROLS};
+use roxmltree::{Document, Node};
+
+use crate::constants;
+use crate::error::service::ServiceError;
+
+pub const DAV_NAMESPACE: &str = "DAV:";
+pub const CALDAV_NAMESPACE: &str = "urn:ietf:params:xml:ns:caldav";
+pub const CALENDAR_SERVER_NAMESPACE: &str = "http://calendarserver.org/ns/";
+
+/// Property named in a request, unknown ones are answered with `404`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DavProperty {
+    pub namespace: String,
+    pub name: String,
+}
+
+/// Properties asked for by `PROPFIND` or a `REPORT`.
+#[derive(Debug, Clone, Default)]
+pub enum DavPropsDto {
+    /// An empty `PROPFIND` body means the same
+    #[default]
+    AllProp,
+    PropName,
+    Prop(Vec<DavProperty>),
+}
+
+#[derive(Debug)]
+pub enum DavReportDto {
+    /// Only component filters are honoured, tasks are never filtered by
+    /// their properties
+    CalendarQuery {
+        props: DavPropsDto,
+        components: Vec<String>,
+    },
+    CalendarMultiget {
+        props: DavPropsDto,
+        hrefs: Vec<String>,
+    },
+    SyncCollection {
+        props: DavPropsDto,
+        token: String,
+    },
+}
+
+/// Value of the `Depth` header, `infinity` is served as `1`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DavDepth {
+    Zero,
+    One,
+}
+
+impl DavProperty {
+    pub fn new(namespace: &str, name: &str) -> Self {
+        Self {
+            namespace: namespace.to_string(),
+            name: name.to_string(),
+        }
+    }
+
+    pub fn is(&self, namespace: &str, name: &str) -> bool {
+        self.namespace == namespace && self.name == name
+    }
+}
+
+impl From<Option<&str>> for DavDepth {
+    fn from(value: Option<&str>) -> Self {
+        match value.map(str::trim) {
+            Some("0") => Self::Zero,
+            _ => Self::One,
+        }
+    }
+}
+
+impl FromStr for DavPropsDto {
+    type Err = ServiceError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.trim().is_empty() {
+            return Ok(Self::AllProp);
+        }
+
+        let document: Document = parse(s)?;
+        let root: Node = document.root_element();
+
+        if !is(&root, DAV_NAMESPACE, "propfind") {
+            return Err(ServiceError::BadRequest(
+                "Expected a 'propfind' element".to_string(),
+            ));
+        }
+
+        Ok(props(&root))
+    }
+}
+
+impl FromStr for DavReportDto {
+    type Err = ServiceError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let document: Document = parse(s)?;
+        let root: Node = document.root_element();
+
+        if is(&root, CALDAV_NAMESPACE, "calendar-query") {
+            return Ok(Self::CalendarQuery {
+                props: props(&root),
+                components: root
+                    .descendants()
+                    .filter(|node| is(node, CALDAV_NAMESPACE, "comp-filter"))
+                    .filter_map(|node| node.attribute("name"))
+                    .map(str::to_ascii_uppercase)
+                    .collect::<Vec<String>>(),
+            });
+        }
+        if is(&root, CALDAV_NAMESPACE, "calendar-multiget") {
+            return Ok(Self::CalendarMultiget {
+                props: props(&root),
+                hrefs: root
+                    .children()
+                    .filter(|node| is(node, DAV_NAMESPACE, "href"))
+                    .map(|node| node.text().unwrap_or_default().trim().to_string())
+                    .collect::<Vec<String>>(),
+            });
+        }
+        if is(&root, DAV_NAMESPACE, "sync-collection") {
+            return Ok(Self::SyncCollection {
+                props: props(&root),
+                token: root
+                    .children()
+                    .find(|node| is(node, DAV_NAMESPACE, "sync-token"))
+                    .and_then(|node| node.text())
+                    .unwrap_or_default()
+                    .trim()
+                    .to_string(),
+            });
+        }
+
+        Err(ServiceError::DavPrecondition(
+            "D:supported-report".to_string(),
+        ))
+    }
+}
+
+fn parse(s: &str) -> Result<Document<'_>, ServiceError> {
+    Document::parse(s).map_err(|err| ServiceError::BadRequest(format!("Invalid XML body: {err}")))
+}
+
+fn is(node: &Node, namespace: &str, name: &str) -> bool {
+    node.is_element()
+        && node.tag_name().name() == name
+        && node.tag_name().namespace() == Some(namespace)
+}
+
+/// Reads the `prop`, `allprop` or `propname` child of a request element.
+fn props(node: &Node) -> DavPropsDto {
+    for child in node.children() {
+        if is(&child, DAV_NAMESPACE, "allprop") {
+            return DavPropsDto::AllProp;
+        }
+        if is(&child, DAV_NAMESPACE, "propname") {
+            return DavPropsDto::PropName;
+        }
+        if is(&child, DAV_NAMESPACE, "prop") {
+            return DavPropsDto::Prop(
+                child
+                    .children()
+                    .filter(Node::is_element)
+                    .map(|node| {
+                        DavProperty::new(
+                            node.tag_name().namespace().unwrap_or_default(),
+                            node.tag_name().name(),
+                        )
+                    })
+                    .collect::<Vec<DavProperty>>(),
+            );
+        }
+    }
+
+    DavPropsDto::AllProp
+}
+
+/// Characters of a resource name that can't appear as is in a path segment.
+const SEGMENT: &AsciiSet = &CONTROLS
+    .add(b' ')
+    .add(b'"')
+    .add(b'#')
+    .add(b'%')
+    .add(b'/')
+    .add(b'<')
+    .add(b'>')
+    .add(b'?')
+    .add(b'`')
+    .add(b'{')
+    .add(b'}');
+
+/// Resources of the DAV tree, each user only sees their own calendar.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DavResource {
+    Root,
+    Principal,
+    /// Calendar home, it holds a single task calendar
+    Home,
+    Calendar,
+    /// Task by resource name, `{id}.ics` unless a client chose the name
+    Object(String),
+}
+
+impl DavResource {
+    /// Resolves a path relative to the DAV root, still percent-encoded.
+    pub fn from_path(path: &str) -> Option<Self> {
+        let path: String = percent_decode_str(path).decode_utf8().ok()?.into_owned();
+
+        match path.trim_start_matches('/').trim_end_matches('/') {
+            "" => Some(Self::Root),
+            "principal" => Some(Self::Principal),
+            "calendars" => Some(Self::Home),
+            "calendars/tasks" => Some(Self::Calendar),
+            value => match value.strip_prefix("calendars/tasks/") {
+                Some(name) if !name.contains('/') && !path.ends_with('/') => {
+                    Some(Self::Object(name.to_string()))
+                }
+                _ => None,
+            },
+        }
+    }
+
+    /// Resolves an `href` of a request body, absolute URLs included.
+    pub fn from_href(href: &str) -> Option<Self> {
+        let path: &str = match href.split_once("://") {
+            Some((_, rest)) => &rest[rest.find('/')?..],
+            None => href,
+        };
+
+        Self::from_path(path.strip_prefix(constants::DAV_PATH)?)
+    }
+
+    pub fn href(&self) -> String {
+        match self {
+            Self::Root => format!("{}/", constants::DAV_PATH),
+            Self::Principal => format!("{}/principal/", constants::DAV_PATH),
+            Self::Home => format!("{}/calendars/", constants::DAV_PATH),
+            Self::Calendar => format!("{}/calendars/tasks/", constants::DAV_PATH),
+            Self::Object(name) => format!(
+                "{}/calendars/tasks/{}",
+                constants::DAV_PATH,
+                utf8_percent_encode(name, SEGMENT)
+            ),
+        }
+    }
+}
@@ -0,0 +1,68 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::constants;
+use crate::dto::task::TaskGetQuery;
+use crate::entity::sea_orm_active_enums::{TaskPriority, TaskStatus};
+
+/// How tasks appear in a calendar.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum CalendarComponent {
+    /// `VTODO` for every task
+    #[default]
+    Todo,
+    /// `VEVENT` for tasks with a deadline, for clients that ignore to-dos
+    Event,
+}
+
+/// Filters of [`TaskGetQuery`] without paging.
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CalendarQuery {
+    pub status: Option<TaskStatus>,
+    pub status_id: Option<Uuid>,
+    pub priority: Option<TaskPriority>,
+    pub actionable: Option<bool>,
+    pub field_id: Option<Uuid>,
+    pub field_value: Option<String>,
+
+    #[serde(default)]
+    pub component: CalendarComponent,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct CalendarTokenReadDto {
+    /// Secret of the feed, anyone knowing it can read the tasks
+    pub token: String,
+
+    #[schema(example = "/calendar/feed/3f9a0c...e1.ics")]
+    pub path: String,
+}
+
+impl From<CalendarQuery> for TaskGetQuery {
+    fn from(value: CalendarQuery) -> Self {
+        Self {
+            limit: constants::CALENDAR_MAX_TASKS,
+            offset: 0,
+            status: value.status,
+            status_id: value.status_id,
+            priority: value.priority,
+            actionable: value.actionable,
+            field_id: value.field_id,
+            field_value: value.field_value,
+            sort_field_id: None,
+            sort: None,
+            descending: None,
+        }
+    }
+}
+
+impl CalendarTokenReadDto {
+    pub fn new(token: String) -> Self {
+        Self {
+            path: format!("/calendar/feed/{token}.ics"),
+            token,
+        }
+    }
+}
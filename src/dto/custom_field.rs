@@ -0,0 +1,79 @@
+use garde::Validate;
+use sea_orm::{IntoActiveModel, Set};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::constants;
+use crate::entity::prelude::{CustomFieldActiveModel, CustomFieldModel};
+use crate::entity::sea_orm_active_enums::CustomFieldKind;
+
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct CustomFieldCreateDto {
+    #[garde(length(min = constants::CUSTOM_FIELD_NAME_MIN_LENGTH, max = constants::CUSTOM_FIELD_NAME_MAX_LENGTH))]
+    #[schema(example = "Client")]
+    pub name: String,
+
+    #[garde(skip)]
+    #[schema(example = "Select")]
+    pub kind: CustomFieldKind,
+
+    /// Allowed values, required for `Select` and `MultiSelect` only
+    #[garde(length(max = constants::CUSTOM_FIELD_OPTIONS_MAX_COUNT), inner(length(min = 1, max = constants::CUSTOM_FIELD_OPTION_MAX_LENGTH)))]
+    #[schema(example = json!(["Acme", "Globex"]))]
+    pub options: Option<Vec<String>>,
+}
+
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct CustomFieldUpdateDto {
+    #[garde(length(min = constants::CUSTOM_FIELD_NAME_MIN_LENGTH, max = constants::CUSTOM_FIELD_NAME_MAX_LENGTH))]
+    #[schema(example = "Client")]
+    pub name: Option<String>,
+
+    /// Replaces the allowed values, options still used by tasks can't be
+    /// removed
+    #[garde(length(max = constants::CUSTOM_FIELD_OPTIONS_MAX_COUNT), inner(length(min = 1, max = constants::CUSTOM_FIELD_OPTION_MAX_LENGTH)))]
+    #[schema(example = json!(["Acme", "Globex", "Initech"]))]
+    pub options: Option<Vec<String>>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct CustomFieldReadDto {
+    pub id: Uuid,
+
+    #[schema(example = "Client")]
+    pub name: String,
+
+    #[schema(example = "Select")]
+    pub kind: CustomFieldKind,
+
+    #[schema(example = json!(["Acme", "Globex"]))]
+    pub options: Vec<String>,
+
+    pub updated_at: String,
+    pub created_at: String,
+}
+
+impl IntoActiveModel<CustomFieldActiveModel> for CustomFieldCreateDto {
+    fn into_active_model(self) -> CustomFieldActiveModel {
+        CustomFieldActiveModel {
+            name: Set(self.name),
+            kind: Set(self.kind),
+            options: Set(serde_json::json!(self.options.unwrap_or_default())),
+            ..Default::default()
+        }
+    }
+}
+
+impl From<CustomFieldModel> for CustomFieldReadDto {
+    fn from(value: CustomFieldModel) -> Self {
+        Self {
+            id: value.id,
+            name: value.name,
+            kind: value.kind,
+            options: serde_json::from_value(value.options).unwrap_or_default(),
+            updated_at: value.updated_at.to_rfc3339(),
+            created_at: value.created_at.to_rfc3339(),
+        }
+    }
+}
@@ -1,4 +1,17 @@
+pub mod account_deletion;
+pub mod account_export;
+pub mod app_password;
+pub mod attachment;
 pub mod auth;
+pub mod caldav;
+pub mod calendar;
+pub mod custom_field;
 pub mod error;
+pub mod precondition;
+pub mod saved_filter;
+pub mod sync;
 pub mod task;
+pub mod task_import;
 pub mod user;
+pub mod user_preference;
+pub mod workflow_status;
@@ -0,0 +1,160 @@
+use std::future::{ready, Ready};
+
+use actix_web::http::header::{ETag, EntityTag, Header, IfMatch, IfNoneMatch};
+use actix_web::{FromRequest, HttpResponse, HttpResponseBuilder};
+use serde::Serialize;
+
+use crate::error::service::{ServiceError, ServiceResult};
+
+/// Versions the client expects the record to have, taken from `If-Match`.
+#[derive(Debug, Clone, Default)]
+pub enum IfMatchDto {
+    #[default]
+    Any,
+    Versions(Vec<i32>),
+}
+
+/// Versions the client already holds, taken from `If-None-Match`.
+#[derive(Debug, Clone, Default)]
+pub enum IfNoneMatchDto {
+    #[default]
+    None,
+    Any,
+    Versions(Vec<i32>),
+}
+
+/// Entity tags the client already holds, taken from `If-None-Match`, for
+/// responses tagged by their contents rather than a version.
+#[derive(Debug, Clone, Default)]
+pub enum IfNoneMatchTagDto {
+    #[default]
+    None,
+    Any,
+    Tags(Vec<EntityTag>),
+}
+
+pub trait VersionedDto: Serialize {
+    fn version(&self) -> i32;
+}
+
+pub fn etag(version: i32) -> ETag {
+    ETag(EntityTag::new_strong(version.to_string()))
+}
+
+impl IfMatchDto {
+    pub fn check(&self, version: i32) -> ServiceResult {
+        match self {
+            Self::Any => Ok(()),
+            Self::Versions(values) if values.contains(&version) => Ok(()),
+            Self::Versions(_) => Err(ServiceError::PreconditionFailed(version)),
+        }
+    }
+}
+
+impl From<Option<i32>> for IfMatchDto {
+    fn from(value: Option<i32>) -> Self {
+        match value {
+            Some(version) => Self::Versions(vec![version]),
+            None => Self::Any,
+        }
+    }
+}
+
+impl IfNoneMatchDto {
+    pub fn matches(&self, version: i32) -> bool {
+        match self {
+            Self::None => false,
+            Self::Any => true,
+            Self::Versions(values) => values.contains(&version),
+        }
+    }
+
+    /// Builds `304 Not Modified` when the client copy is current, the full
+    /// response otherwise; both carry the `ETag` of `body`.
+    pub fn respond<T: VersionedDto>(
+        &self,
+        mut builder: HttpResponseBuilder,
+        body: T,
+    ) -> HttpResponse {
+        let version: i32 = body.version();
+
+        if self.matches(version) {
+            return HttpResponse::NotModified()
+                .insert_header(etag(version))
+                .finish();
+        }
+
+        builder.insert_header(etag(version)).json(body)
+    }
+}
+
+impl IfNoneMatchTagDto {
+    pub fn matches(&self, tag: &str) -> bool {
+        match self {
+            Self::None => false,
+            Self::Any => true,
+            Self::Tags(values) => values
+                .iter()
+                .any(|value| value.weak_eq(&EntityTag::new_strong(tag.to_string()))),
+        }
+    }
+}
+
+impl FromRequest for IfMatchDto {
+    type Error = ServiceError;
+    type Future = Ready<ServiceResult<Self>>;
+
+    fn from_request(req: &actix_web::HttpRequest, _: &mut actix_web::dev::Payload) -> Self::Future {
+        if !req.headers().contains_key(IfMatch::name()) {
+            return ready(Ok(Self::Any));
+        }
+
+        match IfMatch::parse(req) {
+            Ok(IfMatch::Any) => ready(Ok(Self::Any)),
+            Ok(IfMatch::Items(tags)) => ready(Ok(Self::Versions(
+                tags.iter()
+                    .filter(|tag| !tag.weak)
+                    .filter_map(|tag| tag.tag().parse::<i32>().ok())
+                    .collect::<Vec<i32>>(),
+            ))),
+            Err(_) => ready(Err(ServiceError::BadRequest(
+                "Invalid 'If-Match' header".to_string(),
+            ))),
+        }
+    }
+}
+
+impl FromRequest for IfNoneMatchDto {
+    type Error = ServiceError;
+    type Future = Ready<ServiceResult<Self>>;
+
+    fn from_request(req: &actix_web::HttpRequest, _: &mut actix_web::dev::Payload) -> Self::Future {
+        if !req.headers().contains_key(IfNoneMatch::name()) {
+            return ready(Ok(Self::None));
+        }
+
+        match IfNoneMatch::parse(req) {
+            Ok(IfNoneMatch::Any) => ready(Ok(Self::Any)),
+            // Weak comparison is allowed for `If-None-Match`
+            Ok(IfNoneMatch::Items(tags)) => ready(Ok(Self::Versions(
+                tags.iter()
+                    .filter_map(|tag| tag.tag().parse::<i32>().ok())
+                    .collect::<Vec<i32>>(),
+            ))),
+            Err(_) => ready(Ok(Self::None)),
+        }
+    }
+}
+
+impl FromRequest for IfNoneMatchTagDto {
+    type Error = ServiceError;
+    type Future = Ready<ServiceResult<Self>>;
+
+    fn from_request(req: &actix_web::HttpRequest, _: &mut actix_web::dev::Payload) -> Self::Future {
+        match IfNoneMatch::parse(req) {
+            Ok(IfNoneMatch::Any) => ready(Ok(Self::Any)),
+            Ok(IfNoneMatch::Items(tags)) => ready(Ok(Self::Tags(tags))),
+            Err(_) => ready(Ok(Self::None)),
+        }
+    }
+}
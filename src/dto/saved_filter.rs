@@ -0,0 +1,74 @@
+use garde::Validate;
+use sea_orm::{IntoActiveModel, Set};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::constants;
+use crate::entity::prelude::{SavedFilterActiveModel, SavedFilterModel};
+
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct SavedFilterCreateDto {
+    #[garde(length(min = constants::SAVED_FILTER_NAME_MIN_LENGTH, max = constants::SAVED_FILTER_NAME_MAX_LENGTH))]
+    #[schema(example = "Due this week")]
+    pub name: String,
+
+    /// Terms `status:`, `priority:`, `deadline:`, `text:` and `blocked:`
+    /// joined with `and`, `or`, `not` and parentheses
+    #[garde(length(min = 1, max = constants::SAVED_FILTER_EXPRESSION_MAX_LENGTH))]
+    #[schema(example = "deadline:\"next 7 days\" and not status:done")]
+    pub expression: String,
+}
+
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct SavedFilterUpdateDto {
+    #[garde(length(min = constants::SAVED_FILTER_NAME_MIN_LENGTH, max = constants::SAVED_FILTER_NAME_MAX_LENGTH))]
+    #[schema(example = "Due this week")]
+    pub name: Option<String>,
+
+    #[garde(length(min = 1, max = constants::SAVED_FILTER_EXPRESSION_MAX_LENGTH))]
+    #[schema(example = "deadline:\"next 7 days\" and priority:high")]
+    pub expression: Option<String>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct SavedFilterRunQuery {
+    pub limit: u64,
+    pub offset: u64,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SavedFilterReadDto {
+    pub id: Uuid,
+
+    #[schema(example = "Due this week")]
+    pub name: String,
+
+    #[schema(example = "deadline:\"next 7 days\" and not status:done")]
+    pub expression: String,
+
+    pub updated_at: String,
+    pub created_at: String,
+}
+
+impl IntoActiveModel<SavedFilterActiveModel> for SavedFilterCreateDto {
+    fn into_active_model(self) -> SavedFilterActiveModel {
+        SavedFilterActiveModel {
+            name: Set(self.name),
+            expression: Set(self.expression),
+            ..Default::default()
+        }
+    }
+}
+
+impl From<SavedFilterModel> for SavedFilterReadDto {
+    fn from(value: SavedFilterModel) -> Self {
+        Self {
+            id: value.id,
+            name: value.name,
+            expression: value.expression,
+            updated_at: value.updated_at.to_rfc3339(),
+            created_at: value.created_at.to_rfc3339(),
+        }
+    }
+}
@@ -0,0 +1,167 @@
+use std::str::FromStr;
+
+use garde::Validate;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::constants;
+use crate::entity::prelude::TombstoneModel;
+use crate::entity::sea_orm_active_enums::SyncEntity;
+use crate::error::service::ServiceError;
+
+use super::error::ErrorDto;
+use super::task::{
+    TaskCommentCreateDto, TaskCommentReadDto, TaskCommentUpdateDto, TaskCreateDto, TaskReadDto,
+    TaskUpdateDto,
+};
+
+/// Opaque position in the change sequence handed out to clients. A pull also
+/// keeps the snapshot it read in, as writers still running then may hold
+/// positions below it.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SyncToken {
+    pub seq: i64,
+    /// Postgres snapshot as `xmin:xmax:xip,...`
+    pub snapshot: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct SyncPullQuery {
+    #[garde(skip)]
+    pub token: Option<String>,
+
+    #[garde(range(min = 1, max = constants::SYNC_PULL_MAX_LIMIT))]
+    pub limit: u64,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct TombstoneReadDto {
+    #[schema(example = "Task")]
+    pub entity: SyncEntity,
+
+    #[schema(example = "00000000-0000-0000-0000-000000000000")]
+    pub id: Uuid,
+
+    #[schema(example = "2024-05-15T15:36:21.434500+03:00")]
+    pub deleted_at: String,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SyncPullDto {
+    #[schema(example = "000000000000002a.1043:1047:1045")]
+    pub token: String,
+    pub has_more: bool,
+    pub tasks: Vec<TaskReadDto>,
+    pub comments: Vec<TaskCommentReadDto>,
+    pub tombstones: Vec<TombstoneReadDto>,
+}
+
+/// Client-side change; `version` makes updates and deletes conditional like `If-Match`.
+#[derive(Debug, Deserialize, ToSchema)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum SyncMutationDto {
+    CreateTask {
+        body: TaskCreateDto,
+    },
+    UpdateTask {
+        id: Uuid,
+        version: Option<i32>,
+        body: TaskUpdateDto,
+    },
+    DeleteTask {
+        id: Uuid,
+        version: Option<i32>,
+    },
+    CreateComment {
+        task_id: Uuid,
+        body: TaskCommentCreateDto,
+    },
+    UpdateComment {
+        task_id: Uuid,
+        id: Uuid,
+        version: Option<i32>,
+        body: TaskCommentUpdateDto,
+    },
+    DeleteComment {
+        task_id: Uuid,
+        id: Uuid,
+        version: Option<i32>,
+    },
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct SyncPushDto {
+    pub mutations: Vec<SyncMutationDto>,
+}
+
+#[derive(Debug, Default, Serialize, ToSchema)]
+pub struct SyncMutationResultDto {
+    #[schema(example = 0)]
+    pub index: usize,
+
+    #[schema(example = 201)]
+    pub status: u16,
+
+    pub task: Option<TaskReadDto>,
+    pub comment: Option<TaskCommentReadDto>,
+    pub error: Option<ErrorDto>,
+}
+
+impl FromStr for SyncToken {
+    type Err = ServiceError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let invalid = || ServiceError::BadRequest("Invalid sync token".to_string());
+
+        // Tokens from before snapshots were kept have none
+        let (seq, snapshot) = match value.split_once('.') {
+            Some((seq, snapshot)) => (seq, Some(snapshot)),
+            None => (value, None),
+        };
+
+        let seq: i64 = match i64::from_str_radix(seq, 16) {
+            Ok(value) if value >= 0 => value,
+            _ => return Err(invalid()),
+        };
+
+        if let Some(snapshot) = snapshot {
+            let parts: Vec<&str> = snapshot.split(':').collect();
+            let valid: bool = parts.len() == 3
+                && parts[0].parse::<u64>().is_ok()
+                && parts[1].parse::<u64>().is_ok()
+                && (parts[2].is_empty()
+                    || parts[2].split(',').all(|xid| xid.parse::<u64>().is_ok()));
+
+            if !valid {
+                return Err(invalid());
+            }
+        }
+
+        Ok(Self {
+            seq,
+            snapshot: snapshot.map(|value| value.to_string()),
+        })
+    }
+}
+
+impl std::fmt::Display for SyncToken {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:016x}", self.seq)?;
+
+        match &self.snapshot {
+            Some(snapshot) => write!(f, ".{snapshot}"),
+            None => Ok(()),
+        }
+    }
+}
+
+impl From<TombstoneModel> for TombstoneReadDto {
+    fn from(value: TombstoneModel) -> Self {
+        Self {
+            entity: value.entity,
+            id: value.entity_id,
+            deleted_at: value.created_at.to_rfc3339(),
+        }
+    }
+}
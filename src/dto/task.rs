@@ -1,18 +1,39 @@
-use chrono::{DateTime, Local};
+use std::collections::HashMap;
+
+use chrono::{DateTime, FixedOffset, Local, NaiveDate, NaiveTime};
+use chrono_tz::Tz;
 use garde::Validate;
 use sea_orm::ActiveValue::NotSet;
 use sea_orm::{IntoActiveModel, Set};
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
 use utoipa::ToSchema;
 use uuid::Uuid;
 
 use crate::constants;
+use crate::dto::error::ErrorDto;
+use crate::dto::precondition::VersionedDto;
+use crate::dto::workflow_status::WorkflowStatusReadDto;
 use crate::entity::prelude::{
-    TaskActiveModel, TaskCommentActiveModel, TaskCommentModel, TaskModel,
+    TaskActiveModel, TaskCommentActiveModel, TaskCommentModel, TaskDependencyModel, TaskEventModel,
+    TaskModel,
+};
+use crate::entity::sea_orm_active_enums::{
+    DeadlineKind, TaskEventAction, TaskPriority, TaskSort, TaskStatus,
 };
-use crate::entity::sea_orm_active_enums::{TaskPriority, TaskStatus};
 
-#[derive(Debug, Deserialize, Validate, ToSchema)]
+/// Either an exact moment or a whole day in the user's time zone.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(untagged)]
+pub enum TaskDeadlineDto {
+    #[schema(value_type = String, format = DateTime, example = "2024-10-15T13:34:20.282397+03:00")]
+    Instant(DateTime<FixedOffset>),
+
+    /// Due until the end of the day
+    #[schema(value_type = String, format = Date, example = "2024-10-15")]
+    Date(NaiveDate),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Validate, ToSchema)]
 pub struct TaskCreateDto {
     #[garde(length(min = constants::TASK_NAME_MIN_LENGTH, max = constants::TASK_NAME_MAX_LENGTH))]
     #[schema(example = "Implement auth")]
@@ -22,17 +43,29 @@ pub struct TaskCreateDto {
     #[schema(example = "Need implement auth into api with JWT tokens")]
     pub description: String,
 
+    /// Category of the new task, it gets the first status of it. Defaults
+    /// to `ToDo`
     #[garde(skip)]
-    #[schema(example = "to_do")]
-    pub status: TaskStatus,
+    #[schema(example = "ToDo")]
+    pub status: Option<TaskStatus>,
+
+    /// Status of the new task, takes precedence over `status`
+    #[garde(skip)]
+    pub status_id: Option<Uuid>,
 
     #[garde(skip)]
     #[schema(example = "2024-10-15T13:34:20.282397+03:00")]
-    pub deadline: Option<DateTime<Local>>,
+    pub deadline: Option<TaskDeadlineDto>,
 
+    /// Defaults to the preferred priority of the user
     #[garde(skip)]
     #[schema(example = "normal")]
-    pub priority: TaskPriority,
+    pub priority: Option<TaskPriority>,
+
+    /// Values keyed by custom field id
+    #[garde(skip)]
+    #[schema(value_type = Option<Object>, example = json!({"9b2d1c3e-7f4a-4e8b-a1d2-3c4b5a6f7e80": 1500}))]
+    pub custom_fields: Option<HashMap<Uuid, serde_json::Value>>,
 }
 
 #[derive(Debug, Deserialize, Validate, ToSchema)]
@@ -47,9 +80,32 @@ pub struct TaskReadDto {
     pub id: Uuid,
     pub name: String,
     pub description: String,
+
+    /// Category of the task status
     pub status: TaskStatus,
+
+    pub status_id: Uuid,
+
+    /// Exact deadlines are in the user's time zone, all-day ones are dates
+    #[schema(example = "2024-10-15")]
     pub deadline: Option<String>,
+
+    pub deadline_kind: Option<DeadlineKind>,
     pub priority: TaskPriority,
+    pub version: i32,
+
+    /// Whether an unfinished task blocks this one
+    pub blocked: bool,
+
+    /// Rank inside the status column, tasks sort by it as plain strings
+    #[schema(example = "i")]
+    pub position: String,
+
+    /// Values keyed by custom field id, fields without a value are left out
+    #[schema(value_type = Object, example = json!({"9b2d1c3e-7f4a-4e8b-a1d2-3c4b5a6f7e80": 1500}))]
+    pub custom_fields: HashMap<Uuid, serde_json::Value>,
+
+    pub deleted_at: Option<String>,
     pub updated_at: String,
     pub created_at: String,
 }
@@ -57,7 +113,10 @@ pub struct TaskReadDto {
 #[derive(Debug, Serialize, ToSchema)]
 pub struct TaskCommentReadDto {
     pub id: Uuid,
+    pub task_id: Uuid,
     pub text: String,
+    pub version: i32,
+    pub deleted_at: Option<String>,
     pub updated_at: String,
     pub created_at: String,
 }
@@ -67,7 +126,30 @@ pub struct TaskGetQuery {
     pub limit: u64,
     pub offset: u64,
     pub status: Option<TaskStatus>,
+
+    /// Takes precedence over `status`
+    pub status_id: Option<Uuid>,
+
     pub priority: Option<TaskPriority>,
+
+    /// Only tasks that no unfinished task blocks
+    pub actionable: Option<bool>,
+
+    /// Only tasks having this custom field set
+    pub field_id: Option<Uuid>,
+
+    /// Only tasks whose `field_id` value equals this one, or includes it for
+    /// a multi-select field
+    pub field_value: Option<String>,
+
+    /// Sorts tasks by the value of this custom field, tasks without one last.
+    /// Takes precedence over `sort`
+    pub sort_field_id: Option<Uuid>,
+
+    /// Defaults to the preferred order of the user
+    pub sort: Option<TaskSort>,
+
+    pub descending: Option<bool>,
 }
 
 #[derive(Debug, Deserialize, ToSchema)]
@@ -76,6 +158,72 @@ pub struct TaskCommentGetQuery {
     pub offset: u64,
 }
 
+#[derive(Debug, Serialize, ToSchema)]
+pub struct TaskEventReadDto {
+    pub id: Uuid,
+    pub task_id: Uuid,
+    pub comment_id: Option<Uuid>,
+    pub actor_id: Option<Uuid>,
+
+    #[schema(example = "Update")]
+    pub action: TaskEventAction,
+
+    /// Changed fields mapped to their `before` and `after` values
+    #[schema(value_type = Object, example = json!({"status": {"before": "ToDo", "after": "Done"}}))]
+    pub changes: serde_json::Value,
+
+    pub created_at: String,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct TaskEventGetQuery {
+    pub limit: u64,
+    pub offset: u64,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct TaskDependencyCreateDto {
+    pub blocker_id: Uuid,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct TaskDependencyReadDto {
+    pub task_id: Uuid,
+    pub blocker_id: Uuid,
+    pub created_at: String,
+}
+
+/// Moves a task into a status column between two of its tasks, a missing
+/// neighbour means the start or the end of the column.
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct TaskMoveDto {
+    pub status_id: Uuid,
+
+    /// Task that ends up right before the moved one
+    pub previous_id: Option<Uuid>,
+
+    /// Task that ends up right after the moved one
+    pub next_id: Option<Uuid>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct TaskKanbanQuery {
+    /// Limit of tasks in each column
+    pub limit: u64,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct TaskKanbanColumnDto {
+    pub status: WorkflowStatusReadDto,
+    pub tasks: Vec<TaskReadDto>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct TaskTrashGetQuery {
+    pub limit: u64,
+    pub offset: u64,
+}
+
 #[derive(Debug, Deserialize, Validate, ToSchema)]
 pub struct TaskUpdateDto {
     #[garde(length(min = constants::TASK_NAME_MIN_LENGTH, max = constants::TASK_NAME_MAX_LENGTH))]
@@ -86,17 +234,107 @@ pub struct TaskUpdateDto {
     #[schema(example = "Need implement auth into api with JWT tokens")]
     pub description: Option<String>,
 
+    /// Moves the task to the first status of the category unless it is
+    /// already in it
     #[garde(skip)]
-    #[schema(example = "to_do")]
+    #[schema(example = "ToDo")]
     pub status: Option<TaskStatus>,
 
+    /// Takes precedence over `status`
     #[garde(skip)]
-    #[schema(example = "2024-10-15T13:34:20.282397+03:00")]
-    pub deadline: Option<DateTime<Local>>,
+    pub status_id: Option<Uuid>,
+
+    /// `null` removes the deadline, an absent field keeps it
+    #[garde(skip)]
+    #[serde(default, deserialize_with = "double_option")]
+    #[schema(value_type = Option<TaskDeadlineDto>, example = "2024-10-15T13:34:20.282397+03:00")]
+    pub deadline: Option<Option<TaskDeadlineDto>>,
 
     #[garde(skip)]
     #[schema(example = "hight")]
     pub priority: Option<TaskPriority>,
+
+    /// Values keyed by custom field id, `null` clears a value and fields not
+    /// listed are kept
+    #[garde(skip)]
+    #[schema(value_type = Option<Object>, example = json!({"9b2d1c3e-7f4a-4e8b-a1d2-3c4b5a6f7e80": null}))]
+    pub custom_fields: Option<HashMap<Uuid, serde_json::Value>>,
+}
+
+/// Tells an explicit `null` (`Some(None)`) from an absent field (`None`).
+pub fn double_option<'de, D, T>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Some)
+}
+
+/// Operation applied to every task of a bulk request.
+#[derive(Debug, Deserialize, ToSchema)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum TaskBulkOperationDto {
+    /// Moves tasks to the first status of the category
+    SetStatus {
+        status: TaskStatus,
+    },
+    SetStatusId {
+        status_id: Uuid,
+    },
+    SetPriority {
+        priority: TaskPriority,
+    },
+    SetDeadline {
+        #[schema(example = "2024-10-15T13:34:20.282397+03:00")]
+        deadline: Option<TaskDeadlineDto>,
+    },
+    Delete,
+}
+
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct TaskBulkDto {
+    #[garde(length(min = 1, max = constants::TASK_BULK_MAX_SIZE))]
+    pub ids: Option<Vec<Uuid>>,
+
+    /// Selects the user's active tasks with a filter expression, same syntax as saved filters
+    #[garde(length(min = 1, max = constants::SAVED_FILTER_EXPRESSION_MAX_LENGTH))]
+    #[schema(example = "status:todo and priority:low")]
+    pub filter: Option<String>,
+
+    #[garde(skip)]
+    pub operation: TaskBulkOperationDto,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct TaskBulkResultDto {
+    pub id: Uuid,
+
+    #[schema(example = 200)]
+    pub status: u16,
+
+    pub task: Option<TaskReadDto>,
+    pub error: Option<ErrorDto>,
+}
+
+/// Single line entry, see `service::quick_add` for what it understands.
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct TaskQuickDto {
+    #[garde(length(min = 1, max = constants::TASK_QUICK_TEXT_MAX_LENGTH))]
+    #[schema(example = "Pay rent tomorrow 9am !high // ask about the deposit")]
+    pub text: String,
+
+    /// Only parses the text without creating the task
+    #[garde(skip)]
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct TaskQuickReadDto {
+    pub parsed: TaskCreateDto,
+
+    /// Created task, missing on a dry run
+    pub task: Option<TaskReadDto>,
 }
 
 #[derive(Debug, Deserialize, Validate, ToSchema)]
@@ -111,12 +349,12 @@ impl IntoActiveModel<TaskActiveModel> for TaskCreateDto {
         TaskActiveModel {
             name: Set(self.name),
             description: Set(self.description),
-            status: Set(self.status),
-            deadline: match self.deadline {
-                Some(value) => Set(Some(value.fixed_offset())),
+            deadline: Set(self.deadline.clone().map(|value| value.value())),
+            deadline_kind: Set(self.deadline.map(|value| value.kind())),
+            priority: match self.priority {
+                Some(priority) => Set(priority),
                 None => NotSet,
             },
-            priority: Set(self.priority),
             ..Default::default()
         }
     }
@@ -131,20 +369,96 @@ impl IntoActiveModel<TaskCommentActiveModel> for TaskCommentCreateDto {
     }
 }
 
+impl TaskDeadlineDto {
+    /// Stored value of an all-day deadline: midnight UTC of its date, so the
+    /// day stays the same whatever zone the user moves to.
+    pub fn all_day(date: NaiveDate) -> DateTime<FixedOffset> {
+        date.and_time(NaiveTime::MIN).and_utc().fixed_offset()
+    }
+
+    /// Deadline of a stored task.
+    pub fn from_model(value: &TaskModel) -> Option<Self> {
+        match (value.deadline, &value.deadline_kind) {
+            (Some(deadline), Some(DeadlineKind::Date)) => {
+                Some(TaskDeadlineDto::Date(deadline.naive_utc().date()))
+            }
+            (Some(deadline), _) => Some(TaskDeadlineDto::Instant(deadline)),
+            (None, _) => None,
+        }
+    }
+
+    pub fn value(&self) -> DateTime<FixedOffset> {
+        match self {
+            TaskDeadlineDto::Instant(value) => *value,
+            TaskDeadlineDto::Date(value) => Self::all_day(*value),
+        }
+    }
+
+    pub fn kind(&self) -> DeadlineKind {
+        match self {
+            TaskDeadlineDto::Instant(_) => DeadlineKind::Instant,
+            TaskDeadlineDto::Date(_) => DeadlineKind::Date,
+        }
+    }
+}
+
 impl From<TaskModel> for TaskReadDto {
     fn from(value: TaskModel) -> Self {
+        Self::localized(value, &Tz::UTC)
+    }
+}
+
+impl TaskReadDto {
+    /// Renders the timestamps of the task in `time_zone`.
+    pub fn localized(value: TaskModel, time_zone: &Tz) -> Self {
+        let local = |value: DateTime<FixedOffset>| value.with_timezone(time_zone).to_rfc3339();
+        let deadline: Option<TaskDeadlineDto> = TaskDeadlineDto::from_model(&value);
+
         Self {
             id: value.id,
             name: value.name,
             description: value.description,
             status: value.status,
-            deadline: match value.deadline {
-                Some(value) => Some(value.to_rfc3339()),
+            status_id: value.status_id,
+            deadline: match deadline {
+                Some(TaskDeadlineDto::Date(date)) => Some(date.to_string()),
+                Some(TaskDeadlineDto::Instant(deadline)) => Some(local(deadline)),
                 None => None,
             },
+            deadline_kind: value.deadline_kind,
             priority: value.priority,
+            version: value.version,
+            // Stored apart from the task, see `TaskService::schemas`
+            blocked: false,
+            position: value.position,
+            custom_fields: HashMap::new(),
+            deleted_at: value.deleted_at.map(local),
+            created_at: local(value.created_at),
+            updated_at: local(value.updated_at),
+        }
+    }
+}
+
+impl From<TaskDependencyModel> for TaskDependencyReadDto {
+    fn from(value: TaskDependencyModel) -> Self {
+        Self {
+            task_id: value.task_id,
+            blocker_id: value.blocker_id,
+            created_at: value.created_at.to_rfc3339(),
+        }
+    }
+}
+
+impl From<TaskEventModel> for TaskEventReadDto {
+    fn from(value: TaskEventModel) -> Self {
+        Self {
+            id: value.id,
+            task_id: value.task_id,
+            comment_id: value.comment_id,
+            actor_id: value.actor_id,
+            action: value.action,
+            changes: value.changes,
             created_at: value.created_at.to_rfc3339(),
-            updated_at: value.updated_at.to_rfc3339(),
         }
     }
 }
@@ -153,13 +467,28 @@ impl From<TaskCommentModel> for TaskCommentReadDto {
     fn from(value: TaskCommentModel) -> Self {
         Self {
             id: value.id,
+            task_id: value.task_id,
             text: value.text,
+            version: value.version,
+            deleted_at: value.deleted_at.map(|value| value.to_rfc3339()),
             updated_at: value.updated_at.to_rfc3339(),
             created_at: value.created_at.to_rfc3339(),
         }
     }
 }
 
+impl VersionedDto for TaskReadDto {
+    fn version(&self) -> i32 {
+        self.version
+    }
+}
+
+impl VersionedDto for TaskCommentReadDto {
+    fn version(&self) -> i32 {
+        self.version
+    }
+}
+
 impl IntoActiveModel<TaskActiveModel> for TaskUpdateDto {
     fn into_active_model(self) -> TaskActiveModel {
         TaskActiveModel {
@@ -175,8 +504,12 @@ impl IntoActiveModel<TaskActiveModel> for TaskUpdateDto {
                 Some(value) => Set(value),
                 None => NotSet,
             },
-            deadline: match self.deadline {
-                Some(value) => Set(Some(value.fixed_offset())),
+            deadline: match &self.deadline {
+                Some(value) => Set(value.as_ref().map(|value| value.value())),
+                None => NotSet,
+            },
+            deadline_kind: match &self.deadline {
+                Some(value) => Set(value.as_ref().map(|value| value.kind())),
                 None => NotSet,
             },
             priority: match self.priority {
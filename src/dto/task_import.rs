@@ -0,0 +1,146 @@
+use garde::Validate;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::constants;
+use crate::dto::task::TaskCreateDto;
+use crate::entity::{
+    prelude::TaskImportModel,
+    sea_orm_active_enums::{ImportFormat, ImportStatus},
+};
+
+/// Headers of the CSV columns holding each field, matched without regard to
+/// case. Unset ones fall back to common header names such as `title` or
+/// `due date`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema)]
+pub struct CsvMappingDto {
+    #[schema(example = "Title")]
+    pub name: Option<String>,
+
+    pub description: Option<String>,
+
+    /// Values are matched against the user's status names, then against
+    /// common words such as `done` or `in progress`
+    pub status: Option<String>,
+
+    pub priority: Option<String>,
+
+    /// RFC 3339 times, local `YYYY-MM-DD HH:MM` times or all-day dates
+    #[schema(example = "Due date")]
+    pub deadline: Option<String>,
+
+    /// Column with a stable id of each row. Without it rows are told apart by
+    /// their content
+    pub key: Option<String>,
+
+    /// Defaults to `,`
+    #[schema(value_type = Option<String>, example = ";")]
+    pub delimiter: Option<char>,
+}
+
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct TaskImportCreateDto {
+    #[garde(skip)]
+    #[schema(example = "Csv")]
+    pub format: ImportFormat,
+
+    /// The exported file: CSV, Todoist CSV template, Microsoft To Do JSON,
+    /// the `tasks.json` of an account export or an iCalendar file
+    #[garde(length(min = 1, max = constants::TASK_IMPORT_CONTENT_MAX_LENGTH))]
+    #[schema(example = "Title,Due date\nBuy milk,2024-10-15")]
+    pub content: String,
+
+    /// Only read for `Csv`
+    #[garde(skip)]
+    pub mapping: Option<CsvMappingDto>,
+
+    /// Reports what would be imported without creating anything
+    #[garde(skip)]
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub enum TaskImportRowStatus {
+    Created,
+    /// An earlier import already created the task
+    Skipped,
+    Failed,
+    /// Would be created, rows of a dry run end up here
+    Valid,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct TaskImportRowDto {
+    /// Line of a CSV file, or position of the item in other formats
+    pub row: usize,
+
+    /// Identifies the row across imports of the same source
+    #[schema(example = "csv:42")]
+    pub key: String,
+
+    pub status: TaskImportRowStatus,
+
+    /// Task the row created or matched
+    pub task_id: Option<Uuid>,
+
+    /// Task the row maps to, for previews
+    pub task: Option<TaskCreateDto>,
+
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct TaskImportReadDto {
+    pub id: Uuid,
+
+    #[schema(example = "Todoist")]
+    pub format: ImportFormat,
+
+    #[schema(example = "Done")]
+    pub status: ImportStatus,
+
+    pub dry_run: bool,
+    pub created: usize,
+    pub skipped: usize,
+    pub failed: usize,
+
+    /// Rows a dry run would create
+    pub valid: usize,
+
+    /// Outcome of every row, empty until the import ran
+    pub rows: Vec<TaskImportRowDto>,
+
+    /// Why the whole file was rejected
+    pub error: Option<String>,
+
+    pub updated_at: String,
+    pub created_at: String,
+}
+
+impl From<TaskImportModel> for TaskImportReadDto {
+    fn from(value: TaskImportModel) -> Self {
+        let rows: Vec<TaskImportRowDto> = value
+            .rows
+            .and_then(|rows| serde_json::from_value::<Vec<TaskImportRowDto>>(rows).ok())
+            .unwrap_or_default();
+        let count =
+            |status: TaskImportRowStatus| rows.iter().filter(|row| row.status == status).count();
+
+        Self {
+            id: value.id,
+            format: value.format,
+            status: value.status,
+            dry_run: value.dry_run,
+            created: count(TaskImportRowStatus::Created),
+            skipped: count(TaskImportRowStatus::Skipped),
+            failed: count(TaskImportRowStatus::Failed),
+            valid: count(TaskImportRowStatus::Valid),
+            rows,
+            error: value.error,
+            updated_at: value.updated_at.to_rfc3339(),
+            created_at: value.created_at.to_rfc3339(),
+        }
+    }
+}
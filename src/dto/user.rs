@@ -1,6 +1,11 @@
+use std::future::{ready, Ready};
+
 use actix_multipart::form::tempfile::TempFile;
 use actix_multipart::form::MultipartForm;
+use actix_web::http::header::{Accept, Header};
+use actix_web::FromRequest;
 use chrono::Local;
+use chrono_tz::Tz;
 use garde::rules::pattern::regex::Regex;
 use garde::Validate;
 use sea_orm::{IntoActiveModel, NotSet, Set};
@@ -9,7 +14,11 @@ use utoipa::ToSchema;
 use uuid::Uuid;
 
 use crate::constants;
+use crate::dto::account_deletion::AccountDeletionReadDto;
+use crate::dto::precondition::VersionedDto;
 use crate::entity::prelude::{UserActiveModel, UserModel};
+use crate::entity::sea_orm_active_enums::AvatarFormat;
+use crate::error::service::{ServiceError, ServiceResult};
 
 #[derive(Debug, Deserialize, Validate, ToSchema)]
 pub struct UserCreateDto {
@@ -24,6 +33,11 @@ pub struct UserCreateDto {
     #[garde(length(min = constants::PASSWORD_MIN_LENGTH, max = constants::PASSWORD_MAX_LENGTH))]
     #[schema(example = "some_password12345")]
     pub password: String,
+
+    /// IANA time zone, defaults to `UTC`
+    #[garde(custom(validate_time_zone))]
+    #[schema(example = "Europe/Moscow")]
+    pub time_zone: Option<String>,
 }
 
 #[derive(Debug, MultipartForm, ToSchema)]
@@ -32,6 +46,27 @@ pub struct UserAvatarUploadDto {
     pub image: TempFile,
 }
 
+/// Part of the upload to make the avatar of, the whole image when left out.
+/// The centre square of the box is used.
+#[derive(Debug, Clone, Default, Deserialize, ToSchema)]
+pub struct UserAvatarCropQuery {
+    pub x: Option<u32>,
+    pub y: Option<u32>,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct UserAvatarGetQuery {
+    /// Side in pixels, the closest made size that is not smaller is sent
+    #[schema(example = 128)]
+    pub size: Option<u32>,
+}
+
+/// Avatar format the client prefers by `Accept`, PNG unless it asks for WebP.
+#[derive(Debug, Clone)]
+pub struct AvatarFormatDto(pub AvatarFormat);
+
 #[derive(Debug, Serialize, ToSchema)]
 pub struct UserReadDto {
     #[schema(example = "00000000-0000-0000-0000-000000000000")]
@@ -43,6 +78,23 @@ pub struct UserReadDto {
     #[schema(example = "archdroider@proton.me")]
     pub email: String,
 
+    #[schema(example = "Arch Droider")]
+    pub display_name: Option<String>,
+
+    pub bio: Option<String>,
+
+    #[schema(example = "Europe/Moscow")]
+    pub time_zone: String,
+
+    /// Whether the user uploaded an avatar, one is generated otherwise
+    pub custom_avatar: bool,
+
+    /// Pending deletion of the account
+    pub deletion: Option<AccountDeletionReadDto>,
+
+    #[schema(example = 1)]
+    pub version: i32,
+
     #[schema(example = "2024-05-15T15:36:21.434500+03:00")]
     pub created_at: String,
 
@@ -50,10 +102,40 @@ pub struct UserReadDto {
     pub updated_at: String,
 }
 
-#[derive(Debug, Deserialize, ToSchema)]
+/// What other users see of a user, without anything private to it.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct UserProfileReadDto {
+    #[schema(example = "00000000-0000-0000-0000-000000000000")]
+    pub id: Uuid,
+
+    #[schema(example = "archdrdr")]
+    pub name: String,
+
+    #[schema(example = "Arch Droider")]
+    pub display_name: Option<String>,
+
+    pub bio: Option<String>,
+
+    /// Whether the avatar shown is an uploaded one
+    pub custom_avatar: bool,
+
+    #[schema(example = 1)]
+    pub version: i32,
+
+    #[schema(example = "2024-05-15T15:36:21.434500+03:00")]
+    pub created_at: String,
+}
+
+#[derive(Debug, Deserialize, Validate, ToSchema)]
 pub struct UserSearchQuery {
+    /// Part of the name or display name, case and small typos don't matter
+    #[garde(length(min = constants::USER_SEARCH_MIN_LENGTH, max = constants::USER_SEARCH_MAX_LENGTH))]
     pub name: String,
+
+    #[garde(skip)]
     pub limit: u64,
+
+    #[garde(skip)]
     pub offset: u64,
 }
 
@@ -70,6 +152,41 @@ pub struct UserUpdateDto {
     #[garde(length(min = constants::PASSWORD_MIN_LENGTH, max = constants::PASSWORD_MAX_LENGTH))]
     #[schema(example = "some_password12345")]
     pub password: Option<String>,
+
+    #[garde(custom(validate_time_zone))]
+    #[schema(example = "Europe/Moscow")]
+    pub time_zone: Option<String>,
+}
+
+pub fn validate_time_zone(value: &Option<String>, _: &()) -> garde::Result {
+    match value {
+        Some(value) if value.parse::<Tz>().is_err() => {
+            Err(garde::Error::new("not a known IANA time zone"))
+        }
+        _ => Ok(()),
+    }
+}
+
+impl FromRequest for AvatarFormatDto {
+    type Error = ServiceError;
+    type Future = Ready<ServiceResult<Self>>;
+
+    fn from_request(req: &actix_web::HttpRequest, _: &mut actix_web::dev::Payload) -> Self::Future {
+        let format: AvatarFormat = match Accept::parse(req) {
+            Ok(accept) => accept
+                .ranked()
+                .into_iter()
+                .find_map(|mime| match mime.essence_str() {
+                    "image/webp" => Some(AvatarFormat::Webp),
+                    "image/png" => Some(AvatarFormat::Png),
+                    _ => None,
+                })
+                .unwrap_or(AvatarFormat::Png),
+            Err(_) => AvatarFormat::Png,
+        };
+
+        ready(Ok(Self(format)))
+    }
 }
 
 impl IntoActiveModel<UserActiveModel> for UserCreateDto {
@@ -78,6 +195,10 @@ impl IntoActiveModel<UserActiveModel> for UserCreateDto {
             name: Set(self.name),
             email: Set(self.email),
             password: Set(self.password),
+            time_zone: match self.time_zone {
+                Some(value) => Set(value),
+                None => NotSet,
+            },
             ..Default::default()
         }
     }
@@ -85,16 +206,51 @@ impl IntoActiveModel<UserActiveModel> for UserCreateDto {
 
 impl From<UserModel> for UserReadDto {
     fn from(value: UserModel) -> Self {
+        let deletion: Option<AccountDeletionReadDto> = AccountDeletionReadDto::new(&value);
+
         Self {
             id: value.id,
             name: value.name,
             email: value.email,
+            // Stored apart from the user, see `UserService::schemas`
+            display_name: None,
+            bio: None,
+            time_zone: value.time_zone,
+            custom_avatar: false,
+            deletion,
+            version: value.version,
             created_at: value.created_at.to_rfc3339(),
             updated_at: value.updated_at.to_rfc3339(),
         }
     }
 }
 
+impl From<UserReadDto> for UserProfileReadDto {
+    fn from(value: UserReadDto) -> Self {
+        Self {
+            id: value.id,
+            name: value.name,
+            display_name: value.display_name,
+            bio: value.bio,
+            custom_avatar: value.custom_avatar,
+            version: value.version,
+            created_at: value.created_at,
+        }
+    }
+}
+
+impl VersionedDto for UserProfileReadDto {
+    fn version(&self) -> i32 {
+        self.version
+    }
+}
+
+impl VersionedDto for UserReadDto {
+    fn version(&self) -> i32 {
+        self.version
+    }
+}
+
 impl IntoActiveModel<UserActiveModel> for UserUpdateDto {
     fn into_active_model(self) -> UserActiveModel {
         UserActiveModel {
@@ -110,6 +266,10 @@ impl IntoActiveModel<UserActiveModel> for UserUpdateDto {
                 Some(password) => Set(password),
                 None => NotSet,
             },
+            time_zone: match self.time_zone {
+                Some(time_zone) => Set(time_zone),
+                None => NotSet,
+            },
             updated_at: Set(Local::now().fixed_offset()),
             ..Default::default()
         }
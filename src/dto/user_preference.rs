@@ -0,0 +1,180 @@
+use chrono::Local;
+use garde::rules::pattern::regex::Regex;
+use garde::Validate;
+use sea_orm::{IntoActiveModel, NotSet, Set};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::constants;
+use crate::dto::precondition::VersionedDto;
+use crate::dto::task::double_option;
+use crate::dto::user::validate_time_zone;
+use crate::entity::prelude::{UserPreferenceActiveModel, UserPreferenceModel};
+use crate::entity::sea_orm_active_enums::{DateFormat, TaskPriority, TaskSort, WeekDay};
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct UserPreferenceReadDto {
+    /// Name shown instead of the login name when set
+    #[schema(example = "Arch Droider")]
+    pub display_name: Option<String>,
+
+    pub bio: Option<String>,
+
+    /// BCP 47 language tag
+    #[schema(example = "en-GB")]
+    pub locale: String,
+
+    /// Same as the time zone of the user
+    #[schema(example = "Europe/Moscow")]
+    pub time_zone: String,
+
+    #[schema(example = "Monday")]
+    pub week_start: WeekDay,
+
+    #[schema(example = "Dmy")]
+    pub date_format: DateFormat,
+
+    /// Priority of new tasks that don't give one
+    #[schema(example = "Normal")]
+    pub default_priority: TaskPriority,
+
+    /// Order of task lists that don't ask for one
+    #[schema(example = "Deadline")]
+    pub task_sort: TaskSort,
+
+    pub task_sort_descending: bool,
+
+    /// Whether others find the user when searching by name
+    pub discoverable: bool,
+
+    /// Whether others see the uploaded avatar rather than a generated one
+    pub show_avatar: bool,
+
+    #[schema(example = 1)]
+    pub version: i32,
+
+    pub updated_at: String,
+}
+
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct UserPreferenceUpdateDto {
+    /// `null` removes the display name, an absent field keeps it
+    #[garde(length(min = constants::DISPLAY_NAME_MIN_LENGTH, max = constants::DISPLAY_NAME_MAX_LENGTH))]
+    #[serde(default, deserialize_with = "double_option")]
+    #[schema(value_type = Option<String>, example = "Arch Droider")]
+    pub display_name: Option<Option<String>>,
+
+    /// `null` removes the bio, an absent field keeps it
+    #[garde(length(max = constants::BIO_MAX_LENGTH))]
+    #[serde(default, deserialize_with = "double_option")]
+    #[schema(value_type = Option<String>)]
+    pub bio: Option<Option<String>>,
+
+    #[garde(pattern(Regex::new(constants::LOCALE_PATTERN).unwrap()), length(max = constants::LOCALE_MAX_LENGTH))]
+    #[schema(example = "en-GB")]
+    pub locale: Option<String>,
+
+    #[garde(custom(validate_time_zone))]
+    #[schema(example = "Europe/Moscow")]
+    pub time_zone: Option<String>,
+
+    #[garde(skip)]
+    #[schema(example = "Sunday")]
+    pub week_start: Option<WeekDay>,
+
+    #[garde(skip)]
+    #[schema(example = "Mdy")]
+    pub date_format: Option<DateFormat>,
+
+    #[garde(skip)]
+    #[schema(example = "Hight")]
+    pub default_priority: Option<TaskPriority>,
+
+    #[garde(skip)]
+    #[schema(example = "Priority")]
+    pub task_sort: Option<TaskSort>,
+
+    #[garde(skip)]
+    pub task_sort_descending: Option<bool>,
+
+    #[garde(skip)]
+    pub discoverable: Option<bool>,
+
+    #[garde(skip)]
+    pub show_avatar: Option<bool>,
+}
+
+impl UserPreferenceReadDto {
+    pub fn new(value: UserPreferenceModel, time_zone: String) -> Self {
+        Self {
+            display_name: value.display_name,
+            bio: value.bio,
+            locale: value.locale,
+            time_zone,
+            week_start: value.week_start,
+            date_format: value.date_format,
+            default_priority: value.default_priority,
+            task_sort: value.task_sort,
+            task_sort_descending: value.task_sort_descending,
+            discoverable: value.discoverable,
+            show_avatar: value.show_avatar,
+            version: value.version,
+            updated_at: value.updated_at.to_rfc3339(),
+        }
+    }
+}
+
+impl VersionedDto for UserPreferenceReadDto {
+    fn version(&self) -> i32 {
+        self.version
+    }
+}
+
+impl IntoActiveModel<UserPreferenceActiveModel> for UserPreferenceUpdateDto {
+    fn into_active_model(self) -> UserPreferenceActiveModel {
+        UserPreferenceActiveModel {
+            display_name: match self.display_name {
+                Some(display_name) => Set(display_name),
+                None => NotSet,
+            },
+            bio: match self.bio {
+                Some(bio) => Set(bio),
+                None => NotSet,
+            },
+            locale: match self.locale {
+                Some(locale) => Set(locale),
+                None => NotSet,
+            },
+            week_start: match self.week_start {
+                Some(week_start) => Set(week_start),
+                None => NotSet,
+            },
+            date_format: match self.date_format {
+                Some(date_format) => Set(date_format),
+                None => NotSet,
+            },
+            default_priority: match self.default_priority {
+                Some(default_priority) => Set(default_priority),
+                None => NotSet,
+            },
+            task_sort: match self.task_sort {
+                Some(task_sort) => Set(task_sort),
+                None => NotSet,
+            },
+            task_sort_descending: match self.task_sort_descending {
+                Some(task_sort_descending) => Set(task_sort_descending),
+                None => NotSet,
+            },
+            discoverable: match self.discoverable {
+                Some(discoverable) => Set(discoverable),
+                None => NotSet,
+            },
+            show_avatar: match self.show_avatar {
+                Some(show_avatar) => Set(show_avatar),
+                None => NotSet,
+            },
+            updated_at: Set(Local::now().fixed_offset()),
+            ..Default::default()
+        }
+    }
+}
@@ -0,0 +1,83 @@
+use garde::Validate;
+use sea_orm::{IntoActiveModel, Set};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::constants;
+use crate::entity::prelude::{WorkflowStatusActiveModel, WorkflowStatusModel};
+use crate::entity::sea_orm_active_enums::TaskStatus;
+
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct WorkflowStatusCreateDto {
+    #[garde(length(min = constants::WORKFLOW_STATUS_NAME_MIN_LENGTH, max = constants::WORKFLOW_STATUS_NAME_MAX_LENGTH))]
+    #[schema(example = "Review")]
+    pub name: String,
+
+    /// Built-in status the new one behaves like
+    #[garde(skip)]
+    #[schema(example = "InProgress")]
+    pub category: TaskStatus,
+}
+
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct WorkflowStatusUpdateDto {
+    #[garde(length(min = constants::WORKFLOW_STATUS_NAME_MIN_LENGTH, max = constants::WORKFLOW_STATUS_NAME_MAX_LENGTH))]
+    #[schema(example = "Review")]
+    pub name: Option<String>,
+
+    #[garde(skip)]
+    #[schema(example = "InProgress")]
+    pub category: Option<TaskStatus>,
+
+    /// New zero based place in the workflow, the others shift to make room
+    #[garde(range(min = 0))]
+    #[schema(example = 1)]
+    pub position: Option<i32>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct WorkflowStatusDeleteQuery {
+    /// Status that takes over the tasks of the deleted one
+    pub replacement_id: Option<Uuid>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct WorkflowStatusReadDto {
+    pub id: Uuid,
+
+    #[schema(example = "Review")]
+    pub name: String,
+
+    #[schema(example = "InProgress")]
+    pub category: TaskStatus,
+
+    #[schema(example = 1)]
+    pub position: i32,
+
+    pub updated_at: String,
+    pub created_at: String,
+}
+
+impl IntoActiveModel<WorkflowStatusActiveModel> for WorkflowStatusCreateDto {
+    fn into_active_model(self) -> WorkflowStatusActiveModel {
+        WorkflowStatusActiveModel {
+            name: Set(self.name),
+            category: Set(self.category),
+            ..Default::default()
+        }
+    }
+}
+
+impl From<WorkflowStatusModel> for WorkflowStatusReadDto {
+    fn from(value: WorkflowStatusModel) -> Self {
+        Self {
+            id: value.id,
+            name: value.name,
+            category: value.category,
+            position: value.position,
+            updated_at: value.updated_at.to_rfc3339(),
+            created_at: value.created_at.to_rfc3339(),
+        }
+    }
+}
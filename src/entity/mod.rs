@@ -2,8 +2,23 @@
 
 pub mod prelude;
 
+pub mod account_deletion;
+pub mod account_export;
+pub mod app_password;
+pub mod attachment;
+pub mod custom_field;
+pub mod saved_filter;
 pub mod sea_orm_active_enums;
+pub mod storage_orphan;
 pub mod task;
 pub mod task_comment;
+pub mod task_custom_field_value;
+pub mod task_dependency;
+pub mod task_event;
+pub mod task_import;
+pub mod tombstone;
 pub mod user;
 pub mod user_avatar;
+pub mod user_avatar_variant;
+pub mod user_preference;
+pub mod workflow_status;
@@ -2,6 +2,39 @@ pub use super::user::{
     ActiveModel as UserActiveModel, Column as UserColumn, Entity as UserEntity, Model as UserModel,
 };
 
+pub use super::account_deletion::{
+    ActiveModel as AccountDeletionActiveModel, Column as AccountDeletionColumn,
+    Entity as AccountDeletionEntity, Model as AccountDeletionModel,
+};
+pub use super::account_export::{
+    ActiveModel as AccountExportActiveModel, Column as AccountExportColumn,
+    Entity as AccountExportEntity, Model as AccountExportModel,
+};
+
+pub use super::app_password::{
+    ActiveModel as AppPasswordActiveModel, Column as AppPasswordColumn,
+    Entity as AppPasswordEntity, Model as AppPasswordModel,
+};
+
+pub use super::attachment::{
+    ActiveModel as AttachmentActiveModel, Column as AttachmentColumn, Entity as AttachmentEntity,
+    Model as AttachmentModel,
+};
+
+pub use super::custom_field::{
+    ActiveModel as CustomFieldActiveModel, Column as CustomFieldColumn,
+    Entity as CustomFieldEntity, Model as CustomFieldModel,
+};
+
+pub use super::saved_filter::{
+    ActiveModel as SavedFilterActiveModel, Column as SavedFilterColumn,
+    Entity as SavedFilterEntity, Model as SavedFilterModel,
+};
+
+pub use super::storage_orphan::{
+    ActiveModel as StorageOrphanActiveModel, Column as StorageOrphanColumn,
+    Entity as StorageOrphanEntity, Model as StorageOrphanModel,
+};
 pub use super::task::{
     ActiveModel as TaskActiveModel, Column as TaskColumn, Entity as TaskEntity, Model as TaskModel,
 };
@@ -9,7 +42,40 @@ pub use super::task_comment::{
     ActiveModel as TaskCommentActiveModel, Column as TaskCommentColumn,
     Entity as TaskCommentEntity, Model as TaskCommentModel,
 };
+pub use super::task_custom_field_value::{
+    ActiveModel as TaskCustomFieldValueActiveModel, Column as TaskCustomFieldValueColumn,
+    Entity as TaskCustomFieldValueEntity, Model as TaskCustomFieldValueModel,
+};
+pub use super::task_dependency::{
+    ActiveModel as TaskDependencyActiveModel, Column as TaskDependencyColumn,
+    Entity as TaskDependencyEntity, Model as TaskDependencyModel,
+};
+pub use super::task_event::{
+    ActiveModel as TaskEventActiveModel, Column as TaskEventColumn, Entity as TaskEventEntity,
+    Model as TaskEventModel,
+};
+
+pub use super::task_import::{
+    ActiveModel as TaskImportActiveModel, Column as TaskImportColumn, Entity as TaskImportEntity,
+    Model as TaskImportModel,
+};
+pub use super::tombstone::{
+    ActiveModel as TombstoneActiveModel, Column as TombstoneColumn, Entity as TombstoneEntity,
+    Model as TombstoneModel,
+};
 pub use super::user_avatar::{
     ActiveModel as UserAvatarActiveModel, Column as UserAvatarColumn, Entity as UserAvatarEntity,
     Model as UserAvatarModel,
 };
+pub use super::user_avatar_variant::{
+    ActiveModel as UserAvatarVariantActiveModel, Column as UserAvatarVariantColumn,
+    Entity as UserAvatarVariantEntity, Model as UserAvatarVariantModel,
+};
+pub use super::user_preference::{
+    ActiveModel as UserPreferenceActiveModel, Column as UserPreferenceColumn,
+    Entity as UserPreferenceEntity, Model as UserPreferenceModel,
+};
+pub use super::workflow_status::{
+    ActiveModel as WorkflowStatusActiveModel, Column as WorkflowStatusColumn,
+    Entity as WorkflowStatusEntity, Model as WorkflowStatusModel,
+};
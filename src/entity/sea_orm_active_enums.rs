@@ -27,3 +27,162 @@ pub enum TaskPriority {
     #[sea_orm(string_value = "hight")]
     Hight,
 }
+
+#[derive(
+    Debug, Clone, PartialEq, Eq, EnumIter, DeriveActiveEnum, Serialize, Deserialize, ToSchema,
+)]
+#[sea_orm(rs_type = "String", db_type = "Enum", enum_name = "sync_entity")]
+pub enum SyncEntity {
+    #[sea_orm(string_value = "task")]
+    Task,
+    #[sea_orm(string_value = "task_comment")]
+    TaskComment,
+}
+
+#[derive(
+    Debug, Clone, PartialEq, Eq, EnumIter, DeriveActiveEnum, Serialize, Deserialize, ToSchema,
+)]
+#[sea_orm(rs_type = "String", db_type = "Enum", enum_name = "task_event_action")]
+pub enum TaskEventAction {
+    #[sea_orm(string_value = "create")]
+    Create,
+    #[sea_orm(string_value = "update")]
+    Update,
+    #[sea_orm(string_value = "delete")]
+    Delete,
+    #[sea_orm(string_value = "restore")]
+    Restore,
+    #[sea_orm(string_value = "purge")]
+    Purge,
+}
+
+#[derive(
+    Debug, Clone, PartialEq, Eq, EnumIter, DeriveActiveEnum, Serialize, Deserialize, ToSchema,
+)]
+#[sea_orm(rs_type = "String", db_type = "Enum", enum_name = "custom_field_kind")]
+pub enum CustomFieldKind {
+    #[sea_orm(string_value = "text")]
+    Text,
+    #[sea_orm(string_value = "number")]
+    Number,
+    #[sea_orm(string_value = "date")]
+    Date,
+    #[sea_orm(string_value = "boolean")]
+    Boolean,
+    #[sea_orm(string_value = "select")]
+    Select,
+    #[sea_orm(string_value = "multi_select")]
+    MultiSelect,
+    #[sea_orm(string_value = "url")]
+    Url,
+}
+
+#[derive(
+    Debug, Clone, PartialEq, Eq, EnumIter, DeriveActiveEnum, Serialize, Deserialize, ToSchema,
+)]
+#[sea_orm(rs_type = "String", db_type = "Enum", enum_name = "deadline_kind")]
+pub enum DeadlineKind {
+    #[sea_orm(string_value = "date")]
+    Date,
+    #[sea_orm(string_value = "instant")]
+    Instant,
+}
+
+#[derive(
+    Debug, Clone, PartialEq, Eq, EnumIter, DeriveActiveEnum, Serialize, Deserialize, ToSchema,
+)]
+#[sea_orm(rs_type = "String", db_type = "Enum", enum_name = "export_status")]
+pub enum ExportStatus {
+    #[sea_orm(string_value = "failed")]
+    Failed,
+    #[sea_orm(string_value = "pending")]
+    Pending,
+    #[sea_orm(string_value = "ready")]
+    Ready,
+}
+
+#[derive(
+    Debug, Clone, PartialEq, Eq, EnumIter, DeriveActiveEnum, Serialize, Deserialize, ToSchema,
+)]
+#[sea_orm(rs_type = "String", db_type = "Enum", enum_name = "import_format")]
+pub enum ImportFormat {
+    #[sea_orm(string_value = "csv")]
+    Csv,
+    #[sea_orm(string_value = "ical")]
+    Ical,
+    #[sea_orm(string_value = "microsoft_to_do")]
+    MicrosoftToDo,
+    #[sea_orm(string_value = "task_flow")]
+    TaskFlow,
+    #[sea_orm(string_value = "todoist")]
+    Todoist,
+}
+
+#[derive(
+    Debug, Clone, PartialEq, Eq, EnumIter, DeriveActiveEnum, Serialize, Deserialize, ToSchema,
+)]
+#[sea_orm(rs_type = "String", db_type = "Enum", enum_name = "import_status")]
+pub enum ImportStatus {
+    #[sea_orm(string_value = "done")]
+    Done,
+    #[sea_orm(string_value = "failed")]
+    Failed,
+    #[sea_orm(string_value = "pending")]
+    Pending,
+}
+
+#[derive(
+    Debug, Clone, PartialEq, Eq, EnumIter, DeriveActiveEnum, Serialize, Deserialize, ToSchema,
+)]
+#[sea_orm(rs_type = "String", db_type = "Enum", enum_name = "avatar_format")]
+pub enum AvatarFormat {
+    #[sea_orm(string_value = "png")]
+    Png,
+    #[sea_orm(string_value = "webp")]
+    Webp,
+}
+
+#[derive(
+    Debug, Clone, PartialEq, Eq, EnumIter, DeriveActiveEnum, Serialize, Deserialize, ToSchema,
+)]
+#[sea_orm(rs_type = "String", db_type = "Enum", enum_name = "week_day")]
+pub enum WeekDay {
+    #[sea_orm(string_value = "monday")]
+    Monday,
+    #[sea_orm(string_value = "saturday")]
+    Saturday,
+    #[sea_orm(string_value = "sunday")]
+    Sunday,
+}
+
+#[derive(
+    Debug, Clone, PartialEq, Eq, EnumIter, DeriveActiveEnum, Serialize, Deserialize, ToSchema,
+)]
+#[sea_orm(rs_type = "String", db_type = "Enum", enum_name = "date_format")]
+pub enum DateFormat {
+    #[sea_orm(string_value = "dmy")]
+    Dmy,
+    #[sea_orm(string_value = "mdy")]
+    Mdy,
+    #[sea_orm(string_value = "ymd")]
+    Ymd,
+}
+
+#[derive(
+    Debug, Clone, PartialEq, Eq, EnumIter, DeriveActiveEnum, Serialize, Deserialize, ToSchema,
+)]
+#[sea_orm(rs_type = "String", db_type = "Enum", enum_name = "task_sort")]
+pub enum TaskSort {
+    #[sea_orm(string_value = "created")]
+    Created,
+    #[sea_orm(string_value = "deadline")]
+    Deadline,
+    #[sea_orm(string_value = "name")]
+    Name,
+    #[sea_orm(string_value = "position")]
+    Position,
+    #[sea_orm(string_value = "priority")]
+    Priority,
+    #[sea_orm(string_value = "updated")]
+    Updated,
+}
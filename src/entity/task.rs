@@ -1,4 +1,4 @@
-use super::sea_orm_active_enums::{TaskPriority, TaskStatus};
+use super::sea_orm_active_enums::{DeadlineKind, TaskPriority, TaskStatus};
 use sea_orm::entity::prelude::*;
 
 #[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq)]
@@ -14,12 +14,25 @@ pub struct Model {
     pub user_id: Uuid,
     pub updated_at: DateTimeWithTimeZone,
     pub created_at: DateTimeWithTimeZone,
+    pub change_seq: i64,
+    pub version: i32,
+    pub deleted_at: Option<DateTimeWithTimeZone>,
+    pub position: String,
+    pub status_id: Uuid,
+    pub deadline_kind: Option<DeadlineKind>,
+    pub dav_name: Option<String>,
+    pub ical_uid: Option<String>,
+    pub import_key: Option<String>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {
+    #[sea_orm(has_many = "super::attachment::Entity")]
+    Attachment,
     #[sea_orm(has_many = "super::task_comment::Entity")]
     TaskComment,
+    #[sea_orm(has_many = "super::task_custom_field_value::Entity")]
+    TaskCustomFieldValue,
     #[sea_orm(
         belongs_to = "super::user::Entity",
         from = "Column::UserId",
@@ -28,6 +41,20 @@ pub enum Relation {
         on_delete = "Cascade"
     )]
     User,
+    #[sea_orm(
+        belongs_to = "super::workflow_status::Entity",
+        from = "Column::StatusId",
+        to = "super::workflow_status::Column::Id",
+        on_update = "NoAction",
+        on_delete = "Restrict"
+    )]
+    WorkflowStatus,
+}
+
+impl Related<super::attachment::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Attachment.def()
+    }
 }
 
 impl Related<super::task_comment::Entity> for Entity {
@@ -36,10 +63,22 @@ impl Related<super::task_comment::Entity> for Entity {
     }
 }
 
+impl Related<super::task_custom_field_value::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::TaskCustomFieldValue.def()
+    }
+}
+
 impl Related<super::user::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::User.def()
     }
 }
 
+impl Related<super::workflow_status::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::WorkflowStatus.def()
+    }
+}
+
 impl ActiveModelBehavior for ActiveModel {}
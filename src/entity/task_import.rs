@@ -0,0 +1,41 @@
+use super::sea_orm_active_enums::{ImportFormat, ImportStatus};
+use sea_orm::entity::prelude::*;
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq)]
+#[sea_orm(table_name = "task_import")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub format: ImportFormat,
+    pub status: ImportStatus,
+    pub dry_run: bool,
+    pub content: Option<String>,
+    #[sea_orm(column_type = "JsonBinary", nullable)]
+    pub mapping: Option<Json>,
+    #[sea_orm(column_type = "JsonBinary", nullable)]
+    pub rows: Option<Json>,
+    pub error: Option<String>,
+    pub updated_at: DateTimeWithTimeZone,
+    pub created_at: DateTimeWithTimeZone,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::user::Entity",
+        from = "Column::UserId",
+        to = "super::user::Column::Id",
+        on_update = "NoAction",
+        on_delete = "Cascade"
+    )]
+    User,
+}
+
+impl Related<super::user::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::User.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
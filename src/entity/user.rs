@@ -12,16 +12,74 @@ pub struct Model {
     pub password: String,
     pub created_at: DateTimeWithTimeZone,
     pub updated_at: DateTimeWithTimeZone,
+    pub version: i32,
+    pub time_zone: String,
+    #[sea_orm(unique)]
+    pub calendar_token: Option<String>,
+    pub deletion_requested_at: Option<DateTimeWithTimeZone>,
+    pub deletion_scheduled_at: Option<DateTimeWithTimeZone>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {
+    #[sea_orm(has_many = "super::account_export::Entity")]
+    AccountExport,
+    #[sea_orm(has_many = "super::app_password::Entity")]
+    AppPassword,
+    #[sea_orm(has_many = "super::attachment::Entity")]
+    Attachment,
+    #[sea_orm(has_many = "super::custom_field::Entity")]
+    CustomField,
+    #[sea_orm(has_many = "super::saved_filter::Entity")]
+    SavedFilter,
     #[sea_orm(has_many = "super::task::Entity")]
     Task,
     #[sea_orm(has_many = "super::task_comment::Entity")]
     TaskComment,
+    #[sea_orm(has_many = "super::task_dependency::Entity")]
+    TaskDependency,
+    #[sea_orm(has_many = "super::task_event::Entity")]
+    TaskEvent,
+    #[sea_orm(has_many = "super::task_import::Entity")]
+    TaskImport,
+    #[sea_orm(has_many = "super::tombstone::Entity")]
+    Tombstone,
     #[sea_orm(has_one = "super::user_avatar::Entity")]
     UserAvatar,
+    #[sea_orm(has_one = "super::user_preference::Entity")]
+    UserPreference,
+    #[sea_orm(has_many = "super::workflow_status::Entity")]
+    WorkflowStatus,
+}
+
+impl Related<super::account_export::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::AccountExport.def()
+    }
+}
+
+impl Related<super::app_password::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::AppPassword.def()
+    }
+}
+
+impl Related<super::attachment::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Attachment.def()
+    }
+}
+
+impl Related<super::custom_field::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::CustomField.def()
+    }
+}
+
+impl Related<super::saved_filter::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::SavedFilter.def()
+    }
 }
 
 impl Related<super::task::Entity> for Entity {
@@ -36,10 +94,46 @@ impl Related<super::task_comment::Entity> for Entity {
     }
 }
 
+impl Related<super::task_dependency::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::TaskDependency.def()
+    }
+}
+
+impl Related<super::task_event::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::TaskEvent.def()
+    }
+}
+
+impl Related<super::task_import::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::TaskImport.def()
+    }
+}
+
+impl Related<super::tombstone::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Tombstone.def()
+    }
+}
+
 impl Related<super::user_avatar::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::UserAvatar.def()
     }
 }
 
+impl Related<super::user_preference::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::UserPreference.def()
+    }
+}
+
+impl Related<super::workflow_status::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::WorkflowStatus.def()
+    }
+}
+
 impl ActiveModelBehavior for ActiveModel {}
@@ -8,8 +8,11 @@ pub struct Model {
     pub id: Uuid,
     #[sea_orm(unique)]
     pub user_id: Uuid,
+    /// Avatars stored before their variants were made, converted on start
     #[sea_orm(column_type = "Binary(constants::AVATAR_MAX_SIZE as u32)", nullable)]
-    pub file: Vec<u8>,
+    pub file: Option<Vec<u8>>,
+    pub storage_key: Option<String>,
+    pub sha256: Option<String>,
     pub updated_at: DateTimeWithTimeZone,
     pub created_at: DateTimeWithTimeZone,
 }
@@ -24,6 +27,8 @@ pub enum Relation {
         on_delete = "Cascade"
     )]
     User,
+    #[sea_orm(has_many = "super::user_avatar_variant::Entity")]
+    UserAvatarVariant,
 }
 
 impl Related<super::user::Entity> for Entity {
@@ -32,4 +37,10 @@ impl Related<super::user::Entity> for Entity {
     }
 }
 
+impl Related<super::user_avatar_variant::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::UserAvatarVariant.def()
+    }
+}
+
 impl ActiveModelBehavior for ActiveModel {}
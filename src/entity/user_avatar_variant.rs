@@ -0,0 +1,35 @@
+use super::sea_orm_active_enums::AvatarFormat;
+use sea_orm::entity::prelude::*;
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq)]
+#[sea_orm(table_name = "user_avatar_variant")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    pub avatar_id: Uuid,
+    pub size: i32,
+    pub format: AvatarFormat,
+    pub storage_key: String,
+    pub sha256: String,
+    pub created_at: DateTimeWithTimeZone,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::user_avatar::Entity",
+        from = "Column::AvatarId",
+        to = "super::user_avatar::Column::Id",
+        on_update = "NoAction",
+        on_delete = "Cascade"
+    )]
+    UserAvatar,
+}
+
+impl Related<super::user_avatar::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::UserAvatar.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,41 @@
+use super::sea_orm_active_enums::{DateFormat, TaskPriority, TaskSort, WeekDay};
+use sea_orm::entity::prelude::*;
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq)]
+#[sea_orm(table_name = "user_preference")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub user_id: Uuid,
+    pub display_name: Option<String>,
+    pub bio: Option<String>,
+    pub locale: String,
+    pub week_start: WeekDay,
+    pub date_format: DateFormat,
+    pub default_priority: TaskPriority,
+    pub task_sort: TaskSort,
+    pub task_sort_descending: bool,
+    pub version: i32,
+    pub updated_at: DateTimeWithTimeZone,
+    pub discoverable: bool,
+    pub show_avatar: bool,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::user::Entity",
+        from = "Column::UserId",
+        to = "super::user::Column::Id",
+        on_update = "NoAction",
+        on_delete = "Cascade"
+    )]
+    User,
+}
+
+impl Related<super::user::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::User.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
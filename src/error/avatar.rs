@@ -0,0 +1,30 @@
+use image::ImageError;
+use thiserror::Error;
+
+use super::service::ServiceError;
+
+pub type AvatarResult<T = ()> = Result<T, AvatarError>;
+
+/// Failures of avatar processing, which runs on a blocking thread and so
+/// can't carry a `ServiceError`.
+#[derive(Debug, Error)]
+pub enum AvatarError {
+    #[error("Image error: {0}")]
+    Image(#[from] ImageError),
+
+    #[error("Unsupported image format {0}")]
+    Format(String),
+
+    #[error("{0}")]
+    Crop(String),
+}
+
+impl From<AvatarError> for ServiceError {
+    fn from(value: AvatarError) -> Self {
+        match value {
+            AvatarError::Image(err) => ServiceError::InvalidImage(err),
+            AvatarError::Format(format) => ServiceError::UnsupportedMediaType(format),
+            AvatarError::Crop(message) => ServiceError::BadRequest(message),
+        }
+    }
+}
@@ -6,4 +6,10 @@ pub type ClientResult<T = ()> = Result<T, ClientError>;
 pub enum ClientError {
     #[error("Error creating postgres client")]
     Postgres,
+
+    #[error("Error creating storage client: {0}")]
+    Storage(String),
+
+    #[error("Error creating mail client: {0}")]
+    Mail(String),
 }
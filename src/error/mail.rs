@@ -0,0 +1,15 @@
+use thiserror::Error;
+
+pub type MailResult<T = ()> = Result<T, MailError>;
+
+#[derive(Debug, Error)]
+pub enum MailError {
+    #[error("Invalid mail address: {0}")]
+    Address(#[from] lettre::address::AddressError),
+
+    #[error("Can't build mail: {0}")]
+    Build(#[from] lettre::error::Error),
+
+    #[error("Can't send mail: {0}")]
+    Smtp(#[from] lettre::transport::smtp::Error),
+}
@@ -1,3 +1,6 @@
+pub mod avatar;
 pub mod client;
+pub mod mail;
 pub mod server;
 pub mod service;
+pub mod storage;
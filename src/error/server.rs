@@ -9,6 +9,9 @@ pub enum ServerError {
     #[error("Create client error: {0}")]
     ClientCreate(#[from] ClientError),
 
+    #[error("Converting avatars failed: {0}")]
+    AvatarConvert(String),
+
     #[error("Can't run server: {0}")]
     Run(String),
 }
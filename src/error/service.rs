@@ -1,15 +1,39 @@
 use actix_multipart::MultipartError;
-use actix_web::{http::StatusCode, HttpResponse, ResponseError};
+use actix_web::{
+    http::{header, StatusCode},
+    HttpResponse, ResponseError,
+};
 use garde::Report;
 use image::ImageError;
-use sea_orm::DbErr;
+use sea_orm::{
+    sqlx::{self, postgres::PgDatabaseError},
+    DbErr, RuntimeErr,
+};
 use thiserror::Error;
 use uuid::Uuid;
 
 use crate::dto::error::{ErrorDto, FromReport, ValidateErrorDto};
 
+use super::{mail::MailError, storage::StorageError};
+
 pub type ServiceResult<T = ()> = Result<T, ServiceError>;
 
+/// SQLSTATE of a unique violation.
+const UNIQUE_VIOLATION: &str = "23505";
+
+/// Field each unique constraint or index guards, by its name.
+const UNIQUE_FIELDS: &[(&str, &str)] = &[
+    ("idx-user-name-lower", "name"),
+    ("idx-user-email-lower", "email"),
+    ("user_calendar_token_key", "calendar_token"),
+    ("idx-workflow-status-user-id-name", "name"),
+    ("idx-custom-field-user-id-name", "name"),
+    ("idx-saved-filter-user-id-name", "name"),
+    ("idx-app-password-user-id-name", "name"),
+    ("idx-task-user-id-dav-name", "name"),
+    ("idx-task-user-id-import-key", "import_key"),
+];
+
 #[derive(Debug, Error)]
 pub enum ServiceError {
     #[error("Record with {field}={value} already exists error")]
@@ -33,8 +57,21 @@ pub enum ServiceError {
     #[error("Forbidden")]
     Forbidden,
 
+    #[error("Bad request: {0}")]
+    BadRequest(String),
+
+    #[error("Task with id={0} is blocked by unfinished tasks")]
+    Blocked(Uuid),
+
+    #[error("Record was modified, current version is {0}")]
+    PreconditionFailed(i32),
+
+    /// Failed WebDAV or CalDAV precondition, named by its XML element
+    #[error("Precondition {0} failed")]
+    DavPrecondition(String),
+
     #[error("Unknow db error: {0}")]
-    UnknowDb(#[from] DbErr),
+    UnknowDb(DbErr),
 
     #[error("Multipart error")]
     Multipart(#[from] MultipartError),
@@ -45,21 +82,95 @@ pub enum ServiceError {
     #[error("File is to large")]
     LargeFile,
 
+    #[error("Unsupported media type {0}")]
+    UnsupportedMediaType(String),
+
+    /// Requested byte range lies outside the file of this size
+    #[error("Range not satisfiable, file size is {0}")]
+    RangeNotSatisfiable(u64),
+
+    #[error(transparent)]
+    Storage(#[from] StorageError),
+
+    #[error(transparent)]
+    Mail(#[from] MailError),
+
     #[error("Error: {0}")]
     Unknow(String),
 }
 
+/// Unique violations become conflicts on the field the constraint guards, so
+/// services can leave uniqueness to the database instead of checking first.
+impl From<DbErr> for ServiceError {
+    fn from(value: DbErr) -> Self {
+        match unique_violation(&value) {
+            Some((field, value)) => ServiceError::Conflict { field, value },
+            None => ServiceError::UnknowDb(value),
+        }
+    }
+}
+
+/// Field and value of a violated unique constraint. The value is that of the
+/// last key column, which the ones before only scope, such as to a user.
+fn unique_violation(err: &DbErr) -> Option<(String, String)> {
+    let err = match err {
+        DbErr::Exec(RuntimeErr::SqlxError(sqlx::Error::Database(err)))
+        | DbErr::Query(RuntimeErr::SqlxError(sqlx::Error::Database(err))) => err,
+        _ => return None,
+    };
+
+    if err.code().as_deref() != Some(UNIQUE_VIOLATION) {
+        return None;
+    }
+
+    let constraint: &str = err.constraint()?;
+    let field: String = match UNIQUE_FIELDS.iter().find(|(name, _)| *name == constraint) {
+        Some((_, field)) => field.to_string(),
+        None => constraint.to_string(),
+    };
+
+    let detail: Option<&str> = err
+        .try_downcast_ref::<PgDatabaseError>()
+        .and_then(|err| err.detail());
+
+    Some((field, key_value(detail)))
+}
+
+/// Last key value of a unique violation detail such as `Key (user_id,
+/// name)=(..., value) already exists.`, empty when there is no detail or it
+/// reads otherwise. Expression keys such as `lower(name)` give the value of
+/// the expression.
+fn key_value(detail: Option<&str>) -> String {
+    detail
+        .and_then(|detail| {
+            let (columns, values) = detail.split_once(")=(")?;
+            let values: &str = values.strip_suffix(") already exists.")?;
+
+            values
+                .splitn(columns.matches(", ").count() + 1, ", ")
+                .last()
+                .map(|value| value.to_string())
+        })
+        .unwrap_or_default()
+}
+
 impl ResponseError for ServiceError {
     fn status_code(&self) -> StatusCode {
         match self {
-            ServiceError::Forbidden => StatusCode::FORBIDDEN,
-            ServiceError::Conflict { field: _, value: _ } => StatusCode::CONFLICT,
+            ServiceError::Forbidden | ServiceError::DavPrecondition(_) => StatusCode::FORBIDDEN,
+            ServiceError::BadRequest(_) => StatusCode::BAD_REQUEST,
+            ServiceError::PreconditionFailed(_) => StatusCode::PRECONDITION_FAILED,
+            ServiceError::Conflict { field: _, value: _ } | ServiceError::Blocked(_) => {
+                StatusCode::CONFLICT
+            }
             ServiceError::NotFound(_) => StatusCode::NOT_FOUND,
             ServiceError::Validation(_)
             | ServiceError::Multipart(_)
             | ServiceError::InvalidImage(_)
             | ServiceError::LargeFile => StatusCode::UNPROCESSABLE_ENTITY,
             ServiceError::InvalidCredentials(_) => StatusCode::UNAUTHORIZED,
+            ServiceError::UnsupportedMediaType(_) => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            ServiceError::RangeNotSatisfiable(_) => StatusCode::RANGE_NOT_SATISFIABLE,
             _ => StatusCode::INTERNAL_SERVER_ERROR,
         }
     }
@@ -71,9 +182,80 @@ impl ResponseError for ServiceError {
             Self::Validation(err) => {
                 HttpResponse::build(status_code).json(ValidateErrorDto::from_report(err))
             }
+            // Clients expect the precondition as a DAV error element
+            Self::DavPrecondition(element) => HttpResponse::build(status_code)
+                .content_type("application/xml; charset=utf-8")
+                .body(format!(
+                    r#"<?xml version="1.0" encoding="utf-8"?><D:error xmlns:D="DAV:" xmlns:C="urn:ietf:params:xml:ns:caldav"><{element}/></D:error>"#
+                )),
+            Self::RangeNotSatisfiable(size) => HttpResponse::build(status_code)
+                .insert_header((header::CONTENT_RANGE, format!("bytes */{size}")))
+                .json(ErrorDto {
+                    detail: self.to_string(),
+                }),
             _ => HttpResponse::build(status_code).json(ErrorDto {
                 detail: self.to_string(),
             }),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn single_column() {
+        assert_eq!(
+            key_value(Some("Key (calendar_token)=(abc) already exists.")),
+            "abc"
+        );
+    }
+
+    #[test]
+    fn scoping_columns_are_skipped() {
+        assert_eq!(
+            key_value(Some(
+                "Key (user_id, name)=(9b2d6a1e-0c4f-4d8e-9a57-3f1b2c4d5e6f, Backlog) already \
+                 exists."
+            )),
+            "Backlog"
+        );
+        assert_eq!(
+            key_value(Some("Key (a, b, c)=(1, 2, 3) already exists.")),
+            "3"
+        );
+    }
+
+    #[test]
+    fn last_value_may_hold_separators() {
+        assert_eq!(
+            key_value(Some(
+                "Key (user_id, name)=(9b2d6a1e-0c4f-4d8e-9a57-3f1b2c4d5e6f, Later, maybe) already \
+                 exists."
+            )),
+            "Later, maybe"
+        );
+    }
+
+    #[test]
+    fn expression_keys() {
+        assert_eq!(
+            key_value(Some("Key (lower(name))=(john) already exists.")),
+            "john"
+        );
+        assert_eq!(
+            key_value(Some(
+                "Key (lower(email))=(john@example.com) already exists."
+            )),
+            "john@example.com"
+        );
+    }
+
+    #[test]
+    fn missing_or_unknown_detail() {
+        assert_eq!(key_value(None), "");
+        assert_eq!(key_value(Some("Failing row contains (1).")), "");
+        assert_eq!(key_value(Some("Key (name)=(john) is duplicated.")), "");
+    }
+}
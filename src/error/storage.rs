@@ -0,0 +1,18 @@
+use thiserror::Error;
+
+pub type StorageResult<T = ()> = Result<T, StorageError>;
+
+#[derive(Debug, Error)]
+pub enum StorageError {
+    #[error("Object {0} not found")]
+    NotFound(String),
+
+    #[error("Storage io error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Storage request error: {0}")]
+    Request(#[from] reqwest::Error),
+
+    #[error("Storage responded with {status}: {body}")]
+    Response { status: u16, body: String },
+}
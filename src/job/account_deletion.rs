@@ -0,0 +1,43 @@
+use std::time::Duration;
+
+use crate::{
+    server::State,
+    service::{account_deletion::AccountDeletionService, storage::StorageService},
+};
+
+use super::Job;
+
+/// Purges accounts whose deletion grace period ended, and right after the
+/// files they left behind.
+pub struct AccountDeletionJob;
+
+#[async_trait::async_trait]
+impl Job for AccountDeletionJob {
+    const NAME: &'static str = "account_deletion";
+
+    fn interval(state: &State) -> Duration {
+        Duration::from_secs(state.config.user.deletion_interval)
+    }
+
+    async fn run(state: &State) -> Result<(), String> {
+        let mut purged: bool = false;
+        while AccountDeletionService::purge_next(&state.postgres)
+            .await
+            .map_err(|err| err.to_string())?
+        {
+            purged = true;
+        }
+
+        if !purged {
+            return Ok(());
+        }
+
+        while StorageService::purge_orphans(&state.postgres, &state.storage)
+            .await
+            .map_err(|err| err.to_string())?
+            > 0
+        {}
+
+        Ok(())
+    }
+}
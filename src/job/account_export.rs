@@ -0,0 +1,39 @@
+use std::time::Duration;
+
+use crate::{server::State, service::account_export::AccountExportService};
+
+use super::Job;
+
+/// Builds exports still waiting, such as ones queued before a restart, and
+/// removes the ones whose download link expired.
+pub struct AccountExportJob;
+
+#[async_trait::async_trait]
+impl Job for AccountExportJob {
+    const NAME: &'static str = "account_export";
+
+    fn interval(state: &State) -> Duration {
+        Duration::from_secs(state.config.export.interval)
+    }
+
+    async fn run(state: &State) -> Result<(), String> {
+        while AccountExportService::process_next(
+            &state.postgres,
+            &state.storage,
+            state.config.export.expire,
+        )
+        .await
+        .map_err(|err| err.to_string())?
+        {}
+
+        let count: u64 = AccountExportService::purge_expired(&state.postgres)
+            .await
+            .map_err(|err| err.to_string())?;
+
+        if count > 0 {
+            log::info!("Removed {} expired exports", count);
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,37 @@
+pub mod account_deletion;
+pub mod account_export;
+pub mod rank_rebalance;
+pub mod storage_cleanup;
+pub mod task_import;
+pub mod trash_purge;
+
+use std::time::Duration;
+
+use actix_web::rt;
+
+use crate::server::State;
+
+/// Background work that runs on a fixed interval for the lifetime of the server.
+#[async_trait::async_trait]
+pub trait Job: Send + Sync + 'static {
+    const NAME: &'static str;
+
+    fn interval(state: &State) -> Duration;
+
+    async fn run(state: &State) -> Result<(), String>;
+}
+
+/// Spawns `J` on the current runtime, the first run happens immediately.
+pub fn spawn<J: Job>(state: State) {
+    rt::spawn(async move {
+        let mut interval = rt::time::interval(J::interval(&state));
+
+        loop {
+            interval.tick().await;
+
+            if let Err(err) = J::run(&state).await {
+                log::error!("Job {} failed: {}", J::NAME, err);
+            }
+        }
+    });
+}
@@ -0,0 +1,30 @@
+use std::time::Duration;
+
+use crate::{constants, server::State, service::task::TaskService};
+
+use super::Job;
+
+/// Respaces task positions in columns where repeated moves grew the ranks.
+pub struct RankRebalanceJob;
+
+#[async_trait::async_trait]
+impl Job for RankRebalanceJob {
+    const NAME: &'static str = "rank_rebalance";
+
+    fn interval(state: &State) -> Duration {
+        Duration::from_secs(state.config.task.rebalance_interval)
+    }
+
+    async fn run(state: &State) -> Result<(), String> {
+        let columns: u64 =
+            TaskService::rebalance(&state.postgres, constants::TASK_POSITION_MAX_LENGTH)
+                .await
+                .map_err(|err| err.to_string())?;
+
+        if columns > 0 {
+            log::info!("Rebalanced positions in {} task columns", columns);
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,27 @@
+use std::time::Duration;
+
+use crate::{server::State, service::storage::StorageService};
+
+use super::Job;
+
+/// Removes stored files nothing refers to anymore.
+pub struct StorageCleanupJob;
+
+#[async_trait::async_trait]
+impl Job for StorageCleanupJob {
+    const NAME: &'static str = "storage_cleanup";
+
+    fn interval(state: &State) -> Duration {
+        Duration::from_secs(state.config.storage.cleanup_interval)
+    }
+
+    async fn run(state: &State) -> Result<(), String> {
+        while StorageService::purge_orphans(&state.postgres, &state.storage)
+            .await
+            .map_err(|err| err.to_string())?
+            > 0
+        {}
+
+        Ok(())
+    }
+}
@@ -0,0 +1,26 @@
+use std::time::Duration;
+
+use crate::{server::State, service::task_import::TaskImportService};
+
+use super::Job;
+
+/// Runs imports still waiting, such as ones queued before a restart.
+pub struct TaskImportJob;
+
+#[async_trait::async_trait]
+impl Job for TaskImportJob {
+    const NAME: &'static str = "task_import";
+
+    fn interval(state: &State) -> Duration {
+        Duration::from_secs(state.config.import.interval)
+    }
+
+    async fn run(state: &State) -> Result<(), String> {
+        while TaskImportService::process_next(&state.postgres)
+            .await
+            .map_err(|err| err.to_string())?
+        {}
+
+        Ok(())
+    }
+}
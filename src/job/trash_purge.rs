@@ -0,0 +1,72 @@
+use std::time::Duration;
+
+use chrono::{DateTime, FixedOffset, Local};
+
+use crate::{
+    constants,
+    server::State,
+    service::{task::TaskService, task_comment::TaskCommentService},
+};
+
+use super::Job;
+
+/// Permanently deletes tasks and comments that stayed in the trash longer than
+/// the configured retention.
+pub struct TrashPurgeJob;
+
+#[async_trait::async_trait]
+impl Job for TrashPurgeJob {
+    const NAME: &'static str = "trash_purge";
+
+    fn interval(state: &State) -> Duration {
+        Duration::from_secs(state.config.trash.purge_interval)
+    }
+
+    async fn run(state: &State) -> Result<(), String> {
+        let before: DateTime<FixedOffset> =
+            (Local::now() - Duration::from_secs(state.config.trash.retention)).fixed_offset();
+
+        // Tasks first, their comments go with them by cascade
+        let mut tasks: u64 = 0;
+        loop {
+            let count: u64 = TaskService::purge_expired(
+                &state.postgres,
+                before,
+                constants::TRASH_PURGE_BATCH_SIZE,
+            )
+            .await
+            .map_err(|err| err.to_string())?;
+
+            tasks += count;
+            if count < constants::TRASH_PURGE_BATCH_SIZE {
+                break;
+            }
+        }
+
+        let mut comments: u64 = 0;
+        loop {
+            let count: u64 = TaskCommentService::purge_expired(
+                &state.postgres,
+                before,
+                constants::TRASH_PURGE_BATCH_SIZE,
+            )
+            .await
+            .map_err(|err| err.to_string())?;
+
+            comments += count;
+            if count < constants::TRASH_PURGE_BATCH_SIZE {
+                break;
+            }
+        }
+
+        if tasks + comments > 0 {
+            log::info!(
+                "Purged {} tasks and {} comments from trash",
+                tasks,
+                comments
+            );
+        }
+
+        Ok(())
+    }
+}
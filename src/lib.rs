@@ -5,6 +5,7 @@ pub mod constants;
 pub mod dto;
 pub mod entity;
 pub mod error;
+pub mod job;
 pub mod migration;
 pub mod server;
 pub mod service;
@@ -1,6 +1,6 @@
 use std::path::PathBuf;
 
-use clap::{command, Parser};
+use clap::Parser;
 use config::{Config as ConfigLoader, File};
 use sea_orm_migration::MigratorTrait;
 use task_flow_backend::{
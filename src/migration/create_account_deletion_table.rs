@@ -0,0 +1,142 @@
+use sea_orm_migration::prelude::*;
+
+use super::{create_table_extension::GenerateUuidFunc, create_user_table::User};
+
+/// Deletion requests wait on the user until their grace period ends, what was
+/// purged is recorded apart, as the user is gone by then.
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(User::Table)
+                    .add_column(
+                        ColumnDef::new(User::DeletionRequestedAt)
+                            .timestamp_with_time_zone()
+                            .null(),
+                    )
+                    .add_column(
+                        ColumnDef::new(User::DeletionScheduledAt)
+                            .timestamp_with_time_zone()
+                            .null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-user-deletion-scheduled-at")
+                    .table(User::Table)
+                    .col(User::DeletionScheduledAt)
+                    .to_owned(),
+            )
+            .await?;
+
+        // No foreign key, the record outlives the user
+        manager
+            .create_table(
+                Table::create()
+                    .table(AccountDeletion::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(AccountDeletion::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key()
+                            .default(Func::cust(GenerateUuidFunc)),
+                    )
+                    .col(ColumnDef::new(AccountDeletion::UserId).uuid().not_null())
+                    .col(
+                        ColumnDef::new(AccountDeletion::RequestedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(AccountDeletion::Tasks)
+                            .big_integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(AccountDeletion::Comments)
+                            .big_integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(AccountDeletion::Files)
+                            .big_integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(AccountDeletion::Tokens)
+                            .big_integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(AccountDeletion::PurgedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(SimpleExpr::Keyword(Keyword::CurrentTimestamp)),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-account-deletion-user-id")
+                    .table(AccountDeletion::Table)
+                    .col(AccountDeletion::UserId)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(AccountDeletion::Table).to_owned())
+            .await?;
+
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx-user-deletion-scheduled-at")
+                    .table(User::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(User::Table)
+                    .drop_column(User::DeletionRequestedAt)
+                    .drop_column(User::DeletionScheduledAt)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+pub enum AccountDeletion {
+    Table,
+    Id,
+    UserId,
+    RequestedAt,
+    Tasks,
+    Comments,
+    Files,
+    Tokens,
+    PurgedAt,
+}
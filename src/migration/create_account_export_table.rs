@@ -0,0 +1,137 @@
+use extension::postgres::Type;
+use sea_orm::{ActiveEnum, DeriveActiveEnum, EnumIter};
+use sea_orm_migration::prelude::*;
+
+use super::{create_table_extension::GenerateUuidFunc, create_user_table::User};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_type(
+                Type::create()
+                    .as_enum(ExportStatus::name())
+                    .values(ExportStatus::iden_values())
+                    .to_owned(),
+            )
+            .await?;
+
+        // The archive is kept until the download link expires
+        manager
+            .create_table(
+                Table::create()
+                    .table(AccountExport::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(AccountExport::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key()
+                            .default(Func::cust(GenerateUuidFunc)),
+                    )
+                    .col(ColumnDef::new(AccountExport::UserId).uuid().not_null())
+                    .col(
+                        ColumnDef::new(AccountExport::Status)
+                            .enumeration(ExportStatus::name(), ExportStatus::iden_values())
+                            .not_null()
+                            .default(ExportStatus::Pending.as_enum()),
+                    )
+                    .col(
+                        ColumnDef::new(AccountExport::Token)
+                            .text()
+                            .null()
+                            .unique_key(),
+                    )
+                    .col(ColumnDef::new(AccountExport::File).binary().null())
+                    .col(ColumnDef::new(AccountExport::Error).text().null())
+                    .col(
+                        ColumnDef::new(AccountExport::ExpiresAt)
+                            .timestamp_with_time_zone()
+                            .null(),
+                    )
+                    .col(
+                        ColumnDef::new(AccountExport::UpdatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(SimpleExpr::Keyword(Keyword::CurrentTimestamp)),
+                    )
+                    .col(
+                        ColumnDef::new(AccountExport::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(SimpleExpr::Keyword(Keyword::CurrentTimestamp)),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-account-export-user-id")
+                            .from(AccountExport::Table, AccountExport::UserId)
+                            .to(User::Table, User::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-account-export-status-created-at")
+                    .table(AccountExport::Table)
+                    .col(AccountExport::Status)
+                    .col(AccountExport::CreatedAt)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(AccountExport::Table).to_owned())
+            .await?;
+
+        manager
+            .drop_type(
+                Type::drop()
+                    .if_exists()
+                    .name(ExportStatus::name())
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+pub enum AccountExport {
+    Table,
+    Id,
+    UserId,
+    Status,
+    Token,
+    File,
+    StorageKey,
+    Size,
+    Error,
+    ExpiresAt,
+    UpdatedAt,
+    CreatedAt,
+}
+
+#[derive(EnumIter, DeriveActiveEnum)]
+#[sea_orm(rs_type = "String", db_type = "Enum", enum_name = "export_status")]
+pub enum ExportStatus {
+    #[sea_orm(string_value = "pending")]
+    Pending,
+
+    #[sea_orm(string_value = "ready")]
+    Ready,
+
+    #[sea_orm(string_value = "failed")]
+    Failed,
+}
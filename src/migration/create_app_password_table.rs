@@ -0,0 +1,122 @@
+use sea_orm_migration::prelude::*;
+
+use super::{
+    create_table_extension::GenerateUuidFunc, create_task_table::Task, create_user_table::User,
+};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(AppPassword::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(AppPassword::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key()
+                            .default(Func::cust(GenerateUuidFunc)),
+                    )
+                    .col(ColumnDef::new(AppPassword::UserId).uuid().not_null())
+                    .col(ColumnDef::new(AppPassword::Name).text().not_null())
+                    .col(
+                        ColumnDef::new(AppPassword::Hash)
+                            .text()
+                            .not_null()
+                            .unique_key(),
+                    )
+                    .col(
+                        ColumnDef::new(AppPassword::LastUsedAt)
+                            .timestamp_with_time_zone()
+                            .null(),
+                    )
+                    .col(
+                        ColumnDef::new(AppPassword::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(SimpleExpr::Keyword(Keyword::CurrentTimestamp)),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-app-password-user-id")
+                            .from(AppPassword::Table, AppPassword::UserId)
+                            .to(User::Table, User::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-app-password-user-id-name")
+                    .table(AppPassword::Table)
+                    .col(AppPassword::UserId)
+                    .col(AppPassword::Name)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        // Resource name and iCalendar UID chosen by a CalDAV client, tasks
+        // created elsewhere are served as `{id}.ics` with their id as UID
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Task::Table)
+                    .add_column(ColumnDef::new(Task::DavName).text().null())
+                    .add_column(ColumnDef::new(Task::IcalUid).text().null())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-task-user-id-dav-name")
+                    .table(Task::Table)
+                    .col(Task::UserId)
+                    .col(Task::DavName)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Task::Table)
+                    .drop_column(Task::DavName)
+                    .drop_column(Task::IcalUid)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(AppPassword::Table).to_owned())
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+pub enum AppPassword {
+    Table,
+    Id,
+    UserId,
+    Name,
+    Hash,
+    LastUsedAt,
+    CreatedAt,
+}
@@ -0,0 +1,170 @@
+use sea_orm_migration::prelude::*;
+
+use super::{
+    create_table_extension::GenerateUuidFunc,
+    create_task_table::{Task, TaskComment},
+    create_user_table::User,
+};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // File contents live in the storage backend, under `storage_key`
+        manager
+            .create_table(
+                Table::create()
+                    .table(Attachment::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(Attachment::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key()
+                            .default(Func::cust(GenerateUuidFunc)),
+                    )
+                    .col(ColumnDef::new(Attachment::UserId).uuid().not_null())
+                    .col(ColumnDef::new(Attachment::TaskId).uuid().not_null())
+                    .col(ColumnDef::new(Attachment::CommentId).uuid().null())
+                    .col(ColumnDef::new(Attachment::Name).text().not_null())
+                    .col(ColumnDef::new(Attachment::ContentType).text().not_null())
+                    .col(ColumnDef::new(Attachment::Size).big_integer().not_null())
+                    .col(
+                        ColumnDef::new(Attachment::StorageKey)
+                            .text()
+                            .not_null()
+                            .unique_key(),
+                    )
+                    .col(ColumnDef::new(Attachment::Sha256).text().not_null())
+                    .col(
+                        ColumnDef::new(Attachment::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(SimpleExpr::Keyword(Keyword::CurrentTimestamp)),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-attachment-user-id")
+                            .from(Attachment::Table, Attachment::UserId)
+                            .to(User::Table, User::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-attachment-task-id")
+                            .from(Attachment::Table, Attachment::TaskId)
+                            .to(Task::Table, Task::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-attachment-comment-id")
+                            .from(Attachment::Table, Attachment::CommentId)
+                            .to(TaskComment::Table, TaskComment::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-attachment-task-id")
+                    .table(Attachment::Table)
+                    .col(Attachment::TaskId)
+                    .to_owned(),
+            )
+            .await?;
+
+        // Keys of files whose record is gone, the cleanup job removes them
+        manager
+            .create_table(
+                Table::create()
+                    .table(StorageOrphan::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(StorageOrphan::StorageKey)
+                            .text()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(StorageOrphan::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(SimpleExpr::Keyword(Keyword::CurrentTimestamp)),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // Cascading deletes of tasks, comments and users skip the service
+        // layer, so the file is handed over here
+        manager
+            .get_connection()
+            .execute_unprepared(
+                r#"
+            CREATE OR REPLACE FUNCTION record_storage_orphan() RETURNS trigger AS $$
+            BEGIN
+                INSERT INTO storage_orphan (storage_key) VALUES (OLD.storage_key)
+                    ON CONFLICT (storage_key) DO NOTHING;
+                RETURN OLD;
+            END;
+            $$ LANGUAGE plpgsql;
+
+            CREATE TRIGGER attachment_record_storage_orphan AFTER DELETE ON attachment
+                FOR EACH ROW EXECUTE FUNCTION record_storage_orphan();
+            "#,
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .get_connection()
+            .execute_unprepared(
+                r#"
+            DROP TRIGGER IF EXISTS attachment_record_storage_orphan ON attachment;
+            DROP FUNCTION IF EXISTS record_storage_orphan();
+            "#,
+            )
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(StorageOrphan::Table).to_owned())
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(Attachment::Table).to_owned())
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+pub enum Attachment {
+    Table,
+    Id,
+    UserId,
+    TaskId,
+    CommentId,
+    Name,
+    ContentType,
+    Size,
+    StorageKey,
+    Sha256,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+pub enum StorageOrphan {
+    Table,
+    StorageKey,
+    CreatedAt,
+}
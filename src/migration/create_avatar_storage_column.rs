@@ -0,0 +1,91 @@
+use sea_orm_migration::prelude::*;
+
+use crate::constants;
+
+use super::create_user_table::UserAvatar;
+
+/// Avatars move to the file storage, `file` only keeps the ones stored before
+/// until the server moves them on start.
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(UserAvatar::Table)
+                    .add_column(ColumnDef::new(UserAvatar::StorageKey).text().null())
+                    .add_column(ColumnDef::new(UserAvatar::Sha256).text().null())
+                    .modify_column(
+                        ColumnDef::new(UserAvatar::File)
+                            .var_binary(constants::AVATAR_MAX_SIZE as u32)
+                            .null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-user-avatar-storage-key")
+                    .table(UserAvatar::Table)
+                    .col(UserAvatar::StorageKey)
+                    .to_owned(),
+            )
+            .await?;
+
+        // Avatars share files by content, the cleanup skips keys still in use
+        manager
+            .get_connection()
+            .execute_unprepared(
+                r#"
+            CREATE TRIGGER user_avatar_record_storage_orphan AFTER DELETE ON user_avatar
+                FOR EACH ROW WHEN (OLD.storage_key IS NOT NULL)
+                EXECUTE FUNCTION record_storage_orphan();
+
+            CREATE TRIGGER user_avatar_replace_storage_orphan AFTER UPDATE OF storage_key ON user_avatar
+                FOR EACH ROW WHEN (OLD.storage_key IS DISTINCT FROM NEW.storage_key AND OLD.storage_key IS NOT NULL)
+                EXECUTE FUNCTION record_storage_orphan();
+            "#,
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .get_connection()
+            .execute_unprepared(
+                r#"
+            DROP TRIGGER IF EXISTS user_avatar_replace_storage_orphan ON user_avatar;
+            DROP TRIGGER IF EXISTS user_avatar_record_storage_orphan ON user_avatar;
+            "#,
+            )
+            .await?;
+
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx-user-avatar-storage-key")
+                    .table(UserAvatar::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(UserAvatar::Table)
+                    .drop_column(UserAvatar::StorageKey)
+                    .drop_column(UserAvatar::Sha256)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,143 @@
+use extension::postgres::Type;
+use sea_orm::{ActiveEnum, DeriveActiveEnum, EnumIter};
+use sea_orm_migration::prelude::*;
+
+use super::{create_table_extension::GenerateUuidFunc, create_user_table::UserAvatar};
+
+/// Every avatar is kept in a few sizes and formats, made once on upload.
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_type(
+                Type::create()
+                    .as_enum(AvatarFormat::name())
+                    .values(AvatarFormat::iden_values())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(UserAvatarVariant::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(UserAvatarVariant::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key()
+                            .default(Func::cust(GenerateUuidFunc)),
+                    )
+                    .col(
+                        ColumnDef::new(UserAvatarVariant::AvatarId)
+                            .uuid()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(UserAvatarVariant::Size).integer().not_null())
+                    .col(
+                        ColumnDef::new(UserAvatarVariant::Format)
+                            .enumeration(AvatarFormat::name(), AvatarFormat::iden_values())
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(UserAvatarVariant::StorageKey)
+                            .text()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(UserAvatarVariant::Sha256).text().not_null())
+                    .col(
+                        ColumnDef::new(UserAvatarVariant::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(SimpleExpr::Keyword(Keyword::CurrentTimestamp)),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-user-avatar-variant-avatar-id")
+                            .from(UserAvatarVariant::Table, UserAvatarVariant::AvatarId)
+                            .to(UserAvatar::Table, UserAvatar::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-user-avatar-variant-avatar-id-size-format")
+                    .table(UserAvatarVariant::Table)
+                    .col(UserAvatarVariant::AvatarId)
+                    .col(UserAvatarVariant::Size)
+                    .col(UserAvatarVariant::Format)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-user-avatar-variant-storage-key")
+                    .table(UserAvatarVariant::Table)
+                    .col(UserAvatarVariant::StorageKey)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .get_connection()
+            .execute_unprepared(
+                r#"
+            CREATE TRIGGER user_avatar_variant_record_storage_orphan AFTER DELETE ON user_avatar_variant
+                FOR EACH ROW EXECUTE FUNCTION record_storage_orphan();
+            "#,
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(UserAvatarVariant::Table).to_owned())
+            .await?;
+
+        manager
+            .drop_type(
+                Type::drop()
+                    .if_exists()
+                    .name(AvatarFormat::name())
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+pub enum UserAvatarVariant {
+    Table,
+    Id,
+    AvatarId,
+    Size,
+    Format,
+    StorageKey,
+    Sha256,
+    CreatedAt,
+}
+
+#[derive(EnumIter, DeriveActiveEnum)]
+#[sea_orm(rs_type = "String", db_type = "Enum", enum_name = "avatar_format")]
+pub enum AvatarFormat {
+    #[sea_orm(string_value = "png")]
+    Png,
+
+    #[sea_orm(string_value = "webp")]
+    Webp,
+}
@@ -0,0 +1,43 @@
+use sea_orm_migration::prelude::*;
+
+/// Names and emails are unique regardless of case. Fails while users differing
+/// only in case exist, they have to be renamed first.
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        db.execute_unprepared(
+            r#"
+            ALTER TABLE "user" DROP CONSTRAINT IF EXISTS user_name_key;
+            ALTER TABLE "user" DROP CONSTRAINT IF EXISTS user_email_key;
+
+            CREATE UNIQUE INDEX "idx-user-name-lower" ON "user" (lower(name));
+            CREATE UNIQUE INDEX "idx-user-email-lower" ON "user" (lower(email));
+            "#,
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        db.execute_unprepared(
+            r#"
+            DROP INDEX IF EXISTS "idx-user-email-lower";
+            DROP INDEX IF EXISTS "idx-user-name-lower";
+
+            ALTER TABLE "user" ADD CONSTRAINT user_name_key UNIQUE (name);
+            ALTER TABLE "user" ADD CONSTRAINT user_email_key UNIQUE (email);
+            "#,
+        )
+        .await?;
+
+        Ok(())
+    }
+}
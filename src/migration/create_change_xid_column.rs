@@ -0,0 +1,58 @@
+use sea_orm_migration::prelude::*;
+
+/// Transaction that made each change, so a sync pull can tell which changes
+/// below its token were not committed yet when the token was handed out.
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        db.execute_unprepared(
+            r#"
+            ALTER TABLE task ADD COLUMN change_xid xid8 NOT NULL DEFAULT pg_current_xact_id();
+            ALTER TABLE task_comment ADD COLUMN change_xid xid8 NOT NULL DEFAULT pg_current_xact_id();
+            ALTER TABLE tombstone ADD COLUMN change_xid xid8 NOT NULL DEFAULT pg_current_xact_id();
+
+            CREATE INDEX "idx-task-change-xid" ON task (change_xid);
+            CREATE INDEX "idx-task-comment-change-xid" ON task_comment (change_xid);
+            CREATE INDEX "idx-tombstone-change-xid" ON tombstone (change_xid);
+
+            CREATE OR REPLACE FUNCTION bump_change_seq() RETURNS trigger AS $$
+            BEGIN
+                NEW.change_seq := nextval('change_seq');
+                NEW.change_xid := pg_current_xact_id();
+                RETURN NEW;
+            END;
+            $$ LANGUAGE plpgsql;
+            "#,
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        db.execute_unprepared(
+            r#"
+            CREATE OR REPLACE FUNCTION bump_change_seq() RETURNS trigger AS $$
+            BEGIN
+                NEW.change_seq := nextval('change_seq');
+                RETURN NEW;
+            END;
+            $$ LANGUAGE plpgsql;
+
+            ALTER TABLE tombstone DROP COLUMN IF EXISTS change_xid;
+            ALTER TABLE task_comment DROP COLUMN IF EXISTS change_xid;
+            ALTER TABLE task DROP COLUMN IF EXISTS change_xid;
+            "#,
+        )
+        .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,218 @@
+use extension::postgres::Type;
+use sea_orm::{ActiveEnum, DeriveActiveEnum, EnumIter};
+use sea_orm_migration::prelude::*;
+
+use super::{
+    create_table_extension::GenerateUuidFunc, create_task_table::Task, create_user_table::User,
+};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_type(
+                Type::create()
+                    .as_enum(CustomFieldKind::name())
+                    .values(CustomFieldKind::iden_values())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(CustomField::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(CustomField::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key()
+                            .default(Func::cust(GenerateUuidFunc)),
+                    )
+                    .col(ColumnDef::new(CustomField::UserId).uuid().not_null())
+                    .col(ColumnDef::new(CustomField::Name).text().not_null())
+                    .col(
+                        ColumnDef::new(CustomField::Kind)
+                            .enumeration(CustomFieldKind::name(), CustomFieldKind::iden_values())
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(CustomField::Options)
+                            .json_binary()
+                            .not_null()
+                            .default(Expr::cust("'[]'::jsonb")),
+                    )
+                    .col(
+                        ColumnDef::new(CustomField::UpdatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(SimpleExpr::Keyword(Keyword::CurrentTimestamp)),
+                    )
+                    .col(
+                        ColumnDef::new(CustomField::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(SimpleExpr::Keyword(Keyword::CurrentTimestamp)),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-custom-field-user-id")
+                            .from(CustomField::Table, CustomField::UserId)
+                            .to(User::Table, User::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-custom-field-user-id-name")
+                    .table(CustomField::Table)
+                    .col(CustomField::UserId)
+                    .col(CustomField::Name)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        // Values are stored as JSON of the field kind, so jsonb ordering sorts
+        // numbers numerically and ISO dates chronologically
+        manager
+            .create_table(
+                Table::create()
+                    .table(TaskCustomFieldValue::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(TaskCustomFieldValue::TaskId)
+                            .uuid()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(TaskCustomFieldValue::FieldId)
+                            .uuid()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(TaskCustomFieldValue::Value)
+                            .json_binary()
+                            .not_null(),
+                    )
+                    .primary_key(
+                        Index::create()
+                            .col(TaskCustomFieldValue::TaskId)
+                            .col(TaskCustomFieldValue::FieldId),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-task-custom-field-value-task-id")
+                            .from(TaskCustomFieldValue::Table, TaskCustomFieldValue::TaskId)
+                            .to(Task::Table, Task::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-task-custom-field-value-field-id")
+                            .from(TaskCustomFieldValue::Table, TaskCustomFieldValue::FieldId)
+                            .to(CustomField::Table, CustomField::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-task-custom-field-value-field-id-value")
+                    .table(TaskCustomFieldValue::Table)
+                    .col(TaskCustomFieldValue::FieldId)
+                    .col(TaskCustomFieldValue::Value)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(
+                Table::drop()
+                    .if_exists()
+                    .table(TaskCustomFieldValue::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_table(
+                Table::drop()
+                    .if_exists()
+                    .table(CustomField::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_type(
+                Type::drop()
+                    .if_exists()
+                    .name(CustomFieldKind::name())
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+pub enum CustomField {
+    Table,
+    Id,
+    UserId,
+    Name,
+    Kind,
+    Options,
+    UpdatedAt,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+pub enum TaskCustomFieldValue {
+    Table,
+    TaskId,
+    FieldId,
+    Value,
+}
+
+#[derive(EnumIter, DeriveActiveEnum)]
+#[sea_orm(rs_type = "String", db_type = "Enum", enum_name = "custom_field_kind")]
+pub enum CustomFieldKind {
+    #[sea_orm(string_value = "text")]
+    Text,
+
+    #[sea_orm(string_value = "number")]
+    Number,
+
+    #[sea_orm(string_value = "date")]
+    Date,
+
+    #[sea_orm(string_value = "boolean")]
+    Boolean,
+
+    #[sea_orm(string_value = "select")]
+    Select,
+
+    #[sea_orm(string_value = "multi_select")]
+    MultiSelect,
+
+    #[sea_orm(string_value = "url")]
+    Url,
+}
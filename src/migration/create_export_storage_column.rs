@@ -0,0 +1,87 @@
+use sea_orm_migration::prelude::*;
+
+use super::create_account_export_table::AccountExport;
+
+/// Export archives move to the file storage. Ones built before expire soon
+/// anyway, they are dropped rather than moved and can be requested again.
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .get_connection()
+            .execute_unprepared("DELETE FROM account_export WHERE file IS NOT NULL")
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(AccountExport::Table)
+                    .drop_column(AccountExport::File)
+                    .add_column(ColumnDef::new(AccountExport::StorageKey).text().null())
+                    .add_column(ColumnDef::new(AccountExport::Size).big_integer().null())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-account-export-storage-key")
+                    .table(AccountExport::Table)
+                    .col(AccountExport::StorageKey)
+                    .to_owned(),
+            )
+            .await?;
+
+        // Deleted and expired exports leave their archive to the storage cleanup
+        manager
+            .get_connection()
+            .execute_unprepared(
+                r#"
+            CREATE TRIGGER account_export_record_storage_orphan AFTER DELETE ON account_export
+                FOR EACH ROW WHEN (OLD.storage_key IS NOT NULL)
+                EXECUTE FUNCTION record_storage_orphan();
+            "#,
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .get_connection()
+            .execute_unprepared(
+                r#"
+            DELETE FROM account_export WHERE storage_key IS NOT NULL;
+            DROP TRIGGER IF EXISTS account_export_record_storage_orphan ON account_export;
+            "#,
+            )
+            .await?;
+
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx-account-export-storage-key")
+                    .table(AccountExport::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(AccountExport::Table)
+                    .drop_column(AccountExport::StorageKey)
+                    .drop_column(AccountExport::Size)
+                    .add_column(ColumnDef::new(AccountExport::File).binary().null())
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
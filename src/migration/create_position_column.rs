@@ -0,0 +1,73 @@
+use sea_orm_migration::prelude::*;
+
+use super::create_task_table::Task;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        // Byte order collation, ranks are compared digit by digit
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Task::Table)
+                    .add_column(
+                        ColumnDef::new(Task::Position)
+                            .text()
+                            .null()
+                            .extra("COLLATE \"C\""),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // Existing tasks keep their creation order. Hex digits are valid rank
+        // digits and the suffix keeps ranks from ending with zero.
+        db.execute_unprepared(
+            r#"
+            UPDATE task SET position = ranked.position
+            FROM (
+                SELECT id, lpad(to_hex(row_number() OVER (
+                    PARTITION BY user_id, status ORDER BY created_at, id
+                )), 8, '0') || 'i' AS position
+                FROM task
+            ) ranked
+            WHERE task.id = ranked.id;
+
+            ALTER TABLE task ALTER COLUMN position SET NOT NULL;
+            "#,
+        )
+        .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-task-user-id-status-position")
+                    .table(Task::Table)
+                    .col(Task::UserId)
+                    .col(Task::Status)
+                    .col(Task::Position)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Task::Table)
+                    .drop_column(Task::Position)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
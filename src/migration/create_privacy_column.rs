@@ -0,0 +1,48 @@
+use sea_orm_migration::prelude::*;
+
+use super::create_user_preference_table::UserPreference;
+
+/// Privacy settings of a user. Both default to what every user had before.
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(UserPreference::Table)
+                    .add_column(
+                        ColumnDef::new(UserPreference::Discoverable)
+                            .boolean()
+                            .not_null()
+                            .default(true),
+                    )
+                    .add_column(
+                        ColumnDef::new(UserPreference::ShowAvatar)
+                            .boolean()
+                            .not_null()
+                            .default(true),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(UserPreference::Table)
+                    .drop_column(UserPreference::Discoverable)
+                    .drop_column(UserPreference::ShowAvatar)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,88 @@
+use sea_orm_migration::prelude::*;
+
+use super::{create_table_extension::GenerateUuidFunc, create_user_table::User};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // The expression is kept as written and parsed again on every run
+        manager
+            .create_table(
+                Table::create()
+                    .table(SavedFilter::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(SavedFilter::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key()
+                            .default(Func::cust(GenerateUuidFunc)),
+                    )
+                    .col(ColumnDef::new(SavedFilter::UserId).uuid().not_null())
+                    .col(ColumnDef::new(SavedFilter::Name).text().not_null())
+                    .col(ColumnDef::new(SavedFilter::Expression).text().not_null())
+                    .col(
+                        ColumnDef::new(SavedFilter::UpdatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(SimpleExpr::Keyword(Keyword::CurrentTimestamp)),
+                    )
+                    .col(
+                        ColumnDef::new(SavedFilter::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(SimpleExpr::Keyword(Keyword::CurrentTimestamp)),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-saved-filter-user-id")
+                            .from(SavedFilter::Table, SavedFilter::UserId)
+                            .to(User::Table, User::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-saved-filter-user-id-name")
+                    .table(SavedFilter::Table)
+                    .col(SavedFilter::UserId)
+                    .col(SavedFilter::Name)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(
+                Table::drop()
+                    .if_exists()
+                    .table(SavedFilter::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+pub enum SavedFilter {
+    Table,
+    Id,
+    UserId,
+    Name,
+    Expression,
+    UpdatedAt,
+    CreatedAt,
+}
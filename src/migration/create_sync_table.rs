@@ -0,0 +1,234 @@
+use extension::postgres::Type;
+use sea_orm::{ActiveEnum, DeriveActiveEnum, EnumIter};
+use sea_orm_migration::prelude::*;
+
+use super::{
+    create_table_extension::GenerateUuidFunc,
+    create_task_table::{Task, TaskComment},
+    create_user_table::User,
+};
+
+const CHANGE_SEQ: &str = "change_seq";
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        db.execute_unprepared(&format!("CREATE SEQUENCE IF NOT EXISTS {CHANGE_SEQ};"))
+            .await?;
+
+        manager
+            .create_type(
+                Type::create()
+                    .as_enum(SyncEntity::name())
+                    .values(SyncEntity::iden_values())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Task::Table)
+                    .add_column(change_seq_column(Task::ChangeSeq))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(TaskComment::Table)
+                    .add_column(change_seq_column(TaskComment::ChangeSeq))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(Tombstone::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(Tombstone::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key()
+                            .default(Func::cust(GenerateUuidFunc)),
+                    )
+                    .col(ColumnDef::new(Tombstone::UserId).uuid().not_null())
+                    .col(
+                        ColumnDef::new(Tombstone::Entity)
+                            .enumeration(SyncEntity::name(), SyncEntity::iden_values())
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(Tombstone::EntityId).uuid().not_null())
+                    .col(change_seq_column(Tombstone::ChangeSeq))
+                    .col(
+                        ColumnDef::new(Tombstone::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(SimpleExpr::Keyword(Keyword::CurrentTimestamp)),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-tombstone-user-id")
+                            .from(Tombstone::Table, Tombstone::UserId)
+                            .to(User::Table, User::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-task-user-id-change-seq")
+                    .table(Task::Table)
+                    .col(Task::UserId)
+                    .col(Task::ChangeSeq)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-task-comment-user-id-change-seq")
+                    .table(TaskComment::Table)
+                    .col(TaskComment::UserId)
+                    .col(TaskComment::ChangeSeq)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-tombstone-user-id-change-seq")
+                    .table(Tombstone::Table)
+                    .col(Tombstone::UserId)
+                    .col(Tombstone::ChangeSeq)
+                    .to_owned(),
+            )
+            .await?;
+
+        // Every update takes a fresh sequence value, so a row always sorts after
+        // the sync token that last saw it.
+        db.execute_unprepared(&format!(
+            r#"
+            CREATE OR REPLACE FUNCTION bump_change_seq() RETURNS trigger AS $$
+            BEGIN
+                NEW.change_seq := nextval('{CHANGE_SEQ}');
+                RETURN NEW;
+            END;
+            $$ LANGUAGE plpgsql;
+
+            CREATE OR REPLACE FUNCTION record_tombstone() RETURNS trigger AS $$
+            BEGIN
+                -- Rows removed by the cascade of a user deletion need no tombstone
+                IF EXISTS (SELECT 1 FROM "user" WHERE id = OLD.user_id) THEN
+                    INSERT INTO tombstone (user_id, entity, entity_id)
+                    VALUES (OLD.user_id, TG_ARGV[0]::sync_entity, OLD.id);
+                END IF;
+                RETURN OLD;
+            END;
+            $$ LANGUAGE plpgsql;
+
+            CREATE TRIGGER task_bump_change_seq BEFORE UPDATE ON task
+                FOR EACH ROW EXECUTE FUNCTION bump_change_seq();
+            CREATE TRIGGER task_comment_bump_change_seq BEFORE UPDATE ON task_comment
+                FOR EACH ROW EXECUTE FUNCTION bump_change_seq();
+
+            CREATE TRIGGER task_record_tombstone AFTER DELETE ON task
+                FOR EACH ROW EXECUTE FUNCTION record_tombstone('task');
+            CREATE TRIGGER task_comment_record_tombstone AFTER DELETE ON task_comment
+                FOR EACH ROW EXECUTE FUNCTION record_tombstone('task_comment');
+            "#
+        ))
+        .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        db.execute_unprepared(
+            r#"
+            DROP TRIGGER IF EXISTS task_comment_record_tombstone ON task_comment;
+            DROP TRIGGER IF EXISTS task_record_tombstone ON task;
+            DROP TRIGGER IF EXISTS task_comment_bump_change_seq ON task_comment;
+            DROP TRIGGER IF EXISTS task_bump_change_seq ON task;
+            DROP FUNCTION IF EXISTS record_tombstone();
+            DROP FUNCTION IF EXISTS bump_change_seq();
+            "#,
+        )
+        .await?;
+
+        manager
+            .drop_table(Table::drop().if_exists().table(Tombstone::Table).to_owned())
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(TaskComment::Table)
+                    .drop_column(TaskComment::ChangeSeq)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Task::Table)
+                    .drop_column(Task::ChangeSeq)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_type(Type::drop().if_exists().name(SyncEntity::name()).to_owned())
+            .await?;
+
+        db.execute_unprepared(&format!("DROP SEQUENCE IF EXISTS {CHANGE_SEQ};"))
+            .await?;
+
+        Ok(())
+    }
+}
+
+fn change_seq_column<T: IntoIden>(column: T) -> ColumnDef {
+    ColumnDef::new(column)
+        .big_integer()
+        .not_null()
+        .default(Expr::cust(format!("nextval('{CHANGE_SEQ}')")))
+        .to_owned()
+}
+
+#[derive(DeriveIden)]
+pub enum Tombstone {
+    Table,
+    Id,
+    UserId,
+    Entity,
+    EntityId,
+    ChangeSeq,
+    CreatedAt,
+}
+
+#[derive(EnumIter, DeriveActiveEnum)]
+#[sea_orm(rs_type = "String", db_type = "Enum", enum_name = "sync_entity")]
+pub enum SyncEntity {
+    #[sea_orm(string_value = "task")]
+    Task,
+
+    #[sea_orm(string_value = "task_comment")]
+    TaskComment,
+}
@@ -0,0 +1,92 @@
+use sea_orm_migration::prelude::*;
+
+use super::{create_task_table::Task, create_user_table::User};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(TaskDependency::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(TaskDependency::TaskId).uuid().not_null())
+                    .col(ColumnDef::new(TaskDependency::BlockerId).uuid().not_null())
+                    .col(ColumnDef::new(TaskDependency::UserId).uuid().not_null())
+                    .col(
+                        ColumnDef::new(TaskDependency::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(SimpleExpr::Keyword(Keyword::CurrentTimestamp)),
+                    )
+                    .primary_key(
+                        Index::create()
+                            .col(TaskDependency::TaskId)
+                            .col(TaskDependency::BlockerId),
+                    )
+                    .check(
+                        Expr::col(TaskDependency::TaskId).ne(Expr::col(TaskDependency::BlockerId)),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-task-dependency-task-id")
+                            .from(TaskDependency::Table, TaskDependency::TaskId)
+                            .to(Task::Table, Task::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-task-dependency-blocker-id")
+                            .from(TaskDependency::Table, TaskDependency::BlockerId)
+                            .to(Task::Table, Task::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-task-dependency-user-id")
+                            .from(TaskDependency::Table, TaskDependency::UserId)
+                            .to(User::Table, User::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-task-dependency-blocker-id")
+                    .table(TaskDependency::Table)
+                    .col(TaskDependency::BlockerId)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(
+                Table::drop()
+                    .if_exists()
+                    .table(TaskDependency::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+pub enum TaskDependency {
+    Table,
+    TaskId,
+    BlockerId,
+    UserId,
+    CreatedAt,
+}
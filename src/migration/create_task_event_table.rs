@@ -0,0 +1,118 @@
+use extension::postgres::Type;
+use sea_orm::{ActiveEnum, DeriveActiveEnum, EnumIter};
+use sea_orm_migration::prelude::*;
+
+use super::{create_table_extension::GenerateUuidFunc, create_user_table::User};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_type(
+                Type::create()
+                    .as_enum(TaskEventAction::name())
+                    .values(TaskEventAction::iden_values())
+                    .to_owned(),
+            )
+            .await?;
+
+        // No foreign key to task: history must outlive the task it describes
+        manager
+            .create_table(
+                Table::create()
+                    .table(TaskEvent::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(TaskEvent::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key()
+                            .default(Func::cust(GenerateUuidFunc)),
+                    )
+                    .col(ColumnDef::new(TaskEvent::TaskId).uuid().not_null())
+                    .col(ColumnDef::new(TaskEvent::CommentId).uuid().null())
+                    .col(ColumnDef::new(TaskEvent::UserId).uuid().not_null())
+                    .col(ColumnDef::new(TaskEvent::ActorId).uuid().not_null())
+                    .col(
+                        ColumnDef::new(TaskEvent::Action)
+                            .enumeration(TaskEventAction::name(), TaskEventAction::iden_values())
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(TaskEvent::Changes).json_binary().not_null())
+                    .col(
+                        ColumnDef::new(TaskEvent::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(SimpleExpr::Keyword(Keyword::CurrentTimestamp)),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-task-event-user-id")
+                            .from(TaskEvent::Table, TaskEvent::UserId)
+                            .to(User::Table, User::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-task-event-task-id-created-at")
+                    .table(TaskEvent::Table)
+                    .col(TaskEvent::TaskId)
+                    .col(TaskEvent::CreatedAt)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().if_exists().table(TaskEvent::Table).to_owned())
+            .await?;
+
+        manager
+            .drop_type(
+                Type::drop()
+                    .if_exists()
+                    .name(TaskEventAction::name())
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+pub enum TaskEvent {
+    Table,
+    Id,
+    TaskId,
+    CommentId,
+    UserId,
+    ActorId,
+    Action,
+    Changes,
+    CreatedAt,
+}
+
+#[derive(EnumIter, DeriveActiveEnum)]
+#[sea_orm(rs_type = "String", db_type = "Enum", enum_name = "task_event_action")]
+pub enum TaskEventAction {
+    #[sea_orm(string_value = "create")]
+    Create,
+
+    #[sea_orm(string_value = "update")]
+    Update,
+
+    #[sea_orm(string_value = "delete")]
+    Delete,
+}
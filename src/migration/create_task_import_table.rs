@@ -0,0 +1,220 @@
+use extension::postgres::Type;
+use sea_orm::{ActiveEnum, DeriveActiveEnum, EnumIter};
+use sea_orm_migration::prelude::*;
+
+use super::{
+    create_table_extension::GenerateUuidFunc, create_task_table::Task, create_user_table::User,
+};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_type(
+                Type::create()
+                    .as_enum(ImportFormat::name())
+                    .values(ImportFormat::iden_values())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_type(
+                Type::create()
+                    .as_enum(ImportStatus::name())
+                    .values(ImportStatus::iden_values())
+                    .to_owned(),
+            )
+            .await?;
+
+        // The uploaded file is kept until the import ran, or for good on a
+        // dry run so it can be applied later
+        manager
+            .create_table(
+                Table::create()
+                    .table(TaskImport::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(TaskImport::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key()
+                            .default(Func::cust(GenerateUuidFunc)),
+                    )
+                    .col(ColumnDef::new(TaskImport::UserId).uuid().not_null())
+                    .col(
+                        ColumnDef::new(TaskImport::Format)
+                            .enumeration(ImportFormat::name(), ImportFormat::iden_values())
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(TaskImport::Status)
+                            .enumeration(ImportStatus::name(), ImportStatus::iden_values())
+                            .not_null()
+                            .default(ImportStatus::Pending.as_enum()),
+                    )
+                    .col(
+                        ColumnDef::new(TaskImport::DryRun)
+                            .boolean()
+                            .not_null()
+                            .default(false),
+                    )
+                    .col(ColumnDef::new(TaskImport::Content).text().null())
+                    .col(ColumnDef::new(TaskImport::Mapping).json_binary().null())
+                    .col(ColumnDef::new(TaskImport::Rows).json_binary().null())
+                    .col(ColumnDef::new(TaskImport::Error).text().null())
+                    .col(
+                        ColumnDef::new(TaskImport::UpdatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(SimpleExpr::Keyword(Keyword::CurrentTimestamp)),
+                    )
+                    .col(
+                        ColumnDef::new(TaskImport::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(SimpleExpr::Keyword(Keyword::CurrentTimestamp)),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-task-import-user-id")
+                            .from(TaskImport::Table, TaskImport::UserId)
+                            .to(User::Table, User::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-task-import-status-created-at")
+                    .table(TaskImport::Table)
+                    .col(TaskImport::Status)
+                    .col(TaskImport::CreatedAt)
+                    .to_owned(),
+            )
+            .await?;
+
+        // Identifies where an imported task came from, so importing the same
+        // file again skips it
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Task::Table)
+                    .add_column(ColumnDef::new(Task::ImportKey).text().null())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-task-user-id-import-key")
+                    .table(Task::Table)
+                    .col(Task::UserId)
+                    .col(Task::ImportKey)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx-task-user-id-import-key")
+                    .table(Task::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Task::Table)
+                    .drop_column(Task::ImportKey)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(TaskImport::Table).to_owned())
+            .await?;
+
+        manager
+            .drop_type(
+                Type::drop()
+                    .if_exists()
+                    .name(ImportStatus::name())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_type(
+                Type::drop()
+                    .if_exists()
+                    .name(ImportFormat::name())
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+pub enum TaskImport {
+    Table,
+    Id,
+    UserId,
+    Format,
+    Status,
+    DryRun,
+    Content,
+    Mapping,
+    Rows,
+    Error,
+    UpdatedAt,
+    CreatedAt,
+}
+
+#[derive(EnumIter, DeriveActiveEnum)]
+#[sea_orm(rs_type = "String", db_type = "Enum", enum_name = "import_format")]
+pub enum ImportFormat {
+    #[sea_orm(string_value = "csv")]
+    Csv,
+
+    #[sea_orm(string_value = "todoist")]
+    Todoist,
+
+    #[sea_orm(string_value = "microsoft_to_do")]
+    MicrosoftToDo,
+
+    #[sea_orm(string_value = "task_flow")]
+    TaskFlow,
+
+    #[sea_orm(string_value = "ical")]
+    Ical,
+}
+
+#[derive(EnumIter, DeriveActiveEnum)]
+#[sea_orm(rs_type = "String", db_type = "Enum", enum_name = "import_status")]
+pub enum ImportStatus {
+    #[sea_orm(string_value = "pending")]
+    Pending,
+
+    #[sea_orm(string_value = "done")]
+    Done,
+
+    #[sea_orm(string_value = "failed")]
+    Failed,
+}
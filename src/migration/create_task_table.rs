@@ -184,6 +184,15 @@ pub enum Task {
     UserId,
     CreatedAt,
     UpdatedAt,
+    ChangeSeq,
+    Version,
+    DeletedAt,
+    Position,
+    StatusId,
+    DeadlineKind,
+    DavName,
+    IcalUid,
+    ImportKey,
 }
 
 #[derive(EnumIter, DeriveActiveEnum)]
@@ -221,4 +230,7 @@ pub enum TaskComment {
     UserId,
     UpdatedAt,
     CreatedAt,
+    ChangeSeq,
+    Version,
+    DeletedAt,
 }
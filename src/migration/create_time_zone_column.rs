@@ -0,0 +1,106 @@
+use extension::postgres::Type;
+use sea_orm::{ActiveEnum, DeriveActiveEnum, EnumIter};
+use sea_orm_migration::prelude::*;
+
+use super::{create_task_table::Task, create_user_table::User};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(User::Table)
+                    .add_column(
+                        ColumnDef::new(User::TimeZone)
+                            .text()
+                            .not_null()
+                            .default("UTC"),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_type(
+                Type::create()
+                    .as_enum(DeadlineKind::name())
+                    .values(DeadlineKind::iden_values())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Task::Table)
+                    .add_column(
+                        ColumnDef::new(Task::DeadlineKind)
+                            .enumeration(DeadlineKind::name(), DeadlineKind::iden_values())
+                            .null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // Existing deadlines are exact moments. A kind goes with every
+        // deadline and only with one.
+        db.execute_unprepared(
+            r#"
+            UPDATE task SET deadline_kind = 'instant' WHERE deadline IS NOT NULL;
+
+            ALTER TABLE task ADD CONSTRAINT chk_task_deadline_kind
+                CHECK ((deadline IS NULL) = (deadline_kind IS NULL));
+            "#,
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Task::Table)
+                    .drop_column(Task::DeadlineKind)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_type(
+                Type::drop()
+                    .if_exists()
+                    .name(DeadlineKind::name())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(User::Table)
+                    .drop_column(User::TimeZone)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(EnumIter, DeriveActiveEnum)]
+#[sea_orm(rs_type = "String", db_type = "Enum", enum_name = "deadline_kind")]
+pub enum DeadlineKind {
+    #[sea_orm(string_value = "instant")]
+    Instant,
+
+    #[sea_orm(string_value = "date")]
+    Date,
+}
@@ -0,0 +1,171 @@
+use extension::postgres::Type;
+use sea_orm::ActiveEnum;
+use sea_orm_migration::prelude::*;
+
+use super::{
+    create_task_event_table::{TaskEvent, TaskEventAction},
+    create_task_table::{Task, TaskComment},
+};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Task::Table)
+                    .add_column(
+                        ColumnDef::new(Task::DeletedAt)
+                            .timestamp_with_time_zone()
+                            .null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(TaskComment::Table)
+                    .add_column(
+                        ColumnDef::new(TaskComment::DeletedAt)
+                            .timestamp_with_time_zone()
+                            .null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-task-deleted-at")
+                    .table(Task::Table)
+                    .col(Task::DeletedAt)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-task-comment-deleted-at")
+                    .table(TaskComment::Table)
+                    .col(TaskComment::DeletedAt)
+                    .to_owned(),
+            )
+            .await?;
+
+        // Purges made by the retention job have no actor
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(TaskEvent::Table)
+                    .modify_column(ColumnDef::new(TaskEvent::ActorId).uuid().null())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_type(
+                Type::alter()
+                    .name(TaskEventAction::name())
+                    .add_value(Alias::new("restore")),
+            )
+            .await?;
+
+        manager
+            .alter_type(
+                Type::alter()
+                    .name(TaskEventAction::name())
+                    .add_value(Alias::new("purge")),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Postgres cannot drop enum values, so the type is rebuilt without
+        // them once the events using them are gone
+        manager
+            .get_connection()
+            .execute_unprepared(
+                r#"
+            DELETE FROM task_event WHERE action IN ('restore', 'purge') OR actor_id IS NULL;
+            ALTER TYPE task_event_action RENAME TO task_event_action_old;
+            "#,
+            )
+            .await?;
+
+        manager
+            .create_type(
+                Type::create()
+                    .as_enum(TaskEventAction::name())
+                    .values(TaskEventAction::iden_values())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .get_connection()
+            .execute_unprepared(
+                r#"
+            ALTER TABLE task_event
+                ALTER COLUMN action TYPE task_event_action USING action::text::task_event_action;
+            DROP TYPE task_event_action_old;
+            "#,
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(TaskEvent::Table)
+                    .modify_column(ColumnDef::new(TaskEvent::ActorId).uuid().not_null())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx-task-comment-deleted-at")
+                    .table(TaskComment::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx-task-deleted-at")
+                    .table(Task::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(TaskComment::Table)
+                    .drop_column(TaskComment::DeletedAt)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Task::Table)
+                    .drop_column(Task::DeletedAt)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,208 @@
+use extension::postgres::Type;
+use sea_orm::{ActiveEnum, DeriveActiveEnum, EnumIter};
+use sea_orm_migration::prelude::*;
+
+use super::{create_task_table::TaskPriority, create_user_table::User};
+
+/// Profile and settings of a user, one row each. Existing users get the
+/// defaults.
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        manager
+            .create_type(
+                Type::create()
+                    .as_enum(WeekDay::name())
+                    .values(WeekDay::iden_values())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_type(
+                Type::create()
+                    .as_enum(DateFormat::name())
+                    .values(DateFormat::iden_values())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_type(
+                Type::create()
+                    .as_enum(TaskSort::name())
+                    .values(TaskSort::iden_values())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(UserPreference::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(UserPreference::UserId)
+                            .uuid()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(UserPreference::DisplayName).text().null())
+                    .col(ColumnDef::new(UserPreference::Bio).text().null())
+                    .col(
+                        ColumnDef::new(UserPreference::Locale)
+                            .text()
+                            .not_null()
+                            .default("en"),
+                    )
+                    .col(
+                        ColumnDef::new(UserPreference::WeekStart)
+                            .enumeration(WeekDay::name(), WeekDay::iden_values())
+                            .not_null()
+                            .default("monday"),
+                    )
+                    .col(
+                        ColumnDef::new(UserPreference::DateFormat)
+                            .enumeration(DateFormat::name(), DateFormat::iden_values())
+                            .not_null()
+                            .default("ymd"),
+                    )
+                    .col(
+                        ColumnDef::new(UserPreference::DefaultPriority)
+                            .enumeration(TaskPriority::name(), TaskPriority::iden_values())
+                            .not_null()
+                            .default("normal"),
+                    )
+                    .col(
+                        ColumnDef::new(UserPreference::TaskSort)
+                            .enumeration(TaskSort::name(), TaskSort::iden_values())
+                            .not_null()
+                            .default("created"),
+                    )
+                    .col(
+                        ColumnDef::new(UserPreference::TaskSortDescending)
+                            .boolean()
+                            .not_null()
+                            .default(false),
+                    )
+                    .col(
+                        ColumnDef::new(UserPreference::Version)
+                            .integer()
+                            .not_null()
+                            .default(1),
+                    )
+                    .col(
+                        ColumnDef::new(UserPreference::UpdatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(SimpleExpr::Keyword(Keyword::CurrentTimestamp)),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-user-preference-user-id")
+                            .from(UserPreference::Table, UserPreference::UserId)
+                            .to(User::Table, User::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        db.execute_unprepared(
+            r#"
+            CREATE TRIGGER user_preference_bump_version BEFORE UPDATE ON user_preference
+                FOR EACH ROW EXECUTE FUNCTION bump_version();
+
+            INSERT INTO user_preference (user_id) SELECT id FROM "user";
+            "#,
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(UserPreference::Table).to_owned())
+            .await?;
+
+        for name in [TaskSort::name(), DateFormat::name(), WeekDay::name()] {
+            manager
+                .drop_type(Type::drop().if_exists().name(name).to_owned())
+                .await?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+pub enum UserPreference {
+    Table,
+    UserId,
+    DisplayName,
+    Bio,
+    Locale,
+    WeekStart,
+    DateFormat,
+    DefaultPriority,
+    TaskSort,
+    TaskSortDescending,
+    Version,
+    UpdatedAt,
+    Discoverable,
+    ShowAvatar,
+}
+
+#[derive(EnumIter, DeriveActiveEnum)]
+#[sea_orm(rs_type = "String", db_type = "Enum", enum_name = "week_day")]
+pub enum WeekDay {
+    #[sea_orm(string_value = "monday")]
+    Monday,
+
+    #[sea_orm(string_value = "saturday")]
+    Saturday,
+
+    #[sea_orm(string_value = "sunday")]
+    Sunday,
+}
+
+#[derive(EnumIter, DeriveActiveEnum)]
+#[sea_orm(rs_type = "String", db_type = "Enum", enum_name = "date_format")]
+pub enum DateFormat {
+    #[sea_orm(string_value = "ymd")]
+    Ymd,
+
+    #[sea_orm(string_value = "dmy")]
+    Dmy,
+
+    #[sea_orm(string_value = "mdy")]
+    Mdy,
+}
+
+#[derive(EnumIter, DeriveActiveEnum)]
+#[sea_orm(rs_type = "String", db_type = "Enum", enum_name = "task_sort")]
+pub enum TaskSort {
+    #[sea_orm(string_value = "created")]
+    Created,
+
+    #[sea_orm(string_value = "updated")]
+    Updated,
+
+    #[sea_orm(string_value = "deadline")]
+    Deadline,
+
+    #[sea_orm(string_value = "priority")]
+    Priority,
+
+    #[sea_orm(string_value = "name")]
+    Name,
+
+    #[sea_orm(string_value = "position")]
+    Position,
+}
@@ -0,0 +1,42 @@
+use sea_orm_migration::prelude::*;
+
+/// Trigram indexes for finding users by a part of their name or display
+/// name, misspelled or not.
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        db.execute_unprepared(
+            r#"
+            CREATE EXTENSION IF NOT EXISTS pg_trgm;
+
+            CREATE INDEX "idx-user-name-trgm" ON "user" USING gin (name gin_trgm_ops);
+            CREATE INDEX "idx-user-preference-display-name-trgm" ON user_preference
+                USING gin (display_name gin_trgm_ops);
+            "#,
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        db.execute_unprepared(
+            r#"
+            DROP INDEX IF EXISTS "idx-user-preference-display-name-trgm";
+            DROP INDEX IF EXISTS "idx-user-name-trgm";
+
+            DROP EXTENSION IF EXISTS pg_trgm;
+            "#,
+        )
+        .await?;
+
+        Ok(())
+    }
+}
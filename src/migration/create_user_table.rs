@@ -122,6 +122,11 @@ pub enum User {
     Password,
     CreatedAt,
     UpdatedAt,
+    Version,
+    TimeZone,
+    CalendarToken,
+    DeletionRequestedAt,
+    DeletionScheduledAt,
 }
 
 #[derive(DeriveIden)]
@@ -130,6 +135,8 @@ pub enum UserAvatar {
     Id,
     UserId,
     File,
+    StorageKey,
+    Sha256,
     UpdatedAt,
     CreatedAt,
 }
@@ -0,0 +1,115 @@
+use sea_orm_migration::prelude::*;
+
+use super::{
+    create_task_table::{Task, TaskComment},
+    create_user_table::User,
+};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(User::Table)
+                    .add_column(version_column(User::Version))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Task::Table)
+                    .add_column(version_column(Task::Version))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(TaskComment::Table)
+                    .add_column(version_column(TaskComment::Version))
+                    .to_owned(),
+            )
+            .await?;
+
+        db.execute_unprepared(
+            r#"
+            CREATE OR REPLACE FUNCTION bump_version() RETURNS trigger AS $$
+            BEGIN
+                NEW.version := OLD.version + 1;
+                RETURN NEW;
+            END;
+            $$ LANGUAGE plpgsql;
+
+            CREATE TRIGGER user_bump_version BEFORE UPDATE ON "user"
+                FOR EACH ROW EXECUTE FUNCTION bump_version();
+            CREATE TRIGGER task_bump_version BEFORE UPDATE ON task
+                FOR EACH ROW EXECUTE FUNCTION bump_version();
+            CREATE TRIGGER task_comment_bump_version BEFORE UPDATE ON task_comment
+                FOR EACH ROW EXECUTE FUNCTION bump_version();
+            "#,
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        db.execute_unprepared(
+            r#"
+            DROP TRIGGER IF EXISTS task_comment_bump_version ON task_comment;
+            DROP TRIGGER IF EXISTS task_bump_version ON task;
+            DROP TRIGGER IF EXISTS user_bump_version ON "user";
+            DROP FUNCTION IF EXISTS bump_version();
+            "#,
+        )
+        .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(TaskComment::Table)
+                    .drop_column(TaskComment::Version)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Task::Table)
+                    .drop_column(Task::Version)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(User::Table)
+                    .drop_column(User::Version)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
+
+fn version_column<T: IntoIden>(column: T) -> ColumnDef {
+    ColumnDef::new(column)
+        .integer()
+        .not_null()
+        .default(1)
+        .to_owned()
+}
@@ -0,0 +1,199 @@
+use sea_orm::ActiveEnum;
+use sea_orm_migration::prelude::*;
+
+use super::{
+    create_table_extension::GenerateUuidFunc,
+    create_task_table::{Task, TaskStatus},
+    create_user_table::User,
+};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        // The built-in enum stays as the category every user status maps to
+        manager
+            .create_table(
+                Table::create()
+                    .table(WorkflowStatus::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(WorkflowStatus::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key()
+                            .default(Func::cust(GenerateUuidFunc)),
+                    )
+                    .col(ColumnDef::new(WorkflowStatus::UserId).uuid().not_null())
+                    .col(ColumnDef::new(WorkflowStatus::Name).text().not_null())
+                    .col(
+                        ColumnDef::new(WorkflowStatus::Category)
+                            .enumeration(TaskStatus::name(), TaskStatus::iden_values())
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(WorkflowStatus::Position)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(WorkflowStatus::UpdatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(SimpleExpr::Keyword(Keyword::CurrentTimestamp)),
+                    )
+                    .col(
+                        ColumnDef::new(WorkflowStatus::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(SimpleExpr::Keyword(Keyword::CurrentTimestamp)),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-workflow-status-user-id")
+                            .from(WorkflowStatus::Table, WorkflowStatus::UserId)
+                            .to(User::Table, User::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-workflow-status-user-id-name")
+                    .table(WorkflowStatus::Table)
+                    .col(WorkflowStatus::UserId)
+                    .col(WorkflowStatus::Name)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        db.execute_unprepared(
+            r#"
+            INSERT INTO workflow_status (user_id, name, category, position)
+            SELECT "user".id, defaults.name, defaults.category::task_status, defaults.position
+            FROM "user" CROSS JOIN (VALUES
+                ('To do', 'to_do', 0),
+                ('In progress', 'in_progress', 1),
+                ('Done', 'done', 2)
+            ) AS defaults (name, category, position);
+            "#,
+        )
+        .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Task::Table)
+                    .add_column(ColumnDef::new(Task::StatusId).uuid().null())
+                    .add_foreign_key(
+                        TableForeignKey::new()
+                            .name("fk-task-status-id")
+                            .from_tbl(Task::Table)
+                            .from_col(Task::StatusId)
+                            .to_tbl(WorkflowStatus::Table)
+                            .to_col(WorkflowStatus::Id)
+                            .on_delete(ForeignKeyAction::Restrict),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        db.execute_unprepared(
+            r#"
+            UPDATE task SET status_id = workflow_status.id
+            FROM workflow_status
+            WHERE workflow_status.user_id = task.user_id
+                AND workflow_status.category = task.status;
+
+            ALTER TABLE task ALTER COLUMN status_id SET NOT NULL;
+            "#,
+        )
+        .await?;
+
+        // Kanban columns are user statuses now
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx-task-user-id-status-position")
+                    .table(Task::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-task-status-id-position")
+                    .table(Task::Table)
+                    .col(Task::StatusId)
+                    .col(Task::Position)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx-task-status-id-position")
+                    .table(Task::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-task-user-id-status-position")
+                    .table(Task::Table)
+                    .col(Task::UserId)
+                    .col(Task::Status)
+                    .col(Task::Position)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Task::Table)
+                    .drop_column(Task::StatusId)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_table(
+                Table::drop()
+                    .if_exists()
+                    .table(WorkflowStatus::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+pub enum WorkflowStatus {
+    Table,
+    Id,
+    UserId,
+    Name,
+    Category,
+    Position,
+    UpdatedAt,
+    CreatedAt,
+}
@@ -1,6 +1,30 @@
+mod create_account_deletion_table;
+mod create_account_export_table;
+mod create_app_password_table;
+mod create_attachment_table;
+mod create_avatar_storage_column;
+mod create_avatar_variant_table;
+mod create_calendar_token_column;
+mod create_case_insensitive_index;
+mod create_change_xid_column;
+mod create_custom_field_table;
+mod create_export_storage_column;
+mod create_position_column;
+mod create_privacy_column;
+mod create_saved_filter_table;
+mod create_sync_table;
 mod create_table_extension;
+mod create_task_dependency_table;
+mod create_task_event_table;
+mod create_task_import_table;
 mod create_task_table;
+mod create_time_zone_column;
+mod create_trash_column;
+mod create_user_preference_table;
+mod create_user_search_index;
 mod create_user_table;
+mod create_version_column;
+mod create_workflow_status_table;
 
 use sea_orm_migration::{MigrationTrait, MigratorTrait};
 
@@ -12,6 +36,30 @@ impl MigratorTrait for Migrator {
             Box::new(create_table_extension::Migration),
             Box::new(create_user_table::Migration),
             Box::new(create_task_table::Migration),
+            Box::new(create_sync_table::Migration),
+            Box::new(create_version_column::Migration),
+            Box::new(create_task_event_table::Migration),
+            Box::new(create_trash_column::Migration),
+            Box::new(create_task_dependency_table::Migration),
+            Box::new(create_position_column::Migration),
+            Box::new(create_workflow_status_table::Migration),
+            Box::new(create_custom_field_table::Migration),
+            Box::new(create_saved_filter_table::Migration),
+            Box::new(create_time_zone_column::Migration),
+            Box::new(create_calendar_token_column::Migration),
+            Box::new(create_app_password_table::Migration),
+            Box::new(create_account_export_table::Migration),
+            Box::new(create_task_import_table::Migration),
+            Box::new(create_attachment_table::Migration),
+            Box::new(create_avatar_storage_column::Migration),
+            Box::new(create_avatar_variant_table::Migration),
+            Box::new(create_user_preference_table::Migration),
+            Box::new(create_privacy_column::Migration),
+            Box::new(create_user_search_index::Migration),
+            Box::new(create_account_deletion_table::Migration),
+            Box::new(create_case_insensitive_index::Migration),
+            Box::new(create_change_xid_column::Migration),
+            Box::new(create_export_storage_column::Migration),
         ]
     }
 }
@@ -2,14 +2,22 @@ use actix_web::{middleware::Logger, web, App, HttpServer};
 
 use crate::{
     api::service_configure,
-    client::{postgres::PostgresClient, ClientBuilder},
+    client::{mail::MailClient, postgres::PostgresClient, storage::StorageClient, ClientBuilder},
     config::Config,
     error::server::{ServerError, ServerResult},
+    job::{
+        self, account_deletion::AccountDeletionJob, account_export::AccountExportJob,
+        rank_rebalance::RankRebalanceJob, storage_cleanup::StorageCleanupJob,
+        task_import::TaskImportJob, trash_purge::TrashPurgeJob,
+    },
+    service::user_avatar::UserAvatarService,
 };
 
 #[derive(Debug, Clone)]
 pub struct State {
     pub postgres: PostgresClient,
+    pub storage: StorageClient,
+    pub mail: MailClient,
     pub config: Config,
 }
 
@@ -22,26 +30,48 @@ pub struct Server {
 impl State {
     pub async fn new(config: &Config) -> ServerResult<Self> {
         let postgres: PostgresClient = PostgresClient::from_config(config).await?;
+        let storage: StorageClient = StorageClient::from_config(config).await?;
+        let mail: MailClient = MailClient::from_config(config).await?;
 
         Ok(Self {
             config: config.clone(),
             postgres,
+            storage,
+            mail,
         })
     }
 }
 
 impl Server {
     pub async fn new(config: Config) -> ServerResult<Self> {
+        let state: State = State::new(&config).await?;
+
+        // Avatars from before their sizes were made, the migration can't
+        // reach the file storage
+        let converted: u64 = UserAvatarService::convert_legacy(&state.postgres, &state.storage)
+            .await
+            .map_err(|err| ServerError::AvatarConvert(err.to_string()))?;
+        if converted > 0 {
+            log::info!("Converted {} avatars", converted);
+        }
+
         Ok(Self {
             host: config.server.host.clone(),
             port: config.server.port,
-            state: State::new(&config).await?,
+            state,
         })
     }
 
     pub async fn run(&self) -> ServerResult {
         let app_data: web::Data<State> = web::Data::new(self.state.clone());
 
+        job::spawn::<TrashPurgeJob>(self.state.clone());
+        job::spawn::<RankRebalanceJob>(self.state.clone());
+        job::spawn::<AccountExportJob>(self.state.clone());
+        job::spawn::<TaskImportJob>(self.state.clone());
+        job::spawn::<StorageCleanupJob>(self.state.clone());
+        job::spawn::<AccountDeletionJob>(self.state.clone());
+
         match HttpServer::new(move || {
             App::new()
                 .wrap(Logger::default())
@@ -0,0 +1,241 @@
+//! Account deletion: requested with the password, cancellable for a grace
+//! period, then purged with everything the user owns.
+
+use chrono::{DateTime, FixedOffset, Local};
+use chrono_tz::Tz;
+use sea_orm::{
+    sea_query::{LockBehavior, LockType},
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, DatabaseTransaction, EntityTrait,
+    IntoActiveModel, ModelTrait, PaginatorTrait, QueryFilter, QueryOrder, QuerySelect, Set,
+    TransactionTrait,
+};
+use uuid::Uuid;
+
+use crate::{
+    client::mail::{Mail, MailClient},
+    dto::account_deletion::{AccountDeletionCreateDto, AccountDeletionReadDto},
+    entity::prelude::{
+        AccountDeletionActiveModel, AccountExportColumn, AccountExportEntity, AppPasswordColumn,
+        AppPasswordEntity, AttachmentColumn, AttachmentEntity, TaskColumn, TaskCommentColumn,
+        TaskCommentEntity, TaskEntity, UserActiveModel, UserAvatarColumn, UserAvatarEntity,
+        UserAvatarVariantEntity, UserColumn, UserEntity, UserModel,
+    },
+    error::service::{ServiceError, ServiceResult},
+};
+
+use super::common;
+
+pub struct AccountDeletionService;
+
+impl AccountDeletionService {
+    /// Schedules deletion of the account `grace` seconds from now and tells
+    /// the user by mail. Nothing is scheduled when the mail can't be sent.
+    pub async fn request(
+        db: &DatabaseConnection,
+        mail: &MailClient,
+        user_id: Uuid,
+        body: AccountDeletionCreateDto,
+        grace: u64,
+    ) -> ServiceResult<AccountDeletionReadDto> {
+        let tx: DatabaseTransaction = db.begin().await?;
+
+        let model: UserModel = Self::find(&tx, user_id).await?;
+
+        if !common::verify_hash(body.password, model.password.clone())? {
+            return Err(ServiceError::InvalidCredentials(
+                "Invalid password".to_string(),
+            ));
+        }
+
+        if let Some(scheduled_at) = model.deletion_scheduled_at {
+            return Err(ServiceError::Conflict {
+                field: "deletion".to_string(),
+                value: scheduled_at.to_rfc3339(),
+            });
+        }
+
+        let now: DateTime<FixedOffset> = Local::now().fixed_offset();
+        let scheduled_at: DateTime<FixedOffset> = now + chrono::Duration::seconds(grace as i64);
+
+        let mut active_model: UserActiveModel = model.clone().into_active_model();
+        active_model.deletion_requested_at = Set(Some(now));
+        active_model.deletion_scheduled_at = Set(Some(scheduled_at));
+        active_model.updated_at = Set(now);
+        active_model.update(&tx).await?;
+
+        let time_zone: Tz = common::time_zone(&model.time_zone);
+        mail.send(Mail {
+            to: model.email,
+            subject: "Your account will be deleted".to_string(),
+            body: format!(
+                "Hello {},\n\n\
+                 deletion of your account was requested. On {} the account is \
+                 deleted with all of its tasks, comments and files, this can't \
+                 be undone.\n\n\
+                 To keep the account, sign in and cancel the deletion before \
+                 then. If you did not ask for this, cancel it and change your \
+                 password.\n",
+                model.name,
+                scheduled_at
+                    .with_timezone(&time_zone)
+                    .format("%Y-%m-%d %H:%M %Z"),
+            ),
+        })
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(AccountDeletionReadDto {
+            requested_at: now.to_rfc3339(),
+            scheduled_at: scheduled_at.to_rfc3339(),
+        })
+    }
+
+    /// Keeps the account. Cancelling when no deletion is pending does
+    /// nothing.
+    pub async fn cancel(
+        db: &DatabaseConnection,
+        mail: &MailClient,
+        user_id: Uuid,
+    ) -> ServiceResult {
+        let tx: DatabaseTransaction = db.begin().await?;
+
+        let model: UserModel = Self::find(&tx, user_id).await?;
+
+        if model.deletion_scheduled_at.is_none() {
+            return Ok(());
+        }
+
+        let mut active_model: UserActiveModel = model.clone().into_active_model();
+        active_model.deletion_requested_at = Set(None);
+        active_model.deletion_scheduled_at = Set(None);
+        active_model.updated_at = Set(Local::now().fixed_offset());
+        active_model.update(&tx).await?;
+
+        tx.commit().await?;
+
+        // The account is kept either way, a lost notice is no reason to fail
+        if let Err(err) = mail
+            .send(Mail {
+                to: model.email,
+                subject: "Your account will not be deleted".to_string(),
+                body: format!(
+                    "Hello {},\n\n\
+                     deletion of your account was cancelled, everything stays \
+                     as it was.\n",
+                    model.name
+                ),
+            })
+            .await
+        {
+            log::error!("Mail on cancelled deletion of {} failed: {}", user_id, err);
+        }
+
+        Ok(())
+    }
+
+    /// Purges the account whose grace period ended first: tasks, comments,
+    /// files, app passwords, calendar and export tokens, then the user with
+    /// anything left. Returns `false` when none is due. Concurrent runs skip
+    /// the account another one is purging.
+    ///
+    /// Stored files are queued for the storage cleanup, which removes them.
+    pub async fn purge_next(db: &DatabaseConnection) -> ServiceResult<bool> {
+        let tx: DatabaseTransaction = db.begin().await?;
+
+        let now: DateTime<FixedOffset> = Local::now().fixed_offset();
+
+        let model: UserModel = match UserEntity::find()
+            .filter(UserColumn::DeletionScheduledAt.lte(now))
+            .order_by_asc(UserColumn::DeletionScheduledAt)
+            .lock_with_behavior(LockType::Update, LockBehavior::SkipLocked)
+            .one(&tx)
+            .await?
+        {
+            Some(value) => value,
+            None => return Ok(false),
+        };
+
+        let user_id: Uuid = model.id;
+
+        // Attachments and comments go with their tasks too, but are counted
+        // on their own
+        let attachments: u64 = AttachmentEntity::delete_many()
+            .filter(AttachmentColumn::UserId.eq(user_id))
+            .exec(&tx)
+            .await?
+            .rows_affected;
+        let comments: u64 = TaskCommentEntity::delete_many()
+            .filter(TaskCommentColumn::UserId.eq(user_id))
+            .exec(&tx)
+            .await?
+            .rows_affected;
+        let tasks: u64 = TaskEntity::delete_many()
+            .filter(TaskColumn::UserId.eq(user_id))
+            .exec(&tx)
+            .await?
+            .rows_affected;
+
+        let variants: u64 = UserAvatarVariantEntity::find()
+            .inner_join(UserAvatarEntity)
+            .filter(UserAvatarColumn::UserId.eq(user_id))
+            .count(&tx)
+            .await?;
+        let avatars: u64 = UserAvatarEntity::delete_many()
+            .filter(UserAvatarColumn::UserId.eq(user_id))
+            .exec(&tx)
+            .await?
+            .rows_affected;
+
+        let app_passwords: u64 = AppPasswordEntity::delete_many()
+            .filter(AppPasswordColumn::UserId.eq(user_id))
+            .exec(&tx)
+            .await?
+            .rows_affected;
+        let exports: u64 = AccountExportEntity::delete_many()
+            .filter(AccountExportColumn::UserId.eq(user_id))
+            .exec(&tx)
+            .await?
+            .rows_affected;
+        let calendar_tokens: u64 = model.calendar_token.is_some() as u64;
+
+        let requested_at: DateTime<FixedOffset> = model.deletion_requested_at.unwrap_or(now);
+
+        model.delete(&tx).await?;
+
+        AccountDeletionActiveModel {
+            user_id: Set(user_id),
+            requested_at: Set(requested_at),
+            tasks: Set(tasks as i64),
+            comments: Set(comments as i64),
+            files: Set((attachments + avatars + variants) as i64),
+            tokens: Set((app_passwords + exports + calendar_tokens) as i64),
+            ..Default::default()
+        }
+        .insert(&tx)
+        .await?;
+
+        tx.commit().await?;
+
+        log::info!(
+            "Purged account {}: {} tasks, {} comments, {} files",
+            user_id,
+            tasks,
+            comments,
+            attachments + avatars + variants
+        );
+
+        Ok(true)
+    }
+
+    async fn find(tx: &DatabaseTransaction, user_id: Uuid) -> ServiceResult<UserModel> {
+        match UserEntity::find_by_id(user_id)
+            .lock_exclusive()
+            .one(tx)
+            .await?
+        {
+            Some(value) => Ok(value),
+            None => Err(ServiceError::NotFound(user_id)),
+        }
+    }
+}
@@ -0,0 +1,577 @@
+//! Account export: a ZIP of everything a user owns, as JSON for machines and
+//! as CSV and Markdown for people.
+
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, FixedOffset, Local, Utc};
+use chrono_tz::Tz;
+use futures::StreamExt;
+use sea_orm::{
+    sea_query::LockBehavior, sea_query::LockType, ActiveModelTrait, ColumnTrait,
+    DatabaseConnection, DatabaseTransaction, DbErr, EntityTrait, IntoActiveModel, ModelTrait,
+    QueryFilter, QueryOrder, QuerySelect, Set, TransactionTrait,
+};
+use serde::Serialize;
+use tokio::fs::{self, File};
+use tokio_util::io::ReaderStream;
+use uuid::Uuid;
+use zip::{write::SimpleFileOptions, CompressionMethod, ZipWriter};
+
+use crate::{
+    client::storage::{ByteStream, StorageClient},
+    constants,
+    dto::{
+        account_export::AccountExportReadDto,
+        custom_field::CustomFieldReadDto,
+        task::{TaskCommentReadDto, TaskReadDto},
+        user::UserReadDto,
+        user_preference::UserPreferenceReadDto,
+        workflow_status::WorkflowStatusReadDto,
+    },
+    entity::{
+        prelude::{
+            AccountExportActiveModel, AccountExportColumn, AccountExportEntity, AccountExportModel,
+            CustomFieldColumn, CustomFieldEntity, TaskColumn, TaskCommentColumn, TaskCommentEntity,
+            TaskEntity, UserAvatarColumn, UserAvatarEntity, UserAvatarVariantColumn,
+            UserAvatarVariantEntity, UserAvatarVariantModel, UserEntity, UserModel,
+            WorkflowStatusColumn, WorkflowStatusEntity,
+        },
+        sea_orm_active_enums::{AvatarFormat, ExportStatus, TaskPriority, TaskStatus},
+    },
+    error::service::{ServiceError, ServiceResult},
+};
+
+use super::{common, task::TaskService, user::UserService, user_preference::UserPreferenceService};
+
+pub struct AccountExportService;
+
+impl AccountExportService {
+    /// Queues an export, it is built in the background.
+    pub async fn create(
+        db: &DatabaseConnection,
+        user_id: Uuid,
+    ) -> ServiceResult<AccountExportReadDto> {
+        let tx: DatabaseTransaction = db.begin().await?;
+
+        if AccountExportEntity::find()
+            .filter(AccountExportColumn::UserId.eq(user_id))
+            .filter(AccountExportColumn::Status.eq(ExportStatus::Pending))
+            .one(&tx)
+            .await?
+            .is_some()
+        {
+            return Err(ServiceError::Conflict {
+                field: "status".to_string(),
+                value: "Pending".to_string(),
+            });
+        }
+
+        let model: AccountExportModel = AccountExportActiveModel {
+            user_id: Set(user_id),
+            ..Default::default()
+        }
+        .insert(&tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(AccountExportReadDto::from(model))
+    }
+
+    pub async fn list(
+        db: &DatabaseConnection,
+        user_id: Uuid,
+    ) -> ServiceResult<Vec<AccountExportReadDto>> {
+        let tx: DatabaseTransaction = db.begin().await?;
+
+        let models: Vec<AccountExportModel> = AccountExportEntity::find()
+            .filter(AccountExportColumn::UserId.eq(user_id))
+            .order_by_desc(AccountExportColumn::CreatedAt)
+            .all(&tx)
+            .await?;
+
+        let schemas: Vec<AccountExportReadDto> = models
+            .into_iter()
+            .map(AccountExportReadDto::from)
+            .collect::<Vec<AccountExportReadDto>>();
+
+        Ok(schemas)
+    }
+
+    pub async fn get_by_id(
+        db: &DatabaseConnection,
+        user_id: Uuid,
+        id: Uuid,
+    ) -> ServiceResult<AccountExportReadDto> {
+        let tx: DatabaseTransaction = db.begin().await?;
+
+        Ok(AccountExportReadDto::from(
+            Self::find(&tx, user_id, id).await?,
+        ))
+    }
+
+    pub async fn delete(db: &DatabaseConnection, user_id: Uuid, id: Uuid) -> ServiceResult {
+        let tx: DatabaseTransaction = db.begin().await?;
+
+        Self::find(&tx, user_id, id).await?.delete(&tx).await?;
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    /// Streams the archive behind a download token, with its size and when
+    /// it was made.
+    pub async fn download(
+        db: &DatabaseConnection,
+        storage: &StorageClient,
+        token: String,
+    ) -> ServiceResult<(ByteStream, u64, DateTime<FixedOffset>)> {
+        let tx: DatabaseTransaction = db.begin().await?;
+
+        let model: Option<AccountExportModel> = AccountExportEntity::find()
+            .filter(AccountExportColumn::Token.eq(token))
+            .filter(AccountExportColumn::ExpiresAt.gt(Local::now().fixed_offset()))
+            .one(&tx)
+            .await?;
+
+        tx.commit().await?;
+
+        match model {
+            Some(AccountExportModel {
+                storage_key: Some(storage_key),
+                size: Some(size),
+                created_at,
+                ..
+            }) => Ok((
+                storage.get(&storage_key, None).await?,
+                size as u64,
+                created_at,
+            )),
+            _ => Err(ServiceError::InvalidCredentials(
+                "Invalid or expired download token".to_string(),
+            )),
+        }
+    }
+
+    /// Builds the oldest waiting export. Returns `false` when none waits.
+    /// Concurrent runs skip the export another one is building.
+    pub async fn process_next(
+        db: &DatabaseConnection,
+        storage: &StorageClient,
+        expire: u64,
+    ) -> ServiceResult<bool> {
+        let tx: DatabaseTransaction = db.begin().await?;
+
+        let model: AccountExportModel = match AccountExportEntity::find()
+            .filter(AccountExportColumn::Status.eq(ExportStatus::Pending))
+            .order_by_asc(AccountExportColumn::CreatedAt)
+            .lock_with_behavior(LockType::Update, LockBehavior::SkipLocked)
+            .one(&tx)
+            .await?
+        {
+            Some(value) => value,
+            None => return Ok(false),
+        };
+
+        let id: Uuid = model.id;
+
+        // Spooled to a temporary file, the storage needs the size up front
+        let path: PathBuf =
+            std::env::temp_dir().join(format!("task-flow-export-{}", Uuid::new_v4()));
+        let key: String = format!("export/{}", Uuid::new_v4());
+
+        // `ServiceError` is not `Send`, only its message may live across awaits
+        let result: Result<u64, String> = async {
+            Self::archive(&tx, storage, model.user_id, &path).await?;
+
+            let file: File = File::open(&path).await.map_err(archive_error)?;
+            let size: u64 = file.metadata().await.map_err(archive_error)?.len();
+            storage
+                .put(&key, ReaderStream::new(file).boxed(), size)
+                .await?;
+
+            Ok::<u64, ServiceError>(size)
+        }
+        .await
+        .map_err(|err| err.to_string());
+
+        let _ = fs::remove_file(&path).await;
+
+        match result {
+            Ok(size) => {
+                let now: DateTime<FixedOffset> = Local::now().fixed_offset();
+
+                let mut active_model: AccountExportActiveModel = model.into_active_model();
+                active_model.status = Set(ExportStatus::Ready);
+                active_model.token = Set(Some(common::token(constants::EXPORT_TOKEN_SIZE)));
+                active_model.storage_key = Set(Some(key.clone()));
+                active_model.size = Set(Some(size as i64));
+                active_model.expires_at = Set(Some(now + chrono::Duration::seconds(expire as i64)));
+                active_model.updated_at = Set(now);
+
+                let saved: Result<(), DbErr> = async move {
+                    active_model.update(&tx).await?;
+                    tx.commit().await
+                }
+                .await;
+
+                if let Err(err) = saved {
+                    if let Err(err) = storage.delete(&key).await {
+                        log::warn!("Removing unrecorded export {} failed: {}", key, err);
+                    }
+
+                    return Err(err.into());
+                }
+            }
+            Err(err) => {
+                tx.rollback().await?;
+
+                log::error!("Export {} failed: {}", id, err);
+
+                let tx: DatabaseTransaction = db.begin().await?;
+
+                AccountExportActiveModel {
+                    id: Set(id),
+                    status: Set(ExportStatus::Failed),
+                    error: Set(Some(err)),
+                    updated_at: Set(Local::now().fixed_offset()),
+                    ..Default::default()
+                }
+                .update(&tx)
+                .await?;
+
+                tx.commit().await?;
+            }
+        }
+
+        Ok(true)
+    }
+
+    /// Removes exports whose download link expired.
+    pub async fn purge_expired(db: &DatabaseConnection) -> ServiceResult<u64> {
+        let tx: DatabaseTransaction = db.begin().await?;
+
+        let result = AccountExportEntity::delete_many()
+            .filter(AccountExportColumn::ExpiresAt.lte(Local::now().fixed_offset()))
+            .exec(&tx)
+            .await?;
+
+        tx.commit().await?;
+
+        Ok(result.rows_affected)
+    }
+
+    async fn find(
+        tx: &DatabaseTransaction,
+        user_id: Uuid,
+        id: Uuid,
+    ) -> ServiceResult<AccountExportModel> {
+        match AccountExportEntity::find_by_id(id).one(tx).await? {
+            Some(value) => {
+                if value.user_id != user_id {
+                    return Err(ServiceError::Forbidden);
+                }
+
+                Ok(value)
+            }
+            None => Err(ServiceError::NotFound(id)),
+        }
+    }
+
+    /// Writes the archive to `path` from what `tx` sees, trashed records
+    /// included.
+    async fn archive(
+        tx: &DatabaseTransaction,
+        storage: &StorageClient,
+        user_id: Uuid,
+        path: &Path,
+    ) -> ServiceResult {
+        let user: UserModel = match UserEntity::find_by_id(user_id).one(tx).await? {
+            Some(value) => value,
+            None => return Err(ServiceError::NotFound(user_id)),
+        };
+        let time_zone: Tz = common::time_zone(&user.time_zone);
+
+        let tasks: Vec<TaskReadDto> = TaskService::schemas(
+            tx,
+            TaskEntity::find()
+                .filter(TaskColumn::UserId.eq(user_id))
+                .order_by_asc(TaskColumn::CreatedAt)
+                .order_by_asc(TaskColumn::Id)
+                .all(tx)
+                .await?,
+        )
+        .await?;
+
+        let comments: Vec<TaskCommentReadDto> = TaskCommentEntity::find()
+            .filter(TaskCommentColumn::UserId.eq(user_id))
+            .order_by_asc(TaskCommentColumn::CreatedAt)
+            .order_by_asc(TaskCommentColumn::Id)
+            .all(tx)
+            .await?
+            .into_iter()
+            .map(TaskCommentReadDto::from)
+            .collect::<Vec<TaskCommentReadDto>>();
+
+        let statuses: Vec<WorkflowStatusReadDto> = WorkflowStatusEntity::find()
+            .filter(WorkflowStatusColumn::UserId.eq(user_id))
+            .order_by_asc(WorkflowStatusColumn::Position)
+            .all(tx)
+            .await?
+            .into_iter()
+            .map(WorkflowStatusReadDto::from)
+            .collect::<Vec<WorkflowStatusReadDto>>();
+
+        let custom_fields: Vec<CustomFieldReadDto> = CustomFieldEntity::find()
+            .filter(CustomFieldColumn::UserId.eq(user_id))
+            .order_by_asc(CustomFieldColumn::CreatedAt)
+            .all(tx)
+            .await?
+            .into_iter()
+            .map(CustomFieldReadDto::from)
+            .collect::<Vec<CustomFieldReadDto>>();
+
+        // Largest PNG of the avatar
+        let avatar: Option<UserAvatarVariantModel> = UserAvatarVariantEntity::find()
+            .inner_join(UserAvatarEntity)
+            .filter(UserAvatarColumn::UserId.eq(user_id))
+            .filter(UserAvatarVariantColumn::Format.eq(AvatarFormat::Png))
+            .order_by_desc(UserAvatarVariantColumn::Size)
+            .one(tx)
+            .await?;
+
+        let preferences: UserPreferenceReadDto = UserPreferenceReadDto::new(
+            UserPreferenceService::find(tx, user_id).await?,
+            user.time_zone.clone(),
+        );
+        let profile: UserReadDto = UserService::schema(tx, user).await?;
+        let exported_at: DateTime<Tz> = Utc::now().with_timezone(&time_zone);
+
+        let mut archive: Archive = Archive::create(path)?;
+
+        archive.json("profile.json", &profile)?;
+        archive.json("preferences.json", &preferences)?;
+        archive.json("tasks.json", &tasks)?;
+        archive.json("comments.json", &comments)?;
+        archive.json("statuses.json", &statuses)?;
+        archive.json("custom_fields.json", &custom_fields)?;
+        if let Some(avatar) = avatar {
+            archive.file("avatar.png", &storage.read(&avatar.storage_key).await?)?;
+        }
+        archive.file("tasks.csv", &tasks_csv(&tasks, &statuses, &custom_fields)?)?;
+        archive.file(
+            "tasks.md",
+            tasks_markdown(&profile, exported_at, &tasks, &comments, &statuses).as_bytes(),
+        )?;
+
+        archive.finish()
+    }
+}
+
+struct Archive {
+    writer: ZipWriter<std::fs::File>,
+}
+
+impl Archive {
+    fn create(path: &Path) -> ServiceResult<Self> {
+        Ok(Self {
+            writer: ZipWriter::new(std::fs::File::create(path).map_err(archive_error)?),
+        })
+    }
+
+    fn json<T: Serialize>(&mut self, name: &str, value: &T) -> ServiceResult {
+        let content: Vec<u8> = serde_json::to_vec_pretty(value)
+            .map_err(|err| ServiceError::Unknow(err.to_string()))?;
+
+        self.file(name, &content)
+    }
+
+    fn file(&mut self, name: &str, content: &[u8]) -> ServiceResult {
+        let options: SimpleFileOptions =
+            SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+
+        self.writer
+            .start_file(name, options)
+            .map_err(|err| ServiceError::Unknow(err.to_string()))?;
+        self.writer
+            .write_all(content)
+            .map_err(|err| ServiceError::Unknow(err.to_string()))
+    }
+
+    fn finish(self) -> ServiceResult {
+        self.writer
+            .finish()
+            .map(|_| ())
+            .map_err(|err| ServiceError::Unknow(err.to_string()))
+    }
+}
+
+fn archive_error(err: std::io::Error) -> ServiceError {
+    ServiceError::Unknow(format!("Writing export failed: {err}"))
+}
+
+/// One row per task, custom fields get a column each named after the field.
+fn tasks_csv(
+    tasks: &[TaskReadDto],
+    statuses: &[WorkflowStatusReadDto],
+    custom_fields: &[CustomFieldReadDto],
+) -> ServiceResult<Vec<u8>> {
+    let names: HashMap<Uuid, &str> = statuses
+        .iter()
+        .map(|status| (status.id, status.name.as_str()))
+        .collect::<HashMap<Uuid, &str>>();
+
+    let mut writer = csv::Writer::from_writer(Vec::new());
+
+    let mut header: Vec<&str> = vec![
+        "id",
+        "name",
+        "description",
+        "status",
+        "category",
+        "priority",
+        "deadline",
+        "deadline_kind",
+        "blocked",
+        "created_at",
+        "updated_at",
+        "deleted_at",
+    ];
+    header.extend(custom_fields.iter().map(|field| field.name.as_str()));
+
+    writer
+        .write_record(&header)
+        .map_err(|err| ServiceError::Unknow(err.to_string()))?;
+
+    for task in tasks {
+        let mut record: Vec<String> = vec![
+            task.id.to_string(),
+            task.name.clone(),
+            task.description.clone(),
+            names
+                .get(&task.status_id)
+                .copied()
+                .unwrap_or_default()
+                .to_string(),
+            format!("{:?}", task.status),
+            format!("{:?}", task.priority),
+            task.deadline.clone().unwrap_or_default(),
+            task.deadline_kind
+                .as_ref()
+                .map(|value| format!("{value:?}"))
+                .unwrap_or_default(),
+            task.blocked.to_string(),
+            task.created_at.clone(),
+            task.updated_at.clone(),
+            task.deleted_at.clone().unwrap_or_default(),
+        ];
+        record.extend(
+            custom_fields
+                .iter()
+                .map(|field| match task.custom_fields.get(&field.id) {
+                    Some(serde_json::Value::String(value)) => value.clone(),
+                    Some(value) => value.to_string(),
+                    None => String::new(),
+                }),
+        );
+
+        writer
+            .write_record(&record)
+            .map_err(|err| ServiceError::Unknow(err.to_string()))?;
+    }
+
+    writer
+        .into_inner()
+        .map_err(|err| ServiceError::Unknow(err.to_string()))
+}
+
+/// Tasks as a checklist grouped by status in board order, trashed ones last.
+fn tasks_markdown(
+    profile: &UserReadDto,
+    exported_at: DateTime<Tz>,
+    tasks: &[TaskReadDto],
+    comments: &[TaskCommentReadDto],
+    statuses: &[WorkflowStatusReadDto],
+) -> String {
+    let mut task_comments: HashMap<Uuid, Vec<&TaskCommentReadDto>> = HashMap::new();
+    for comment in comments
+        .iter()
+        .filter(|comment| comment.deleted_at.is_none())
+    {
+        task_comments
+            .entry(comment.task_id)
+            .or_default()
+            .push(comment);
+    }
+
+    let mut content: String = format!(
+        "# Tasks of {}\n\nExported on {}.\n",
+        profile.name,
+        exported_at.format("%Y-%m-%d %H:%M %Z")
+    );
+
+    let sections = statuses
+        .iter()
+        .map(|status| {
+            (
+                status.name.as_str(),
+                tasks
+                    .iter()
+                    .filter(|task| task.deleted_at.is_none() && task.status_id == status.id)
+                    .collect::<Vec<&TaskReadDto>>(),
+            )
+        })
+        .chain(std::iter::once((
+            "Trash",
+            tasks
+                .iter()
+                .filter(|task| task.deleted_at.is_some())
+                .collect::<Vec<&TaskReadDto>>(),
+        )));
+
+    for (name, tasks) in sections {
+        if tasks.is_empty() {
+            continue;
+        }
+
+        content.push_str(&format!("\n## {name}\n\n"));
+
+        for task in tasks {
+            let mut details: Vec<String> = vec![match task.priority {
+                TaskPriority::Hight => "high priority".to_string(),
+                TaskPriority::Normal => "normal priority".to_string(),
+                TaskPriority::Low => "low priority".to_string(),
+            }];
+            if let Some(deadline) = &task.deadline {
+                details.push(format!("due {deadline}"));
+            }
+
+            content.push_str(&format!(
+                "- [{}] **{}** ({})\n",
+                match task.status {
+                    TaskStatus::Done => "x",
+                    _ => " ",
+                },
+                task.name,
+                details.join(", ")
+            ));
+
+            for line in task.description.lines() {
+                content.push_str(&format!("  {line}\n"));
+            }
+
+            for comment in task_comments.get(&task.id).into_iter().flatten() {
+                content.push_str(&format!(
+                    "  - _{}_: {}\n",
+                    comment.created_at,
+                    comment.text.lines().collect::<Vec<&str>>().join(" ")
+                ));
+            }
+        }
+    }
+
+    content
+}
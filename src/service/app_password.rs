@@ -0,0 +1,154 @@
+use chrono::Local;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, DatabaseTransaction, EntityTrait,
+    IntoActiveModel, ModelTrait, QueryFilter, QueryOrder, Set, TransactionTrait,
+};
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+use crate::{
+    constants,
+    dto::app_password::{AppPasswordCreateDto, AppPasswordReadDto},
+    entity::prelude::{
+        AppPasswordActiveModel, AppPasswordColumn, AppPasswordEntity, AppPasswordModel, UserEntity,
+        UserModel,
+    },
+    error::service::{ServiceError, ServiceResult},
+};
+
+use super::{common, user::UserService};
+
+/// Passwords for clients that only know basic authentication, such as CalDAV
+/// apps. They are random, so a plain hash is enough to store them.
+pub struct AppPasswordService;
+
+impl AppPasswordService {
+    /// Creates a password, the only time it is returned in clear.
+    pub async fn create(
+        db: &DatabaseConnection,
+        user_id: Uuid,
+        body: AppPasswordCreateDto,
+    ) -> ServiceResult<AppPasswordReadDto> {
+        let tx: DatabaseTransaction = db.begin().await?;
+
+        if AppPasswordEntity::find()
+            .filter(AppPasswordColumn::UserId.eq(user_id))
+            .filter(AppPasswordColumn::Name.eq(body.name.clone()))
+            .one(&tx)
+            .await?
+            .is_some()
+        {
+            return Err(ServiceError::Conflict {
+                field: "name".to_string(),
+                value: body.name,
+            });
+        }
+
+        let password: String = common::token(constants::APP_PASSWORD_SIZE);
+
+        let model: AppPasswordModel = AppPasswordActiveModel {
+            user_id: Set(user_id),
+            name: Set(body.name),
+            hash: Set(Self::hash(&password)),
+            ..Default::default()
+        }
+        .insert(&tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(AppPasswordReadDto {
+            password: Some(password),
+            ..AppPasswordReadDto::from(model)
+        })
+    }
+
+    pub async fn list(
+        db: &DatabaseConnection,
+        user_id: Uuid,
+    ) -> ServiceResult<Vec<AppPasswordReadDto>> {
+        let tx: DatabaseTransaction = db.begin().await?;
+
+        let models: Vec<AppPasswordModel> = AppPasswordEntity::find()
+            .filter(AppPasswordColumn::UserId.eq(user_id))
+            .order_by_asc(AppPasswordColumn::CreatedAt)
+            .all(&tx)
+            .await?;
+
+        let schemas: Vec<AppPasswordReadDto> = models
+            .into_iter()
+            .map(AppPasswordReadDto::from)
+            .collect::<Vec<AppPasswordReadDto>>();
+
+        Ok(schemas)
+    }
+
+    pub async fn delete(db: &DatabaseConnection, user_id: Uuid, id: Uuid) -> ServiceResult {
+        let tx: DatabaseTransaction = db.begin().await?;
+
+        match AppPasswordEntity::find_by_id(id).one(&tx).await? {
+            Some(value) => {
+                if value.user_id != user_id {
+                    return Err(ServiceError::Forbidden);
+                }
+
+                value.delete(&tx).await?;
+            }
+            None => return Err(ServiceError::NotFound(id)),
+        }
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    /// Finds the user a login and app password belong to.
+    pub async fn authenticate(
+        db: &DatabaseConnection,
+        login: &str,
+        password: &str,
+    ) -> ServiceResult<Uuid> {
+        let tx: DatabaseTransaction = db.begin().await?;
+
+        let model: AppPasswordModel = match AppPasswordEntity::find()
+            .filter(AppPasswordColumn::Hash.eq(Self::hash(password)))
+            .one(&tx)
+            .await?
+        {
+            Some(value) => value,
+            None => {
+                return Err(ServiceError::InvalidCredentials(
+                    "Invalid app password".to_string(),
+                ))
+            }
+        };
+
+        let user: Option<UserModel> = UserEntity::find_by_id(model.user_id)
+            .filter(UserService::login_condition(login))
+            .one(&tx)
+            .await?;
+
+        if user.is_none() {
+            return Err(ServiceError::InvalidCredentials(
+                "Invalid app password".to_string(),
+            ));
+        }
+
+        let user_id: Uuid = model.user_id;
+
+        let mut active_model: AppPasswordActiveModel = model.into_active_model();
+        active_model.last_used_at = Set(Some(Local::now().fixed_offset()));
+        active_model.update(&tx).await?;
+
+        tx.commit().await?;
+
+        Ok(user_id)
+    }
+
+    fn hash(password: &str) -> String {
+        Sha256::digest(password.as_bytes())
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect::<String>()
+    }
+}
@@ -0,0 +1,359 @@
+use std::path::{Path, PathBuf};
+
+use actix_multipart::{Field, Multipart};
+use futures::{StreamExt, TryStreamExt};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, Condition, DatabaseConnection, DatabaseTransaction, EntityTrait,
+    ModelTrait, QueryFilter, QueryOrder, QuerySelect, Set, TransactionTrait,
+};
+use sha2::{Digest, Sha256};
+use tokio::{
+    fs::{self, File},
+    io::AsyncWriteExt,
+};
+use tokio_util::io::ReaderStream;
+use uuid::Uuid;
+
+use crate::{
+    client::storage::{ByteStream, StorageClient},
+    config::attachment::AttachmentConfig,
+    constants,
+    dto::attachment::{AttachmentReadDto, RangeDto},
+    entity::prelude::{
+        AttachmentActiveModel, AttachmentColumn, AttachmentEntity, AttachmentModel, TaskColumn,
+        TaskCommentColumn, TaskCommentEntity, TaskEntity,
+    },
+    error::service::{ServiceError, ServiceResult},
+};
+
+/// File contents to send, all of them or the asked range.
+pub struct AttachmentContent {
+    pub attachment: AttachmentReadDto,
+    /// Range sent and the file size, for `Content-Range`
+    pub range: Option<(u64, u64, u64)>,
+    pub body: ByteStream,
+}
+
+pub struct AttachmentService;
+
+impl AttachmentService {
+    /// Streams the `file` field of `body` into storage and records it on the
+    /// task, or on one of its comments.
+    pub async fn create(
+        db: &DatabaseConnection,
+        storage: &StorageClient,
+        config: &AttachmentConfig,
+        user_id: Uuid,
+        task_id: Uuid,
+        comment_id: Option<Uuid>,
+        mut body: Multipart,
+    ) -> ServiceResult<AttachmentReadDto> {
+        let tx: DatabaseTransaction = db.begin().await?;
+        Self::check(&tx, user_id, task_id, comment_id).await?;
+        tx.commit().await?;
+
+        let mut field: Field = loop {
+            match body.try_next().await? {
+                Some(field) if field.name() == Some("file") => break field,
+                Some(_) => continue,
+                None => return Err(ServiceError::BadRequest("Missing 'file' field".to_string())),
+            }
+        };
+
+        let name: String = file_name(
+            field
+                .content_disposition()
+                .and_then(|value| value.get_filename())
+                .unwrap_or_default(),
+        );
+        let content_type: String = match field.content_type() {
+            Some(value) if value.essence_str() != "application/octet-stream" => {
+                value.essence_str().to_string()
+            }
+            _ => mime_guess::from_path(&name)
+                .first_or_octet_stream()
+                .essence_str()
+                .to_string(),
+        };
+        if !accepts(config, &content_type) {
+            return Err(ServiceError::UnsupportedMediaType(content_type));
+        }
+
+        // Spooled to a temporary file, the storage needs the size up front
+        let path: PathBuf =
+            std::env::temp_dir().join(format!("task-flow-upload-{}", Uuid::new_v4()));
+        let key: String = format!("attachment/{}", Uuid::new_v4());
+
+        let result: ServiceResult<(u64, String)> = async {
+            let (size, sha256) = spool(&mut field, &path, config.max_size).await?;
+
+            let file: File = File::open(&path).await.map_err(upload_error)?;
+            storage
+                .put(&key, ReaderStream::new(file).boxed(), size)
+                .await?;
+
+            Ok((size, sha256))
+        }
+        .await;
+
+        let _ = fs::remove_file(&path).await;
+        let (size, sha256) = result?;
+
+        let tx: DatabaseTransaction = db.begin().await?;
+
+        // The task may have been trashed while the file was uploading
+        let model: ServiceResult<AttachmentModel> = async {
+            Self::check(&tx, user_id, task_id, comment_id).await?;
+
+            Ok(AttachmentActiveModel {
+                user_id: Set(user_id),
+                task_id: Set(task_id),
+                comment_id: Set(comment_id),
+                name: Set(name),
+                content_type: Set(content_type),
+                size: Set(size as i64),
+                storage_key: Set(key.clone()),
+                sha256: Set(sha256),
+                ..Default::default()
+            }
+            .insert(&tx)
+            .await?)
+        }
+        .await;
+
+        match model {
+            Ok(model) => {
+                tx.commit().await?;
+
+                Ok(AttachmentReadDto::from(model))
+            }
+            Err(err) => {
+                if let Err(err) = storage.delete(&key).await {
+                    log::warn!("Removing unrecorded file {} failed: {}", key, err);
+                }
+
+                Err(err)
+            }
+        }
+    }
+
+    /// Files of the task and of its comments, oldest first. Files of
+    /// trashed comments are left out.
+    pub async fn list(
+        db: &DatabaseConnection,
+        user_id: Uuid,
+        task_id: Uuid,
+    ) -> ServiceResult<Vec<AttachmentReadDto>> {
+        let tx: DatabaseTransaction = db.begin().await?;
+
+        Self::check(&tx, user_id, task_id, None).await?;
+
+        let comment_ids: Vec<Uuid> = TaskCommentEntity::find()
+            .select_only()
+            .column(TaskCommentColumn::Id)
+            .filter(TaskCommentColumn::TaskId.eq(task_id))
+            .filter(TaskCommentColumn::DeletedAt.is_null())
+            .into_tuple::<Uuid>()
+            .all(&tx)
+            .await?;
+
+        let models: Vec<AttachmentModel> = AttachmentEntity::find()
+            .filter(AttachmentColumn::TaskId.eq(task_id))
+            .filter(
+                Condition::any()
+                    .add(AttachmentColumn::CommentId.is_null())
+                    .add(AttachmentColumn::CommentId.is_in(comment_ids)),
+            )
+            .order_by_asc(AttachmentColumn::CreatedAt)
+            .order_by_asc(AttachmentColumn::Id)
+            .all(&tx)
+            .await?;
+
+        let schemas: Vec<AttachmentReadDto> = models
+            .into_iter()
+            .map(AttachmentReadDto::from)
+            .collect::<Vec<AttachmentReadDto>>();
+
+        Ok(schemas)
+    }
+
+    pub async fn get_by_id(
+        db: &DatabaseConnection,
+        user_id: Uuid,
+        task_id: Uuid,
+        id: Uuid,
+    ) -> ServiceResult<AttachmentReadDto> {
+        let tx: DatabaseTransaction = db.begin().await?;
+
+        Ok(AttachmentReadDto::from(
+            Self::find(&tx, user_id, task_id, id).await?,
+        ))
+    }
+
+    /// Opens the contents of a file, or the part `range` asks for.
+    pub async fn content(
+        db: &DatabaseConnection,
+        storage: &StorageClient,
+        user_id: Uuid,
+        task_id: Uuid,
+        id: Uuid,
+        range: RangeDto,
+    ) -> ServiceResult<AttachmentContent> {
+        let tx: DatabaseTransaction = db.begin().await?;
+
+        let model: AttachmentModel = Self::find(&tx, user_id, task_id, id).await?;
+
+        tx.commit().await?;
+
+        let size: u64 = model.size as u64;
+        let range = range.resolve(size)?;
+        let body: ByteStream = storage.get(&model.storage_key, range.clone()).await?;
+
+        Ok(AttachmentContent {
+            attachment: AttachmentReadDto::from(model),
+            range: range.map(|range| (range.start, range.end - 1, size)),
+            body,
+        })
+    }
+
+    /// Removes the record, the file follows with the next storage cleanup.
+    pub async fn delete(
+        db: &DatabaseConnection,
+        user_id: Uuid,
+        task_id: Uuid,
+        id: Uuid,
+    ) -> ServiceResult {
+        let tx: DatabaseTransaction = db.begin().await?;
+
+        Self::find(&tx, user_id, task_id, id)
+            .await?
+            .delete(&tx)
+            .await?;
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    /// Checks that the user owns the task, and that the comment is one of
+    /// its comments. Neither may be in the trash.
+    async fn check(
+        tx: &DatabaseTransaction,
+        user_id: Uuid,
+        task_id: Uuid,
+        comment_id: Option<Uuid>,
+    ) -> ServiceResult {
+        match TaskEntity::find_by_id(task_id)
+            .filter(TaskColumn::DeletedAt.is_null())
+            .one(tx)
+            .await?
+        {
+            Some(value) => {
+                if value.user_id != user_id {
+                    return Err(ServiceError::Forbidden);
+                }
+            }
+            None => return Err(ServiceError::NotFound(task_id)),
+        }
+
+        if let Some(comment_id) = comment_id {
+            if TaskCommentEntity::find_by_id(comment_id)
+                .filter(TaskCommentColumn::TaskId.eq(task_id))
+                .filter(TaskCommentColumn::DeletedAt.is_null())
+                .one(tx)
+                .await?
+                .is_none()
+            {
+                return Err(ServiceError::NotFound(comment_id));
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn find(
+        tx: &DatabaseTransaction,
+        user_id: Uuid,
+        task_id: Uuid,
+        id: Uuid,
+    ) -> ServiceResult<AttachmentModel> {
+        Self::check(tx, user_id, task_id, None).await?;
+
+        match AttachmentEntity::find_by_id(id)
+            .filter(AttachmentColumn::TaskId.eq(task_id))
+            .one(tx)
+            .await?
+        {
+            Some(value) => {
+                if let Some(comment_id) = value.comment_id {
+                    Self::check(tx, user_id, task_id, Some(comment_id))
+                        .await
+                        .map_err(|_| ServiceError::NotFound(id))?;
+                }
+
+                Ok(value)
+            }
+            None => Err(ServiceError::NotFound(id)),
+        }
+    }
+}
+
+/// Writes `field` to `path`, failing once it outgrows `max_size`. Returns
+/// the size and the SHA-256 of the contents.
+async fn spool(field: &mut Field, path: &Path, max_size: u64) -> ServiceResult<(u64, String)> {
+    let mut file: File = File::create(path).await.map_err(upload_error)?;
+    let mut hasher = Sha256::new();
+    let mut size: u64 = 0;
+
+    while let Some(chunk) = field.try_next().await? {
+        size += chunk.len() as u64;
+        if size > max_size {
+            return Err(ServiceError::LargeFile);
+        }
+
+        hasher.update(&chunk);
+        file.write_all(&chunk).await.map_err(upload_error)?;
+    }
+    file.flush().await.map_err(upload_error)?;
+
+    let sha256: String = hasher
+        .finalize()
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect::<String>();
+
+    Ok((size, sha256))
+}
+
+/// Last segment of an uploaded file name without control characters.
+fn file_name(value: &str) -> String {
+    let name: String = value
+        .rsplit(['/', '\\'])
+        .next()
+        .unwrap_or_default()
+        .chars()
+        .filter(|char| !char.is_control())
+        .take(constants::ATTACHMENT_NAME_MAX_LENGTH)
+        .collect::<String>();
+
+    match name.trim() {
+        "" | "." | ".." => "file".to_string(),
+        value => value.to_string(),
+    }
+}
+
+fn accepts(config: &AttachmentConfig, content_type: &str) -> bool {
+    config
+        .content_types
+        .iter()
+        .any(|value| match value.strip_suffix("/*") {
+            Some(prefix) => content_type
+                .split_once('/')
+                .is_some_and(|(kind, _)| kind.eq_ignore_ascii_case(prefix)),
+            None => value.eq_ignore_ascii_case(content_type),
+        })
+}
+
+fn upload_error(err: std::io::Error) -> ServiceError {
+    ServiceError::Unknow(format!("Saving upload failed: {err}"))
+}
@@ -41,7 +41,7 @@ impl AuthService {
         expire: u64,
         secret: String,
     ) -> ServiceResult<TokenDto> {
-        let (id, hashed_password) = UserService::get_by_login(&db, credentials.login).await?;
+        let (id, hashed_password) = UserService::get_by_login(db, credentials.login).await?;
 
         if verify_hash(credentials.password, hashed_password)? {
             Ok(TokenDto {
@@ -0,0 +1,437 @@
+use std::io::Cursor;
+
+use image::{
+    codecs::{png::PngEncoder, webp::WebPEncoder},
+    imageops, DynamicImage, ExtendedColorType, ImageEncoder, ImageError, ImageFormat, ImageReader,
+    Limits, Rgba, RgbaImage,
+};
+use sha2::{Digest, Sha256};
+
+use crate::{
+    constants,
+    dto::user::UserAvatarCropQuery,
+    entity::sea_orm_active_enums::AvatarFormat,
+    error::avatar::{AvatarError, AvatarResult},
+};
+
+/// Upload formats, anything else is refused before decoding.
+const INPUT_FORMATS: [ImageFormat; 4] = [
+    ImageFormat::Png,
+    ImageFormat::Jpeg,
+    ImageFormat::WebP,
+    ImageFormat::Gif,
+];
+
+/// EXIF tag of the orientation the camera was held in.
+const EXIF_ORIENTATION_TAG: u16 = 0x0112;
+
+/// Cells on each side of a generated avatar.
+const IDENTICON_CELLS: u32 = 5;
+
+/// Background of generated avatars.
+const IDENTICON_BACKGROUND: Rgba<u8> = Rgba([240, 240, 240, 255]);
+
+/// One encoded size and format of an avatar.
+#[derive(Debug, Clone)]
+pub struct AvatarImage {
+    pub size: u32,
+    pub format: AvatarFormat,
+    pub content: Vec<u8>,
+}
+
+/// Makes every size and format of the avatar from an upload: turns it
+/// upright, takes the centre square of `crop` and encodes it anew, which
+/// leaves the metadata of the upload behind. Blocks, keep it off the
+/// executor.
+pub fn process(file: &[u8], crop: &UserAvatarCropQuery) -> AvatarResult<Vec<AvatarImage>> {
+    let mut reader = ImageReader::new(Cursor::new(file))
+        .with_guessed_format()
+        .map_err(ImageError::from)?;
+
+    let format: ImageFormat = match reader.format() {
+        Some(value) if INPUT_FORMATS.contains(&value) => value,
+        Some(value) => return Err(AvatarError::Format(value.to_mime_type().to_string())),
+        None => return Err(AvatarError::Format("application/octet-stream".to_string())),
+    };
+
+    // Guards against small files that decode to huge images
+    let mut limits: Limits = Limits::default();
+    limits.max_image_width = Some(constants::AVATAR_MAX_DIMENSION);
+    limits.max_image_height = Some(constants::AVATAR_MAX_DIMENSION);
+    reader.limits(limits);
+
+    let image: DynamicImage = match format {
+        ImageFormat::Jpeg => orient(reader.decode()?, orientation(file)),
+        _ => reader.decode()?,
+    };
+
+    let (x, y, side) = square(image.width(), image.height(), crop)?;
+    let image: RgbaImage = image.crop_imm(x, y, side, side).to_rgba8();
+
+    let mut images: Vec<AvatarImage> = Vec::new();
+
+    for size in constants::AVATAR_SIZES {
+        let resized: RgbaImage =
+            imageops::resize(&image, size, size, imageops::FilterType::Lanczos3);
+
+        for format in [AvatarFormat::Png, AvatarFormat::Webp] {
+            images.push(AvatarImage {
+                size,
+                content: encode(&resized, &format)?,
+                format,
+            });
+        }
+    }
+
+    Ok(images)
+}
+
+/// Default avatar of `size`: a mirrored 5x5 pattern in a colour, both taken
+/// from the SHA-256 of `seed`, so the same seed always gives the same image.
+pub fn generate(seed: &[u8], size: u32, format: AvatarFormat) -> AvatarResult<AvatarImage> {
+    let hash = Sha256::digest(seed);
+
+    let hue: f32 = (u16::from_be_bytes([hash[0], hash[1]]) % 360) as f32;
+    let colour: Rgba<u8> = hsl(hue, 0.55, 0.55);
+
+    // Left columns and the middle one, the right ones mirror them
+    let filled = |column: u32, row: u32| -> bool {
+        let column: u32 = column.min(IDENTICON_CELLS - 1 - column);
+        let bit: usize = (column * IDENTICON_CELLS + row) as usize;
+
+        hash[2 + bit / 8] & (1 << (bit % 8)) != 0
+    };
+
+    // Whole pixels per cell within a margin of about a tenth
+    let cell: u32 = (size - 2 * (size / 10)) / IDENTICON_CELLS;
+    let margin: u32 = (size - cell * IDENTICON_CELLS) / 2;
+    let pattern = margin..margin + cell * IDENTICON_CELLS;
+
+    let image: RgbaImage = RgbaImage::from_fn(size, size, |x, y| {
+        if pattern.contains(&x)
+            && pattern.contains(&y)
+            && filled((x - margin) / cell, (y - margin) / cell)
+        {
+            colour
+        } else {
+            IDENTICON_BACKGROUND
+        }
+    });
+
+    Ok(AvatarImage {
+        size,
+        content: encode(&image, &format)?,
+        format,
+    })
+}
+
+/// Encodes anew without any metadata, WebP losslessly.
+fn encode(image: &RgbaImage, format: &AvatarFormat) -> AvatarResult<Vec<u8>> {
+    let mut content: Vec<u8> = Vec::new();
+
+    match format {
+        AvatarFormat::Png => PngEncoder::new(&mut content).write_image(
+            image.as_raw(),
+            image.width(),
+            image.height(),
+            ExtendedColorType::Rgba8,
+        )?,
+        AvatarFormat::Webp => WebPEncoder::new_lossless(&mut content).write_image(
+            image.as_raw(),
+            image.width(),
+            image.height(),
+            ExtendedColorType::Rgba8,
+        )?,
+    }
+
+    Ok(content)
+}
+
+/// Opaque colour of a hue in degrees, saturation and lightness.
+fn hsl(hue: f32, saturation: f32, lightness: f32) -> Rgba<u8> {
+    let chroma: f32 = (1.0 - (2.0 * lightness - 1.0).abs()) * saturation;
+    let x: f32 = chroma * (1.0 - ((hue / 60.0) % 2.0 - 1.0).abs());
+    let m: f32 = lightness - chroma / 2.0;
+
+    let (r, g, b) = match hue as u32 / 60 {
+        0 => (chroma, x, 0.0),
+        1 => (x, chroma, 0.0),
+        2 => (0.0, chroma, x),
+        3 => (0.0, x, chroma),
+        4 => (x, 0.0, chroma),
+        _ => (chroma, 0.0, x),
+    };
+    let channel = |value: f32| ((value + m) * 255.0).round() as u8;
+
+    Rgba([channel(r), channel(g), channel(b), 255])
+}
+
+/// Centre square of the crop box, or of the whole image, as its corner and
+/// side.
+fn square(width: u32, height: u32, crop: &UserAvatarCropQuery) -> AvatarResult<(u32, u32, u32)> {
+    let (x, y, box_width, box_height) = match (crop.x, crop.y, crop.width, crop.height) {
+        (None, None, None, None) => (0, 0, width, height),
+        (Some(x), Some(y), Some(box_width), Some(box_height)) => {
+            let inside = box_width > 0
+                && box_height > 0
+                && x.checked_add(box_width).is_some_and(|right| right <= width)
+                && y.checked_add(box_height)
+                    .is_some_and(|bottom| bottom <= height);
+            if !inside {
+                return Err(AvatarError::Crop(format!(
+                    "Crop box must lie inside the {width}x{height} image"
+                )));
+            }
+
+            (x, y, box_width, box_height)
+        }
+        _ => {
+            return Err(AvatarError::Crop(
+                "Crop box needs x, y, width and height".to_string(),
+            ))
+        }
+    };
+
+    let side: u32 = box_width.min(box_height);
+
+    Ok((
+        x + (box_width - side) / 2,
+        y + (box_height - side) / 2,
+        side,
+    ))
+}
+
+/// Turns an image upright by its EXIF orientation.
+fn orient(image: DynamicImage, orientation: u16) -> DynamicImage {
+    match orientation {
+        2 => image.fliph(),
+        3 => image.rotate180(),
+        4 => image.flipv(),
+        5 => image.rotate90().fliph(),
+        6 => image.rotate90(),
+        7 => image.rotate270().fliph(),
+        8 => image.rotate270(),
+        _ => image,
+    }
+}
+
+/// EXIF orientation of a JPEG, 1 (upright) when it has none.
+fn orientation(file: &[u8]) -> u16 {
+    if !file.starts_with(&[0xFF, 0xD8]) {
+        return 1;
+    }
+
+    // Segments up to the image data, EXIF lives in APP1
+    let mut position: usize = 2;
+    while let Some(header) = file.get(position..position + 4) {
+        if header[0] != 0xFF || header[1] == 0xDA || header[1] == 0xD9 {
+            break;
+        }
+
+        let length: usize = u16::from_be_bytes([header[2], header[3]]) as usize;
+        let segment: &[u8] = match file.get(position + 4..position + 2 + length) {
+            Some(value) if length >= 2 => value,
+            _ => break,
+        };
+
+        if header[1] == 0xE1 {
+            if let Some(tiff) = segment.strip_prefix(b"Exif\0\0") {
+                return exif_orientation(tiff).unwrap_or(1);
+            }
+        }
+
+        position += 2 + length;
+    }
+
+    1
+}
+
+/// Orientation tag of the first IFD of EXIF data.
+fn exif_orientation(tiff: &[u8]) -> Option<u16> {
+    let big_endian: bool = match tiff.get(0..2)? {
+        b"MM" => true,
+        b"II" => false,
+        _ => return None,
+    };
+
+    let read_u16 = |at: usize| -> Option<u16> {
+        let bytes: [u8; 2] = tiff.get(at..at + 2)?.try_into().ok()?;
+        Some(match big_endian {
+            true => u16::from_be_bytes(bytes),
+            false => u16::from_le_bytes(bytes),
+        })
+    };
+    let read_u32 = |at: usize| -> Option<u32> {
+        let bytes: [u8; 4] = tiff.get(at..at + 4)?.try_into().ok()?;
+        Some(match big_endian {
+            true => u32::from_be_bytes(bytes),
+            false => u32::from_le_bytes(bytes),
+        })
+    };
+
+    let ifd: usize = read_u32(4)? as usize;
+    let count: usize = read_u16(ifd)? as usize;
+
+    (0..count)
+        .map(|index| ifd + 2 + index * 12)
+        .find(|entry| read_u16(*entry) == Some(EXIF_ORIENTATION_TAG))
+        .and_then(|entry| read_u16(entry + 8))
+        .filter(|value| (1..=8).contains(value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn crop(x: u32, y: u32, width: u32, height: u32) -> UserAvatarCropQuery {
+        UserAvatarCropQuery {
+            x: Some(x),
+            y: Some(y),
+            width: Some(width),
+            height: Some(height),
+        }
+    }
+
+    fn png(width: u32, height: u32) -> Vec<u8> {
+        let image: RgbaImage = RgbaImage::from_pixel(width, height, Rgba([200, 10, 10, 255]));
+        let mut content: Vec<u8> = Vec::new();
+        PngEncoder::new(&mut content)
+            .write_image(image.as_raw(), width, height, ExtendedColorType::Rgba8)
+            .unwrap();
+
+        content
+    }
+
+    /// JPEG start with an APP1 segment holding only an orientation tag.
+    fn jpeg_exif(big_endian: bool, orientation: u16) -> Vec<u8> {
+        let u16_bytes = |value: u16| match big_endian {
+            true => value.to_be_bytes(),
+            false => value.to_le_bytes(),
+        };
+        let u32_bytes = |value: u32| match big_endian {
+            true => value.to_be_bytes(),
+            false => value.to_le_bytes(),
+        };
+
+        let mut tiff: Vec<u8> = Vec::new();
+        tiff.extend_from_slice(if big_endian { b"MM" } else { b"II" });
+        tiff.extend_from_slice(&u16_bytes(42));
+        tiff.extend_from_slice(&u32_bytes(8));
+        tiff.extend_from_slice(&u16_bytes(1));
+        tiff.extend_from_slice(&u16_bytes(EXIF_ORIENTATION_TAG));
+        tiff.extend_from_slice(&u16_bytes(3));
+        tiff.extend_from_slice(&u32_bytes(1));
+        tiff.extend_from_slice(&u16_bytes(orientation));
+        tiff.extend_from_slice(&[0, 0]);
+        tiff.extend_from_slice(&u32_bytes(0));
+
+        let mut segment: Vec<u8> = b"Exif\0\0".to_vec();
+        segment.extend_from_slice(&tiff);
+
+        let mut file: Vec<u8> = vec![0xFF, 0xD8, 0xFF, 0xE0, 0x00, 0x04, 0x00, 0x00];
+        file.extend_from_slice(&[0xFF, 0xE1]);
+        file.extend_from_slice(&((segment.len() + 2) as u16).to_be_bytes());
+        file.extend_from_slice(&segment);
+        file.extend_from_slice(&[0xFF, 0xDA]);
+
+        file
+    }
+
+    #[test]
+    fn makes_every_size_and_format() {
+        let images: Vec<AvatarImage> =
+            process(&png(90, 60), &UserAvatarCropQuery::default()).unwrap();
+
+        assert_eq!(images.len(), constants::AVATAR_SIZES.len() * 2);
+        for image in images {
+            let decoded: DynamicImage = image::load_from_memory(&image.content).unwrap();
+            assert_eq!(
+                (decoded.width(), decoded.height()),
+                (image.size, image.size)
+            );
+        }
+    }
+
+    #[test]
+    fn squares_the_centre_of_the_crop_box() {
+        let whole = UserAvatarCropQuery::default();
+
+        assert_eq!(square(90, 60, &whole).unwrap(), (15, 0, 60));
+        assert_eq!(square(40, 100, &whole).unwrap(), (0, 30, 40));
+        assert_eq!(square(90, 60, &crop(10, 10, 40, 20)).unwrap(), (20, 10, 20));
+        assert!(square(90, 60, &crop(60, 0, 40, 40)).is_err());
+        assert!(square(90, 60, &crop(0, 0, 0, 10)).is_err());
+        assert!(square(
+            90,
+            60,
+            &UserAvatarCropQuery {
+                x: Some(0),
+                ..Default::default()
+            }
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn reads_jpeg_orientation() {
+        assert_eq!(orientation(&jpeg_exif(true, 6)), 6);
+        assert_eq!(orientation(&jpeg_exif(false, 8)), 8);
+        assert_eq!(orientation(&jpeg_exif(false, 42)), 1);
+        assert_eq!(orientation(&png(2, 2)), 1);
+        assert_eq!(orientation(&[0xFF, 0xD8, 0xFF, 0xE1, 0xFF]), 1);
+    }
+
+    #[test]
+    fn turns_images_upright() {
+        let image = DynamicImage::ImageRgba8(RgbaImage::new(4, 2));
+
+        assert_eq!(orient(image.clone(), 6).width(), 2);
+        assert_eq!(orient(image.clone(), 3).width(), 4);
+        assert_eq!(orient(image, 1).height(), 2);
+    }
+
+    #[test]
+    fn refuses_huge_and_unknown_images() {
+        let file: Vec<u8> = png(constants::AVATAR_MAX_DIMENSION + 1, 1);
+
+        assert!(matches!(
+            process(&file, &UserAvatarCropQuery::default()),
+            Err(AvatarError::Image(ImageError::Limits(_)))
+        ));
+        assert!(matches!(
+            process(b"BM not really", &UserAvatarCropQuery::default()),
+            Err(AvatarError::Format(_))
+        ));
+    }
+
+    #[test]
+    fn generates_the_same_avatar_for_the_same_seed() {
+        let first: AvatarImage = generate(b"seed", 64, AvatarFormat::Png).unwrap();
+        let second: AvatarImage = generate(b"seed", 64, AvatarFormat::Png).unwrap();
+        let other: AvatarImage = generate(b"other seed", 64, AvatarFormat::Png).unwrap();
+
+        assert_eq!(first.content, second.content);
+        assert_ne!(first.content, other.content);
+
+        for size in constants::AVATAR_SIZES {
+            let image: AvatarImage = generate(b"seed", size, AvatarFormat::Webp).unwrap();
+            let decoded: RgbaImage = image::load_from_memory(&image.content).unwrap().to_rgba8();
+
+            assert_eq!((decoded.width(), decoded.height()), (size, size));
+            // Mirrored about the middle column
+            for y in 0..size {
+                for x in 0..size {
+                    assert_eq!(decoded.get_pixel(x, y), decoded.get_pixel(size - 1 - x, y));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn converts_hsl_colours() {
+        assert_eq!(hsl(0.0, 1.0, 0.5), Rgba([255, 0, 0, 255]));
+        assert_eq!(hsl(120.0, 1.0, 0.5), Rgba([0, 255, 0, 255]));
+        assert_eq!(hsl(240.0, 1.0, 0.5), Rgba([0, 0, 255, 255]));
+        assert_eq!(hsl(0.0, 0.0, 1.0), Rgba([255, 255, 255, 255]));
+    }
+}
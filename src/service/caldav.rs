@@ -0,0 +1,700 @@
+//! CalDAV (RFC 4791) access to tasks as a single `VTODO` calendar, with
+//! collection synchronization (RFC 6578).
+
+use chrono::{DateTime, FixedOffset};
+use chrono_tz::Tz;
+use garde::Validate;
+use sea_orm::{
+    sea_query::Expr, ActiveModelTrait, ColumnTrait, Condition, ConnectionTrait, DatabaseConnection,
+    DatabaseTransaction, EntityTrait, IntoActiveModel, QueryFilter, QueryOrder, QuerySelect, Set,
+    TransactionTrait,
+};
+use uuid::Uuid;
+
+use crate::{
+    constants,
+    dto::{
+        caldav::{
+            DavDepth, DavProperty, DavPropsDto, DavReportDto, DavResource, CALDAV_NAMESPACE,
+            CALENDAR_SERVER_NAMESPACE, DAV_NAMESPACE,
+        },
+        precondition::{IfMatchDto, IfNoneMatchDto},
+        sync::SyncToken,
+        task::{TaskCreateDto, TaskReadDto, TaskUpdateDto},
+    },
+    entity::{
+        prelude::{
+            TaskActiveModel, TaskColumn, TaskEntity, TaskModel, TombstoneColumn, TombstoneEntity,
+            TombstoneModel, UserEntity, UserModel,
+        },
+        sea_orm_active_enums::SyncEntity,
+    },
+    error::service::{ServiceError, ServiceResult},
+};
+
+use super::{
+    calendar::{CalendarService, CalendarTodo},
+    sync::SyncService,
+    task::TaskService,
+    user::UserService,
+};
+
+const CALENDAR_NAME: &str = "Tasks";
+
+const CONTENT_TYPE: &str = "text/calendar; charset=utf-8; component=VTODO";
+
+/// Live properties, in the order `allprop` and `propname` list them.
+const PROPERTIES: [(&str, &str); 17] = [
+    (DAV_NAMESPACE, "resourcetype"),
+    (DAV_NAMESPACE, "displayname"),
+    (DAV_NAMESPACE, "current-user-principal"),
+    (DAV_NAMESPACE, "principal-URL"),
+    (DAV_NAMESPACE, "owner"),
+    (DAV_NAMESPACE, "current-user-privilege-set"),
+    (DAV_NAMESPACE, "supported-report-set"),
+    (DAV_NAMESPACE, "sync-token"),
+    (DAV_NAMESPACE, "getetag"),
+    (DAV_NAMESPACE, "getcontenttype"),
+    (DAV_NAMESPACE, "getlastmodified"),
+    (CALDAV_NAMESPACE, "calendar-home-set"),
+    (CALDAV_NAMESPACE, "calendar-user-address-set"),
+    (CALDAV_NAMESPACE, "supported-calendar-component-set"),
+    (CALDAV_NAMESPACE, "calendar-data"),
+    (CALENDAR_SERVER_NAMESPACE, "getctag"),
+    (DAV_NAMESPACE, "supportedlock"),
+];
+
+/// Result of a `PUT`: whether the task was created and its new version.
+pub struct CalDavPut {
+    pub created: bool,
+    pub version: i32,
+}
+
+pub struct CalDavService;
+
+impl CalDavService {
+    /// Answers a `PROPFIND` with a multistatus body.
+    pub async fn propfind(
+        db: &DatabaseConnection,
+        user_id: Uuid,
+        resource: DavResource,
+        depth: DavDepth,
+        props: DavPropsDto,
+    ) -> ServiceResult<Option<String>> {
+        let tx: DatabaseTransaction = db.begin().await?;
+
+        let context: Context = Context::new(&tx, user_id).await?;
+        let mut multistatus: Multistatus = Multistatus::default();
+
+        match &resource {
+            DavResource::Object(name) => match Self::find(&tx, user_id, name, false).await? {
+                Some(model) => multistatus.resource(&context, &resource, Some(&model), &props),
+                None => return Ok(None),
+            },
+            _ => multistatus.resource(&context, &resource, None, &props),
+        }
+
+        if depth == DavDepth::One {
+            match resource {
+                DavResource::Root => {
+                    multistatus.resource(&context, &DavResource::Principal, None, &props);
+                    multistatus.resource(&context, &DavResource::Home, None, &props);
+                }
+                DavResource::Home => {
+                    multistatus.resource(&context, &DavResource::Calendar, None, &props)
+                }
+                DavResource::Calendar => {
+                    for model in Self::tasks(&tx, user_id).await? {
+                        multistatus.resource(
+                            &context,
+                            &DavResource::Object(Self::name(&model)),
+                            Some(&model),
+                            &props,
+                        );
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        Ok(Some(multistatus.finish(None)))
+    }
+
+    /// Answers a `REPORT` on the task calendar.
+    pub async fn report(
+        db: &DatabaseConnection,
+        user_id: Uuid,
+        report: DavReportDto,
+    ) -> ServiceResult<String> {
+        let tx: DatabaseTransaction = db.begin().await?;
+
+        let context: Context = Context::new(&tx, user_id).await?;
+        let mut multistatus: Multistatus = Multistatus::default();
+
+        match report {
+            DavReportDto::CalendarQuery { props, components } => {
+                let todo: bool = components
+                    .iter()
+                    .all(|component| matches!(component.as_str(), "VCALENDAR" | "VTODO"));
+
+                if todo {
+                    for model in Self::tasks(&tx, user_id).await? {
+                        multistatus.resource(
+                            &context,
+                            &DavResource::Object(Self::name(&model)),
+                            Some(&model),
+                            &props,
+                        );
+                    }
+                }
+
+                Ok(multistatus.finish(None))
+            }
+            DavReportDto::CalendarMultiget { props, hrefs } => {
+                for href in hrefs {
+                    let resource: Option<DavResource> = DavResource::from_href(&href);
+
+                    let model: Option<TaskModel> = match &resource {
+                        Some(DavResource::Object(name)) => {
+                            Self::find(&tx, user_id, name, false).await?
+                        }
+                        _ => None,
+                    };
+
+                    match (resource, model) {
+                        (Some(resource), Some(model)) => {
+                            multistatus.resource(&context, &resource, Some(&model), &props)
+                        }
+                        _ => multistatus.status(&href, "404 Not Found"),
+                    }
+                }
+
+                Ok(multistatus.finish(None))
+            }
+            DavReportDto::SyncCollection { props, token } => {
+                let since: Option<SyncToken> = match token.is_empty() {
+                    true => None,
+                    false => Some(
+                        token
+                            .strip_prefix(constants::DAV_SYNC_TOKEN_PREFIX)
+                            .and_then(|value| value.parse::<SyncToken>().ok())
+                            .ok_or(ServiceError::DavPrecondition(
+                                "D:valid-sync-token".to_string(),
+                            ))?,
+                    ),
+                };
+
+                match since {
+                    // An initial sync lists what exists, nothing was deleted yet
+                    None => {
+                        for model in Self::tasks(&tx, user_id).await? {
+                            multistatus.resource(
+                                &context,
+                                &DavResource::Object(Self::name(&model)),
+                                Some(&model),
+                                &props,
+                            );
+                        }
+                    }
+                    Some(since) => {
+                        let models: Vec<TaskModel> = TaskEntity::find()
+                            .filter(TaskColumn::UserId.eq(user_id))
+                            .filter(SyncService::changed_since(
+                                "task",
+                                TaskColumn::ChangeSeq,
+                                &since,
+                            ))
+                            .order_by_asc(TaskColumn::ChangeSeq)
+                            .all(&tx)
+                            .await?;
+
+                        for model in models {
+                            let resource: DavResource = DavResource::Object(Self::name(&model));
+
+                            match model.deleted_at {
+                                Some(_) => multistatus.status(&resource.href(), "404 Not Found"),
+                                None => {
+                                    multistatus.resource(&context, &resource, Some(&model), &props)
+                                }
+                            }
+                        }
+
+                        // Purged tasks lost the name a client gave them
+                        let tombstones: Vec<TombstoneModel> = TombstoneEntity::find()
+                            .filter(TombstoneColumn::UserId.eq(user_id))
+                            .filter(TombstoneColumn::Entity.eq(SyncEntity::Task))
+                            .filter(SyncService::changed_since(
+                                "tombstone",
+                                TombstoneColumn::ChangeSeq,
+                                &since,
+                            ))
+                            .order_by_asc(TombstoneColumn::ChangeSeq)
+                            .all(&tx)
+                            .await?;
+
+                        for model in tombstones {
+                            let resource: DavResource =
+                                DavResource::Object(format!("{}.ics", model.entity_id));
+
+                            multistatus.status(&resource.href(), "404 Not Found");
+                        }
+                    }
+                }
+
+                Ok(multistatus.finish(Some(&context.sync_token)))
+            }
+        }
+    }
+
+    /// Returns the calendar object of a task and its version.
+    pub async fn get(
+        db: &DatabaseConnection,
+        user_id: Uuid,
+        name: &str,
+    ) -> ServiceResult<Option<(String, i32)>> {
+        let tx: DatabaseTransaction = db.begin().await?;
+
+        let model: Option<TaskModel> = Self::find(&tx, user_id, name, false).await?;
+
+        Ok(model.map(|model| (CalendarService::resource(&model), model.version)))
+    }
+
+    /// Creates or replaces the task behind a resource name from a `VTODO`.
+    /// Properties a task has no place for are dropped.
+    pub async fn put(
+        db: &DatabaseConnection,
+        user_id: Uuid,
+        name: String,
+        body: &str,
+        if_match: IfMatchDto,
+        if_none_match: IfNoneMatchDto,
+        enforce_blockers: bool,
+    ) -> ServiceResult<CalDavPut> {
+        let time_zone: Tz = UserService::time_zone(db, user_id).await?;
+        let todo: CalendarTodo = CalendarService::parse_todo(body, &time_zone)?;
+
+        let tx: DatabaseTransaction = db.begin().await?;
+
+        let before: Option<TaskModel> = Self::find(&tx, user_id, &name, true).await?;
+
+        match before {
+            Some(before) => {
+                if before.deleted_at.is_some() {
+                    return Err(ServiceError::Conflict {
+                        field: "name".to_string(),
+                        value: name,
+                    });
+                }
+                if if_none_match.matches(before.version) {
+                    return Err(ServiceError::PreconditionFailed(before.version));
+                }
+
+                tx.commit().await?;
+
+                let schema: TaskUpdateDto = TaskUpdateDto {
+                    name: todo.summary,
+                    description: todo.description,
+                    status: todo.status,
+                    status_id: None,
+                    // A missing `DUE` removes the deadline, the body is the whole task
+                    deadline: Some(todo.deadline),
+                    priority: todo.priority,
+                    custom_fields: None,
+                };
+                schema.validate()?;
+
+                let task: TaskReadDto =
+                    TaskService::update(db, user_id, before.id, if_match, schema, enforce_blockers)
+                        .await?;
+
+                Ok(CalDavPut {
+                    created: false,
+                    version: task.version,
+                })
+            }
+            None => {
+                // The resource to replace is gone
+                if let IfMatchDto::Versions(_) = if_match {
+                    return Err(ServiceError::PreconditionFailed(0));
+                }
+
+                let summary: String = todo.summary.ok_or(ServiceError::DavPrecondition(
+                    "C:valid-calendar-data".to_string(),
+                ))?;
+
+                let schema: TaskCreateDto = TaskCreateDto {
+                    description: todo.description.unwrap_or_else(|| summary.clone()),
+                    name: summary,
+                    status: todo.status,
+                    status_id: None,
+                    deadline: todo.deadline,
+                    priority: todo.priority,
+                    custom_fields: None,
+                };
+                schema.validate()?;
+
+                let model: TaskModel = TaskService::insert(&tx, user_id, schema).await?;
+
+                let mut active_model: TaskActiveModel = model.into_active_model();
+                active_model.dav_name = Set(Some(name));
+                active_model.ical_uid = Set(todo.uid);
+
+                let model: TaskModel = active_model.update(&tx).await?;
+
+                tx.commit().await?;
+
+                Ok(CalDavPut {
+                    created: true,
+                    version: model.version,
+                })
+            }
+        }
+    }
+
+    /// Moves the task behind a resource name to the trash.
+    pub async fn delete(
+        db: &DatabaseConnection,
+        user_id: Uuid,
+        name: &str,
+        if_match: IfMatchDto,
+    ) -> ServiceResult<bool> {
+        let tx: DatabaseTransaction = db.begin().await?;
+
+        let model: TaskModel = match Self::find(&tx, user_id, name, false).await? {
+            Some(value) => value,
+            None => return Ok(false),
+        };
+
+        tx.commit().await?;
+
+        TaskService::delete(db, user_id, model.id, if_match).await?;
+
+        Ok(true)
+    }
+
+    /// Resource name of a task.
+    fn name(model: &TaskModel) -> String {
+        match &model.dav_name {
+            Some(value) => value.clone(),
+            None => format!("{}.ics", model.id),
+        }
+    }
+
+    /// Task behind a resource name, trashed ones only when asked for.
+    async fn find<C: ConnectionTrait>(
+        conn: &C,
+        user_id: Uuid,
+        name: &str,
+        trashed: bool,
+    ) -> ServiceResult<Option<TaskModel>> {
+        let mut condition: Condition = Condition::any().add(TaskColumn::DavName.eq(name));
+
+        if let Some(id) = name
+            .strip_suffix(".ics")
+            .and_then(|value| value.parse::<Uuid>().ok())
+        {
+            condition = condition.add(
+                Condition::all()
+                    .add(TaskColumn::DavName.is_null())
+                    .add(TaskColumn::Id.eq(id)),
+            );
+        }
+
+        let mut select = TaskEntity::find()
+            .filter(TaskColumn::UserId.eq(user_id))
+            .filter(condition);
+
+        if !trashed {
+            select = select.filter(TaskColumn::DeletedAt.is_null());
+        }
+
+        Ok(select.one(conn).await?)
+    }
+
+    async fn tasks<C: ConnectionTrait>(conn: &C, user_id: Uuid) -> ServiceResult<Vec<TaskModel>> {
+        Ok(TaskEntity::find()
+            .filter(TaskColumn::UserId.eq(user_id))
+            .filter(TaskColumn::DeletedAt.is_null())
+            .order_by_asc(TaskColumn::CreatedAt)
+            .order_by_asc(TaskColumn::Id)
+            .all(conn)
+            .await?)
+    }
+}
+
+/// What properties of every resource in a response are made of.
+struct Context {
+    user: UserModel,
+    sync_token: String,
+    /// Latest change position, unlike the sync token it stays the same
+    /// while the calendar does
+    ctag: String,
+}
+
+impl Context {
+    async fn new<C: ConnectionTrait>(conn: &C, user_id: Uuid) -> ServiceResult<Self> {
+        let user: UserModel = match UserEntity::find_by_id(user_id).one(conn).await? {
+            Some(value) => value,
+            None => return Err(ServiceError::NotFound(user_id)),
+        };
+
+        let snapshot: String = SyncService::snapshot(conn).await?;
+
+        // Deleting a task bumps the sequence too, purging it leaves a tombstone
+        let task: Option<i64> = TaskEntity::find()
+            .select_only()
+            .expr(Expr::col(TaskColumn::ChangeSeq).max())
+            .filter(TaskColumn::UserId.eq(user_id))
+            .into_tuple::<Option<i64>>()
+            .one(conn)
+            .await?
+            .flatten();
+
+        let tombstone: Option<i64> = TombstoneEntity::find()
+            .select_only()
+            .expr(Expr::col(TombstoneColumn::ChangeSeq).max())
+            .filter(TombstoneColumn::UserId.eq(user_id))
+            .filter(TombstoneColumn::Entity.eq(SyncEntity::Task))
+            .into_tuple::<Option<i64>>()
+            .one(conn)
+            .await?
+            .flatten();
+
+        let seq: i64 = task.max(tombstone).unwrap_or_default();
+
+        Ok(Self {
+            user,
+            sync_token: format!(
+                "{}{}",
+                constants::DAV_SYNC_TOKEN_PREFIX,
+                SyncToken {
+                    seq,
+                    snapshot: Some(snapshot),
+                }
+            ),
+            ctag: format!(
+                "{}{}",
+                constants::DAV_SYNC_TOKEN_PREFIX,
+                SyncToken {
+                    seq,
+                    snapshot: None,
+                }
+            ),
+        })
+    }
+
+    /// Value of a property on a resource, `None` if it has none.
+    fn property(
+        &self,
+        resource: &DavResource,
+        model: Option<&TaskModel>,
+        property: &DavProperty,
+    ) -> Option<String> {
+        let principal: String = href(&DavResource::Principal.href());
+
+        let value: String = match (property.namespace.as_str(), property.name.as_str()) {
+            (DAV_NAMESPACE, "resourcetype") => match resource {
+                DavResource::Root | DavResource::Home => "<D:collection/>".to_string(),
+                DavResource::Principal => "<D:collection/><D:principal/>".to_string(),
+                DavResource::Calendar => "<D:collection/><C:calendar/>".to_string(),
+                DavResource::Object(_) => String::new(),
+            },
+            (DAV_NAMESPACE, "displayname") => match resource {
+                DavResource::Principal => escape(&self.user.name),
+                DavResource::Calendar => CALENDAR_NAME.to_string(),
+                _ => return None,
+            },
+            (DAV_NAMESPACE, "current-user-principal") => principal,
+            (DAV_NAMESPACE, "principal-URL") | (DAV_NAMESPACE, "owner") => match resource {
+                DavResource::Principal | DavResource::Calendar => principal,
+                _ => return None,
+            },
+            (DAV_NAMESPACE, "current-user-privilege-set") => match resource {
+                DavResource::Object(_) => [
+                    "read",
+                    "write",
+                    "write-content",
+                    "read-current-user-privilege-set",
+                ]
+                .iter()
+                .map(|privilege| format!("<D:privilege><D:{privilege}/></D:privilege>"))
+                .collect::<String>(),
+                DavResource::Calendar => [
+                    "read",
+                    "write",
+                    "write-content",
+                    "bind",
+                    "unbind",
+                    "read-current-user-privilege-set",
+                ]
+                .iter()
+                .map(|privilege| format!("<D:privilege><D:{privilege}/></D:privilege>"))
+                .collect::<String>(),
+                _ => "<D:privilege><D:read/></D:privilege>\
+                    <D:privilege><D:read-current-user-privilege-set/></D:privilege>"
+                    .to_string(),
+            },
+            (DAV_NAMESPACE, "supported-report-set") => match resource {
+                DavResource::Calendar => [
+                    "<C:calendar-query/>",
+                    "<C:calendar-multiget/>",
+                    "<D:sync-collection/>",
+                ]
+                .iter()
+                .map(|report| {
+                    format!(
+                        "<D:supported-report><D:report>{report}</D:report></D:supported-report>"
+                    )
+                })
+                .collect::<String>(),
+                _ => return None,
+            },
+            (DAV_NAMESPACE, "sync-token") => match resource {
+                DavResource::Calendar => escape(&self.sync_token),
+                _ => return None,
+            },
+            (CALENDAR_SERVER_NAMESPACE, "getctag") => match resource {
+                DavResource::Calendar => escape(&self.ctag),
+                _ => return None,
+            },
+            (DAV_NAMESPACE, "getetag") => escape(&format!("\"{}\"", model?.version)),
+            (DAV_NAMESPACE, "getcontenttype") => {
+                model?;
+                CONTENT_TYPE.to_string()
+            }
+            (DAV_NAMESPACE, "getlastmodified") => http_date(model?.updated_at),
+            (CALDAV_NAMESPACE, "calendar-home-set") => match resource {
+                DavResource::Principal => href(&DavResource::Home.href()),
+                _ => return None,
+            },
+            (CALDAV_NAMESPACE, "calendar-user-address-set") => match resource {
+                DavResource::Principal => href(&format!("mailto:{}", self.user.email)),
+                _ => return None,
+            },
+            (CALDAV_NAMESPACE, "supported-calendar-component-set") => match resource {
+                DavResource::Calendar => r#"<C:comp name="VTODO"/>"#.to_string(),
+                _ => return None,
+            },
+            (CALDAV_NAMESPACE, "calendar-data") => escape(&CalendarService::resource(model?)),
+            // Locking is not supported
+            (DAV_NAMESPACE, "supportedlock") => String::new(),
+            _ => return None,
+        };
+
+        Some(value)
+    }
+}
+
+#[derive(Default)]
+struct Multistatus {
+    content: String,
+}
+
+impl Multistatus {
+    /// Adds a resource with the asked properties, the ones it lacks are listed
+    /// as not found.
+    fn resource(
+        &mut self,
+        context: &Context,
+        resource: &DavResource,
+        model: Option<&TaskModel>,
+        props: &DavPropsDto,
+    ) {
+        let mut found: String = String::new();
+        let mut missing: String = String::new();
+
+        match props {
+            DavPropsDto::Prop(properties) => {
+                for property in properties {
+                    match context.property(resource, model, property) {
+                        Some(value) => found.push_str(&element(property, &value)),
+                        None => missing.push_str(&element(property, "")),
+                    }
+                }
+            }
+            // Calendar data is only sent when asked for by name
+            DavPropsDto::AllProp | DavPropsDto::PropName => {
+                for (namespace, name) in PROPERTIES {
+                    let property: DavProperty = DavProperty::new(namespace, name);
+
+                    if property.is(CALDAV_NAMESPACE, "calendar-data")
+                        && matches!(props, DavPropsDto::AllProp)
+                    {
+                        continue;
+                    }
+
+                    if let Some(value) = context.property(resource, model, &property) {
+                        match props {
+                            DavPropsDto::PropName => found.push_str(&element(&property, "")),
+                            _ => found.push_str(&element(&property, &value)),
+                        }
+                    }
+                }
+            }
+        }
+
+        self.content.push_str("<D:response>");
+        self.content.push_str(&href(&resource.href()));
+
+        if !found.is_empty() {
+            self.content.push_str(&format!(
+                "<D:propstat><D:prop>{found}</D:prop><D:status>HTTP/1.1 200 OK</D:status></D:propstat>"
+            ));
+        }
+        if !missing.is_empty() {
+            self.content.push_str(&format!(
+                "<D:propstat><D:prop>{missing}</D:prop><D:status>HTTP/1.1 404 Not Found</D:status></D:propstat>"
+            ));
+        }
+
+        self.content.push_str("</D:response>");
+    }
+
+    fn status(&mut self, resource: &str, status: &str) {
+        self.content.push_str(&format!(
+            "<D:response>{}<D:status>HTTP/1.1 {status}</D:status></D:response>",
+            href(resource)
+        ));
+    }
+
+    fn finish(self, sync_token: Option<&str>) -> String {
+        let sync_token: String = match sync_token {
+            Some(value) => format!("<D:sync-token>{}</D:sync-token>", escape(value)),
+            None => String::new(),
+        };
+
+        format!(
+            r#"<?xml version="1.0" encoding="utf-8"?><D:multistatus xmlns:D="{DAV_NAMESPACE}" xmlns:C="{CALDAV_NAMESPACE}" xmlns:CS="{CALENDAR_SERVER_NAMESPACE}">{}{sync_token}</D:multistatus>"#,
+            self.content
+        )
+    }
+}
+
+/// Writes a property in its own namespace, so unknown ones can be echoed back.
+fn element(property: &DavProperty, value: &str) -> String {
+    let namespace: String = escape(&property.namespace);
+
+    match value.is_empty() {
+        true => format!(r#"<{} xmlns="{namespace}"/>"#, property.name),
+        false => format!(r#"<{0} xmlns="{namespace}">{value}</{0}>"#, property.name),
+    }
+}
+
+fn href(value: &str) -> String {
+    format!("<D:href>{}</D:href>", escape(value))
+}
+
+fn escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn http_date(value: DateTime<FixedOffset>) -> String {
+    value
+        .naive_utc()
+        .format("%a, %d %b %Y %H:%M:%S GMT")
+        .to_string()
+}
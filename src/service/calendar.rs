@@ -0,0 +1,533 @@
+//! iCalendar (RFC 5545) export and import of tasks.
+
+use chrono::{DateTime, Days, FixedOffset, NaiveDate, NaiveDateTime, TimeDelta, TimeZone};
+use chrono_tz::Tz;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, DatabaseTransaction, EntityTrait,
+    IntoActiveModel, QueryFilter, QueryOrder, QuerySelect, Set, TransactionTrait,
+};
+use uuid::Uuid;
+
+use crate::{
+    constants,
+    dto::{
+        calendar::{CalendarComponent, CalendarQuery, CalendarTokenReadDto},
+        task::{TaskDeadlineDto, TaskGetQuery},
+    },
+    entity::{
+        prelude::{TaskColumn, TaskModel, UserActiveModel, UserColumn, UserEntity, UserModel},
+        sea_orm_active_enums::{TaskPriority, TaskStatus},
+    },
+    error::service::{ServiceError, ServiceResult},
+};
+
+use super::{common, task::TaskService};
+
+/// Content lines are folded after this many octets.
+const LINE_MAX_LENGTH: usize = 75;
+
+const PRODUCT_ID: &str = "-//task-flow//task-flow-backend//EN";
+
+pub struct CalendarService;
+
+impl CalendarService {
+    pub async fn export(
+        db: &DatabaseConnection,
+        user_id: Uuid,
+        query: CalendarQuery,
+    ) -> ServiceResult<String> {
+        let tx: DatabaseTransaction = db.begin().await?;
+
+        Self::render(&tx, user_id, query).await
+    }
+
+    /// Same as [`Self::export`] for the owner of a feed token.
+    pub async fn feed(
+        db: &DatabaseConnection,
+        token: String,
+        query: CalendarQuery,
+    ) -> ServiceResult<String> {
+        let tx: DatabaseTransaction = db.begin().await?;
+
+        let model: UserModel = match UserEntity::find()
+            .filter(UserColumn::CalendarToken.eq(token))
+            .one(&tx)
+            .await?
+        {
+            Some(value) => value,
+            None => {
+                return Err(ServiceError::InvalidCredentials(
+                    "Invalid calendar token".to_string(),
+                ))
+            }
+        };
+
+        Self::render(&tx, model.id, query).await
+    }
+
+    /// Issues a new feed token, the previous one stops working.
+    pub async fn regenerate_token(
+        db: &DatabaseConnection,
+        user_id: Uuid,
+    ) -> ServiceResult<CalendarTokenReadDto> {
+        let tx: DatabaseTransaction = db.begin().await?;
+
+        let token: String = common::token(constants::CALENDAR_TOKEN_SIZE);
+
+        let mut active_model: UserActiveModel =
+            Self::find_user(&tx, user_id).await?.into_active_model();
+        active_model.calendar_token = Set(Some(token.clone()));
+        active_model.update(&tx).await?;
+
+        tx.commit().await?;
+
+        Ok(CalendarTokenReadDto::new(token))
+    }
+
+    pub async fn revoke_token(db: &DatabaseConnection, user_id: Uuid) -> ServiceResult {
+        let tx: DatabaseTransaction = db.begin().await?;
+
+        let mut active_model: UserActiveModel =
+            Self::find_user(&tx, user_id).await?.into_active_model();
+        active_model.calendar_token = Set(None);
+        active_model.update(&tx).await?;
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    /// Single task as a calendar object resource, the body of a CalDAV `GET`.
+    pub fn resource(model: &TaskModel) -> String {
+        let mut calendar: Calendar = Calendar::default();
+
+        calendar.header();
+        calendar.todo(model);
+        calendar.property("END", "VCALENDAR");
+
+        calendar.content
+    }
+
+    /// Reads the first `VTODO` of a calendar object. Floating and unknown
+    /// zone times are taken in the user's time zone.
+    pub fn parse_todo(input: &str, time_zone: &Tz) -> ServiceResult<CalendarTodo> {
+        let (todos, other) = read_todos(input, time_zone)?;
+
+        match todos.into_iter().next() {
+            Some(value) => Ok(value),
+            None => Err(match other {
+                true => ServiceError::DavPrecondition("C:supported-calendar-component".to_string()),
+                false => invalid_data(),
+            }),
+        }
+    }
+
+    /// Reads every `VTODO` of a calendar, other components are skipped.
+    pub fn parse_todos(input: &str, time_zone: &Tz) -> ServiceResult<Vec<CalendarTodo>> {
+        Ok(read_todos(input, time_zone)?.0)
+    }
+
+    async fn find_user(tx: &DatabaseTransaction, user_id: Uuid) -> ServiceResult<UserModel> {
+        match UserEntity::find_by_id(user_id)
+            .lock_exclusive()
+            .one(tx)
+            .await?
+        {
+            Some(value) => Ok(value),
+            None => Err(ServiceError::NotFound(user_id)),
+        }
+    }
+
+    async fn render(
+        tx: &DatabaseTransaction,
+        user_id: Uuid,
+        query: CalendarQuery,
+    ) -> ServiceResult<String> {
+        let component: CalendarComponent = query.component;
+        let query: TaskGetQuery = TaskGetQuery::from(query);
+
+        let models: Vec<TaskModel> = TaskService::select(tx, user_id, &query)
+            .await?
+            .order_by_asc(TaskColumn::CreatedAt)
+            .order_by_asc(TaskColumn::Id)
+            .limit(query.limit)
+            .all(tx)
+            .await?;
+
+        let mut calendar: Calendar = Calendar::default();
+
+        calendar.header();
+        calendar.property("X-WR-CALNAME", "Tasks");
+
+        for model in models.iter() {
+            match component {
+                CalendarComponent::Todo => calendar.todo(model),
+                CalendarComponent::Event => calendar.event(model),
+            }
+        }
+
+        calendar.property("END", "VCALENDAR");
+
+        Ok(calendar.content)
+    }
+}
+
+/// Fields of a `VTODO` that map onto a task, absent ones are `None`.
+#[derive(Debug, Default)]
+pub struct CalendarTodo {
+    pub uid: Option<String>,
+    pub summary: Option<String>,
+    pub description: Option<String>,
+    pub status: Option<TaskStatus>,
+    pub priority: Option<TaskPriority>,
+    pub deadline: Option<TaskDeadlineDto>,
+}
+
+#[derive(Default)]
+struct Calendar {
+    content: String,
+}
+
+impl Calendar {
+    fn header(&mut self) {
+        self.property("BEGIN", "VCALENDAR");
+        self.property("VERSION", "2.0");
+        self.property("PRODID", PRODUCT_ID);
+        self.property("CALSCALE", "GREGORIAN");
+    }
+
+    fn todo(&mut self, model: &TaskModel) {
+        self.property("BEGIN", "VTODO");
+        self.common(model);
+
+        self.property(
+            "STATUS",
+            match model.status {
+                TaskStatus::ToDo => "NEEDS-ACTION",
+                TaskStatus::InProgress => "IN-PROCESS",
+                TaskStatus::Done => "COMPLETED",
+            },
+        );
+
+        match TaskDeadlineDto::from_model(model) {
+            Some(TaskDeadlineDto::Date(date)) => self.property("DUE;VALUE=DATE", &date_value(date)),
+            Some(TaskDeadlineDto::Instant(value)) => self.property("DUE", &date_time_value(value)),
+            None => {}
+        }
+
+        self.property("END", "VTODO");
+    }
+
+    /// Tasks without a deadline have no place in time and are left out.
+    fn event(&mut self, model: &TaskModel) {
+        let deadline: TaskDeadlineDto = match TaskDeadlineDto::from_model(model) {
+            Some(value) => value,
+            None => return,
+        };
+
+        self.property("BEGIN", "VEVENT");
+        self.common(model);
+
+        match deadline {
+            TaskDeadlineDto::Date(date) => {
+                self.property("DTSTART;VALUE=DATE", &date_value(date));
+                self.property("DTEND;VALUE=DATE", &date_value(date + Days::new(1)));
+            }
+            // An event without an end takes no time
+            TaskDeadlineDto::Instant(value) => self.property("DTSTART", &date_time_value(value)),
+        }
+
+        self.property("STATUS", "CONFIRMED");
+        self.property("TRANSP", "TRANSPARENT");
+        self.property("END", "VEVENT");
+    }
+
+    /// Properties both components share.
+    fn common(&mut self, model: &TaskModel) {
+        // Tasks created over CalDAV keep the client's UID
+        let uid: String = match &model.ical_uid {
+            Some(value) => text_value(value),
+            None => model.id.to_string(),
+        };
+
+        self.property("UID", &uid);
+        self.property("DTSTAMP", &date_time_value(model.updated_at));
+        self.property("CREATED", &date_time_value(model.created_at));
+        self.property("LAST-MODIFIED", &date_time_value(model.updated_at));
+        self.property("SEQUENCE", &(model.version - 1).max(0).to_string());
+        self.property("SUMMARY", &text_value(&model.name));
+        self.property("DESCRIPTION", &text_value(&model.description));
+
+        // 1 is the highest priority and 9 the lowest
+        self.property(
+            "PRIORITY",
+            match model.priority {
+                TaskPriority::Hight => "1",
+                TaskPriority::Normal => "5",
+                TaskPriority::Low => "9",
+            },
+        );
+    }
+
+    /// Writes a content line folded into lines of at most 75 octets, never
+    /// splitting a character.
+    fn property(&mut self, name: &str, value: &str) {
+        let mut length: usize = 0;
+
+        for char in name
+            .chars()
+            .chain(std::iter::once(':'))
+            .chain(value.chars())
+        {
+            if length + char.len_utf8() > LINE_MAX_LENGTH {
+                self.content.push_str("\r\n ");
+                length = 1;
+            }
+
+            self.content.push(char);
+            length += char.len_utf8();
+        }
+
+        self.content.push_str("\r\n");
+    }
+}
+
+fn date_value(value: NaiveDate) -> String {
+    value.format("%Y%m%d").to_string()
+}
+
+/// UTC date-time, the form every client reads without a `VTIMEZONE`.
+fn date_time_value(value: DateTime<FixedOffset>) -> String {
+    value.naive_utc().format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+/// Escapes a TEXT value, other control characters are not allowed in it.
+fn text_value(value: &str) -> String {
+    value
+        .replace("\r\n", "\n")
+        .chars()
+        .filter(|char| !char.is_control() || matches!(char, '\t' | '\n'))
+        .fold(String::with_capacity(value.len()), |mut result, char| {
+            match char {
+                '\\' => result.push_str("\\\\"),
+                ';' => result.push_str("\\;"),
+                ',' => result.push_str("\\,"),
+                '\n' => result.push_str("\\n"),
+                _ => result.push(char),
+            }
+
+            result
+        })
+}
+
+fn unescape(value: &str) -> String {
+    let mut result: String = String::with_capacity(value.len());
+    let mut chars = value.chars();
+
+    while let Some(char) = chars.next() {
+        match char {
+            '\\' => match chars.next() {
+                Some('n' | 'N') => result.push('\n'),
+                Some(char) => result.push(char),
+                None => {}
+            },
+            _ => result.push(char),
+        }
+    }
+
+    result
+}
+
+/// Joins folded lines back into content lines.
+fn unfold(input: &str) -> String {
+    input
+        .replace("\r\n", "\n")
+        .replace("\n ", "")
+        .replace("\n\t", "")
+}
+
+/// Parsed content line, the name and parameter names are upper-cased.
+struct ContentLine {
+    name: String,
+    params: Vec<(String, String)>,
+    value: String,
+}
+
+/// Splits a content line into its name, parameters and value. Colons and
+/// semicolons inside quoted parameter values don't count.
+fn content_line(line: &str) -> Option<ContentLine> {
+    let mut quoted: bool = false;
+    let mut parts: Vec<&str> = Vec::new();
+    let mut start: usize = 0;
+
+    for (index, char) in line.char_indices() {
+        match char {
+            '"' => quoted = !quoted,
+            ';' if !quoted => {
+                parts.push(&line[start..index]);
+                start = index + 1;
+            }
+            ':' if !quoted => {
+                parts.push(&line[start..index]);
+
+                let name: String = parts[0].trim().to_ascii_uppercase();
+                if name.is_empty() {
+                    return None;
+                }
+
+                let params: Vec<(String, String)> = parts[1..]
+                    .iter()
+                    .filter_map(|param| param.split_once('='))
+                    .map(|(key, value)| {
+                        (
+                            key.trim().to_ascii_uppercase(),
+                            value.trim_matches('"').to_string(),
+                        )
+                    })
+                    .collect::<Vec<(String, String)>>();
+
+                return Some(ContentLine {
+                    name,
+                    params,
+                    value: line[index + 1..].to_string(),
+                });
+            }
+            _ => {}
+        }
+    }
+
+    None
+}
+
+fn due_value(
+    params: &[(String, String)],
+    value: &str,
+    time_zone: &Tz,
+) -> ServiceResult<TaskDeadlineDto> {
+    let param = |name: &str| {
+        params
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    };
+    let value: &str = value.trim();
+
+    if param("VALUE").is_some_and(|value| value.eq_ignore_ascii_case("DATE")) || value.len() == 8 {
+        return NaiveDate::parse_from_str(value, "%Y%m%d")
+            .map(TaskDeadlineDto::Date)
+            .map_err(|_| invalid_data());
+    }
+
+    let (local, utc) = match value.strip_suffix('Z') {
+        Some(value) => (value, true),
+        None => (value, false),
+    };
+    let local: NaiveDateTime =
+        NaiveDateTime::parse_from_str(local, "%Y%m%dT%H%M%S").map_err(|_| invalid_data())?;
+
+    if utc {
+        return Ok(TaskDeadlineDto::Instant(local.and_utc().fixed_offset()));
+    }
+
+    let time_zone: Tz = param("TZID")
+        .and_then(|value| value.trim_start_matches('/').parse::<Tz>().ok())
+        .unwrap_or(*time_zone);
+
+    // Times skipped by a DST change are taken an hour later
+    time_zone
+        .from_local_datetime(&local)
+        .earliest()
+        .or_else(|| {
+            time_zone
+                .from_local_datetime(&(local + TimeDelta::hours(1)))
+                .earliest()
+        })
+        .map(|value| TaskDeadlineDto::Instant(value.fixed_offset()))
+        .ok_or_else(invalid_data)
+}
+
+/// Reads the `VTODO` components of a calendar and whether it holds other
+/// components, such as events, besides them.
+fn read_todos(input: &str, time_zone: &Tz) -> ServiceResult<(Vec<CalendarTodo>, bool)> {
+    let mut todos: Vec<CalendarTodo> = Vec::new();
+    let mut components: Vec<String> = Vec::new();
+    let mut other: bool = false;
+
+    for line in unfold(input).lines().filter(|line| !line.trim().is_empty()) {
+        let ContentLine {
+            name,
+            params,
+            value,
+        } = match content_line(line) {
+            Some(value) => value,
+            None => return Err(invalid_data()),
+        };
+
+        match name.as_str() {
+            "BEGIN" => {
+                let component: String = value.to_ascii_uppercase();
+
+                if components.len() == 1 {
+                    match component.as_str() {
+                        "VTODO" => todos.push(CalendarTodo::default()),
+                        "VTIMEZONE" => {}
+                        _ => other = true,
+                    }
+                }
+
+                components.push(component);
+                continue;
+            }
+            "END" => {
+                if components.pop().is_none() {
+                    return Err(invalid_data());
+                }
+                continue;
+            }
+            _ => {}
+        }
+
+        // Properties of nested components such as `VALARM` are skipped
+        if components.len() != 2 || components[1] != "VTODO" {
+            continue;
+        }
+        let todo: &mut CalendarTodo = match todos.last_mut() {
+            Some(value) => value,
+            None => return Err(invalid_data()),
+        };
+
+        match name.as_str() {
+            "UID" => todo.uid = Some(unescape(&value)),
+            "SUMMARY" => todo.summary = Some(unescape(&value)),
+            "DESCRIPTION" => todo.description = Some(unescape(&value)),
+            "STATUS" => {
+                todo.status = match value.to_ascii_uppercase().as_str() {
+                    "NEEDS-ACTION" => Some(TaskStatus::ToDo),
+                    "IN-PROCESS" => Some(TaskStatus::InProgress),
+                    "COMPLETED" | "CANCELLED" => Some(TaskStatus::Done),
+                    _ => None,
+                }
+            }
+            "PRIORITY" => {
+                todo.priority = match value.trim().parse::<u8>() {
+                    Ok(1..=4) => Some(TaskPriority::Hight),
+                    Ok(6..=9) => Some(TaskPriority::Low),
+                    Ok(_) => Some(TaskPriority::Normal),
+                    Err(_) => return Err(invalid_data()),
+                }
+            }
+            "DUE" => todo.deadline = Some(due_value(&params, &value, time_zone)?),
+            _ => {}
+        }
+    }
+
+    if !components.is_empty() {
+        return Err(invalid_data());
+    }
+
+    Ok((todos, other))
+}
+
+fn invalid_data() -> ServiceError {
+    ServiceError::DavPrecondition("C:valid-calendar-data".to_string())
+}
@@ -1,7 +1,12 @@
 use argon2::{
-    password_hash::{rand_core::OsRng, PasswordHash, PasswordHasher, PasswordVerifier, SaltString},
+    password_hash::{
+        rand_core::{OsRng, RngCore},
+        PasswordHash, PasswordHasher, PasswordVerifier, SaltString,
+    },
     Argon2,
 };
+use chrono::{DateTime, FixedOffset, NaiveDate, NaiveTime, TimeZone};
+use chrono_tz::Tz;
 
 use crate::error::service::{ServiceError, ServiceResult};
 
@@ -25,3 +30,30 @@ pub fn verify_hash(value: String, hash: String) -> ServiceResult<bool> {
 
     Ok(argon.verify_password(value.as_bytes(), &hash).is_ok())
 }
+
+/// Random secret of `size` bytes in hex.
+pub fn token(size: usize) -> String {
+    let mut bytes: Vec<u8> = vec![0; size];
+    OsRng.fill_bytes(&mut bytes);
+
+    bytes
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect::<String>()
+}
+
+/// Time zone stored on a user, zones that no longer parse fall back to UTC.
+pub fn time_zone(value: &str) -> Tz {
+    value.parse::<Tz>().unwrap_or(Tz::UTC)
+}
+
+/// First moment of `date` in `time_zone`, days starting in a gap begin when
+/// the gap ends.
+pub fn start_of_day(time_zone: &Tz, date: NaiveDate) -> DateTime<FixedOffset> {
+    let midnight = date.and_time(NaiveTime::MIN);
+
+    match time_zone.from_local_datetime(&midnight).earliest() {
+        Some(value) => value.fixed_offset(),
+        None => time_zone.from_utc_datetime(&midnight).fixed_offset(),
+    }
+}
@@ -0,0 +1,413 @@
+use std::collections::HashMap;
+
+use chrono::{Local, NaiveDate};
+use sea_orm::{
+    sea_query::{extension::postgres::PgBinOper, Expr, Query, SimpleExpr, SubQueryStatement},
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseConnection, DatabaseTransaction,
+    EntityTrait, IntoActiveModel, ModelTrait, QueryFilter, QueryOrder, QuerySelect, QueryTrait,
+    Set, TransactionTrait,
+};
+use serde_json::Value;
+use uuid::Uuid;
+
+use crate::{
+    constants,
+    dto::custom_field::{CustomFieldCreateDto, CustomFieldReadDto, CustomFieldUpdateDto},
+    entity::{
+        prelude::{
+            CustomFieldActiveModel, CustomFieldColumn, CustomFieldEntity, CustomFieldModel,
+            TaskColumn, TaskCustomFieldValueActiveModel, TaskCustomFieldValueColumn,
+            TaskCustomFieldValueEntity, TaskCustomFieldValueModel, TaskEntity,
+        },
+        sea_orm_active_enums::CustomFieldKind,
+    },
+    error::service::{ServiceError, ServiceResult},
+};
+
+pub struct CustomFieldService;
+
+impl CustomFieldService {
+    pub async fn create(
+        db: &DatabaseConnection,
+        user_id: Uuid,
+        body: CustomFieldCreateDto,
+    ) -> ServiceResult<CustomFieldReadDto> {
+        let tx: DatabaseTransaction = db.begin().await?;
+
+        Self::check_name(&tx, user_id, None, &body.name).await?;
+        Self::check_options(&body.kind, body.options.as_deref())?;
+
+        let mut active_model: CustomFieldActiveModel = body.into_active_model();
+        active_model.user_id = Set(user_id);
+
+        let model: CustomFieldModel = active_model.insert(&tx).await?;
+
+        tx.commit().await?;
+
+        Ok(CustomFieldReadDto::from(model))
+    }
+
+    pub async fn list(
+        db: &DatabaseConnection,
+        user_id: Uuid,
+    ) -> ServiceResult<Vec<CustomFieldReadDto>> {
+        let tx: DatabaseTransaction = db.begin().await?;
+
+        let models: Vec<CustomFieldModel> = CustomFieldEntity::find()
+            .filter(CustomFieldColumn::UserId.eq(user_id))
+            .order_by_asc(CustomFieldColumn::CreatedAt)
+            .all(&tx)
+            .await?;
+
+        let schemas: Vec<CustomFieldReadDto> = models
+            .into_iter()
+            .map(CustomFieldReadDto::from)
+            .collect::<Vec<CustomFieldReadDto>>();
+
+        Ok(schemas)
+    }
+
+    /// Renames the field or replaces its options. The kind is fixed because
+    /// stored values would not match another one.
+    pub async fn update(
+        db: &DatabaseConnection,
+        user_id: Uuid,
+        id: Uuid,
+        body: CustomFieldUpdateDto,
+    ) -> ServiceResult<CustomFieldReadDto> {
+        let tx: DatabaseTransaction = db.begin().await?;
+
+        let model: CustomFieldModel = Self::find(&tx, user_id, id).await?;
+        let mut active_model: CustomFieldActiveModel = model.clone().into_active_model();
+
+        if let Some(name) = body.name {
+            Self::check_name(&tx, user_id, Some(id), &name).await?;
+
+            active_model.name = Set(name);
+        }
+
+        if let Some(options) = body.options {
+            Self::check_options(&model.kind, Some(&options))?;
+
+            let before: Vec<String> = serde_json::from_value(model.options).unwrap_or_default();
+
+            for option in before.iter().filter(|option| !options.contains(option)) {
+                // A string is contained both by itself and by an array holding it
+                let used: Option<Uuid> = TaskCustomFieldValueEntity::find()
+                    .select_only()
+                    .column(TaskCustomFieldValueColumn::TaskId)
+                    .filter(TaskCustomFieldValueColumn::FieldId.eq(id))
+                    .filter(Self::contains(Value::String(option.clone())))
+                    .into_tuple::<Uuid>()
+                    .one(&tx)
+                    .await?;
+
+                if used.is_some() {
+                    return Err(ServiceError::BadRequest(format!(
+                        "Option {option} is used by tasks"
+                    )));
+                }
+            }
+
+            active_model.options = Set(serde_json::json!(options));
+        }
+
+        active_model.updated_at = Set(Local::now().fixed_offset());
+        let model: CustomFieldModel = active_model.update(&tx).await?;
+
+        tx.commit().await?;
+
+        Ok(CustomFieldReadDto::from(model))
+    }
+
+    /// Deletes the field together with its values on every task.
+    pub async fn delete(db: &DatabaseConnection, user_id: Uuid, id: Uuid) -> ServiceResult {
+        let tx: DatabaseTransaction = db.begin().await?;
+
+        Self::find(&tx, user_id, id).await?.delete(&tx).await?;
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    /// Custom field values of the given tasks, keyed by task and then field id.
+    pub async fn values<C: ConnectionTrait>(
+        conn: &C,
+        task_ids: &[Uuid],
+    ) -> ServiceResult<HashMap<Uuid, HashMap<Uuid, Value>>> {
+        let mut values: HashMap<Uuid, HashMap<Uuid, Value>> = HashMap::new();
+
+        if task_ids.is_empty() {
+            return Ok(values);
+        }
+
+        let models: Vec<TaskCustomFieldValueModel> = TaskCustomFieldValueEntity::find()
+            .filter(TaskCustomFieldValueColumn::TaskId.is_in(task_ids.iter().copied()))
+            .all(conn)
+            .await?;
+
+        for model in models {
+            values
+                .entry(model.task_id)
+                .or_default()
+                .insert(model.field_id, model.value);
+        }
+
+        Ok(values)
+    }
+
+    /// Validates and stores values of a task, `null` clears a value.
+    pub async fn set_values(
+        tx: &DatabaseTransaction,
+        user_id: Uuid,
+        task_id: Uuid,
+        values: HashMap<Uuid, Value>,
+    ) -> ServiceResult {
+        for (field_id, value) in values {
+            let field: CustomFieldModel = Self::find(tx, user_id, field_id).await?;
+
+            TaskCustomFieldValueEntity::delete_many()
+                .filter(TaskCustomFieldValueColumn::TaskId.eq(task_id))
+                .filter(TaskCustomFieldValueColumn::FieldId.eq(field_id))
+                .exec(tx)
+                .await?;
+
+            let value: Value = Self::check_value(&field, value)?;
+
+            if value.is_null() {
+                continue;
+            }
+
+            TaskCustomFieldValueActiveModel {
+                task_id: Set(task_id),
+                field_id: Set(field_id),
+                value: Set(value),
+            }
+            .insert(tx)
+            .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Condition on task rows matching a field value given as a query string.
+    /// Without a value any task having the field set matches. A multi-select
+    /// value matches tasks having that option among others.
+    pub async fn filter<C: ConnectionTrait>(
+        conn: &C,
+        user_id: Uuid,
+        field_id: Uuid,
+        value: Option<&str>,
+    ) -> ServiceResult<SimpleExpr> {
+        let field: CustomFieldModel = Self::find(conn, user_id, field_id).await?;
+
+        let mut query = TaskCustomFieldValueEntity::find()
+            .select_only()
+            .column(TaskCustomFieldValueColumn::TaskId)
+            .filter(TaskCustomFieldValueColumn::FieldId.eq(field_id));
+
+        if let Some(value) = value {
+            let value: Value = match field.kind {
+                CustomFieldKind::Number => match value.parse::<f64>() {
+                    Ok(value) => serde_json::json!(value),
+                    Err(_) => return Err(Self::invalid(&field, "a number")),
+                },
+                CustomFieldKind::Boolean => match value.parse::<bool>() {
+                    Ok(value) => Value::Bool(value),
+                    Err(_) => return Err(Self::invalid(&field, "true or false")),
+                },
+                // An option of a multi-select is checked like a single one
+                CustomFieldKind::MultiSelect => {
+                    Value::Array(vec![Value::String(value.to_string())])
+                }
+                _ => Value::String(value.to_string()),
+            };
+
+            let value: Value = match Self::check_value(&field, value)? {
+                Value::Array(mut values) if values.len() == 1 => values.remove(0),
+                value => value,
+            };
+
+            query = query.filter(Self::contains(value));
+        }
+
+        Ok(TaskColumn::Id.in_subquery(query.into_query()))
+    }
+
+    /// Value of a field for the current task row, to sort tasks by. Jsonb
+    /// compares numbers numerically and ISO dates as strings.
+    pub async fn sort<C: ConnectionTrait>(
+        conn: &C,
+        user_id: Uuid,
+        field_id: Uuid,
+    ) -> ServiceResult<SimpleExpr> {
+        Self::find(conn, user_id, field_id).await?;
+
+        let query = Query::select()
+            .column(TaskCustomFieldValueColumn::Value)
+            .from(TaskCustomFieldValueEntity)
+            .and_where(
+                Expr::col((
+                    TaskCustomFieldValueEntity,
+                    TaskCustomFieldValueColumn::TaskId,
+                ))
+                .equals((TaskEntity, TaskColumn::Id)),
+            )
+            .and_where(TaskCustomFieldValueColumn::FieldId.eq(field_id))
+            .to_owned();
+
+        Ok(SimpleExpr::SubQuery(
+            None,
+            Box::new(SubQueryStatement::SelectStatement(query)),
+        ))
+    }
+
+    /// Normalizes a value against the field definition, `null` stays as is.
+    fn check_value(field: &CustomFieldModel, value: Value) -> ServiceResult<Value> {
+        if value.is_null() {
+            return Ok(value);
+        }
+
+        let options: Vec<String> =
+            serde_json::from_value(field.options.clone()).unwrap_or_default();
+
+        match (&field.kind, value) {
+            (CustomFieldKind::Text, Value::String(value)) => {
+                if value.chars().count() > constants::CUSTOM_FIELD_TEXT_MAX_LENGTH {
+                    return Err(Self::invalid(field, "a shorter text"));
+                }
+
+                Ok(Value::String(value))
+            }
+            (CustomFieldKind::Number, Value::Number(value)) => Ok(Value::Number(value)),
+            (CustomFieldKind::Date, Value::String(value)) => {
+                match NaiveDate::parse_from_str(&value, "%Y-%m-%d") {
+                    Ok(value) => Ok(Value::String(value.format("%Y-%m-%d").to_string())),
+                    Err(_) => Err(Self::invalid(field, "a date as YYYY-MM-DD")),
+                }
+            }
+            (CustomFieldKind::Boolean, Value::Bool(value)) => Ok(Value::Bool(value)),
+            (CustomFieldKind::Select, Value::String(value)) => match options.contains(&value) {
+                true => Ok(Value::String(value)),
+                false => Err(Self::invalid(field, "one of the options")),
+            },
+            (CustomFieldKind::MultiSelect, Value::Array(values)) => {
+                let mut selected: Vec<Value> = Vec::with_capacity(values.len());
+
+                for value in values {
+                    match &value {
+                        Value::String(option) if options.contains(option) => {
+                            if !selected.contains(&value) {
+                                selected.push(value);
+                            }
+                        }
+                        _ => return Err(Self::invalid(field, "a list of the options")),
+                    }
+                }
+
+                // Nothing selected is the same as no value
+                match selected.is_empty() {
+                    true => Ok(Value::Null),
+                    false => Ok(Value::Array(selected)),
+                }
+            }
+            (CustomFieldKind::Url, Value::String(value)) => {
+                if value.len() > constants::CUSTOM_FIELD_URL_MAX_LENGTH
+                    || value.contains(char::is_whitespace)
+                    || !(value.starts_with("http://") || value.starts_with("https://"))
+                {
+                    return Err(Self::invalid(field, "an http or https URL"));
+                }
+
+                Ok(Value::String(value))
+            }
+            (CustomFieldKind::Text, _) => Err(Self::invalid(field, "a string")),
+            (CustomFieldKind::Number, _) => Err(Self::invalid(field, "a number")),
+            (CustomFieldKind::Date, _) => Err(Self::invalid(field, "a date as YYYY-MM-DD")),
+            (CustomFieldKind::Boolean, _) => Err(Self::invalid(field, "true or false")),
+            (CustomFieldKind::Select, _) => Err(Self::invalid(field, "one of the options")),
+            (CustomFieldKind::MultiSelect, _) => Err(Self::invalid(field, "a list of the options")),
+            (CustomFieldKind::Url, _) => Err(Self::invalid(field, "an http or https URL")),
+        }
+    }
+
+    fn check_options(kind: &CustomFieldKind, options: Option<&[String]>) -> ServiceResult {
+        let options: &[String] = options.unwrap_or_default();
+
+        match kind {
+            CustomFieldKind::Select | CustomFieldKind::MultiSelect => {
+                if options.is_empty() {
+                    return Err(ServiceError::BadRequest(
+                        "Select fields need at least one option".to_string(),
+                    ));
+                }
+
+                for (index, option) in options.iter().enumerate() {
+                    if options[..index].contains(option) {
+                        return Err(ServiceError::BadRequest(format!(
+                            "Option {option} is duplicated"
+                        )));
+                    }
+                }
+
+                Ok(())
+            }
+            _ => match options.is_empty() {
+                true => Ok(()),
+                false => Err(ServiceError::BadRequest(
+                    "Only select fields have options".to_string(),
+                )),
+            },
+        }
+    }
+
+    async fn check_name(
+        tx: &DatabaseTransaction,
+        user_id: Uuid,
+        id: Option<Uuid>,
+        name: &str,
+    ) -> ServiceResult {
+        let mut query = CustomFieldEntity::find()
+            .filter(CustomFieldColumn::UserId.eq(user_id))
+            .filter(CustomFieldColumn::Name.eq(name));
+
+        if let Some(value) = id {
+            query = query.filter(CustomFieldColumn::Id.ne(value));
+        }
+
+        if query.one(tx).await?.is_some() {
+            return Err(ServiceError::Conflict {
+                field: "name".to_string(),
+                value: name.to_string(),
+            });
+        }
+
+        Ok(())
+    }
+
+    async fn find<C: ConnectionTrait>(
+        conn: &C,
+        user_id: Uuid,
+        id: Uuid,
+    ) -> ServiceResult<CustomFieldModel> {
+        match CustomFieldEntity::find_by_id(id).one(conn).await? {
+            Some(value) => {
+                if value.user_id != user_id {
+                    return Err(ServiceError::Forbidden);
+                }
+
+                Ok(value)
+            }
+            None => Err(ServiceError::NotFound(id)),
+        }
+    }
+
+    fn contains(value: Value) -> SimpleExpr {
+        Expr::col(TaskCustomFieldValueColumn::Value).binary(PgBinOper::Contains, Expr::val(value))
+    }
+
+    fn invalid(field: &CustomFieldModel, expected: &str) -> ServiceError {
+        ServiceError::BadRequest(format!("Field {} expects {expected}", field.name))
+    }
+}
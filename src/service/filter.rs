@@ -0,0 +1,843 @@
+//! Filter expressions of saved filters.
+//!
+//! An expression is a list of terms joined with `and`, `or` and `not`, where
+//! `not` binds tightest and `or` loosest, grouped with parentheses:
+//!
+//! ```text
+//! status:in_progress and (priority:high or deadline:"next 7 days")
+//! not blocked:true and "release notes"
+//! ```
+//!
+//! A term is `key:value` or a bare value searched in the task name and
+//! description. Values are single words or double quoted strings.
+
+use chrono::{DateTime, Days, FixedOffset, NaiveDate};
+use chrono_tz::Tz;
+use sea_orm::{
+    sea_query::{extension::postgres::PgExpr, Expr, Func, Query, SimpleExpr},
+    ColumnTrait, QueryTrait,
+};
+
+use crate::{
+    dto::task::TaskDeadlineDto,
+    entity::{
+        prelude::{TaskColumn, TaskEntity, WorkflowStatusColumn, WorkflowStatusEntity},
+        sea_orm_active_enums::{DeadlineKind, TaskPriority, TaskStatus},
+    },
+    error::service::{ServiceError, ServiceResult},
+};
+
+use super::{common, task_dependency::TaskDependencyService};
+
+/// Deepest nesting of parentheses and `not` an expression may have.
+const MAX_DEPTH: usize = 32;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Filter {
+    And(Box<Filter>, Box<Filter>),
+    Or(Box<Filter>, Box<Filter>),
+    Not(Box<Filter>),
+    Category(TaskStatus),
+    /// Workflow status by name, case insensitive
+    Status(String),
+    Priority(TaskPriority),
+    Deadline(DeadlineRange),
+    Text(String),
+    Blocked(bool),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DeadlineRange {
+    /// Past deadline of a task that is not done
+    Overdue,
+    Today,
+    Tomorrow,
+    /// From now to the given number of days ahead
+    Next(u64),
+    /// From the given number of days ago to now
+    Last(u64),
+    Any,
+    None,
+}
+
+impl Filter {
+    /// Condition on task rows, relative dates are resolved against `now` in
+    /// its time zone.
+    pub fn condition(&self, now: DateTime<Tz>) -> SimpleExpr {
+        match self {
+            Filter::And(left, right) => left.condition(now).and(right.condition(now)),
+            Filter::Or(left, right) => left.condition(now).or(right.condition(now)),
+            Filter::Not(value) => value.condition(now).not(),
+            Filter::Category(value) => TaskColumn::Status.eq(value.clone()),
+            Filter::Status(value) => TaskColumn::StatusId.in_subquery(
+                Query::select()
+                    .column(WorkflowStatusColumn::Id)
+                    .from(WorkflowStatusEntity)
+                    .and_where(
+                        Expr::col((WorkflowStatusEntity, WorkflowStatusColumn::UserId))
+                            .equals((TaskEntity, TaskColumn::UserId)),
+                    )
+                    .and_where(
+                        Expr::expr(Func::lower(Expr::col(WorkflowStatusColumn::Name)))
+                            .eq(value.to_lowercase()),
+                    )
+                    .to_owned(),
+            ),
+            Filter::Priority(value) => TaskColumn::Priority.eq(value.clone()),
+            Filter::Deadline(value) => value.condition(now),
+            Filter::Text(value) => {
+                let pattern: String = format!(
+                    "%{}%",
+                    value
+                        .replace('\\', "\\\\")
+                        .replace('%', "\\%")
+                        .replace('_', "\\_")
+                );
+
+                Expr::col(TaskColumn::Name)
+                    .ilike(&pattern)
+                    .or(Expr::col(TaskColumn::Description).ilike(&pattern))
+            }
+            Filter::Blocked(value) => {
+                let blocked = TaskDependencyService::unfinished_blockers().into_query();
+
+                match value {
+                    true => TaskColumn::Id.in_subquery(blocked),
+                    false => TaskColumn::Id.not_in_subquery(blocked),
+                }
+            }
+        }
+    }
+}
+
+impl DeadlineRange {
+    fn condition(&self, now: DateTime<Tz>) -> SimpleExpr {
+        let today: NaiveDate = now.date_naive();
+        let start = |date: NaiveDate| common::start_of_day(&now.timezone(), date);
+        let now: DateTime<FixedOffset> = now.fixed_offset();
+
+        // Exact deadlines between `from` and `to`, all-day ones from `first`
+        // to `last` day. Tasks without a deadline have no kind, so they match
+        // neither a range nor its negation unless spelled out with `none`.
+        let within = |from: DateTime<FixedOffset>,
+                      to: DateTime<FixedOffset>,
+                      first: NaiveDate,
+                      last: NaiveDate| {
+            TaskColumn::DeadlineKind
+                .eq(DeadlineKind::Instant)
+                .and(TaskColumn::Deadline.gte(from))
+                .and(TaskColumn::Deadline.lt(to))
+                .or(TaskColumn::DeadlineKind
+                    .eq(DeadlineKind::Date)
+                    .and(TaskColumn::Deadline.gte(TaskDeadlineDto::all_day(first)))
+                    .and(TaskColumn::Deadline.lte(TaskDeadlineDto::all_day(last))))
+        };
+
+        match self {
+            DeadlineRange::Overdue => TaskColumn::DeadlineKind
+                .eq(DeadlineKind::Instant)
+                .and(TaskColumn::Deadline.lt(now))
+                .or(TaskColumn::DeadlineKind
+                    .eq(DeadlineKind::Date)
+                    .and(TaskColumn::Deadline.lt(TaskDeadlineDto::all_day(today))))
+                .and(TaskColumn::Status.ne(TaskStatus::Done)),
+            DeadlineRange::Today => within(start(today), start(today + Days::new(1)), today, today),
+            DeadlineRange::Tomorrow => within(
+                start(today + Days::new(1)),
+                start(today + Days::new(2)),
+                today + Days::new(1),
+                today + Days::new(1),
+            ),
+            DeadlineRange::Next(days) => {
+                within(now, now + Days::new(*days), today, today + Days::new(*days))
+            }
+            DeadlineRange::Last(days) => {
+                within(now - Days::new(*days), now, today - Days::new(*days), today)
+            }
+            DeadlineRange::Any => TaskColumn::Deadline.is_not_null(),
+            DeadlineRange::None => TaskColumn::Deadline.is_null(),
+        }
+    }
+}
+
+/// Parses an expression, errors point at the 1-based character position of
+/// the offending token.
+pub fn parse(input: &str) -> ServiceResult<Filter> {
+    let tokens: Vec<Token> = tokenize(input)?;
+
+    let mut parser: Parser = Parser {
+        tokens,
+        index: 0,
+        depth: 0,
+    };
+
+    let filter: Filter = parser.or()?;
+
+    match parser.peek() {
+        Token {
+            kind: TokenKind::End,
+            ..
+        } => Ok(filter),
+        token => Err(error(
+            token.position,
+            format!(
+                "Expected `and`, `or` or end of expression, found {}",
+                token.kind
+            ),
+        )),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum TokenKind {
+    Word(String),
+    Quoted(String),
+    Colon,
+    LeftParen,
+    RightParen,
+    And,
+    Or,
+    Not,
+    End,
+}
+
+impl std::fmt::Display for TokenKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TokenKind::Word(value) => write!(f, "`{value}`"),
+            TokenKind::Quoted(value) => write!(f, "\"{value}\""),
+            TokenKind::Colon => write!(f, "`:`"),
+            TokenKind::LeftParen => write!(f, "`(`"),
+            TokenKind::RightParen => write!(f, "`)`"),
+            TokenKind::And => write!(f, "`and`"),
+            TokenKind::Or => write!(f, "`or`"),
+            TokenKind::Not => write!(f, "`not`"),
+            TokenKind::End => write!(f, "end of expression"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Token {
+    kind: TokenKind,
+    position: usize,
+}
+
+fn tokenize(input: &str) -> ServiceResult<Vec<Token>> {
+    let chars: Vec<char> = input.chars().collect();
+    let mut tokens: Vec<Token> = Vec::new();
+    let mut index: usize = 0;
+
+    while index < chars.len() {
+        let position: usize = index + 1;
+
+        let kind: TokenKind = match chars[index] {
+            value if value.is_whitespace() => {
+                index += 1;
+                continue;
+            }
+            ':' => {
+                index += 1;
+                TokenKind::Colon
+            }
+            '(' => {
+                index += 1;
+                TokenKind::LeftParen
+            }
+            ')' => {
+                index += 1;
+                TokenKind::RightParen
+            }
+            '"' => {
+                let mut value: String = String::new();
+                index += 1;
+
+                loop {
+                    match chars.get(index) {
+                        Some('"') => break,
+                        Some('\\') if matches!(chars.get(index + 1), Some('"' | '\\')) => {
+                            value.push(chars[index + 1]);
+                            index += 2;
+                        }
+                        Some(value_char) => {
+                            value.push(*value_char);
+                            index += 1;
+                        }
+                        None => return Err(error(position, "Unterminated string".to_string())),
+                    }
+                }
+
+                index += 1;
+                TokenKind::Quoted(value)
+            }
+            value if is_word_char(value) => {
+                let start: usize = index;
+                while index < chars.len() && is_word_char(chars[index]) {
+                    index += 1;
+                }
+
+                let word: String = chars[start..index].iter().collect();
+
+                match word.to_lowercase().as_str() {
+                    "and" => TokenKind::And,
+                    "or" => TokenKind::Or,
+                    "not" => TokenKind::Not,
+                    _ => TokenKind::Word(word),
+                }
+            }
+            value => return Err(error(position, format!("Unexpected character `{value}`"))),
+        };
+
+        tokens.push(Token { kind, position });
+    }
+
+    tokens.push(Token {
+        kind: TokenKind::End,
+        position: chars.len() + 1,
+    });
+
+    Ok(tokens)
+}
+
+fn is_word_char(value: char) -> bool {
+    value.is_alphanumeric() || matches!(value, '_' | '-' | '.')
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    index: usize,
+    depth: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> &Token {
+        &self.tokens[self.index]
+    }
+
+    fn next(&mut self) -> Token {
+        let token: Token = self.tokens[self.index].clone();
+        if token.kind != TokenKind::End {
+            self.index += 1;
+        }
+
+        token
+    }
+
+    fn or(&mut self) -> ServiceResult<Filter> {
+        let mut filter: Filter = self.and()?;
+
+        while self.peek().kind == TokenKind::Or {
+            self.next();
+            filter = Filter::Or(Box::new(filter), Box::new(self.and()?));
+        }
+
+        Ok(filter)
+    }
+
+    fn and(&mut self) -> ServiceResult<Filter> {
+        let mut filter: Filter = self.not()?;
+
+        while self.peek().kind == TokenKind::And {
+            self.next();
+            filter = Filter::And(Box::new(filter), Box::new(self.not()?));
+        }
+
+        Ok(filter)
+    }
+
+    fn not(&mut self) -> ServiceResult<Filter> {
+        if self.peek().kind != TokenKind::Not {
+            return self.term();
+        }
+
+        let token: Token = self.next();
+        self.enter(&token)?;
+        let filter: Filter = Filter::Not(Box::new(self.not()?));
+        self.depth -= 1;
+
+        Ok(filter)
+    }
+
+    fn term(&mut self) -> ServiceResult<Filter> {
+        let token: Token = self.next();
+
+        match token.kind.clone() {
+            TokenKind::LeftParen => {
+                self.enter(&token)?;
+                let filter: Filter = self.or()?;
+                self.depth -= 1;
+
+                match self.next() {
+                    Token {
+                        kind: TokenKind::RightParen,
+                        ..
+                    } => Ok(filter),
+                    closing => Err(error(
+                        closing.position,
+                        format!(
+                            "Expected `)` closing the one at position {}, found {}",
+                            token.position, closing.kind
+                        ),
+                    )),
+                }
+            }
+            TokenKind::Word(key) if self.peek().kind == TokenKind::Colon => {
+                self.next();
+
+                let value: Token = self.next();
+                let text: String = match value.kind {
+                    TokenKind::Word(value) | TokenKind::Quoted(value) => value,
+                    kind => {
+                        return Err(error(
+                            value.position,
+                            format!("Expected a value after `{key}:`, found {kind}"),
+                        ))
+                    }
+                };
+
+                term(&key, token.position, &text, value.position)
+            }
+            TokenKind::Word(value) | TokenKind::Quoted(value) => Ok(Filter::Text(value)),
+            kind => Err(error(
+                token.position,
+                format!("Expected a term, found {kind}"),
+            )),
+        }
+    }
+
+    fn enter(&mut self, token: &Token) -> ServiceResult {
+        self.depth += 1;
+
+        match self.depth > MAX_DEPTH {
+            true => Err(error(
+                token.position,
+                format!("Expression is nested deeper than {MAX_DEPTH} levels"),
+            )),
+            false => Ok(()),
+        }
+    }
+}
+
+fn term(key: &str, key_position: usize, value: &str, position: usize) -> ServiceResult<Filter> {
+    let normalized: String = value.trim().to_lowercase();
+
+    match key.to_lowercase().as_str() {
+        "status" => Ok(match normalized.replace(['_', ' '], "").as_str() {
+            "todo" => Filter::Category(TaskStatus::ToDo),
+            "inprogress" => Filter::Category(TaskStatus::InProgress),
+            "done" => Filter::Category(TaskStatus::Done),
+            _ => Filter::Status(value.to_string()),
+        }),
+        "priority" => match normalized.as_str() {
+            "low" => Ok(Filter::Priority(TaskPriority::Low)),
+            "normal" => Ok(Filter::Priority(TaskPriority::Normal)),
+            "high" | "hight" => Ok(Filter::Priority(TaskPriority::Hight)),
+            _ => Err(error(
+                position,
+                format!("Unknown priority `{value}`, expected low, normal or high"),
+            )),
+        },
+        "deadline" => deadline(&normalized.replace('_', " "))
+            .map(Filter::Deadline)
+            .ok_or_else(|| {
+                error(
+                    position,
+                    format!(
+                        "Unknown deadline `{value}`, expected overdue, today, tomorrow, any, \
+                         none, \"next N days\" or \"last N days\""
+                    ),
+                )
+            }),
+        "text" => match normalized.is_empty() {
+            true => Err(error(position, "Text to search is empty".to_string())),
+            false => Ok(Filter::Text(value.to_string())),
+        },
+        "blocked" => match normalized.as_str() {
+            "true" | "yes" => Ok(Filter::Blocked(true)),
+            "false" | "no" => Ok(Filter::Blocked(false)),
+            _ => Err(error(
+                position,
+                format!("Unknown value `{value}`, expected true or false"),
+            )),
+        },
+        _ => Err(error(
+            key_position,
+            format!("Unknown key `{key}`, expected status, priority, deadline, text or blocked"),
+        )),
+    }
+}
+
+fn deadline(value: &str) -> Option<DeadlineRange> {
+    let words: Vec<&str> = value.split_whitespace().collect();
+
+    match words.as_slice() {
+        ["overdue"] => Some(DeadlineRange::Overdue),
+        ["today"] => Some(DeadlineRange::Today),
+        ["tomorrow"] => Some(DeadlineRange::Tomorrow),
+        ["any"] => Some(DeadlineRange::Any),
+        ["none"] => Some(DeadlineRange::None),
+        [direction @ ("next" | "last"), count, unit] => {
+            let count: u64 = count.parse::<u64>().ok()?;
+            let days: u64 = match *unit {
+                "day" | "days" => count,
+                "week" | "weeks" => count.checked_mul(7)?,
+                _ => return None,
+            };
+
+            // Keeps the date arithmetic within chrono's range
+            if days > 36500 {
+                return None;
+            }
+
+            match *direction {
+                "next" => Some(DeadlineRange::Next(days)),
+                _ => Some(DeadlineRange::Last(days)),
+            }
+        }
+        _ => None,
+    }
+}
+
+fn error(position: usize, message: String) -> ServiceError {
+    ServiceError::BadRequest(format!("{message} at position {position}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn and(left: Filter, right: Filter) -> Filter {
+        Filter::And(Box::new(left), Box::new(right))
+    }
+
+    fn or(left: Filter, right: Filter) -> Filter {
+        Filter::Or(Box::new(left), Box::new(right))
+    }
+
+    fn not(value: Filter) -> Filter {
+        Filter::Not(Box::new(value))
+    }
+
+    fn text(value: &str) -> Filter {
+        Filter::Text(value.to_string())
+    }
+
+    fn message(input: &str) -> String {
+        match parse(input) {
+            Err(ServiceError::BadRequest(message)) => message,
+            other => panic!("expected an error for {input:?}, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn bare_words_are_text() {
+        assert_eq!(parse("release").unwrap(), text("release"));
+        assert_eq!(parse("  release  ").unwrap(), text("release"));
+        assert_eq!(parse("v1.2-rc_3").unwrap(), text("v1.2-rc_3"));
+    }
+
+    #[test]
+    fn and_binds_tighter_than_or() {
+        assert_eq!(
+            parse("a or b and c").unwrap(),
+            or(text("a"), and(text("b"), text("c")))
+        );
+        assert_eq!(
+            parse("a and b or c").unwrap(),
+            or(and(text("a"), text("b")), text("c"))
+        );
+    }
+
+    #[test]
+    fn not_binds_tightest() {
+        assert_eq!(
+            parse("not a and b").unwrap(),
+            and(not(text("a")), text("b"))
+        );
+        assert_eq!(parse("a or not b").unwrap(), or(text("a"), not(text("b"))));
+        assert_eq!(parse("not not a").unwrap(), not(not(text("a"))));
+    }
+
+    #[test]
+    fn operators_are_left_associative() {
+        assert_eq!(
+            parse("a and b and c").unwrap(),
+            and(and(text("a"), text("b")), text("c"))
+        );
+        assert_eq!(
+            parse("a or b or c").unwrap(),
+            or(or(text("a"), text("b")), text("c"))
+        );
+    }
+
+    #[test]
+    fn parentheses_group() {
+        assert_eq!(
+            parse("(a or b) and c").unwrap(),
+            and(or(text("a"), text("b")), text("c"))
+        );
+        assert_eq!(
+            parse("not (a or b)").unwrap(),
+            not(or(text("a"), text("b")))
+        );
+        assert_eq!(parse("((a))").unwrap(), text("a"));
+    }
+
+    #[test]
+    fn operators_are_case_insensitive() {
+        assert_eq!(
+            parse("NOT a AND b Or c").unwrap(),
+            or(and(not(text("a")), text("b")), text("c"))
+        );
+    }
+
+    #[test]
+    fn quoted_strings() {
+        assert_eq!(parse(r#""release notes""#).unwrap(), text("release notes"));
+        assert_eq!(parse(r#""and""#).unwrap(), text("and"));
+        assert_eq!(parse(r#""a:(b)""#).unwrap(), text("a:(b)"));
+        assert_eq!(parse(r#""""#).unwrap(), text(""));
+    }
+
+    #[test]
+    fn quoted_string_escapes() {
+        assert_eq!(parse(r#""say \"hi\"""#).unwrap(), text(r#"say "hi""#));
+        assert_eq!(parse(r#""back\\slash""#).unwrap(), text(r"back\slash"));
+        assert_eq!(parse(r#""keep \n""#).unwrap(), text(r"keep \n"));
+        assert_eq!(parse(r#""end\\""#).unwrap(), text(r"end\"));
+    }
+
+    #[test]
+    fn status_values() {
+        for value in ["todo", "to_do", "ToDo", r#""to do""#] {
+            assert_eq!(
+                parse(&format!("status:{value}")).unwrap(),
+                Filter::Category(TaskStatus::ToDo)
+            );
+        }
+        for value in ["in_progress", "inprogress", r#""In Progress""#] {
+            assert_eq!(
+                parse(&format!("status:{value}")).unwrap(),
+                Filter::Category(TaskStatus::InProgress)
+            );
+        }
+        assert_eq!(
+            parse("status:DONE").unwrap(),
+            Filter::Category(TaskStatus::Done)
+        );
+        assert_eq!(
+            parse(r#"status:"Code Review""#).unwrap(),
+            Filter::Status("Code Review".to_string())
+        );
+    }
+
+    #[test]
+    fn priority_values() {
+        assert_eq!(
+            parse("priority:low").unwrap(),
+            Filter::Priority(TaskPriority::Low)
+        );
+        assert_eq!(
+            parse("priority:Normal").unwrap(),
+            Filter::Priority(TaskPriority::Normal)
+        );
+        assert_eq!(
+            parse("priority:high").unwrap(),
+            Filter::Priority(TaskPriority::Hight)
+        );
+        assert_eq!(
+            parse("priority:hight").unwrap(),
+            Filter::Priority(TaskPriority::Hight)
+        );
+        assert_eq!(
+            message("priority:urgent"),
+            "Unknown priority `urgent`, expected low, normal or high at position 10"
+        );
+    }
+
+    #[test]
+    fn deadline_values() {
+        let cases: [(&str, DeadlineRange); 11] = [
+            ("overdue", DeadlineRange::Overdue),
+            ("today", DeadlineRange::Today),
+            ("Tomorrow", DeadlineRange::Tomorrow),
+            ("any", DeadlineRange::Any),
+            ("none", DeadlineRange::None),
+            (r#""next 7 days""#, DeadlineRange::Next(7)),
+            (r#""next 1 day""#, DeadlineRange::Next(1)),
+            (r#""last 2 weeks""#, DeadlineRange::Last(14)),
+            (r#""last 1 week""#, DeadlineRange::Last(7)),
+            ("next_3_days", DeadlineRange::Next(3)),
+            (r#""next 36500 days""#, DeadlineRange::Next(36500)),
+        ];
+
+        for (value, range) in cases {
+            assert_eq!(
+                parse(&format!("deadline:{value}")).unwrap(),
+                Filter::Deadline(range),
+                "{value}"
+            );
+        }
+    }
+
+    #[test]
+    fn invalid_deadlines() {
+        for value in [
+            "soon",
+            r#""next days""#,
+            r#""next -1 days""#,
+            r#""next 3 months""#,
+            r#""next 36501 days""#,
+            r#""later 3 days""#,
+        ] {
+            assert!(
+                message(&format!("deadline:{value}")).starts_with("Unknown deadline"),
+                "{value}"
+            );
+        }
+    }
+
+    #[test]
+    fn text_values() {
+        assert_eq!(parse("text:notes").unwrap(), text("notes"));
+        assert_eq!(parse(r#"text:"and or""#).unwrap(), text("and or"));
+        assert_eq!(
+            message(r#"text:"  ""#),
+            "Text to search is empty at position 6"
+        );
+    }
+
+    #[test]
+    fn blocked_values() {
+        for value in ["true", "yes", "TRUE"] {
+            assert_eq!(
+                parse(&format!("blocked:{value}")).unwrap(),
+                Filter::Blocked(true)
+            );
+        }
+        for value in ["false", "no"] {
+            assert_eq!(
+                parse(&format!("blocked:{value}")).unwrap(),
+                Filter::Blocked(false)
+            );
+        }
+        assert_eq!(
+            message("blocked:maybe"),
+            "Unknown value `maybe`, expected true or false at position 9"
+        );
+    }
+
+    #[test]
+    fn keys_are_case_insensitive() {
+        assert_eq!(
+            parse("Priority:low").unwrap(),
+            Filter::Priority(TaskPriority::Low)
+        );
+        assert_eq!(parse("BLOCKED:no").unwrap(), Filter::Blocked(false));
+    }
+
+    #[test]
+    fn fields_combine_with_operators() {
+        assert_eq!(
+            parse(r#"status:in_progress and (priority:high or deadline:"next 7 days")"#).unwrap(),
+            and(
+                Filter::Category(TaskStatus::InProgress),
+                or(
+                    Filter::Priority(TaskPriority::Hight),
+                    Filter::Deadline(DeadlineRange::Next(7))
+                )
+            )
+        );
+        assert_eq!(
+            parse(r#"not blocked:true and "release notes""#).unwrap(),
+            and(not(Filter::Blocked(true)), text("release notes"))
+        );
+    }
+
+    #[test]
+    fn depth_limit() {
+        let nots: String = "not ".repeat(MAX_DEPTH);
+        assert!(parse(&format!("{nots}a")).is_ok());
+        assert_eq!(
+            message(&format!("{nots}not a")),
+            "Expression is nested deeper than 32 levels at position 129"
+        );
+
+        let open: String = "(".repeat(MAX_DEPTH);
+        let close: String = ")".repeat(MAX_DEPTH);
+        assert!(parse(&format!("{open}a{close}")).is_ok());
+        assert_eq!(
+            message(&format!("{open}(a){close}")),
+            "Expression is nested deeper than 32 levels at position 33"
+        );
+    }
+
+    #[test]
+    fn unterminated_string_position() {
+        assert_eq!(message(r#""open"#), "Unterminated string at position 1");
+        assert_eq!(
+            message(r#"a and status:"done"#),
+            "Unterminated string at position 14"
+        );
+        assert_eq!(
+            message(r#""escaped \""#),
+            "Unterminated string at position 1"
+        );
+    }
+
+    #[test]
+    fn unknown_key_position() {
+        assert_eq!(
+            message("owner:me"),
+            "Unknown key `owner`, expected status, priority, deadline, text or blocked at \
+             position 1"
+        );
+        assert_eq!(
+            message("a and  owner:me"),
+            "Unknown key `owner`, expected status, priority, deadline, text or blocked at \
+             position 8"
+        );
+    }
+
+    #[test]
+    fn trailing_token_position() {
+        assert_eq!(
+            message("a b"),
+            "Expected `and`, `or` or end of expression, found `b` at position 3"
+        );
+        assert_eq!(
+            message("(a) )"),
+            "Expected `and`, `or` or end of expression, found `)` at position 5"
+        );
+        assert_eq!(
+            message(r#"a "b""#),
+            r#"Expected `and`, `or` or end of expression, found "b" at position 3"#
+        );
+    }
+
+    #[test]
+    fn other_error_positions() {
+        assert_eq!(message("a & b"), "Unexpected character `&` at position 3");
+        assert_eq!(
+            message("a and"),
+            "Expected a term, found end of expression at position 6"
+        );
+        assert_eq!(
+            message(""),
+            "Expected a term, found end of expression at position 1"
+        );
+        assert_eq!(
+            message("(a or b"),
+            "Expected `)` closing the one at position 1, found end of expression at position 8"
+        );
+        assert_eq!(
+            message("status:)"),
+            "Expected a value after `status:`, found `)` at position 8"
+        );
+    }
+
+    #[test]
+    fn positions_count_characters() {
+        assert_eq!(message("été &"), "Unexpected character `&` at position 5");
+    }
+}
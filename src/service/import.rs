@@ -0,0 +1,917 @@
+//! Parsers of task lists exported by other apps. Every format is read into
+//! rows of [`TaskCreateDto`], a row that doesn't map onto a task carries the
+//! reason instead.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, FixedOffset, NaiveDate, NaiveDateTime, TimeZone};
+use chrono_tz::Tz;
+use csv::{ReaderBuilder, StringRecord};
+use serde::Deserialize;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+use crate::{
+    constants,
+    dto::{
+        task::{TaskCreateDto, TaskDeadlineDto},
+        task_import::CsvMappingDto,
+    },
+    entity::{
+        prelude::WorkflowStatusModel,
+        sea_orm_active_enums::{DeadlineKind, ImportFormat, TaskPriority, TaskStatus},
+    },
+    error::service::{ServiceError, ServiceResult},
+};
+
+use super::{calendar::CalendarService, quick_add};
+
+/// What rows are resolved against.
+pub struct ImportContext {
+    pub time_zone: Tz,
+    /// Relative dates of Todoist are taken from this moment
+    pub now: DateTime<Tz>,
+    /// Workflow statuses of the user, status values are matched by name
+    pub statuses: Vec<WorkflowStatusModel>,
+}
+
+pub struct ImportRow {
+    /// Line of a CSV file, or position of the item in other formats
+    pub row: usize,
+    /// Identifies the row across imports of the same source
+    pub key: String,
+    pub task: Result<ImportTask, String>,
+}
+
+pub struct ImportTask {
+    pub body: TaskCreateDto,
+    /// Comments to add to the task, in order
+    pub comments: Vec<String>,
+}
+
+/// Reads `content` in `format`. Errors are about the file as a whole, such
+/// as a missing column or broken JSON.
+pub fn parse(
+    format: &ImportFormat,
+    content: &str,
+    mapping: &CsvMappingDto,
+    context: &ImportContext,
+) -> ServiceResult<Vec<ImportRow>> {
+    let rows: Vec<ImportRow> = match format {
+        ImportFormat::Csv => parse_csv(content, mapping, context)?,
+        ImportFormat::Todoist => parse_todoist(content, context)?,
+        ImportFormat::MicrosoftToDo => parse_microsoft_to_do(content)?,
+        ImportFormat::TaskFlow => parse_task_flow(content, context)?,
+        ImportFormat::Ical => parse_ical(content, context)?,
+    };
+
+    if rows.len() > constants::TASK_IMPORT_MAX_ROWS {
+        return Err(ServiceError::BadRequest(format!(
+            "More than {} rows",
+            constants::TASK_IMPORT_MAX_ROWS
+        )));
+    }
+
+    Ok(rows)
+}
+
+/// Header names tried for a field the mapping leaves unset.
+const NAME_HEADERS: [&str; 5] = ["name", "title", "task", "content", "subject"];
+const DESCRIPTION_HEADERS: [&str; 5] = ["description", "notes", "note", "body", "details"];
+const STATUS_HEADERS: [&str; 3] = ["status", "state", "completed"];
+const PRIORITY_HEADERS: [&str; 2] = ["priority", "importance"];
+const DEADLINE_HEADERS: [&str; 5] = ["deadline", "due", "due date", "due_date", "date"];
+const KEY_HEADERS: [&str; 2] = ["id", "uid"];
+
+fn parse_csv(
+    content: &str,
+    mapping: &CsvMappingDto,
+    context: &ImportContext,
+) -> ServiceResult<Vec<ImportRow>> {
+    let delimiter: u8 = match mapping.delimiter {
+        Some(value) if value.is_ascii() => value as u8,
+        Some(value) => {
+            return Err(ServiceError::BadRequest(format!(
+                "Delimiter '{value}' is not an ASCII character"
+            )))
+        }
+        None => b',',
+    };
+
+    let mut reader = ReaderBuilder::new()
+        .delimiter(delimiter)
+        .flexible(true)
+        .from_reader(content.trim_start_matches('\u{feff}').as_bytes());
+
+    let headers: StringRecord = reader.headers().map_err(invalid_csv)?.clone();
+    let column = |mapped: &Option<String>, defaults: &[&str]| -> ServiceResult<Option<usize>> {
+        match mapped {
+            Some(name) => match find_column(&headers, &[name.as_str()]) {
+                Some(index) => Ok(Some(index)),
+                None => Err(ServiceError::BadRequest(format!("Missing column '{name}'"))),
+            },
+            None => Ok(find_column(&headers, defaults)),
+        }
+    };
+
+    let name: usize = match column(&mapping.name, &NAME_HEADERS)? {
+        Some(value) => value,
+        None => {
+            return Err(ServiceError::BadRequest(
+                "No column holds the task name".to_string(),
+            ))
+        }
+    };
+    let description: Option<usize> = column(&mapping.description, &DESCRIPTION_HEADERS)?;
+    let status: Option<usize> = column(&mapping.status, &STATUS_HEADERS)?;
+    let priority: Option<usize> = column(&mapping.priority, &PRIORITY_HEADERS)?;
+    let deadline: Option<usize> = column(&mapping.deadline, &DEADLINE_HEADERS)?;
+    let key: Option<usize> = column(&mapping.key, &KEY_HEADERS)?;
+
+    let mut keys: KeyCounter = KeyCounter::default();
+    let mut rows: Vec<ImportRow> = Vec::new();
+
+    for record in reader.records() {
+        let record: StringRecord = record.map_err(invalid_csv)?;
+        let row: usize = record.position().map_or(0, |value| value.line() as usize);
+        let field = |index: Option<usize>| {
+            index
+                .and_then(|index| record.get(index))
+                .map(str::trim)
+                .unwrap_or_default()
+        };
+
+        if record.iter().all(|value| value.trim().is_empty()) {
+            continue;
+        }
+
+        let key: String = match field(key) {
+            "" => keys.hashed(
+                "csv",
+                &[
+                    field(Some(name)),
+                    field(description),
+                    field(status),
+                    field(priority),
+                    field(deadline),
+                ],
+            ),
+            value => format!("csv:{value}"),
+        };
+
+        let task: Result<ImportTask, String> = (|| {
+            let (category, status_id) = translate_status(field(status), context)?;
+
+            Ok(ImportTask {
+                body: task_body(
+                    field(Some(name)),
+                    field(description),
+                    category,
+                    status_id,
+                    translate_priority(field(priority))?,
+                    parse_deadline(field(deadline), &context.time_zone)?,
+                ),
+                comments: Vec::new(),
+            })
+        })();
+
+        rows.push(ImportRow { row, key, task });
+    }
+
+    Ok(rows)
+}
+
+/// Todoist CSV template: `task` rows, `note` rows commenting on the task
+/// above them and `section` rows, which have no counterpart and are skipped.
+fn parse_todoist(content: &str, context: &ImportContext) -> ServiceResult<Vec<ImportRow>> {
+    let mut reader = ReaderBuilder::new()
+        .flexible(true)
+        .from_reader(content.trim_start_matches('\u{feff}').as_bytes());
+
+    let headers: StringRecord = reader.headers().map_err(invalid_csv)?.clone();
+    let (kind, content, description, priority, date) = match (
+        find_column(&headers, &["type"]),
+        find_column(&headers, &["content"]),
+        find_column(&headers, &["description"]),
+        find_column(&headers, &["priority"]),
+        find_column(&headers, &["date"]),
+    ) {
+        (Some(kind), Some(content), description, priority, date) => {
+            (kind, content, description, priority, date)
+        }
+        _ => {
+            return Err(ServiceError::BadRequest(
+                "Expected the TYPE and CONTENT columns of a Todoist template".to_string(),
+            ))
+        }
+    };
+
+    let mut keys: KeyCounter = KeyCounter::default();
+    let mut rows: Vec<ImportRow> = Vec::new();
+
+    for record in reader.records() {
+        let record: StringRecord = record.map_err(invalid_csv)?;
+        let row: usize = record.position().map_or(0, |value| value.line() as usize);
+        let field = |index: Option<usize>| {
+            index
+                .and_then(|index| record.get(index))
+                .map(str::trim)
+                .unwrap_or_default()
+        };
+
+        match field(Some(kind)).to_ascii_lowercase().as_str() {
+            "task" => {}
+            "note" => {
+                if let Some(ImportRow { task: Ok(task), .. }) = rows.last_mut() {
+                    task.comments.push(field(Some(content)).to_string());
+                }
+                continue;
+            }
+            _ => continue,
+        }
+
+        let name: &str = field(Some(content));
+        let date: &str = field(date);
+        let key: String = keys.hashed("todoist", &[name, field(description), date]);
+
+        let task: Result<ImportTask, String> = (|| {
+            // Priority 1 is the most urgent, 4 is the default
+            let priority: TaskPriority = match field(priority) {
+                "1" | "2" => TaskPriority::Hight,
+                "" | "3" | "4" => TaskPriority::Normal,
+                value => return Err(format!("Unknown priority '{value}'")),
+            };
+
+            let mut description: String = field(description).to_string();
+            let deadline: Option<TaskDeadlineDto> = match parse_deadline(date, &context.time_zone) {
+                Ok(value) => value,
+                // Dates are written the way they were typed, such as `every monday`
+                Err(_) => match quick_add::parse(date, context.now) {
+                    Ok(parsed) if parsed.name.is_empty() => parsed.deadline,
+                    _ => {
+                        if !description.is_empty() {
+                            description.push_str("\n\n");
+                        }
+                        description.push_str(&format!("Due {date}"));
+                        None
+                    }
+                },
+            };
+
+            Ok(ImportTask {
+                body: task_body(name, &description, None, None, priority, deadline),
+                comments: Vec::new(),
+            })
+        })();
+
+        rows.push(ImportRow { row, key, task });
+    }
+
+    Ok(rows)
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct MicrosoftTask {
+    id: Option<String>,
+    #[serde(default)]
+    title: String,
+    status: Option<String>,
+    importance: Option<String>,
+    body: Option<MicrosoftBody>,
+    due_date_time: Option<MicrosoftDateTime>,
+    #[serde(default)]
+    checklist_items: Vec<MicrosoftChecklistItem>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct MicrosoftBody {
+    #[serde(default)]
+    content: String,
+    content_type: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct MicrosoftDateTime {
+    date_time: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct MicrosoftChecklistItem {
+    #[serde(default)]
+    display_name: String,
+    #[serde(default)]
+    is_checked: bool,
+}
+
+/// Microsoft Graph to-do tasks: a `value` page or a plain array, of tasks or
+/// of task lists holding them under `tasks`.
+fn parse_microsoft_to_do(content: &str) -> ServiceResult<Vec<ImportRow>> {
+    let document: Value = serde_json::from_str(content).map_err(invalid_json)?;
+
+    let mut items: Vec<Value> = Vec::new();
+    for item in json_items(document)? {
+        match item.get("tasks").cloned() {
+            Some(tasks) => items.extend(json_items(tasks)?),
+            None => items.push(item),
+        }
+    }
+
+    let mut keys: KeyCounter = KeyCounter::default();
+    let mut rows: Vec<ImportRow> = Vec::new();
+
+    for (index, item) in items.into_iter().enumerate() {
+        let item: MicrosoftTask = match serde_json::from_value::<MicrosoftTask>(item) {
+            Ok(value) => value,
+            Err(err) => {
+                rows.push(ImportRow {
+                    row: index + 1,
+                    key: format!("microsoft_to_do:#{}", index + 1),
+                    task: Err(format!("Invalid task: {err}")),
+                });
+                continue;
+            }
+        };
+
+        let mut description: String = match item.body {
+            Some(body) if body.content_type.as_deref() == Some("html") => strip_html(&body.content),
+            Some(body) => body.content,
+            None => String::new(),
+        }
+        .trim()
+        .to_string();
+
+        if !item.checklist_items.is_empty() {
+            if !description.is_empty() {
+                description.push_str("\n\n");
+            }
+            for checklist in item.checklist_items.iter() {
+                let mark: char = match checklist.is_checked {
+                    true => 'x',
+                    false => ' ',
+                };
+                description.push_str(&format!("- [{mark}] {}\n", checklist.display_name));
+            }
+            description.truncate(description.trim_end().len());
+        }
+
+        let key: String = match &item.id {
+            Some(value) => format!("microsoft_to_do:{value}"),
+            None => keys.hashed("microsoft_to_do", &[&item.title, &description]),
+        };
+
+        let task: Result<ImportTask, String> = (|| {
+            let status: TaskStatus = match item.status.as_deref() {
+                None | Some("notStarted") => TaskStatus::ToDo,
+                Some("inProgress" | "waitingOnOthers" | "deferred") => TaskStatus::InProgress,
+                Some("completed") => TaskStatus::Done,
+                Some(value) => return Err(format!("Unknown status '{value}'")),
+            };
+            let priority: TaskPriority = match item.importance.as_deref() {
+                Some("high") => TaskPriority::Hight,
+                None | Some("normal") => TaskPriority::Normal,
+                Some("low") => TaskPriority::Low,
+                Some(value) => return Err(format!("Unknown importance '{value}'")),
+            };
+            // Due dates are whole days, stored as midnight of the list's zone
+            let deadline: Option<TaskDeadlineDto> = match &item.due_date_time {
+                Some(value) => Some(TaskDeadlineDto::Date(
+                    value
+                        .date_time
+                        .get(..10)
+                        .and_then(|value| value.parse::<NaiveDate>().ok())
+                        .ok_or(format!("Invalid due date '{}'", value.date_time))?,
+                )),
+                None => None,
+            };
+
+            Ok(ImportTask {
+                body: task_body(
+                    item.title.trim(),
+                    &description,
+                    Some(status),
+                    None,
+                    priority,
+                    deadline,
+                ),
+                comments: Vec::new(),
+            })
+        })();
+
+        rows.push(ImportRow {
+            row: index + 1,
+            key,
+            task,
+        });
+    }
+
+    Ok(rows)
+}
+
+#[derive(Deserialize)]
+struct TaskFlowTask {
+    id: Uuid,
+    name: String,
+    description: String,
+    status: TaskStatus,
+    status_id: Uuid,
+    deadline: Option<String>,
+    deadline_kind: Option<DeadlineKind>,
+    priority: TaskPriority,
+    deleted_at: Option<String>,
+}
+
+/// `tasks.json` of an account export. Trashed tasks stay behind, statuses are
+/// kept when the user has them and fall back to their category otherwise.
+fn parse_task_flow(content: &str, context: &ImportContext) -> ServiceResult<Vec<ImportRow>> {
+    let document: Value = serde_json::from_str(content).map_err(invalid_json)?;
+
+    let mut rows: Vec<ImportRow> = Vec::new();
+
+    for (index, item) in json_items(document)?.into_iter().enumerate() {
+        let item: TaskFlowTask = match serde_json::from_value::<TaskFlowTask>(item) {
+            Ok(value) => value,
+            Err(err) => {
+                rows.push(ImportRow {
+                    row: index + 1,
+                    key: format!("task_flow:#{}", index + 1),
+                    task: Err(format!("Invalid task: {err}")),
+                });
+                continue;
+            }
+        };
+
+        if item.deleted_at.is_some() {
+            continue;
+        }
+
+        let task: Result<ImportTask, String> = (|| {
+            let deadline: Option<TaskDeadlineDto> = match (&item.deadline, item.deadline_kind) {
+                (Some(value), Some(DeadlineKind::Date)) => Some(TaskDeadlineDto::Date(
+                    value
+                        .parse::<NaiveDate>()
+                        .map_err(|_| format!("Invalid deadline '{value}'"))?,
+                )),
+                (Some(value), _) => Some(TaskDeadlineDto::Instant(
+                    DateTime::parse_from_rfc3339(value)
+                        .map_err(|_| format!("Invalid deadline '{value}'"))?,
+                )),
+                (None, _) => None,
+            };
+            let status_id: Option<Uuid> = context
+                .statuses
+                .iter()
+                .any(|status| status.id == item.status_id)
+                .then_some(item.status_id);
+
+            Ok(ImportTask {
+                body: TaskCreateDto {
+                    name: item.name,
+                    description: item.description,
+                    status: Some(item.status),
+                    status_id,
+                    deadline,
+                    priority: Some(item.priority),
+                    custom_fields: None,
+                },
+                comments: Vec::new(),
+            })
+        })();
+
+        rows.push(ImportRow {
+            row: index + 1,
+            key: format!("task_flow:{}", item.id),
+            task,
+        });
+    }
+
+    Ok(rows)
+}
+
+fn parse_ical(content: &str, context: &ImportContext) -> ServiceResult<Vec<ImportRow>> {
+    let todos = CalendarService::parse_todos(content, &context.time_zone)
+        .map_err(|_| ServiceError::BadRequest("Invalid iCalendar data".to_string()))?;
+
+    let mut keys: KeyCounter = KeyCounter::default();
+    let mut rows: Vec<ImportRow> = Vec::new();
+
+    for (index, todo) in todos.into_iter().enumerate() {
+        let summary: String = todo.summary.unwrap_or_default();
+        let description: String = todo.description.unwrap_or_default();
+
+        let key: String = match &todo.uid {
+            Some(value) => format!("ical:{value}"),
+            None => keys.hashed("ical", &[&summary, &description]),
+        };
+
+        let task: Result<ImportTask, String> = match summary.trim().is_empty() {
+            true => Err("Missing SUMMARY".to_string()),
+            false => Ok(ImportTask {
+                body: task_body(
+                    summary.trim(),
+                    description.trim(),
+                    todo.status,
+                    None,
+                    todo.priority.unwrap_or(TaskPriority::Normal),
+                    todo.deadline,
+                ),
+                comments: Vec::new(),
+            }),
+        };
+
+        rows.push(ImportRow {
+            row: index + 1,
+            key,
+            task,
+        });
+    }
+
+    Ok(rows)
+}
+
+/// Task of a row, a task without a description gets its name as one.
+fn task_body(
+    name: &str,
+    description: &str,
+    status: Option<TaskStatus>,
+    status_id: Option<Uuid>,
+    priority: TaskPriority,
+    deadline: Option<TaskDeadlineDto>,
+) -> TaskCreateDto {
+    TaskCreateDto {
+        name: name.to_string(),
+        description: match description.is_empty() {
+            true => name.to_string(),
+            false => description.to_string(),
+        },
+        status,
+        status_id,
+        deadline,
+        priority: Some(priority),
+        custom_fields: None,
+    }
+}
+
+/// Resolves a status value to one of the user's statuses by name, or to a
+/// category by the words apps use for it.
+fn translate_status(
+    value: &str,
+    context: &ImportContext,
+) -> Result<(Option<TaskStatus>, Option<Uuid>), String> {
+    if value.is_empty() {
+        return Ok((None, None));
+    }
+
+    if let Some(status) = context
+        .statuses
+        .iter()
+        .find(|status| status.name.trim().eq_ignore_ascii_case(value))
+    {
+        return Ok((Some(status.category.clone()), Some(status.id)));
+    }
+
+    match normalize(value).as_str() {
+        "todo" | "open" | "new" | "notstarted" | "needsaction" | "backlog" | "pending"
+        | "incomplete" | "false" | "no" | "0" => Ok((Some(TaskStatus::ToDo), None)),
+        "inprogress" | "inprocess" | "doing" | "started" | "active" | "waitingonothers"
+        | "deferred" => Ok((Some(TaskStatus::InProgress), None)),
+        "done" | "completed" | "complete" | "closed" | "finished" | "cancelled" | "canceled"
+        | "true" | "yes" | "x" | "1" => Ok((Some(TaskStatus::Done), None)),
+        _ => Err(format!("Unknown status '{value}'")),
+    }
+}
+
+fn translate_priority(value: &str) -> Result<TaskPriority, String> {
+    match normalize(value).as_str() {
+        "high" | "hight" | "urgent" | "important" | "1" => Ok(TaskPriority::Hight),
+        "" | "normal" | "medium" | "none" | "2" => Ok(TaskPriority::Normal),
+        "low" | "3" => Ok(TaskPriority::Low),
+        _ => Err(format!("Unknown priority '{value}'")),
+    }
+}
+
+/// Reads an RFC 3339 time, a time in `time_zone` or an all-day date.
+fn parse_deadline(value: &str, time_zone: &Tz) -> Result<Option<TaskDeadlineDto>, String> {
+    if value.is_empty() {
+        return Ok(None);
+    }
+
+    if let Ok(value) = DateTime::parse_from_rfc3339(value) {
+        return Ok(Some(TaskDeadlineDto::Instant(value)));
+    }
+    if let Ok(value) = value.parse::<NaiveDate>() {
+        return Ok(Some(TaskDeadlineDto::Date(value)));
+    }
+
+    for pattern in [
+        "%Y-%m-%d %H:%M:%S",
+        "%Y-%m-%d %H:%M",
+        "%Y-%m-%dT%H:%M:%S%.f",
+        "%Y-%m-%dT%H:%M",
+    ] {
+        if let Ok(local) = NaiveDateTime::parse_from_str(value, pattern) {
+            // Times skipped by a DST change are taken an hour later
+            let moment: Option<DateTime<FixedOffset>> = time_zone
+                .from_local_datetime(&local)
+                .earliest()
+                .or_else(|| {
+                    time_zone
+                        .from_local_datetime(&(local + chrono::TimeDelta::hours(1)))
+                        .earliest()
+                })
+                .map(|value| value.fixed_offset());
+
+            return moment
+                .map(|value| Some(TaskDeadlineDto::Instant(value)))
+                .ok_or(format!("Invalid deadline '{value}'"));
+        }
+    }
+
+    Err(format!("Invalid deadline '{value}'"))
+}
+
+/// Keys of rows without an id of their own, made from their content. Rows
+/// that read the same are numbered in order, so each one keeps its key.
+#[derive(Default)]
+struct KeyCounter {
+    seen: HashMap<String, usize>,
+}
+
+impl KeyCounter {
+    fn hashed(&mut self, prefix: &str, fields: &[&str]) -> String {
+        let mut hasher = Sha256::new();
+        for field in fields {
+            hasher.update(field.as_bytes());
+            hasher.update([0]);
+        }
+        let hash: String = hasher
+            .finalize()
+            .iter()
+            .take(16)
+            .map(|byte| format!("{byte:02x}"))
+            .collect::<String>();
+
+        let count: &mut usize = self.seen.entry(hash.clone()).or_default();
+        *count += 1;
+
+        match count {
+            1 => format!("{prefix}:{hash}"),
+            _ => format!("{prefix}:{hash}-{count}"),
+        }
+    }
+}
+
+fn find_column(headers: &StringRecord, names: &[&str]) -> Option<usize> {
+    names.iter().find_map(|name| {
+        headers
+            .iter()
+            .position(|header| header.trim().eq_ignore_ascii_case(name.trim()))
+    })
+}
+
+/// Lowercase letters and digits of `value`, so `In progress` and
+/// `in_progress` read the same.
+fn normalize(value: &str) -> String {
+    value
+        .chars()
+        .filter(char::is_ascii_alphanumeric)
+        .map(|char| char.to_ascii_lowercase())
+        .collect::<String>()
+}
+
+/// Items of a JSON array, or of the `value` array of a Graph page.
+fn json_items(document: Value) -> ServiceResult<Vec<Value>> {
+    match document {
+        Value::Array(items) => Ok(items),
+        Value::Object(mut object) => match object.remove("value") {
+            Some(Value::Array(items)) => Ok(items),
+            _ => Err(ServiceError::BadRequest(
+                "Expected an array of tasks".to_string(),
+            )),
+        },
+        _ => Err(ServiceError::BadRequest(
+            "Expected an array of tasks".to_string(),
+        )),
+    }
+}
+
+/// Text of an HTML body, tags dropped and common entities decoded.
+fn strip_html(value: &str) -> String {
+    let mut text: String = String::new();
+    let mut tag: bool = false;
+
+    for char in value.chars() {
+        match char {
+            '<' => tag = true,
+            '>' if tag => tag = false,
+            _ if !tag => text.push(char),
+            _ => {}
+        }
+    }
+
+    text.replace("&nbsp;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
+}
+
+fn invalid_csv(err: csv::Error) -> ServiceError {
+    ServiceError::BadRequest(format!("Invalid CSV: {err}"))
+}
+
+fn invalid_json(err: serde_json::Error) -> ServiceError {
+    ServiceError::BadRequest(format!("Invalid JSON: {err}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn context() -> ImportContext {
+        let time_zone: Tz = "Europe/Moscow".parse::<Tz>().unwrap();
+        let created_at: DateTime<FixedOffset> =
+            DateTime::parse_from_rfc3339("2026-10-19T14:00:00+03:00").unwrap();
+
+        ImportContext {
+            time_zone,
+            now: created_at.with_timezone(&time_zone),
+            statuses: vec![WorkflowStatusModel {
+                id: Uuid::nil(),
+                user_id: Uuid::nil(),
+                name: "Review".to_string(),
+                category: TaskStatus::InProgress,
+                position: 1,
+                updated_at: created_at,
+                created_at,
+            }],
+        }
+    }
+
+    fn body(row: &ImportRow) -> &TaskCreateDto {
+        &row.task.as_ref().unwrap().body
+    }
+
+    #[test]
+    fn csv_with_default_headers() {
+        let rows: Vec<ImportRow> = parse(
+            &ImportFormat::Csv,
+            "Title,Notes,Status,Priority,Due date\n\
+             Buy milk,,done,high,2026-10-20\n\
+             Call Bob,About the trip,Review,,2026-10-20 18:30\n",
+            &CsvMappingDto::default(),
+            &context(),
+        )
+        .unwrap();
+
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].row, 2);
+        assert_eq!(body(&rows[0]).name, "Buy milk");
+        assert_eq!(body(&rows[0]).description, "Buy milk");
+        assert_eq!(body(&rows[0]).status, Some(TaskStatus::Done));
+        assert_eq!(body(&rows[0]).priority, Some(TaskPriority::Hight));
+        assert_eq!(
+            body(&rows[0]).deadline,
+            Some(TaskDeadlineDto::Date("2026-10-20".parse().unwrap()))
+        );
+        assert_eq!(body(&rows[1]).status_id, Some(Uuid::nil()));
+        assert_eq!(
+            body(&rows[1]).deadline,
+            Some(TaskDeadlineDto::Instant(
+                DateTime::parse_from_rfc3339("2026-10-20T18:30:00+03:00").unwrap()
+            ))
+        );
+    }
+
+    #[test]
+    fn csv_with_mapping_and_bad_rows() {
+        let mapping: CsvMappingDto = CsvMappingDto {
+            name: Some("What".to_string()),
+            key: Some("Ref".to_string()),
+            delimiter: Some(';'),
+            ..Default::default()
+        };
+        let rows: Vec<ImportRow> = parse(
+            &ImportFormat::Csv,
+            "Ref;What;Priority\n7;Write report;urgent\n8;Plan trip;whenever\n",
+            &mapping,
+            &context(),
+        )
+        .unwrap();
+
+        assert_eq!(rows[0].key, "csv:7");
+        assert_eq!(body(&rows[0]).priority, Some(TaskPriority::Hight));
+        assert_eq!(
+            rows[1].task.as_ref().err().unwrap(),
+            "Unknown priority 'whenever'"
+        );
+
+        let missing = parse(&ImportFormat::Csv, "Ref;Name\n", &mapping, &context());
+        assert!(missing.is_err());
+    }
+
+    #[test]
+    fn csv_keys_are_stable() {
+        let content: &str = "name\nBuy milk\nBuy milk\n";
+        let first: Vec<ImportRow> = parse(
+            &ImportFormat::Csv,
+            content,
+            &CsvMappingDto::default(),
+            &context(),
+        )
+        .unwrap();
+        let second: Vec<ImportRow> = parse(
+            &ImportFormat::Csv,
+            content,
+            &CsvMappingDto::default(),
+            &context(),
+        )
+        .unwrap();
+
+        assert_ne!(first[0].key, first[1].key);
+        assert_eq!(first[0].key, second[0].key);
+        assert_eq!(first[1].key, second[1].key);
+    }
+
+    #[test]
+    fn todoist_template() {
+        let rows: Vec<ImportRow> = parse(
+            &ImportFormat::Todoist,
+            "TYPE,CONTENT,DESCRIPTION,PRIORITY,INDENT,AUTHOR,RESPONSIBLE,DATE,DATE_LANG,TIMEZONE\n\
+             section,Errands,,,,,,,,\n\
+             task,Buy milk,,1,1,,,tomorrow,en,\n\
+             note,Oat milk please,,,,,,,,\n\
+             task,Water plants,,4,1,,,every monday,en,\n",
+            &CsvMappingDto::default(),
+            &context(),
+        )
+        .unwrap();
+
+        assert_eq!(rows.len(), 2);
+        assert_eq!(body(&rows[0]).priority, Some(TaskPriority::Hight));
+        assert_eq!(
+            body(&rows[0]).deadline,
+            Some(TaskDeadlineDto::Date("2026-10-20".parse().unwrap()))
+        );
+        assert_eq!(
+            rows[0].task.as_ref().unwrap().comments,
+            vec!["Oat milk please".to_string()]
+        );
+        assert_eq!(body(&rows[1]).deadline, None);
+        assert_eq!(body(&rows[1]).description, "Due every monday");
+    }
+
+    #[test]
+    fn microsoft_to_do_lists() {
+        let rows: Vec<ImportRow> = parse(
+            &ImportFormat::MicrosoftToDo,
+            r#"{"value": [{"displayName": "Tasks", "tasks": [{
+                "id": "AAMk",
+                "title": "Pay rent",
+                "status": "completed",
+                "importance": "high",
+                "body": {"content": "<p>Before the 5th</p>", "contentType": "html"},
+                "dueDateTime": {"dateTime": "2026-11-01T00:00:00.0000000", "timeZone": "UTC"},
+                "checklistItems": [{"displayName": "Transfer", "isChecked": true}]
+            }]}]}"#,
+            &CsvMappingDto::default(),
+            &context(),
+        )
+        .unwrap();
+
+        assert_eq!(rows[0].key, "microsoft_to_do:AAMk");
+        assert_eq!(body(&rows[0]).status, Some(TaskStatus::Done));
+        assert_eq!(body(&rows[0]).priority, Some(TaskPriority::Hight));
+        assert_eq!(
+            body(&rows[0]).description,
+            "Before the 5th\n\n- [x] Transfer"
+        );
+        assert_eq!(
+            body(&rows[0]).deadline,
+            Some(TaskDeadlineDto::Date("2026-11-01".parse().unwrap()))
+        );
+    }
+
+    #[test]
+    fn ical_todos() {
+        let rows: Vec<ImportRow> = parse(
+            &ImportFormat::Ical,
+            "BEGIN:VCALENDAR\r\nVERSION:2.0\r\n\
+             BEGIN:VTODO\r\nUID:one@example.com\r\nSUMMARY:Renew passport\r\nPRIORITY:9\r\nEND:VTODO\r\n\
+             BEGIN:VTODO\r\nUID:two@example.com\r\nEND:VTODO\r\n\
+             END:VCALENDAR\r\n",
+            &CsvMappingDto::default(),
+            &context(),
+        )
+        .unwrap();
+
+        assert_eq!(rows[0].key, "ical:one@example.com");
+        assert_eq!(body(&rows[0]).priority, Some(TaskPriority::Low));
+        assert_eq!(rows[1].task.as_ref().err().unwrap(), "Missing SUMMARY");
+    }
+}
@@ -1,6 +1,26 @@
+pub mod account_deletion;
+pub mod account_export;
+pub mod app_password;
+pub mod attachment;
 pub mod auth;
+pub mod avatar_image;
+pub mod caldav;
+pub mod calendar;
 pub mod common;
+pub mod custom_field;
+pub mod filter;
+pub mod import;
+pub mod quick_add;
+pub mod rank;
+pub mod saved_filter;
+pub mod storage;
+pub mod sync;
 pub mod task;
 pub mod task_comment;
+pub mod task_dependency;
+pub mod task_event;
+pub mod task_import;
 pub mod user;
 pub mod user_avatar;
+pub mod user_preference;
+pub mod workflow_status;
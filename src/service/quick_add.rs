@@ -0,0 +1,718 @@
+//! Parser of single line task entries such as
+//! `Pay rent tomorrow 9am !high // ask about the deposit`.
+//!
+//! Recognized parts are removed from the line and the remaining words become
+//! the task name:
+//!
+//! - priority: `!high`, `!h`, `!!!`, `!normal`, `!n`, `!!`, `!low`, `!l`
+//! - date: `today`, `tomorrow`, weekdays with an optional `next`, `next week`,
+//!   `next month`, `2024-10-15`, `oct 15` or `15 oct`, and `in N days` or
+//!   `in N weeks`
+//! - time: `9am`, `9:30pm`, `21:00`, `9 pm`, `noon`, `midnight`, or `in N
+//!   hours` and `in N minutes` for an exact moment
+//! - `on`, `by`, `at` and `due` right before a date or time
+//! - everything after a `//` starting a word is the description, so links
+//!   stay in the name
+//!
+//! Only the first date and the first time are taken, later ones stay in the
+//! name. A date without a time is an all-day deadline. A negative count such
+//! as `in -3 days` is an error.
+
+use chrono::{
+    DateTime, Datelike, Days, Duration, FixedOffset, Months, NaiveDate, NaiveTime, TimeZone,
+    Weekday,
+};
+
+use crate::{
+    dto::task::TaskDeadlineDto,
+    entity::sea_orm_active_enums::TaskPriority,
+    error::service::{ServiceError, ServiceResult},
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct QuickAdd {
+    pub name: String,
+    pub description: Option<String>,
+    pub priority: Option<TaskPriority>,
+    pub deadline: Option<TaskDeadlineDto>,
+}
+
+/// Parses `input` with relative dates resolved against `now` in its time
+/// zone.
+pub fn parse<Tz: TimeZone>(input: &str, now: DateTime<Tz>) -> ServiceResult<QuickAdd> {
+    let (line, description) = match split_description(input) {
+        Some((line, description)) if !description.trim().is_empty() => {
+            (line, Some(description.trim().to_string()))
+        }
+        Some((line, _)) => (line, None),
+        None => (input, None),
+    };
+
+    let words: Vec<&str> = line.split_whitespace().collect();
+    let mut used: Vec<bool> = vec![false; words.len()];
+
+    let mut priority: Option<TaskPriority> = None;
+    let mut date: Option<NaiveDate> = None;
+    let mut time: Option<NaiveTime> = None;
+    let mut moment: Option<DateTime<FixedOffset>> = None;
+
+    let today: NaiveDate = now.date_naive();
+    let mut index: usize = 0;
+
+    while index < words.len() {
+        if priority.is_none() {
+            if let Some(value) = parse_priority(words[index]) {
+                priority = Some(value);
+                used[index] = true;
+                index += 1;
+                continue;
+            }
+        }
+
+        // A preposition is dropped only together with what it introduces
+        let start: usize = match is_preposition(words[index]) && index + 1 < words.len() {
+            true => index + 1,
+            false => index,
+        };
+        let rest: Vec<String> = words[start..].iter().map(|word| normalize(word)).collect();
+
+        let matched: Option<usize> = if moment.is_none() && date.is_none() && time.is_none() {
+            parse_moment(&rest, &now)?.map(|(value, length)| {
+                moment = Some(value);
+                length
+            })
+        } else {
+            None
+        }
+        .or_else(|| match (date, moment) {
+            (None, None) => parse_date(&rest, today, start > index).map(|(value, length)| {
+                date = Some(value);
+                length
+            }),
+            _ => None,
+        })
+        .or_else(|| match (time, moment) {
+            (None, None) => parse_time(&rest).map(|(value, length)| {
+                time = Some(value);
+                length
+            }),
+            _ => None,
+        });
+
+        match matched {
+            Some(length) => {
+                for flag in used.iter_mut().skip(index).take(start - index + length) {
+                    *flag = true;
+                }
+                index = start + length;
+            }
+            None => index += 1,
+        }
+    }
+
+    let deadline: Option<TaskDeadlineDto> = match (moment, date, time) {
+        (Some(value), _, _) => Some(TaskDeadlineDto::Instant(value)),
+        (None, Some(date), None) => Some(TaskDeadlineDto::Date(date)),
+        (None, Some(date), Some(time)) => at(&now, date, time).map(TaskDeadlineDto::Instant),
+        (None, None, Some(time)) => {
+            // A time alone is the next time the clock shows it
+            let date: NaiveDate = match time > now.time() {
+                true => today,
+                false => today + Days::new(1),
+            };
+            at(&now, date, time).map(TaskDeadlineDto::Instant)
+        }
+        (None, None, None) => None,
+    };
+
+    let name: String = words
+        .iter()
+        .zip(used.iter())
+        .filter(|(_, used)| !**used)
+        .map(|(word, _)| *word)
+        .collect::<Vec<&str>>()
+        .join(" ");
+
+    Ok(QuickAdd {
+        name,
+        description,
+        priority,
+        deadline,
+    })
+}
+
+/// Splits `input` at the first `//` that starts a word, the `//` of a link
+/// such as `https://example.com` is kept.
+fn split_description(input: &str) -> Option<(&str, &str)> {
+    let mut previous: Option<char> = None;
+
+    for (index, value) in input.char_indices() {
+        let starts_word: bool = match previous {
+            Some(previous) => previous.is_whitespace(),
+            None => true,
+        };
+
+        if starts_word && input[index..].starts_with("//") {
+            return Some((&input[..index], &input[index + 2..]));
+        }
+
+        previous = Some(value);
+    }
+
+    None
+}
+
+fn parse_priority(word: &str) -> Option<TaskPriority> {
+    match word.to_lowercase().as_str() {
+        "!high" | "!hight" | "!h" | "!!!" => Some(TaskPriority::Hight),
+        "!normal" | "!n" | "!medium" | "!!" => Some(TaskPriority::Normal),
+        "!low" | "!l" => Some(TaskPriority::Low),
+        _ => None,
+    }
+}
+
+fn is_preposition(word: &str) -> bool {
+    matches!(normalize(word).as_str(), "on" | "by" | "at" | "due")
+}
+
+/// Lowercases a word and drops punctuation that may follow it in a sentence.
+fn normalize(word: &str) -> String {
+    word.trim_end_matches([',', '.', ';', '!', '?'])
+        .to_lowercase()
+}
+
+/// Exact moments: `in N hours` or `in N minutes`. A negative count is an
+/// error for these and for `in N days` and `in N weeks` alike.
+fn parse_moment<Tz: TimeZone>(
+    words: &[String],
+    now: &DateTime<Tz>,
+) -> ServiceResult<Option<(DateTime<FixedOffset>, usize)>> {
+    let [first, count, unit, ..] = words else {
+        return Ok(None);
+    };
+    if first != "in" {
+        return Ok(None);
+    }
+
+    let count: i64 = match count.parse::<i64>() {
+        Ok(value) if value <= 10_000 => value,
+        _ => return Ok(None),
+    };
+
+    let duration: Option<fn(i64) -> Option<Duration>> = match unit.as_str() {
+        "hour" | "hours" | "h" | "hr" | "hrs" => Some(Duration::try_hours),
+        "minute" | "minutes" | "min" | "mins" => Some(Duration::try_minutes),
+        // Whole days are dates, see `parse_date`
+        "day" | "days" | "d" | "week" | "weeks" | "w" => None,
+        _ => return Ok(None),
+    };
+
+    if count < 0 {
+        return Err(ServiceError::BadRequest(format!(
+            "Count of `in {count} {unit}` must not be negative"
+        )));
+    }
+
+    Ok(duration
+        .and_then(|duration| duration(count))
+        .map(|value| (now.fixed_offset() + value, 3)))
+}
+
+/// `introduced` tells whether a preposition comes before the words.
+fn parse_date(words: &[String], today: NaiveDate, introduced: bool) -> Option<(NaiveDate, usize)> {
+    let first: &str = words.first()?;
+    let second: Option<&str> = words.get(1).map(|value| value.as_str());
+
+    match first {
+        "today" => return Some((today, 1)),
+        "tomorrow" | "tmr" | "tmrw" => return Some((today + Days::new(1), 1)),
+        _ => {}
+    }
+
+    if first == "next" {
+        match second? {
+            "week" => return Some((today + Days::new(7), 2)),
+            "month" => return Some((today.checked_add_months(Months::new(1))?, 2)),
+            value => {
+                // `next friday` skips the coming one when it is in this week
+                let weekday: Weekday = parse_weekday(value, true)?;
+                let date: NaiveDate = next_weekday(today, weekday);
+
+                let date: NaiveDate = match date.iso_week() == today.iso_week() {
+                    true => date + Days::new(7),
+                    false => date,
+                };
+
+                return Some((date, 2));
+            }
+        }
+    }
+
+    if first == "in" {
+        let count: u64 = words
+            .get(1)?
+            .parse::<u64>()
+            .ok()
+            .filter(|value| *value <= 3650)?;
+
+        let days: u64 = match words.get(2)?.as_str() {
+            "day" | "days" | "d" => count,
+            "week" | "weeks" | "w" => count * 7,
+            _ => return None,
+        };
+
+        return Some((today + Days::new(days), 3));
+    }
+
+    if let Some(weekday) = parse_weekday(first, introduced) {
+        return Some((next_weekday(today, weekday), 1));
+    }
+
+    if let Ok(date) = NaiveDate::parse_from_str(first, "%Y-%m-%d") {
+        return Some((date, 1));
+    }
+
+    // `oct 15` or `15 oct`, this year unless the day has passed
+    let (month, day) = match (parse_month(first), second.and_then(parse_day)) {
+        (Some(month), Some(day)) => (month, day),
+        _ => match (parse_day(first), second.and_then(parse_month)) {
+            (Some(day), Some(month)) => (month, day),
+            _ => return None,
+        },
+    };
+
+    let date: NaiveDate = NaiveDate::from_ymd_opt(today.year(), month, day)?;
+
+    match date < today {
+        true => Some((NaiveDate::from_ymd_opt(today.year() + 1, month, day)?, 2)),
+        false => Some((date, 2)),
+    }
+}
+
+fn parse_time(words: &[String]) -> Option<(NaiveTime, usize)> {
+    let first: &str = words.first()?;
+
+    match first {
+        "noon" => return Some((NaiveTime::from_hms_opt(12, 0, 0)?, 1)),
+        "midnight" => return Some((NaiveTime::MIN, 1)),
+        _ => {}
+    }
+
+    // `9 am` written apart
+    if let Some(meridiem @ ("am" | "pm")) = words.get(1).map(|value| value.as_str()) {
+        if let Some(time) = clock(first, Some(meridiem)) {
+            return Some((time, 2));
+        }
+    }
+
+    for meridiem in ["am", "pm"] {
+        if let Some(value) = first.strip_suffix(meridiem) {
+            return clock(value, Some(meridiem)).map(|time| (time, 1));
+        }
+    }
+
+    // A bare number is not a time, `21:00` is
+    match first.contains(':') {
+        true => clock(first, None).map(|time| (time, 1)),
+        false => None,
+    }
+}
+
+/// Reads `9`, `9:30` or `21:00`, with `am` or `pm` the hour is 1 to 12.
+fn clock(value: &str, meridiem: Option<&str>) -> Option<NaiveTime> {
+    let (hour, minute) = match value.split_once(':') {
+        Some((hour, minute)) if minute.len() == 2 => (hour, minute.parse::<u32>().ok()?),
+        Some(_) => return None,
+        None => (value, 0),
+    };
+
+    if hour.is_empty() || hour.len() > 2 {
+        return None;
+    }
+    let hour: u32 = hour.parse::<u32>().ok()?;
+
+    let hour: u32 = match meridiem {
+        Some(_) if !(1..=12).contains(&hour) => return None,
+        Some("am") => hour % 12,
+        Some(_) => hour % 12 + 12,
+        None => hour,
+    };
+
+    NaiveTime::from_hms_opt(hour, minute, 0)
+}
+
+/// Short names like `sun` or `sat` are common words, they count only when
+/// `short` is allowed.
+fn parse_weekday(value: &str, short: bool) -> Option<Weekday> {
+    let weekday: Weekday = match value {
+        "monday" | "mon" => Weekday::Mon,
+        "tuesday" | "tue" | "tues" => Weekday::Tue,
+        "wednesday" | "wed" => Weekday::Wed,
+        "thursday" | "thu" | "thurs" => Weekday::Thu,
+        "friday" | "fri" => Weekday::Fri,
+        "saturday" | "sat" => Weekday::Sat,
+        "sunday" | "sun" => Weekday::Sun,
+        _ => return None,
+    };
+
+    match short || value.ends_with("day") {
+        true => Some(weekday),
+        false => None,
+    }
+}
+
+fn parse_month(value: &str) -> Option<u32> {
+    let months: [&str; 12] = [
+        "january",
+        "february",
+        "march",
+        "april",
+        "may",
+        "june",
+        "july",
+        "august",
+        "september",
+        "october",
+        "november",
+        "december",
+    ];
+
+    months
+        .iter()
+        .position(|month| value == *month || (value.len() == 3 && month.starts_with(value)))
+        .map(|index| index as u32 + 1)
+}
+
+fn parse_day(value: &str) -> Option<u32> {
+    let value: &str = ["st", "nd", "rd", "th"]
+        .iter()
+        .find_map(|suffix| value.strip_suffix(suffix))
+        .unwrap_or(value);
+
+    match value.len() <= 2 {
+        true => value
+            .parse::<u32>()
+            .ok()
+            .filter(|day| (1..=31).contains(day)),
+        false => None,
+    }
+}
+
+/// The first day after `today` falling on `weekday`.
+fn next_weekday(today: NaiveDate, weekday: Weekday) -> NaiveDate {
+    let ahead: u32 =
+        (weekday.num_days_from_monday() + 7 - today.weekday().num_days_from_monday()) % 7;
+
+    today
+        + Days::new(match ahead {
+            0 => 7,
+            value => value as u64,
+        })
+}
+
+/// Moment `time` on `date` in the zone of `now`, the earlier one when the
+/// clock shows it twice.
+fn at<Tz: TimeZone>(
+    now: &DateTime<Tz>,
+    date: NaiveDate,
+    time: NaiveTime,
+) -> Option<DateTime<FixedOffset>> {
+    now.timezone()
+        .from_local_datetime(&date.and_time(time))
+        .earliest()
+        .map(|value| value.fixed_offset())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse<Zone: TimeZone>(input: &str, now: DateTime<Zone>) -> QuickAdd {
+        super::parse(input, now).unwrap()
+    }
+
+    /// Monday 2026-10-19 14:00 at UTC+3.
+    fn now() -> DateTime<FixedOffset> {
+        DateTime::parse_from_rfc3339("2026-10-19T14:00:00+03:00").unwrap()
+    }
+
+    fn deadline(value: &str) -> Option<TaskDeadlineDto> {
+        Some(TaskDeadlineDto::Instant(
+            DateTime::parse_from_rfc3339(value).unwrap(),
+        ))
+    }
+
+    fn day(value: &str) -> Option<TaskDeadlineDto> {
+        Some(TaskDeadlineDto::Date(value.parse::<NaiveDate>().unwrap()))
+    }
+
+    #[test]
+    fn plain_text_is_the_name() {
+        let parsed: QuickAdd = parse("  Buy   milk ", now());
+
+        assert_eq!(parsed.name, "Buy milk");
+        assert_eq!(parsed.description, None);
+        assert_eq!(parsed.priority, None);
+        assert_eq!(parsed.deadline, None);
+    }
+
+    #[test]
+    fn full_line() {
+        let parsed: QuickAdd = parse("Pay rent tomorrow 9am !high #home", now());
+
+        assert_eq!(parsed.name, "Pay rent #home");
+        assert_eq!(parsed.priority, Some(TaskPriority::Hight));
+        assert_eq!(parsed.deadline, deadline("2026-10-20T09:00:00+03:00"));
+    }
+
+    #[test]
+    fn priority_markers() {
+        assert_eq!(parse("a !!!", now()).priority, Some(TaskPriority::Hight));
+        assert_eq!(parse("a !h", now()).priority, Some(TaskPriority::Hight));
+        assert_eq!(
+            parse("a !Normal", now()).priority,
+            Some(TaskPriority::Normal)
+        );
+        assert_eq!(parse("a !!", now()).priority, Some(TaskPriority::Normal));
+        assert_eq!(parse("a !low", now()).priority, Some(TaskPriority::Low));
+        assert_eq!(parse("a !", now()).priority, None);
+        assert_eq!(parse("a !urgent", now()).name, "a !urgent");
+    }
+
+    #[test]
+    fn only_first_priority_is_taken() {
+        let parsed: QuickAdd = parse("a !low !high", now());
+
+        assert_eq!(parsed.priority, Some(TaskPriority::Low));
+        assert_eq!(parsed.name, "a !high");
+    }
+
+    #[test]
+    fn date_alone_is_all_day() {
+        assert_eq!(parse("a today", now()).deadline, day("2026-10-19"));
+        assert_eq!(parse("a tmrw", now()).deadline, day("2026-10-20"));
+    }
+
+    #[test]
+    fn time_alone_is_next_occurrence() {
+        assert_eq!(
+            parse("a 5pm", now()).deadline,
+            deadline("2026-10-19T17:00:00+03:00")
+        );
+        assert_eq!(
+            parse("a 9am", now()).deadline,
+            deadline("2026-10-20T09:00:00+03:00")
+        );
+        assert_eq!(
+            parse("a 14:00", now()).deadline,
+            deadline("2026-10-20T14:00:00+03:00")
+        );
+    }
+
+    #[test]
+    fn time_formats() {
+        let cases: [(&str, &str); 6] = [
+            ("a 9:30pm", "2026-10-19T21:30:00+03:00"),
+            ("a 9 pm", "2026-10-19T21:00:00+03:00"),
+            ("a 12am", "2026-10-20T00:00:00+03:00"),
+            ("a 12pm", "2026-10-20T12:00:00+03:00"),
+            ("a noon", "2026-10-20T12:00:00+03:00"),
+            ("a midnight", "2026-10-20T00:00:00+03:00"),
+        ];
+
+        for (input, expected) in cases {
+            assert_eq!(parse(input, now()).deadline, deadline(expected), "{input}");
+            assert_eq!(parse(input, now()).name, "a", "{input}");
+        }
+    }
+
+    #[test]
+    fn invalid_times_stay_in_name() {
+        assert_eq!(parse("a 13pm", now()).name, "a 13pm");
+        assert_eq!(parse("a 25:00", now()).name, "a 25:00");
+        assert_eq!(parse("a 9:5", now()).name, "a 9:5");
+        assert_eq!(parse("buy 3 apples", now()).name, "buy 3 apples");
+        assert_eq!(parse("buy 3 apples", now()).deadline, None);
+    }
+
+    #[test]
+    fn short_weekdays_need_a_preposition() {
+        assert_eq!(parse("Sit in the sun", now()).name, "Sit in the sun");
+        assert_eq!(parse("Game on sat", now()).deadline, day("2026-10-24"));
+        assert_eq!(parse("a next wed", now()).deadline, day("2026-10-28"));
+    }
+
+    #[test]
+    fn weekdays() {
+        assert_eq!(parse("a friday", now()).deadline, day("2026-10-23"));
+        // Today is Monday, a bare weekday never means today
+        assert_eq!(parse("a Monday", now()).deadline, day("2026-10-26"));
+        assert_eq!(parse("a next friday", now()).deadline, day("2026-10-30"));
+        assert_eq!(parse("a next monday", now()).deadline, day("2026-10-26"));
+    }
+
+    #[test]
+    fn relative_dates() {
+        assert_eq!(parse("a next week", now()).deadline, day("2026-10-26"));
+        assert_eq!(parse("a next month", now()).deadline, day("2026-11-19"));
+        assert_eq!(parse("a in 3 days", now()).deadline, day("2026-10-22"));
+        assert_eq!(
+            parse("a in 2 weeks at 10am", now()).deadline,
+            deadline("2026-11-02T10:00:00+03:00")
+        );
+    }
+
+    #[test]
+    fn exact_moments() {
+        let parsed: QuickAdd = parse("Call back in 2 hours", now());
+
+        assert_eq!(parsed.name, "Call back");
+        assert_eq!(parsed.deadline, deadline("2026-10-19T16:00:00+03:00"));
+
+        assert_eq!(
+            parse("a in 45 min", now()).deadline,
+            deadline("2026-10-19T14:45:00+03:00")
+        );
+    }
+
+    #[test]
+    fn absolute_dates() {
+        assert_eq!(parse("a 2026-12-01", now()).deadline, day("2026-12-01"));
+        assert_eq!(parse("a oct 25", now()).deadline, day("2026-10-25"));
+        assert_eq!(
+            parse("a 25th October 8am", now()).deadline,
+            deadline("2026-10-25T08:00:00+03:00")
+        );
+        // Passed this year, so next year
+        assert_eq!(parse("a jan 3", now()).deadline, day("2027-01-03"));
+        assert_eq!(parse("a feb 30", now()).name, "a feb 30");
+    }
+
+    #[test]
+    fn prepositions_go_with_their_date() {
+        let parsed: QuickAdd = parse("Meet Anna on friday at 5pm", now());
+
+        assert_eq!(parsed.name, "Meet Anna");
+        assert_eq!(parsed.deadline, deadline("2026-10-23T17:00:00+03:00"));
+
+        assert_eq!(parse("Look at it", now()).name, "Look at it");
+        assert_eq!(parse("Report due tomorrow", now()).name, "Report");
+        assert_eq!(parse("Stand by", now()).name, "Stand by");
+    }
+
+    #[test]
+    fn time_before_date() {
+        assert_eq!(
+            parse("a 9am tomorrow", now()).deadline,
+            deadline("2026-10-20T09:00:00+03:00")
+        );
+    }
+
+    #[test]
+    fn only_first_date_and_time_are_taken() {
+        let parsed: QuickAdd = parse("Move 5pm meeting to 6pm today", now());
+
+        assert_eq!(parsed.name, "Move meeting to 6pm");
+        assert_eq!(parsed.deadline, deadline("2026-10-19T17:00:00+03:00"));
+    }
+
+    #[test]
+    fn punctuation_after_words() {
+        let parsed: QuickAdd = parse("Pay rent tomorrow, please", now());
+
+        assert_eq!(parsed.name, "Pay rent please");
+        assert_eq!(parsed.deadline, day("2026-10-20"));
+    }
+
+    #[test]
+    fn description_after_slashes() {
+        let parsed: QuickAdd = parse("Pay rent !low // ask about the deposit ", now());
+
+        assert_eq!(parsed.name, "Pay rent");
+        assert_eq!(parsed.description.as_deref(), Some("ask about the deposit"));
+        assert_eq!(parsed.priority, Some(TaskPriority::Low));
+
+        assert_eq!(parse("Pay rent //", now()).description, None);
+        assert_eq!(
+            parse("//only a description", now()).description.as_deref(),
+            Some("only a description")
+        );
+    }
+
+    #[test]
+    fn links_stay_in_the_name() {
+        let parsed: QuickAdd = parse("Read https://example.com/a//b tomorrow // later", now());
+
+        assert_eq!(parsed.name, "Read https://example.com/a//b");
+        assert_eq!(parsed.description.as_deref(), Some("later"));
+        assert_eq!(parsed.deadline, day("2026-10-20"));
+
+        let parsed: QuickAdd = parse("Review http://localhost:8000", now());
+
+        assert_eq!(parsed.name, "Review http://localhost:8000");
+        assert_eq!(parsed.description, None);
+    }
+
+    #[test]
+    fn negative_counts_are_rejected() {
+        for input in [
+            "a in -3 days",
+            "a in -1 week",
+            "a in -2 hours",
+            "a due in -5 min",
+        ] {
+            match super::parse(input, now()) {
+                Err(ServiceError::BadRequest(message)) => {
+                    assert!(message.contains("must not be negative"), "{message}")
+                }
+                other => panic!("expected an error for {input:?}, got {other:?}"),
+            }
+        }
+
+        assert!(matches!(
+            super::parse(&format!("Call mom in {} hours", i64::MIN), now()),
+            Err(ServiceError::BadRequest(_))
+        ));
+        assert!(matches!(
+            super::parse(&format!("Call mom in {} minutes", i64::MIN), now()),
+            Err(ServiceError::BadRequest(_))
+        ));
+
+        assert_eq!(
+            parse("a in 0 hours", now()).deadline,
+            deadline("2026-10-19T14:00:00+03:00")
+        );
+    }
+
+    #[test]
+    fn keywords_are_case_insensitive() {
+        let parsed: QuickAdd = parse("Ship TOMORROW At 10AM", now());
+
+        assert_eq!(parsed.name, "Ship");
+        assert_eq!(parsed.deadline, deadline("2026-10-20T10:00:00+03:00"));
+    }
+
+    #[test]
+    fn dates_follow_zone_of_now() {
+        // Saturday before the end of summer time in Berlin
+        let now: DateTime<chrono_tz::Tz> = chrono_tz::Europe::Berlin
+            .with_ymd_and_hms(2026, 10, 24, 23, 30, 0)
+            .unwrap();
+
+        assert_eq!(parse("a tomorrow", now).deadline, day("2026-10-25"));
+        assert_eq!(
+            parse("a tomorrow 9am", now).deadline,
+            deadline("2026-10-25T09:00:00+01:00")
+        );
+        assert_eq!(
+            parse("a 8am", now).deadline,
+            deadline("2026-10-25T08:00:00+01:00")
+        );
+    }
+
+    #[test]
+    fn huge_counts_are_ignored() {
+        assert_eq!(parse("a in 99999999 days", now()).deadline, None);
+        assert_eq!(parse("a in 99999999 hours", now()).deadline, None);
+    }
+}
@@ -0,0 +1,182 @@
+//! Lexicographic ranks for manual ordering.
+//!
+//! A rank is a base 36 fraction written without the leading `0.`, so plain
+//! string comparison orders ranks. Ranks never end with `0`, which keeps room
+//! for a rank before any other one.
+
+const DIGITS: &[u8] = b"0123456789abcdefghijklmnopqrstuvwxyz";
+const BASE: usize = DIGITS.len();
+
+/// Returns a rank strictly between `before` and `after`, a missing bound is
+/// the start or the end of the list.
+pub fn between(before: Option<&str>, after: Option<&str>) -> String {
+    let before: Vec<usize> = before.map(decode).unwrap_or_default();
+    let after: Option<Vec<usize>> = after.map(decode);
+
+    match after {
+        // Bounds out of order, e.g. two rows sharing a rank, only `before` holds
+        Some(after) if before >= after => encode(&midpoint(&before, None)),
+        after => encode(&midpoint(&before, after.as_deref())),
+    }
+}
+
+/// Returns `count` evenly spaced ranks in ascending order.
+pub fn spread(count: usize) -> Vec<String> {
+    let mut width: u32 = 1;
+    while BASE.pow(width) <= count {
+        width += 1;
+    }
+
+    let step: usize = BASE.pow(width) / (count + 1);
+
+    (1..=count)
+        .map(|index| {
+            let mut value: usize = index * step;
+            let mut digits: Vec<usize> = vec![0; width as usize];
+
+            for digit in digits.iter_mut().rev() {
+                *digit = value % BASE;
+                value /= BASE;
+            }
+
+            while digits.last() == Some(&0) {
+                digits.pop();
+            }
+
+            encode(&digits)
+        })
+        .collect::<Vec<String>>()
+}
+
+fn midpoint(before: &[usize], after: Option<&[usize]>) -> Vec<usize> {
+    if let Some(after) = after {
+        let common: usize = after
+            .iter()
+            .enumerate()
+            .take_while(|(index, digit)| before.get(*index).unwrap_or(&0) == *digit)
+            .count();
+
+        if common > 0 {
+            let mut result: Vec<usize> = after[..common].to_vec();
+            result.extend(midpoint(
+                before.get(common..).unwrap_or_default(),
+                Some(&after[common..]),
+            ));
+
+            return result;
+        }
+    }
+
+    let low: usize = before.first().copied().unwrap_or(0);
+    let high: usize = after.map(|value| value[0]).unwrap_or(BASE);
+
+    if high - low > 1 {
+        return vec![(low + high) / 2];
+    }
+
+    match after {
+        // The first digit of `after` alone is already below it and above `before`
+        Some(after) if after.len() > 1 => vec![after[0]],
+        _ => {
+            let mut result: Vec<usize> = vec![low];
+            result.extend(midpoint(before.get(1..).unwrap_or_default(), None));
+
+            result
+        }
+    }
+}
+
+fn decode(value: &str) -> Vec<usize> {
+    value
+        .bytes()
+        .filter_map(|byte| DIGITS.iter().position(|digit| *digit == byte))
+        .collect::<Vec<usize>>()
+}
+
+fn encode(digits: &[usize]) -> String {
+    digits.iter().map(|digit| DIGITS[*digit] as char).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_between(before: Option<&str>, after: Option<&str>) -> String {
+        let rank: String = between(before, after);
+
+        assert!(!rank.ends_with('0'), "{rank} ends with 0");
+        if let Some(before) = before {
+            assert!(before < rank.as_str(), "{before} < {rank}");
+        }
+        if let Some(after) = after {
+            assert!(rank.as_str() < after, "{rank} < {after}");
+        }
+
+        rank
+    }
+
+    #[test]
+    fn empty_list_takes_the_middle() {
+        assert_eq!(between(None, None), "i");
+        assert_eq!(midpoint(&[], None), vec![18]);
+    }
+
+    #[test]
+    fn open_bounds() {
+        assert_eq!(assert_between(Some("i"), None), "r");
+        assert_eq!(assert_between(None, Some("i")), "9");
+        assert_eq!(assert_between(Some("z"), None), "zi");
+        assert_eq!(assert_between(None, Some("1")), "0i");
+        assert_between(None, Some("01"));
+        assert_between(Some("zzz"), None);
+    }
+
+    #[test]
+    fn adjacent_ranks() {
+        assert_eq!(assert_between(Some("a"), Some("b")), "ai");
+        assert_eq!(assert_between(Some("a"), Some("a1")), "a0i");
+        assert_eq!(assert_between(Some("az"), Some("b")), "azi");
+        assert_between(Some("a"), Some("a01"));
+        assert_between(Some("ay"), Some("az1"));
+    }
+
+    #[test]
+    fn repeated_inserts_stay_ordered() {
+        let mut low: String = between(None, None);
+        let mut high: String = between(Some(&low), None);
+
+        for index in 0..200 {
+            let rank: String = assert_between(Some(&low), Some(&high));
+            match index % 2 {
+                0 => low = rank,
+                _ => high = rank,
+            }
+        }
+    }
+
+    #[test]
+    fn bounds_out_of_order_only_keep_before() {
+        assert!(between(Some("b"), Some("a")).as_str() > "b");
+        assert!(between(Some("b"), Some("b")).as_str() > "b");
+    }
+
+    #[test]
+    fn spread_is_strictly_increasing() {
+        for count in [0, 1, 2, 35, 36, 37, 1295, 1296, 5000] {
+            let ranks: Vec<String> = spread(count);
+
+            assert_eq!(ranks.len(), count);
+            assert!(ranks
+                .iter()
+                .all(|rank| !rank.is_empty() && !rank.ends_with('0')));
+            assert!(ranks.windows(2).all(|pair| pair[0] < pair[1]));
+        }
+    }
+
+    #[test]
+    fn spread_uses_the_shortest_width() {
+        assert_eq!(spread(1), vec!["i"]);
+        assert!(spread(35).iter().all(|rank| rank.len() == 1));
+        assert!(spread(36).iter().all(|rank| rank.len() <= 2));
+    }
+}
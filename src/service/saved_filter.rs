@@ -0,0 +1,192 @@
+use chrono::{Local, Utc};
+use chrono_tz::Tz;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, DatabaseTransaction, EntityTrait,
+    IntoActiveModel, ModelTrait, QueryFilter, QueryOrder, QuerySelect, Set, TransactionTrait,
+};
+use uuid::Uuid;
+
+use crate::{
+    dto::{
+        saved_filter::{
+            SavedFilterCreateDto, SavedFilterReadDto, SavedFilterRunQuery, SavedFilterUpdateDto,
+        },
+        task::TaskReadDto,
+    },
+    entity::prelude::{
+        SavedFilterActiveModel, SavedFilterColumn, SavedFilterEntity, SavedFilterModel, TaskColumn,
+        TaskEntity, TaskModel,
+    },
+    error::service::{ServiceError, ServiceResult},
+};
+
+use super::{
+    filter::{self, Filter},
+    task::TaskService,
+    user::UserService,
+};
+
+pub struct SavedFilterService;
+
+impl SavedFilterService {
+    pub async fn create(
+        db: &DatabaseConnection,
+        user_id: Uuid,
+        body: SavedFilterCreateDto,
+    ) -> ServiceResult<SavedFilterReadDto> {
+        let tx: DatabaseTransaction = db.begin().await?;
+
+        filter::parse(&body.expression)?;
+        Self::check_name(&tx, user_id, None, &body.name).await?;
+
+        let mut active_model: SavedFilterActiveModel = body.into_active_model();
+        active_model.user_id = Set(user_id);
+
+        let model: SavedFilterModel = active_model.insert(&tx).await?;
+
+        tx.commit().await?;
+
+        Ok(SavedFilterReadDto::from(model))
+    }
+
+    pub async fn list(
+        db: &DatabaseConnection,
+        user_id: Uuid,
+    ) -> ServiceResult<Vec<SavedFilterReadDto>> {
+        let tx: DatabaseTransaction = db.begin().await?;
+
+        let models: Vec<SavedFilterModel> = SavedFilterEntity::find()
+            .filter(SavedFilterColumn::UserId.eq(user_id))
+            .order_by_asc(SavedFilterColumn::Name)
+            .all(&tx)
+            .await?;
+
+        let schemas: Vec<SavedFilterReadDto> = models
+            .into_iter()
+            .map(SavedFilterReadDto::from)
+            .collect::<Vec<SavedFilterReadDto>>();
+
+        Ok(schemas)
+    }
+
+    pub async fn get_by_id(
+        db: &DatabaseConnection,
+        user_id: Uuid,
+        id: Uuid,
+    ) -> ServiceResult<SavedFilterReadDto> {
+        let tx: DatabaseTransaction = db.begin().await?;
+
+        Ok(SavedFilterReadDto::from(
+            Self::find(&tx, user_id, id).await?,
+        ))
+    }
+
+    pub async fn update(
+        db: &DatabaseConnection,
+        user_id: Uuid,
+        id: Uuid,
+        body: SavedFilterUpdateDto,
+    ) -> ServiceResult<SavedFilterReadDto> {
+        let tx: DatabaseTransaction = db.begin().await?;
+
+        let mut active_model: SavedFilterActiveModel =
+            Self::find(&tx, user_id, id).await?.into_active_model();
+
+        if let Some(name) = body.name {
+            Self::check_name(&tx, user_id, Some(id), &name).await?;
+
+            active_model.name = Set(name);
+        }
+
+        if let Some(expression) = body.expression {
+            filter::parse(&expression)?;
+
+            active_model.expression = Set(expression);
+        }
+
+        active_model.updated_at = Set(Local::now().fixed_offset());
+        let model: SavedFilterModel = active_model.update(&tx).await?;
+
+        tx.commit().await?;
+
+        Ok(SavedFilterReadDto::from(model))
+    }
+
+    pub async fn delete(db: &DatabaseConnection, user_id: Uuid, id: Uuid) -> ServiceResult {
+        let tx: DatabaseTransaction = db.begin().await?;
+
+        Self::find(&tx, user_id, id).await?.delete(&tx).await?;
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    /// Active tasks matching the filter, oldest first.
+    pub async fn run(
+        db: &DatabaseConnection,
+        user_id: Uuid,
+        id: Uuid,
+        query: SavedFilterRunQuery,
+    ) -> ServiceResult<Vec<TaskReadDto>> {
+        let tx: DatabaseTransaction = db.begin().await?;
+
+        let model: SavedFilterModel = Self::find(&tx, user_id, id).await?;
+        let filter: Filter = filter::parse(&model.expression)?;
+        let time_zone: Tz = UserService::time_zone(&tx, user_id).await?;
+
+        let models: Vec<TaskModel> = TaskEntity::find()
+            .filter(TaskColumn::UserId.eq(user_id))
+            .filter(TaskColumn::DeletedAt.is_null())
+            .filter(filter.condition(Utc::now().with_timezone(&time_zone)))
+            .order_by_asc(TaskColumn::CreatedAt)
+            .order_by_asc(TaskColumn::Id)
+            .limit(query.limit)
+            .offset(query.offset)
+            .all(&tx)
+            .await?;
+
+        TaskService::schemas(&tx, models).await
+    }
+
+    async fn check_name(
+        tx: &DatabaseTransaction,
+        user_id: Uuid,
+        id: Option<Uuid>,
+        name: &str,
+    ) -> ServiceResult {
+        let mut query = SavedFilterEntity::find()
+            .filter(SavedFilterColumn::UserId.eq(user_id))
+            .filter(SavedFilterColumn::Name.eq(name));
+
+        if let Some(value) = id {
+            query = query.filter(SavedFilterColumn::Id.ne(value));
+        }
+
+        if query.one(tx).await?.is_some() {
+            return Err(ServiceError::Conflict {
+                field: "name".to_string(),
+                value: name.to_string(),
+            });
+        }
+
+        Ok(())
+    }
+
+    async fn find(
+        tx: &DatabaseTransaction,
+        user_id: Uuid,
+        id: Uuid,
+    ) -> ServiceResult<SavedFilterModel> {
+        match SavedFilterEntity::find_by_id(id).one(tx).await? {
+            Some(value) => {
+                if value.user_id != user_id {
+                    return Err(ServiceError::Forbidden);
+                }
+
+                Ok(value)
+            }
+            None => Err(ServiceError::NotFound(id)),
+        }
+    }
+}
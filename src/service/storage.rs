@@ -0,0 +1,101 @@
+use sea_orm::{
+    sea_query::{LockBehavior, LockType, OnConflict},
+    ColumnTrait, DatabaseConnection, DatabaseTransaction, EntityTrait, ModelTrait, PaginatorTrait,
+    QueryFilter, QueryOrder, QuerySelect, Set, TransactionTrait,
+};
+
+use crate::{
+    client::storage::StorageClient,
+    constants,
+    entity::prelude::{
+        AccountExportColumn, AccountExportEntity, AttachmentColumn, AttachmentEntity,
+        StorageOrphanActiveModel, StorageOrphanColumn, StorageOrphanEntity, StorageOrphanModel,
+        UserAvatarColumn, UserAvatarEntity, UserAvatarVariantColumn, UserAvatarVariantEntity,
+    },
+    error::service::ServiceResult,
+};
+
+pub struct StorageService;
+
+impl StorageService {
+    /// Claims `key` for a file about to be written, so a running cleanup does
+    /// not remove it from under the new record. Shared keys need this, as the
+    /// file may be waiting for removal.
+    pub async fn claim(tx: &DatabaseTransaction, key: &str) -> ServiceResult {
+        StorageOrphanEntity::delete_many()
+            .filter(StorageOrphanColumn::StorageKey.eq(key))
+            .exec(tx)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Queues `key` for the cleanup, which removes the file unless a record
+    /// uses it.
+    pub async fn release(db: &DatabaseConnection, key: &str) -> ServiceResult {
+        let tx: DatabaseTransaction = db.begin().await?;
+
+        StorageOrphanEntity::insert(StorageOrphanActiveModel {
+            storage_key: Set(key.to_string()),
+            ..Default::default()
+        })
+        .on_conflict(
+            OnConflict::column(StorageOrphanColumn::StorageKey)
+                .do_nothing()
+                .to_owned(),
+        )
+        .do_nothing()
+        .exec(&tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    /// Removes files whose records were deleted, a batch at a time. Keys a
+    /// record took again are only forgotten. Returns how many were handled.
+    pub async fn purge_orphans(
+        db: &DatabaseConnection,
+        storage: &StorageClient,
+    ) -> ServiceResult<u64> {
+        let tx: DatabaseTransaction = db.begin().await?;
+
+        let models: Vec<StorageOrphanModel> = StorageOrphanEntity::find()
+            .order_by_asc(StorageOrphanColumn::CreatedAt)
+            .limit(constants::STORAGE_CLEANUP_BATCH_SIZE)
+            .lock_with_behavior(LockType::Update, LockBehavior::SkipLocked)
+            .all(&tx)
+            .await?;
+
+        let mut count: u64 = 0;
+        for model in models {
+            let used: u64 = AttachmentEntity::find()
+                .filter(AttachmentColumn::StorageKey.eq(&model.storage_key))
+                .count(&tx)
+                .await?
+                + UserAvatarEntity::find()
+                    .filter(UserAvatarColumn::StorageKey.eq(&model.storage_key))
+                    .count(&tx)
+                    .await?
+                + UserAvatarVariantEntity::find()
+                    .filter(UserAvatarVariantColumn::StorageKey.eq(&model.storage_key))
+                    .count(&tx)
+                    .await?
+                + AccountExportEntity::find()
+                    .filter(AccountExportColumn::StorageKey.eq(&model.storage_key))
+                    .count(&tx)
+                    .await?;
+
+            if used == 0 {
+                storage.delete(&model.storage_key).await?;
+            }
+            model.delete(&tx).await?;
+            count += 1;
+        }
+
+        tx.commit().await?;
+
+        Ok(count)
+    }
+}
@@ -0,0 +1,309 @@
+use actix_web::{http::StatusCode, ResponseError};
+use garde::Validate;
+use sea_orm::{
+    sea_query::{Expr, SimpleExpr},
+    ColumnTrait, ConnectionTrait, DatabaseConnection, DatabaseTransaction, DbBackend, EntityTrait,
+    QueryFilter, QueryOrder, QuerySelect, Statement, TransactionTrait,
+};
+use uuid::Uuid;
+
+use crate::{
+    constants,
+    dto::{
+        error::ErrorDto,
+        sync::{
+            SyncMutationDto, SyncMutationResultDto, SyncPullDto, SyncPushDto, SyncToken,
+            TombstoneReadDto,
+        },
+        task::{TaskCommentReadDto, TaskReadDto},
+    },
+    entity::prelude::{
+        TaskColumn, TaskCommentColumn, TaskCommentEntity, TaskCommentModel, TaskEntity, TaskModel,
+        TombstoneColumn, TombstoneEntity, TombstoneModel,
+    },
+    error::service::{ServiceError, ServiceResult},
+};
+
+use super::{task::TaskService, task_comment::TaskCommentService};
+
+pub struct SyncService;
+
+impl SyncService {
+    /// Returns up to `limit` changes made after `token`, oldest first.
+    ///
+    /// Positions are taken when a change is made but seen once it commits,
+    /// so a writer still running may hold one below the token handed out.
+    /// The token keeps the snapshot of the pull, and the next pull sends
+    /// again whatever below the token that snapshot did not see. A client
+    /// following the tokens gets every change at least once.
+    pub async fn pull(
+        db: &DatabaseConnection,
+        user_id: Uuid,
+        token: Option<String>,
+        limit: u64,
+    ) -> ServiceResult<SyncPullDto> {
+        let since: SyncToken = match &token {
+            Some(value) => value.parse()?,
+            None => SyncToken::default(),
+        };
+        let limit: u64 = limit.clamp(1, constants::SYNC_PULL_MAX_LIMIT);
+
+        let tx: DatabaseTransaction = db.begin().await?;
+
+        let snapshot: String = Self::snapshot(&tx).await?;
+
+        let mut tasks: Vec<TaskModel> = TaskEntity::find()
+            .filter(TaskColumn::UserId.eq(user_id))
+            .filter(TaskColumn::ChangeSeq.gt(since.seq))
+            .order_by_asc(TaskColumn::ChangeSeq)
+            .limit(limit + 1)
+            .all(&tx)
+            .await?;
+
+        let mut comments: Vec<TaskCommentModel> = TaskCommentEntity::find()
+            .filter(TaskCommentColumn::UserId.eq(user_id))
+            .filter(TaskCommentColumn::ChangeSeq.gt(since.seq))
+            .order_by_asc(TaskCommentColumn::ChangeSeq)
+            .limit(limit + 1)
+            .all(&tx)
+            .await?;
+
+        // A client without a token has nothing to delete yet
+        let mut tombstones: Vec<TombstoneModel> = match token {
+            Some(_) => {
+                TombstoneEntity::find()
+                    .filter(TombstoneColumn::UserId.eq(user_id))
+                    .filter(TombstoneColumn::ChangeSeq.gt(since.seq))
+                    .order_by_asc(TombstoneColumn::ChangeSeq)
+                    .limit(limit + 1)
+                    .all(&tx)
+                    .await?
+            }
+            None => Vec::new(),
+        };
+
+        let mut seqs: Vec<i64> = tasks
+            .iter()
+            .map(|model| model.change_seq)
+            .chain(comments.iter().map(|model| model.change_seq))
+            .chain(tombstones.iter().map(|model| model.change_seq))
+            .collect::<Vec<i64>>();
+        seqs.sort_unstable();
+
+        let has_more: bool = seqs.len() as u64 > limit;
+        let until: i64 = match has_more {
+            true => seqs[limit as usize - 1],
+            false => seqs.last().copied().unwrap_or(since.seq),
+        };
+
+        tasks.retain(|model| model.change_seq <= until);
+        comments.retain(|model| model.change_seq <= until);
+        tombstones.retain(|model| model.change_seq <= until);
+
+        // Changes below the token committed after the pull that handed it out
+        if let Some(snapshot) = &since.snapshot {
+            tasks.extend(
+                TaskEntity::find()
+                    .filter(TaskColumn::UserId.eq(user_id))
+                    .filter(TaskColumn::ChangeSeq.lte(since.seq))
+                    .filter(unseen("task", snapshot))
+                    .all(&tx)
+                    .await?,
+            );
+            comments.extend(
+                TaskCommentEntity::find()
+                    .filter(TaskCommentColumn::UserId.eq(user_id))
+                    .filter(TaskCommentColumn::ChangeSeq.lte(since.seq))
+                    .filter(unseen("task_comment", snapshot))
+                    .all(&tx)
+                    .await?,
+            );
+            tombstones.extend(
+                TombstoneEntity::find()
+                    .filter(TombstoneColumn::UserId.eq(user_id))
+                    .filter(TombstoneColumn::ChangeSeq.lte(since.seq))
+                    .filter(unseen("tombstone", snapshot))
+                    .all(&tx)
+                    .await?,
+            );
+
+            tasks.sort_by_key(|model| model.change_seq);
+            comments.sort_by_key(|model| model.change_seq);
+            tombstones.sort_by_key(|model| model.change_seq);
+        }
+
+        let tasks: Vec<TaskReadDto> = TaskService::schemas(&tx, tasks).await?;
+
+        tx.commit().await?;
+
+        Ok(SyncPullDto {
+            token: SyncToken {
+                seq: until,
+                snapshot: Some(snapshot),
+            }
+            .to_string(),
+            has_more,
+            tasks,
+            comments: comments
+                .into_iter()
+                .map(TaskCommentReadDto::from)
+                .collect::<Vec<TaskCommentReadDto>>(),
+            tombstones: tombstones
+                .into_iter()
+                .map(TombstoneReadDto::from)
+                .collect::<Vec<TombstoneReadDto>>(),
+        })
+    }
+
+    /// Current snapshot as text, for a token. Taken before reading changes,
+    /// what commits in between is sent twice at worst.
+    pub async fn snapshot<C: ConnectionTrait>(conn: &C) -> ServiceResult<String> {
+        match conn
+            .query_one(Statement::from_string(
+                DbBackend::Postgres,
+                "SELECT pg_current_snapshot()::text",
+            ))
+            .await?
+        {
+            Some(row) => Ok(row.try_get_by_index::<String>(0)?),
+            None => Err(ServiceError::Unknow("No snapshot".to_string())),
+        }
+    }
+
+    /// Rows of `table` a client holding `since` has not been sent: changed
+    /// after it, or below it by a transaction its snapshot did not see
+    /// committed.
+    pub fn changed_since<C: ColumnTrait>(table: &str, seq: C, since: &SyncToken) -> SimpleExpr {
+        match &since.snapshot {
+            Some(snapshot) => seq.gt(since.seq).or(unseen(table, snapshot)),
+            None => seq.gt(since.seq),
+        }
+    }
+
+    /// Applies client mutations in order, each in its own transaction, so one
+    /// failing item does not reject the whole batch.
+    pub async fn push(
+        db: &DatabaseConnection,
+        user_id: Uuid,
+        body: SyncPushDto,
+        enforce_blockers: bool,
+    ) -> ServiceResult<Vec<SyncMutationResultDto>> {
+        let mut results: Vec<SyncMutationResultDto> = Vec::with_capacity(body.mutations.len());
+
+        for (index, mutation) in body.mutations.into_iter().enumerate() {
+            let result: SyncMutationResultDto =
+                match Self::apply(db, user_id, mutation, enforce_blockers).await {
+                    Ok(value) => value,
+                    Err(err) => SyncMutationResultDto {
+                        status: err.status_code().as_u16(),
+                        error: Some(ErrorDto {
+                            detail: err.to_string(),
+                        }),
+                        ..Default::default()
+                    },
+                };
+
+            results.push(SyncMutationResultDto { index, ..result });
+        }
+
+        Ok(results)
+    }
+
+    async fn apply(
+        db: &DatabaseConnection,
+        user_id: Uuid,
+        mutation: SyncMutationDto,
+        enforce_blockers: bool,
+    ) -> ServiceResult<SyncMutationResultDto> {
+        let result: SyncMutationResultDto = match mutation {
+            SyncMutationDto::CreateTask { body } => {
+                body.validate()?;
+
+                SyncMutationResultDto {
+                    status: StatusCode::CREATED.as_u16(),
+                    task: Some(TaskService::create(db, user_id, body).await?),
+                    ..Default::default()
+                }
+            }
+            SyncMutationDto::UpdateTask { id, version, body } => {
+                body.validate()?;
+
+                SyncMutationResultDto {
+                    status: StatusCode::OK.as_u16(),
+                    task: Some(
+                        TaskService::update(
+                            db,
+                            user_id,
+                            id,
+                            version.into(),
+                            body,
+                            enforce_blockers,
+                        )
+                        .await?,
+                    ),
+                    ..Default::default()
+                }
+            }
+            SyncMutationDto::DeleteTask { id, version } => {
+                TaskService::delete(db, user_id, id, version.into()).await?;
+
+                SyncMutationResultDto {
+                    status: StatusCode::NO_CONTENT.as_u16(),
+                    ..Default::default()
+                }
+            }
+            SyncMutationDto::CreateComment { task_id, body } => {
+                body.validate()?;
+
+                SyncMutationResultDto {
+                    status: StatusCode::CREATED.as_u16(),
+                    comment: Some(TaskCommentService::create(db, user_id, task_id, body).await?),
+                    ..Default::default()
+                }
+            }
+            SyncMutationDto::UpdateComment {
+                task_id,
+                id,
+                version,
+                body,
+            } => {
+                body.validate()?;
+
+                SyncMutationResultDto {
+                    status: StatusCode::OK.as_u16(),
+                    comment: Some(
+                        TaskCommentService::update(db, user_id, task_id, id, version.into(), body)
+                            .await?,
+                    ),
+                    ..Default::default()
+                }
+            }
+            SyncMutationDto::DeleteComment {
+                task_id,
+                id,
+                version,
+            } => {
+                TaskCommentService::delete(db, user_id, task_id, id, version.into()).await?;
+
+                SyncMutationResultDto {
+                    status: StatusCode::NO_CONTENT.as_u16(),
+                    ..Default::default()
+                }
+            }
+        };
+
+        Ok(result)
+    }
+}
+
+/// Rows of `table` whose last change was made by a transaction `snapshot` did
+/// not see committed.
+fn unseen(table: &str, snapshot: &str) -> SimpleExpr {
+    Expr::cust_with_values(
+        format!(
+            r#""{table}"."change_xid" >= pg_snapshot_xmin($1::pg_snapshot)
+            AND NOT pg_visible_in_snapshot("{table}"."change_xid", $1::pg_snapshot)"#
+        ),
+        [snapshot],
+    )
+}
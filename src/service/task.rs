@@ -1,19 +1,51 @@
+use std::collections::{HashMap, HashSet};
+
+use actix_web::{http::StatusCode, ResponseError};
+use chrono::{DateTime, FixedOffset, Local, Utc};
+use chrono_tz::Tz;
+use garde::Validate;
 use sea_orm::{
-    sea_query::SimpleExpr, ActiveModelTrait, ColumnTrait, DatabaseConnection, DatabaseTransaction,
-    EntityTrait, IntoActiveModel, ModelTrait, QueryFilter, QuerySelect, Set, TransactionTrait,
-    TryIntoModel,
+    sea_query::{Expr, Func, NullOrdering, SimpleExpr},
+    ActiveEnum, ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseConnection,
+    DatabaseTransaction, EntityTrait, IntoActiveModel, ModelTrait, Order, QueryFilter, QueryOrder,
+    QuerySelect, QueryTrait, Select, Set, TransactionTrait, TryIntoModel,
 };
+use serde_json::Value;
 use uuid::Uuid;
 
 use crate::{
-    dto::task::{TaskCreateDto, TaskReadDto, TaskUpdateDto},
+    constants,
+    dto::{
+        error::ErrorDto,
+        precondition::IfMatchDto,
+        task::{
+            TaskBulkDto, TaskBulkOperationDto, TaskBulkResultDto, TaskCreateDto, TaskGetQuery,
+            TaskKanbanColumnDto, TaskMoveDto, TaskQuickDto, TaskQuickReadDto, TaskReadDto,
+            TaskUpdateDto,
+        },
+    },
     entity::{
-        prelude::{TaskActiveModel, TaskColumn, TaskEntity, TaskModel},
-        sea_orm_active_enums::{TaskPriority, TaskStatus},
+        prelude::{
+            TaskActiveModel, TaskColumn, TaskEntity, TaskModel, UserPreferenceModel,
+            WorkflowStatusModel,
+        },
+        sea_orm_active_enums::{TaskPriority, TaskSort, TaskStatus},
     },
     error::service::{ServiceError, ServiceResult},
 };
 
+use super::{
+    custom_field::CustomFieldService,
+    filter::{self, Filter},
+    quick_add::{self, QuickAdd},
+    rank,
+    task_dependency::TaskDependencyService,
+    task_event::TaskEventService,
+    user::UserService,
+    user_preference::UserPreferenceService,
+    workflow_status::WorkflowStatusService,
+};
+
 pub struct TaskService;
 
 impl TaskService {
@@ -24,102 +56,935 @@ impl TaskService {
     ) -> ServiceResult<TaskReadDto> {
         let tx: DatabaseTransaction = db.begin().await?;
 
+        let model: TaskModel = Self::insert(&tx, user_id, body).await?;
+
+        let schema: TaskReadDto = Self::schema(&tx, model).await?;
+
+        tx.commit().await?;
+
+        Ok(schema)
+    }
+
+    /// Creates a task inside the caller's transaction.
+    pub async fn insert(
+        tx: &DatabaseTransaction,
+        user_id: Uuid,
+        mut body: TaskCreateDto,
+    ) -> ServiceResult<TaskModel> {
+        let custom_fields: Option<HashMap<Uuid, Value>> = body.custom_fields.take();
+        let status_id: Option<Uuid> = body.status_id;
+        // Without either the task starts in the first to do status
+        let category: Option<TaskStatus> = match (&body.status, status_id) {
+            (None, None) => Some(TaskStatus::ToDo),
+            (value, _) => value.clone(),
+        };
+
+        if body.priority.is_none() {
+            body.priority = Some(
+                UserPreferenceService::find(tx, user_id)
+                    .await?
+                    .default_priority,
+            );
+        }
+
         let mut active_model: TaskActiveModel = body.into_active_model();
         active_model.user_id = Set(user_id);
 
-        let model: TaskModel = active_model.save(&tx).await?.try_into_model()?;
+        Self::set_status(
+            tx,
+            user_id,
+            None,
+            status_id,
+            category,
+            false,
+            &mut active_model,
+        )
+        .await?;
 
-        tx.commit().await?;
+        let model: TaskModel = active_model.save(tx).await?.try_into_model()?;
 
-        let schema: TaskReadDto = TaskReadDto::from(model);
+        TaskEventService::record_task(tx, Some(user_id), None, Some(&model)).await?;
 
-        Ok(schema)
+        if let Some(values) = custom_fields {
+            CustomFieldService::set_values(tx, user_id, model.id, values).await?;
+        }
+
+        Ok(model)
+    }
+
+    pub async fn get_by_id(
+        db: &DatabaseConnection,
+        user_id: Uuid,
+        id: Uuid,
+    ) -> ServiceResult<TaskReadDto> {
+        let tx: DatabaseTransaction = db.begin().await?;
+
+        match TaskEntity::find_by_id(id)
+            .filter(TaskColumn::DeletedAt.is_null())
+            .one(&tx)
+            .await?
+        {
+            Some(value) => {
+                if value.user_id != user_id {
+                    return Err(ServiceError::Forbidden);
+                }
+
+                Self::schema(&tx, value).await
+            }
+            None => Err(ServiceError::NotFound(id)),
+        }
     }
 
     pub async fn list(
         db: &DatabaseConnection,
         user_id: Uuid,
-        limit: u64,
-        offset: u64,
-        status: Option<TaskStatus>,
-        priority: Option<TaskPriority>,
+        query: TaskGetQuery,
     ) -> ServiceResult<Vec<TaskReadDto>> {
         let tx: DatabaseTransaction = db.begin().await?;
 
-        let mut query: SimpleExpr = TaskColumn::UserId.eq(user_id);
+        let select: Select<TaskEntity> = Self::select(&tx, user_id, &query).await?;
+
+        let models: Vec<TaskModel> = select
+            .limit(query.limit)
+            .offset(query.offset)
+            .all(&tx)
+            .await?;
 
-        if let Some(value) = status {
-            query = query.and(TaskColumn::Status.eq(value));
+        Self::schemas(&tx, models).await
+    }
+
+    /// Active tasks of the user matching the filters of `query`, sorted as
+    /// it asks. Paging is left to the caller.
+    pub async fn select<C: ConnectionTrait>(
+        conn: &C,
+        user_id: Uuid,
+        query: &TaskGetQuery,
+    ) -> ServiceResult<Select<TaskEntity>> {
+        let mut condition: SimpleExpr = TaskColumn::UserId
+            .eq(user_id)
+            .and(TaskColumn::DeletedAt.is_null());
+
+        if let Some(value) = query.status_id {
+            condition = condition.and(TaskColumn::StatusId.eq(value));
+        } else if let Some(value) = query.status.clone() {
+            condition = condition.and(TaskColumn::Status.eq(value));
         } else {
-            query = query.and(TaskColumn::Status.ne(TaskStatus::Done))
+            condition = condition.and(TaskColumn::Status.ne(TaskStatus::Done))
         }
 
-        if let Some(value) = priority {
-            query = query.and(TaskColumn::Priority.eq(value));
+        if let Some(value) = query.priority.clone() {
+            condition = condition.and(TaskColumn::Priority.eq(value));
         }
 
-        let models: Vec<TaskModel> = TaskEntity::find()
-            .filter(query)
-            .limit(limit)
-            .offset(offset)
-            .all(&tx)
-            .await?;
+        if query.actionable == Some(true) {
+            condition =
+                condition
+                    .and(TaskColumn::Id.not_in_subquery(
+                        TaskDependencyService::unfinished_blockers().into_query(),
+                    ));
+        }
 
-        let schemas: Vec<TaskReadDto> = models
-            .into_iter()
-            .map(|model| TaskReadDto::from(model))
-            .collect::<Vec<TaskReadDto>>();
+        match (query.field_id, &query.field_value) {
+            (Some(field_id), value) => {
+                condition = condition.and(
+                    CustomFieldService::filter(conn, user_id, field_id, value.as_deref()).await?,
+                );
+            }
+            (None, Some(_)) => {
+                return Err(ServiceError::BadRequest(
+                    "field_value requires field_id".to_string(),
+                ))
+            }
+            (None, None) => {}
+        }
 
-        Ok(schemas)
+        let mut select: Select<TaskEntity> = TaskEntity::find().filter(condition);
+
+        if let Some(field_id) = query.sort_field_id {
+            let order: Order = match query.descending {
+                Some(true) => Order::Desc,
+                _ => Order::Asc,
+            };
+
+            select = select
+                .order_by_with_nulls(
+                    CustomFieldService::sort(conn, user_id, field_id).await?,
+                    order,
+                    NullOrdering::Last,
+                )
+                .order_by_asc(TaskColumn::Id);
+        } else {
+            let (sort, descending) = match query.sort.clone() {
+                Some(value) => (value, query.descending.unwrap_or(false)),
+                None => {
+                    let preference: UserPreferenceModel =
+                        UserPreferenceService::find(conn, user_id).await?;
+
+                    (
+                        preference.task_sort,
+                        query.descending.unwrap_or(preference.task_sort_descending),
+                    )
+                }
+            };
+            let order: Order = match descending {
+                true => Order::Desc,
+                false => Order::Asc,
+            };
+
+            select = match sort {
+                TaskSort::Created => select.order_by(TaskColumn::CreatedAt, order),
+                TaskSort::Updated => select.order_by(TaskColumn::UpdatedAt, order),
+                TaskSort::Deadline => {
+                    select.order_by_with_nulls(TaskColumn::Deadline, order, NullOrdering::Last)
+                }
+                TaskSort::Priority => select.order_by(TaskColumn::Priority, order),
+                TaskSort::Name => select.order_by(
+                    SimpleExpr::from(Func::lower(Expr::col(TaskColumn::Name))),
+                    order,
+                ),
+                TaskSort::Position => select.order_by(TaskColumn::Position, order),
+            }
+            .order_by_asc(TaskColumn::Id);
+        }
+
+        Ok(select)
     }
 
     pub async fn update(
         db: &DatabaseConnection,
         user_id: Uuid,
         id: Uuid,
-        body: TaskUpdateDto,
+        if_match: IfMatchDto,
+        mut body: TaskUpdateDto,
+        enforce_blockers: bool,
     ) -> ServiceResult<TaskReadDto> {
         let tx: DatabaseTransaction = db.begin().await?;
 
-        match TaskEntity::find_by_id(id).one(&tx).await? {
-            Some(value) => {
-                if value.user_id != user_id {
-                    return Err(ServiceError::Forbidden);
-                }
-            }
-            None => return Err(ServiceError::NotFound(id)),
-        };
+        let before: TaskModel = Self::find_for_update(&tx, user_id, id, false, if_match).await?;
+
+        let custom_fields: Option<HashMap<Uuid, Value>> = body.custom_fields.take();
+        let status_id: Option<Uuid> = body.status_id;
+        let category: Option<TaskStatus> = body.status.clone();
 
         let mut active_model: TaskActiveModel = body.into_active_model();
         active_model.id = Set(id);
 
+        Self::set_status(
+            &tx,
+            user_id,
+            Some(&before),
+            status_id,
+            category,
+            enforce_blockers,
+            &mut active_model,
+        )
+        .await?;
+
         let model: TaskModel = active_model.save(&tx).await?.try_into_model()?;
 
+        TaskEventService::record_task(&tx, Some(user_id), Some(&before), Some(&model)).await?;
+
+        if let Some(values) = custom_fields {
+            CustomFieldService::set_values(&tx, user_id, id, values).await?;
+        }
+
+        let schema: TaskReadDto = Self::schema(&tx, model).await?;
+
         tx.commit().await?;
 
-        let schema: TaskReadDto = TaskReadDto::from(model);
+        Ok(schema)
+    }
+
+    /// Moves the task into a status column next to the given neighbours. Only
+    /// the moved row is written.
+    pub async fn reposition(
+        db: &DatabaseConnection,
+        user_id: Uuid,
+        id: Uuid,
+        if_match: IfMatchDto,
+        body: TaskMoveDto,
+        enforce_blockers: bool,
+    ) -> ServiceResult<TaskReadDto> {
+        let tx: DatabaseTransaction = db.begin().await?;
+
+        let before: TaskModel = Self::find_for_update(&tx, user_id, id, false, if_match).await?;
+
+        let status: WorkflowStatusModel =
+            match WorkflowStatusService::resolve(&tx, user_id, Some(body.status_id), None).await? {
+                Some(value) => value,
+                None => return Err(ServiceError::NotFound(body.status_id)),
+            };
+
+        if enforce_blockers {
+            TaskDependencyService::check_status(&tx, id, &before.status, &status.category).await?;
+        }
+
+        let previous: Option<String> = match body.previous_id {
+            Some(value) => Some(Self::find_neighbour(&tx, user_id, id, value, status.id).await?),
+            None => None,
+        };
+        let next: Option<String> = match body.next_id {
+            Some(value) => Some(Self::find_neighbour(&tx, user_id, id, value, status.id).await?),
+            None => None,
+        };
+
+        // With one neighbour given the other one is whatever is adjacent now
+        let (previous, next) = match (previous, next) {
+            (Some(previous), None) => {
+                let next: Option<TaskModel> = Self::column(status.id, Some(id))
+                    .filter(TaskColumn::Position.gt(previous.clone()))
+                    .order_by_asc(TaskColumn::Position)
+                    .one(&tx)
+                    .await?;
+
+                (Some(previous), next.map(|model| model.position))
+            }
+            (None, Some(next)) => {
+                let previous: Option<TaskModel> = Self::column(status.id, Some(id))
+                    .filter(TaskColumn::Position.lt(next.clone()))
+                    .order_by_desc(TaskColumn::Position)
+                    .one(&tx)
+                    .await?;
+
+                (previous.map(|model| model.position), Some(next))
+            }
+            (None, None) => (
+                Some(Self::last_position(&tx, status.id, Some(id)).await?),
+                None,
+            ),
+            value => value,
+        };
+
+        let mut active_model: TaskActiveModel = before.clone().into_active_model();
+        active_model.status_id = Set(status.id);
+        active_model.status = Set(status.category);
+        active_model.position = Set(rank::between(previous.as_deref(), next.as_deref()));
+        active_model.updated_at = Set(Local::now().fixed_offset());
+
+        let model: TaskModel = active_model.update(&tx).await?;
+
+        TaskEventService::record_task(&tx, Some(user_id), Some(&before), Some(&model)).await?;
+
+        let schema: TaskReadDto = Self::schema(&tx, model).await?;
+
+        tx.commit().await?;
 
         Ok(schema)
     }
 
-    pub async fn delete(db: &DatabaseConnection, user_id: Uuid, id: Uuid) -> ServiceResult {
+    /// Returns active tasks grouped by the user's statuses in workflow order,
+    /// each column in rank order.
+    pub async fn kanban(
+        db: &DatabaseConnection,
+        user_id: Uuid,
+        limit: u64,
+    ) -> ServiceResult<Vec<TaskKanbanColumnDto>> {
         let tx: DatabaseTransaction = db.begin().await?;
 
-        let model: TaskModel = match TaskEntity::find_by_id(id).one(&tx).await? {
-            Some(value) => {
-                if value.user_id != user_id {
-                    return Err(ServiceError::Forbidden);
-                } else {
-                    value
+        let mut columns: Vec<TaskKanbanColumnDto> = Vec::new();
+
+        for status in WorkflowStatusService::list(&tx, user_id).await? {
+            let models: Vec<TaskModel> = Self::column(status.id, None)
+                .order_by_asc(TaskColumn::Position)
+                .order_by_asc(TaskColumn::Id)
+                .limit(limit)
+                .all(&tx)
+                .await?;
+
+            columns.push(TaskKanbanColumnDto {
+                tasks: Self::schemas(&tx, models).await?,
+                status,
+            });
+        }
+
+        tx.commit().await?;
+
+        Ok(columns)
+    }
+
+    /// Respaces every status column holding a rank longer than `max_length`,
+    /// returns how many columns were rewritten. Trashed tasks keep their rank.
+    pub async fn rebalance(db: &DatabaseConnection, max_length: usize) -> ServiceResult<u64> {
+        let tx: DatabaseTransaction = db.begin().await?;
+
+        let columns: Vec<Uuid> = TaskEntity::find()
+            .select_only()
+            .column(TaskColumn::StatusId)
+            .filter(TaskColumn::DeletedAt.is_null())
+            .filter(
+                Expr::expr(Func::char_length(Expr::col(TaskColumn::Position)))
+                    .gt(max_length as u64),
+            )
+            .distinct()
+            .into_tuple::<Uuid>()
+            .all(&tx)
+            .await?;
+
+        for status_id in &columns {
+            let models: Vec<TaskModel> = Self::column(*status_id, None)
+                .order_by_asc(TaskColumn::Position)
+                .order_by_asc(TaskColumn::Id)
+                .lock_exclusive()
+                .all(&tx)
+                .await?;
+
+            let positions: Vec<String> = rank::spread(models.len());
+
+            for (model, position) in models.into_iter().zip(positions) {
+                if model.position == position {
+                    continue;
                 }
+
+                let mut active_model: TaskActiveModel = model.into_active_model();
+                active_model.position = Set(position);
+                active_model.update(&tx).await?;
+            }
+        }
+
+        tx.commit().await?;
+
+        Ok(columns.len() as u64)
+    }
+
+    /// Moves the task to the trash, it stays restorable until purged.
+    pub async fn delete(
+        db: &DatabaseConnection,
+        user_id: Uuid,
+        id: Uuid,
+        if_match: IfMatchDto,
+    ) -> ServiceResult {
+        let tx: DatabaseTransaction = db.begin().await?;
+
+        let before: TaskModel = Self::find_for_update(&tx, user_id, id, false, if_match).await?;
+
+        let mut active_model: TaskActiveModel = before.clone().into_active_model();
+        active_model.deleted_at = Set(Some(Local::now().fixed_offset()));
+        active_model.updated_at = Set(Local::now().fixed_offset());
+
+        let model: TaskModel = active_model.update(&tx).await?;
+
+        TaskEventService::record_task(&tx, Some(user_id), Some(&before), Some(&model)).await?;
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    /// Parses a single line entry into a task and creates it unless it is a
+    /// dry run. Without a `//` part the whole line is the description.
+    pub async fn quick(
+        db: &DatabaseConnection,
+        user_id: Uuid,
+        body: TaskQuickDto,
+    ) -> ServiceResult<TaskQuickReadDto> {
+        let time_zone: Tz = UserService::time_zone(db, user_id).await?;
+        let parsed: QuickAdd = quick_add::parse(&body.text, Utc::now().with_timezone(&time_zone))?;
+        let priority: TaskPriority = match parsed.priority {
+            Some(value) => value,
+            None => {
+                UserPreferenceService::find(db, user_id)
+                    .await?
+                    .default_priority
+            }
+        };
+
+        let schema: TaskCreateDto = TaskCreateDto {
+            name: parsed.name,
+            description: parsed
+                .description
+                .unwrap_or_else(|| body.text.trim().to_string()),
+            status: None,
+            status_id: None,
+            deadline: parsed.deadline,
+            priority: Some(priority),
+            custom_fields: None,
+        };
+        schema.validate()?;
+
+        let task: Option<TaskReadDto> = match body.dry_run {
+            true => None,
+            false => Some(Self::create(db, user_id, schema.clone()).await?),
+        };
+
+        Ok(TaskQuickReadDto {
+            parsed: schema,
+            task,
+        })
+    }
+
+    /// Applies one operation to the listed or filtered tasks in a single
+    /// transaction. Tasks the user can't touch are reported and skipped.
+    pub async fn bulk(
+        db: &DatabaseConnection,
+        user_id: Uuid,
+        body: TaskBulkDto,
+        enforce_blockers: bool,
+    ) -> ServiceResult<Vec<TaskBulkResultDto>> {
+        let tx: DatabaseTransaction = db.begin().await?;
+
+        let ids: Vec<Uuid> = match (body.ids, body.filter) {
+            (Some(ids), None) => ids,
+            (None, Some(expression)) => {
+                let filter: Filter = filter::parse(&expression)?;
+                let time_zone: Tz = UserService::time_zone(&tx, user_id).await?;
+
+                TaskEntity::find()
+                    .select_only()
+                    .column(TaskColumn::Id)
+                    .filter(TaskColumn::UserId.eq(user_id))
+                    .filter(TaskColumn::DeletedAt.is_null())
+                    .filter(filter.condition(Utc::now().with_timezone(&time_zone)))
+                    .order_by_asc(TaskColumn::CreatedAt)
+                    .order_by_asc(TaskColumn::Id)
+                    .limit(constants::TASK_BULK_MAX_SIZE as u64)
+                    .into_tuple::<Uuid>()
+                    .all(&tx)
+                    .await?
+            }
+            _ => {
+                return Err(ServiceError::BadRequest(
+                    "Exactly one of ids or filter must be given".to_string(),
+                ))
             }
-            None => return Err(ServiceError::NotFound(id)),
         };
 
+        let mut results: Vec<TaskBulkResultDto> = Vec::with_capacity(ids.len());
+
+        for id in ids {
+            let checked: ServiceResult<(TaskModel, TaskActiveModel)> = async {
+                let before: TaskModel =
+                    Self::find_for_update(&tx, user_id, id, false, IfMatchDto::Any).await?;
+
+                let mut active_model: TaskActiveModel = before.clone().into_active_model();
+                active_model.updated_at = Set(Local::now().fixed_offset());
+
+                match &body.operation {
+                    TaskBulkOperationDto::SetStatus { status } => {
+                        Self::set_status(
+                            &tx,
+                            user_id,
+                            Some(&before),
+                            None,
+                            Some(status.clone()),
+                            enforce_blockers,
+                            &mut active_model,
+                        )
+                        .await?;
+                    }
+                    TaskBulkOperationDto::SetStatusId { status_id } => {
+                        Self::set_status(
+                            &tx,
+                            user_id,
+                            Some(&before),
+                            Some(*status_id),
+                            None,
+                            enforce_blockers,
+                            &mut active_model,
+                        )
+                        .await?;
+                    }
+                    TaskBulkOperationDto::SetPriority { priority } => {
+                        active_model.priority = Set(priority.clone());
+                    }
+                    TaskBulkOperationDto::SetDeadline { deadline } => {
+                        active_model.deadline = Set(deadline.as_ref().map(|value| value.value()));
+                        active_model.deadline_kind =
+                            Set(deadline.as_ref().map(|value| value.kind()));
+                    }
+                    TaskBulkOperationDto::Delete => {
+                        active_model.deleted_at = Set(Some(Local::now().fixed_offset()));
+                    }
+                }
+
+                Ok((before, active_model))
+            }
+            .await;
+
+            let (before, active_model) = match checked {
+                Ok(value) => value,
+                Err(
+                    err @ (ServiceError::Forbidden
+                    | ServiceError::NotFound(_)
+                    | ServiceError::Blocked(_)),
+                ) => {
+                    results.push(TaskBulkResultDto {
+                        id,
+                        status: err.status_code().as_u16(),
+                        task: None,
+                        error: Some(ErrorDto {
+                            detail: err.to_string(),
+                        }),
+                    });
+                    continue;
+                }
+                Err(err) => return Err(err),
+            };
+
+            let model: TaskModel = active_model.update(&tx).await?;
+
+            TaskEventService::record_task(&tx, Some(user_id), Some(&before), Some(&model)).await?;
+
+            results.push(match body.operation {
+                TaskBulkOperationDto::Delete => TaskBulkResultDto {
+                    id,
+                    status: StatusCode::NO_CONTENT.as_u16(),
+                    task: None,
+                    error: None,
+                },
+                _ => TaskBulkResultDto {
+                    id,
+                    status: StatusCode::OK.as_u16(),
+                    task: Some(Self::schema(&tx, model).await?),
+                    error: None,
+                },
+            });
+        }
+
+        tx.commit().await?;
+
+        Ok(results)
+    }
+
+    pub async fn list_trash(
+        db: &DatabaseConnection,
+        user_id: Uuid,
+        limit: u64,
+        offset: u64,
+    ) -> ServiceResult<Vec<TaskReadDto>> {
+        let tx: DatabaseTransaction = db.begin().await?;
+
+        let models: Vec<TaskModel> = TaskEntity::find()
+            .filter(TaskColumn::UserId.eq(user_id))
+            .filter(TaskColumn::DeletedAt.is_not_null())
+            .order_by_desc(TaskColumn::DeletedAt)
+            .limit(limit)
+            .offset(offset)
+            .all(&tx)
+            .await?;
+
+        Self::schemas(&tx, models).await
+    }
+
+    pub async fn restore(
+        db: &DatabaseConnection,
+        user_id: Uuid,
+        id: Uuid,
+        if_match: IfMatchDto,
+    ) -> ServiceResult<TaskReadDto> {
+        let tx: DatabaseTransaction = db.begin().await?;
+
+        let before: TaskModel = Self::find_for_update(&tx, user_id, id, true, if_match).await?;
+
+        let mut active_model: TaskActiveModel = before.clone().into_active_model();
+        active_model.deleted_at = Set(None);
+        active_model.updated_at = Set(Local::now().fixed_offset());
+
+        let model: TaskModel = active_model.update(&tx).await?;
+
+        TaskEventService::record_task(&tx, Some(user_id), Some(&before), Some(&model)).await?;
+
+        let schema: TaskReadDto = Self::schema(&tx, model).await?;
+
+        tx.commit().await?;
+
+        Ok(schema)
+    }
+
+    /// Permanently deletes a task that is already in the trash.
+    pub async fn purge(
+        db: &DatabaseConnection,
+        user_id: Uuid,
+        id: Uuid,
+        if_match: IfMatchDto,
+    ) -> ServiceResult {
+        let tx: DatabaseTransaction = db.begin().await?;
+
+        let model: TaskModel = Self::find_for_update(&tx, user_id, id, true, if_match).await?;
+
+        TaskEventService::record_task(&tx, Some(user_id), Some(&model), None).await?;
+
         model.delete(&tx).await?;
 
         tx.commit().await?;
 
         Ok(())
     }
+
+    /// Permanently deletes up to `limit` tasks trashed before `before`, returns
+    /// how many were removed.
+    pub async fn purge_expired(
+        db: &DatabaseConnection,
+        before: DateTime<FixedOffset>,
+        limit: u64,
+    ) -> ServiceResult<u64> {
+        let tx: DatabaseTransaction = db.begin().await?;
+
+        let models: Vec<TaskModel> = TaskEntity::find()
+            .filter(TaskColumn::DeletedAt.lt(before))
+            .limit(limit)
+            .lock_exclusive()
+            .all(&tx)
+            .await?;
+
+        let count: u64 = models.len() as u64;
+
+        for model in models {
+            TaskEventService::record_task(&tx, None, Some(&model), None).await?;
+
+            model.delete(&tx).await?;
+        }
+
+        tx.commit().await?;
+
+        Ok(count)
+    }
+
+    /// Converts tasks to their schemas with the fields stored apart from the
+    /// task row filled in and timestamps in the owner's time zone.
+    pub async fn schemas<C: ConnectionTrait>(
+        conn: &C,
+        models: Vec<TaskModel>,
+    ) -> ServiceResult<Vec<TaskReadDto>> {
+        let ids: Vec<Uuid> = models.iter().map(|model| model.id).collect::<Vec<Uuid>>();
+        let user_ids: Vec<Uuid> = models
+            .iter()
+            .map(|model| model.user_id)
+            .collect::<HashSet<Uuid>>()
+            .into_iter()
+            .collect::<Vec<Uuid>>();
+
+        let time_zones: HashMap<Uuid, Tz> = UserService::time_zones(conn, &user_ids).await?;
+        let blocked: HashSet<Uuid> = TaskDependencyService::blocked(conn, &ids).await?;
+        let mut custom_fields: HashMap<Uuid, HashMap<Uuid, Value>> =
+            CustomFieldService::values(conn, &ids).await?;
+
+        let schemas: Vec<TaskReadDto> = models
+            .into_iter()
+            .map(|model| {
+                let time_zone: Tz = time_zones.get(&model.user_id).copied().unwrap_or(Tz::UTC);
+
+                TaskReadDto {
+                    blocked: blocked.contains(&model.id),
+                    custom_fields: custom_fields.remove(&model.id).unwrap_or_default(),
+                    ..TaskReadDto::localized(model, &time_zone)
+                }
+            })
+            .collect::<Vec<TaskReadDto>>();
+
+        Ok(schemas)
+    }
+
+    /// Same as [`Self::schemas`] for a single task.
+    pub async fn schema<C: ConnectionTrait>(
+        conn: &C,
+        model: TaskModel,
+    ) -> ServiceResult<TaskReadDto> {
+        let mut schemas: Vec<TaskReadDto> = Self::schemas(conn, vec![model]).await?;
+
+        Ok(schemas.remove(0))
+    }
+
+    /// Resolves the requested status and, when the task changes column, points
+    /// the model at it and at the end of the new column. `before` is `None` for
+    /// a new task. A status given along with a category must belong to it.
+    async fn set_status(
+        tx: &DatabaseTransaction,
+        user_id: Uuid,
+        before: Option<&TaskModel>,
+        status_id: Option<Uuid>,
+        category: Option<TaskStatus>,
+        enforce_blockers: bool,
+        active_model: &mut TaskActiveModel,
+    ) -> ServiceResult {
+        // Asking for the category the task is already in keeps its status
+        if let (Some(before), None, Some(category)) = (before, status_id, &category) {
+            if &before.status == category {
+                active_model.status = Set(before.status.clone());
+                return Ok(());
+            }
+        }
+
+        let status: WorkflowStatusModel =
+            match WorkflowStatusService::resolve(tx, user_id, status_id, category.clone()).await? {
+                Some(value) => value,
+                None => return Ok(()),
+            };
+
+        Self::check_category(&status, category.as_ref())?;
+
+        if let Some(before) = before {
+            if before.status_id == status.id {
+                active_model.status = Set(before.status.clone());
+                return Ok(());
+            }
+
+            if enforce_blockers {
+                TaskDependencyService::check_status(
+                    tx,
+                    before.id,
+                    &before.status,
+                    &status.category,
+                )
+                .await?;
+            }
+        }
+
+        let position: String = Self::last_position(tx, status.id, None).await?;
+
+        active_model.status_id = Set(status.id);
+        active_model.status = Set(status.category);
+        active_model.position = Set(rank::between(Some(&position), None));
+
+        Ok(())
+    }
+
+    /// Rejects a `category` the resolved `status` does not belong to.
+    fn check_category(
+        status: &WorkflowStatusModel,
+        category: Option<&TaskStatus>,
+    ) -> ServiceResult {
+        match category {
+            Some(category) if &status.category != category => {
+                Err(ServiceError::BadRequest(format!(
+                    "Status {} is not in category {}",
+                    status.id,
+                    category.to_value()
+                )))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Active tasks of a status column, optionally without one of them.
+    fn column(status_id: Uuid, except: Option<Uuid>) -> Select<TaskEntity> {
+        let mut query: Select<TaskEntity> = TaskEntity::find()
+            .filter(TaskColumn::StatusId.eq(status_id))
+            .filter(TaskColumn::DeletedAt.is_null());
+
+        if let Some(value) = except {
+            query = query.filter(TaskColumn::Id.ne(value));
+        }
+
+        query
+    }
+
+    /// Highest rank in a status column, empty for an empty column.
+    async fn last_position(
+        tx: &DatabaseTransaction,
+        status_id: Uuid,
+        except: Option<Uuid>,
+    ) -> ServiceResult<String> {
+        let model: Option<TaskModel> = Self::column(status_id, except)
+            .order_by_desc(TaskColumn::Position)
+            .one(tx)
+            .await?;
+
+        Ok(model.map(|value| value.position).unwrap_or_default())
+    }
+
+    /// Returns the rank of a task a moved task is placed next to.
+    async fn find_neighbour(
+        tx: &DatabaseTransaction,
+        user_id: Uuid,
+        id: Uuid,
+        neighbour_id: Uuid,
+        status_id: Uuid,
+    ) -> ServiceResult<String> {
+        if neighbour_id == id {
+            return Err(ServiceError::BadRequest(
+                "Task can't be placed next to itself".to_string(),
+            ));
+        }
+
+        match TaskEntity::find_by_id(neighbour_id)
+            .filter(TaskColumn::DeletedAt.is_null())
+            .one(tx)
+            .await?
+        {
+            Some(value) => {
+                if value.user_id != user_id {
+                    return Err(ServiceError::Forbidden);
+                }
+                if value.status_id != status_id {
+                    return Err(ServiceError::BadRequest(format!(
+                        "Task with id={neighbour_id} is in another column"
+                    )));
+                }
+
+                Ok(value.position)
+            }
+            None => Err(ServiceError::NotFound(neighbour_id)),
+        }
+    }
+
+    /// Loads a task for modification, checking ownership, trash state and the
+    /// `If-Match` precondition, and locks its row until the transaction ends.
+    async fn find_for_update(
+        tx: &DatabaseTransaction,
+        user_id: Uuid,
+        id: Uuid,
+        trashed: bool,
+        if_match: IfMatchDto,
+    ) -> ServiceResult<TaskModel> {
+        match TaskEntity::find_by_id(id).lock_exclusive().one(tx).await? {
+            Some(value) => {
+                if value.user_id != user_id {
+                    return Err(ServiceError::Forbidden);
+                }
+                if value.deleted_at.is_some() != trashed {
+                    return Err(ServiceError::NotFound(id));
+                }
+                if_match.check(value.version)?;
+
+                Ok(value)
+            }
+            None => Err(ServiceError::NotFound(id)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn status(category: TaskStatus) -> WorkflowStatusModel {
+        let now: DateTime<FixedOffset> =
+            DateTime::parse_from_rfc3339("2026-10-19T14:00:00+03:00").unwrap();
+
+        WorkflowStatusModel {
+            id: Uuid::nil(),
+            user_id: Uuid::nil(),
+            name: "Review".to_string(),
+            category,
+            position: 0,
+            updated_at: now,
+            created_at: now,
+        }
+    }
+
+    #[test]
+    fn status_alone_is_accepted() {
+        assert!(TaskService::check_category(&status(TaskStatus::InProgress), None).is_ok());
+    }
+
+    #[test]
+    fn status_in_category_is_accepted() {
+        assert!(TaskService::check_category(
+            &status(TaskStatus::InProgress),
+            Some(&TaskStatus::InProgress)
+        )
+        .is_ok());
+    }
+
+    #[test]
+    fn status_outside_category_is_rejected() {
+        match TaskService::check_category(&status(TaskStatus::InProgress), Some(&TaskStatus::Done))
+        {
+            Err(ServiceError::BadRequest(message)) => assert_eq!(
+                message,
+                "Status 00000000-0000-0000-0000-000000000000 is not in category done"
+            ),
+            other => panic!("expected a bad request, got {other:?}"),
+        }
+    }
 }
@@ -1,17 +1,25 @@
+use chrono::{DateTime, FixedOffset, Local};
 use sea_orm::{
     ActiveModelTrait, ColumnTrait, DatabaseConnection, DatabaseTransaction, EntityTrait,
-    IntoActiveModel, ModelTrait, QueryFilter, QuerySelect, Set, TransactionTrait, TryIntoModel,
+    IntoActiveModel, ModelTrait, QueryFilter, QueryOrder, QuerySelect, Set, TransactionTrait,
+    TryIntoModel,
 };
 use uuid::Uuid;
 
 use crate::{
-    dto::task::{TaskCommentCreateDto, TaskCommentReadDto, TaskCommentUpdateDto},
+    dto::{
+        precondition::IfMatchDto,
+        task::{TaskCommentCreateDto, TaskCommentReadDto, TaskCommentUpdateDto},
+    },
     entity::prelude::{
-        TaskCommentActiveModel, TaskCommentColumn, TaskCommentEntity, TaskCommentModel, TaskEntity,
+        TaskColumn, TaskCommentActiveModel, TaskCommentColumn, TaskCommentEntity, TaskCommentModel,
+        TaskEntity,
     },
     error::service::{ServiceError, ServiceResult},
 };
 
+use super::task_event::TaskEventService;
+
 pub struct TaskCommentService;
 
 impl TaskCommentService {
@@ -23,14 +31,7 @@ impl TaskCommentService {
     ) -> ServiceResult<TaskCommentReadDto> {
         let tx: DatabaseTransaction = db.begin().await?;
 
-        match TaskEntity::find_by_id(task_id).one(&tx).await? {
-            Some(value) => {
-                if value.user_id != user_id {
-                    return Err(ServiceError::Forbidden);
-                }
-            }
-            None => return Err(ServiceError::NotFound(task_id)),
-        }
+        Self::check_task(&tx, user_id, task_id).await?;
 
         let mut active_model: TaskCommentActiveModel = body.into_active_model();
         active_model.user_id = Set(user_id);
@@ -38,6 +39,8 @@ impl TaskCommentService {
 
         let model: TaskCommentModel = active_model.save(&tx).await?.try_into_model()?;
 
+        TaskEventService::record_comment(&tx, Some(user_id), None, Some(&model)).await?;
+
         tx.commit().await?;
 
         let schema: TaskCommentReadDto = TaskCommentReadDto::from(model);
@@ -45,26 +48,46 @@ impl TaskCommentService {
         Ok(schema)
     }
 
-    pub async fn list(
+    pub async fn get_by_id(
         db: &DatabaseConnection,
         user_id: Uuid,
         task_id: Uuid,
-        limit: u64,
-        offset: u64,
-    ) -> ServiceResult<Vec<TaskCommentReadDto>> {
+        id: Uuid,
+    ) -> ServiceResult<TaskCommentReadDto> {
         let tx: DatabaseTransaction = db.begin().await?;
 
-        match TaskEntity::find_by_id(task_id).one(&tx).await? {
+        Self::check_task(&tx, user_id, task_id).await?;
+
+        match TaskCommentEntity::find_by_id(id)
+            .filter(TaskCommentColumn::DeletedAt.is_null())
+            .one(&tx)
+            .await?
+        {
             Some(value) => {
-                if value.user_id != user_id {
+                if (value.user_id != user_id) | (value.task_id != task_id) {
                     return Err(ServiceError::Forbidden);
                 }
+
+                Ok(TaskCommentReadDto::from(value))
             }
-            None => return Err(ServiceError::NotFound(task_id)),
+            None => Err(ServiceError::NotFound(id)),
         }
+    }
+
+    pub async fn list(
+        db: &DatabaseConnection,
+        user_id: Uuid,
+        task_id: Uuid,
+        limit: u64,
+        offset: u64,
+    ) -> ServiceResult<Vec<TaskCommentReadDto>> {
+        let tx: DatabaseTransaction = db.begin().await?;
+
+        Self::check_task(&tx, user_id, task_id).await?;
 
         let models: Vec<TaskCommentModel> = TaskCommentEntity::find()
             .filter(TaskCommentColumn::TaskId.eq(task_id))
+            .filter(TaskCommentColumn::DeletedAt.is_null())
             .limit(limit)
             .offset(offset)
             .all(&tx)
@@ -72,7 +95,7 @@ impl TaskCommentService {
 
         let schemas: Vec<TaskCommentReadDto> = models
             .into_iter()
-            .map(|model| TaskCommentReadDto::from(model))
+            .map(TaskCommentReadDto::from)
             .collect::<Vec<TaskCommentReadDto>>();
 
         Ok(schemas)
@@ -83,21 +106,13 @@ impl TaskCommentService {
         user_id: Uuid,
         task_id: Uuid,
         id: Uuid,
+        if_match: IfMatchDto,
         body: TaskCommentUpdateDto,
     ) -> ServiceResult<TaskCommentReadDto> {
         let tx: DatabaseTransaction = db.begin().await?;
 
-        match TaskEntity::find_by_id(task_id).one(&tx).await? {
-            Some(value) => {
-                if value.user_id != user_id {
-                    return Err(ServiceError::Forbidden);
-                }
-                if let None = TaskCommentEntity::find_by_id(id).one(&tx).await? {
-                    return Err(ServiceError::NotFound(id));
-                }
-            }
-            None => return Err(ServiceError::NotFound(task_id)),
-        }
+        let before: TaskCommentModel =
+            Self::find_for_update(&tx, user_id, Some(task_id), id, false, if_match).await?;
 
         let mut active_model: TaskCommentActiveModel = body.into_active_model();
         active_model.user_id = Set(user_id);
@@ -106,6 +121,8 @@ impl TaskCommentService {
 
         let model: TaskCommentModel = active_model.save(&tx).await?.try_into_model()?;
 
+        TaskEventService::record_comment(&tx, Some(user_id), Some(&before), Some(&model)).await?;
+
         tx.commit().await?;
 
         let schema: TaskCommentReadDto = TaskCommentReadDto::from(model);
@@ -113,29 +130,184 @@ impl TaskCommentService {
         Ok(schema)
     }
 
+    /// Moves the comment to the trash, it stays restorable until purged.
     pub async fn delete(
         db: &DatabaseConnection,
         user_id: Uuid,
         task_id: Uuid,
         id: Uuid,
+        if_match: IfMatchDto,
+    ) -> ServiceResult {
+        let tx: DatabaseTransaction = db.begin().await?;
+
+        let before: TaskCommentModel =
+            Self::find_for_update(&tx, user_id, Some(task_id), id, false, if_match).await?;
+
+        let mut active_model: TaskCommentActiveModel = before.clone().into_active_model();
+        active_model.deleted_at = Set(Some(Local::now().fixed_offset()));
+        active_model.updated_at = Set(Local::now().fixed_offset());
+
+        let model: TaskCommentModel = active_model.update(&tx).await?;
+
+        TaskEventService::record_comment(&tx, Some(user_id), Some(&before), Some(&model)).await?;
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    /// Lists trashed comments of tasks that are not in the trash themselves.
+    pub async fn list_trash(
+        db: &DatabaseConnection,
+        user_id: Uuid,
+        limit: u64,
+        offset: u64,
+    ) -> ServiceResult<Vec<TaskCommentReadDto>> {
+        let tx: DatabaseTransaction = db.begin().await?;
+
+        let models: Vec<TaskCommentModel> = TaskCommentEntity::find()
+            .inner_join(TaskEntity)
+            .filter(TaskCommentColumn::UserId.eq(user_id))
+            .filter(TaskCommentColumn::DeletedAt.is_not_null())
+            .filter(TaskColumn::DeletedAt.is_null())
+            .order_by_desc(TaskCommentColumn::DeletedAt)
+            .limit(limit)
+            .offset(offset)
+            .all(&tx)
+            .await?;
+
+        let schemas: Vec<TaskCommentReadDto> = models
+            .into_iter()
+            .map(TaskCommentReadDto::from)
+            .collect::<Vec<TaskCommentReadDto>>();
+
+        Ok(schemas)
+    }
+
+    pub async fn restore(
+        db: &DatabaseConnection,
+        user_id: Uuid,
+        id: Uuid,
+        if_match: IfMatchDto,
+    ) -> ServiceResult<TaskCommentReadDto> {
+        let tx: DatabaseTransaction = db.begin().await?;
+
+        let before: TaskCommentModel =
+            Self::find_for_update(&tx, user_id, None, id, true, if_match).await?;
+
+        let mut active_model: TaskCommentActiveModel = before.clone().into_active_model();
+        active_model.deleted_at = Set(None);
+        active_model.updated_at = Set(Local::now().fixed_offset());
+
+        let model: TaskCommentModel = active_model.update(&tx).await?;
+
+        TaskEventService::record_comment(&tx, Some(user_id), Some(&before), Some(&model)).await?;
+
+        tx.commit().await?;
+
+        let schema: TaskCommentReadDto = TaskCommentReadDto::from(model);
+
+        Ok(schema)
+    }
+
+    /// Permanently deletes a comment that is already in the trash.
+    pub async fn purge(
+        db: &DatabaseConnection,
+        user_id: Uuid,
+        id: Uuid,
+        if_match: IfMatchDto,
     ) -> ServiceResult {
         let tx: DatabaseTransaction = db.begin().await?;
 
-        let model: TaskCommentModel = match TaskCommentEntity::find_by_id(id).one(&tx).await? {
+        let model: TaskCommentModel =
+            Self::find_for_update(&tx, user_id, None, id, true, if_match).await?;
+
+        TaskEventService::record_comment(&tx, Some(user_id), Some(&model), None).await?;
+
+        model.delete(&tx).await?;
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    /// Permanently deletes up to `limit` comments trashed before `before`,
+    /// returns how many were removed.
+    pub async fn purge_expired(
+        db: &DatabaseConnection,
+        before: DateTime<FixedOffset>,
+        limit: u64,
+    ) -> ServiceResult<u64> {
+        let tx: DatabaseTransaction = db.begin().await?;
+
+        let models: Vec<TaskCommentModel> = TaskCommentEntity::find()
+            .filter(TaskCommentColumn::DeletedAt.lt(before))
+            .limit(limit)
+            .lock_exclusive()
+            .all(&tx)
+            .await?;
+
+        let count: u64 = models.len() as u64;
+
+        for model in models {
+            TaskEventService::record_comment(&tx, None, Some(&model), None).await?;
+
+            model.delete(&tx).await?;
+        }
+
+        tx.commit().await?;
+
+        Ok(count)
+    }
+
+    /// Checks that the task exists, is not trashed and belongs to the user.
+    async fn check_task(tx: &DatabaseTransaction, user_id: Uuid, task_id: Uuid) -> ServiceResult {
+        match TaskEntity::find_by_id(task_id)
+            .filter(TaskColumn::DeletedAt.is_null())
+            .one(tx)
+            .await?
+        {
             Some(value) => {
-                if (value.user_id != user_id) | (value.task_id != task_id) {
+                if value.user_id != user_id {
                     return Err(ServiceError::Forbidden);
-                } else {
-                    value
                 }
+
+                Ok(())
             }
+            None => Err(ServiceError::NotFound(task_id)),
+        }
+    }
+
+    /// Loads a comment for modification, checking ownership, trash state and
+    /// the `If-Match` precondition, and locks its row until the transaction ends.
+    async fn find_for_update(
+        tx: &DatabaseTransaction,
+        user_id: Uuid,
+        task_id: Option<Uuid>,
+        id: Uuid,
+        trashed: bool,
+        if_match: IfMatchDto,
+    ) -> ServiceResult<TaskCommentModel> {
+        let model: TaskCommentModel = match TaskCommentEntity::find_by_id(id)
+            .lock_exclusive()
+            .one(tx)
+            .await?
+        {
+            Some(value) => value,
             None => return Err(ServiceError::NotFound(id)),
         };
 
-        model.delete(&tx).await?;
+        if (model.user_id != user_id) | task_id.is_some_and(|value| value != model.task_id) {
+            return Err(ServiceError::Forbidden);
+        }
+        if model.deleted_at.is_some() != trashed {
+            return Err(ServiceError::NotFound(id));
+        }
 
-        tx.commit().await?;
+        Self::check_task(tx, user_id, model.task_id).await?;
 
-        Ok(())
+        if_match.check(model.version)?;
+
+        Ok(model)
     }
 }
@@ -0,0 +1,262 @@
+use std::collections::HashSet;
+
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseConnection, DatabaseTransaction,
+    DbBackend, EntityTrait, JoinType, ModelTrait, QueryFilter, QueryResult, QuerySelect,
+    QueryTrait, RelationTrait, Select, Set, Statement, TransactionTrait,
+};
+use uuid::Uuid;
+
+use crate::{
+    dto::task::{TaskDependencyCreateDto, TaskDependencyReadDto, TaskReadDto},
+    entity::{
+        prelude::{
+            TaskColumn, TaskDependencyActiveModel, TaskDependencyColumn, TaskDependencyEntity,
+            TaskDependencyModel, TaskEntity, TaskModel,
+        },
+        sea_orm_active_enums::TaskStatus,
+        task_dependency::Relation as TaskDependencyRelation,
+    },
+    error::service::{ServiceError, ServiceResult},
+};
+
+use super::task::TaskService;
+
+/// Whether `$2` can be reached from `$1` by following blockers.
+const REACHES_SQL: &str = r#"
+WITH RECURSIVE chain(id) AS (
+    SELECT blocker_id FROM task_dependency WHERE task_id = $1
+    UNION
+    SELECT d.blocker_id FROM task_dependency d JOIN chain c ON d.task_id = c.id
+)
+SELECT EXISTS(SELECT 1 FROM chain WHERE id = $2) AS "exists"
+"#;
+
+pub struct TaskDependencyService;
+
+impl TaskDependencyService {
+    /// Marks `task_id` as blocked by `body.blocker_id`, refusing links that
+    /// would close a cycle.
+    pub async fn create(
+        db: &DatabaseConnection,
+        user_id: Uuid,
+        task_id: Uuid,
+        body: TaskDependencyCreateDto,
+    ) -> ServiceResult<TaskDependencyReadDto> {
+        if task_id == body.blocker_id {
+            return Err(ServiceError::BadRequest(
+                "Task can't block itself".to_string(),
+            ));
+        }
+
+        let tx: DatabaseTransaction = db.begin().await?;
+
+        // Two concurrent links could each pass the cycle check and close one together
+        tx.execute(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            "SELECT pg_advisory_xact_lock(hashtextextended($1, 0))",
+            [user_id.to_string().into()],
+        ))
+        .await?;
+
+        Self::check_task(&tx, user_id, task_id).await?;
+        Self::check_task(&tx, user_id, body.blocker_id).await?;
+
+        if TaskDependencyEntity::find_by_id((task_id, body.blocker_id))
+            .one(&tx)
+            .await?
+            .is_some()
+        {
+            return Err(ServiceError::Conflict {
+                field: "blocker_id".to_string(),
+                value: body.blocker_id.to_string(),
+            });
+        }
+
+        if Self::reaches(&tx, body.blocker_id, task_id).await? {
+            return Err(ServiceError::BadRequest(
+                "Dependency would create a cycle".to_string(),
+            ));
+        }
+
+        let model: TaskDependencyModel = TaskDependencyActiveModel {
+            task_id: Set(task_id),
+            blocker_id: Set(body.blocker_id),
+            user_id: Set(user_id),
+            ..Default::default()
+        }
+        .insert(&tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(TaskDependencyReadDto::from(model))
+    }
+
+    /// Tasks that block `task_id`.
+    pub async fn list_blockers(
+        db: &DatabaseConnection,
+        user_id: Uuid,
+        task_id: Uuid,
+    ) -> ServiceResult<Vec<TaskReadDto>> {
+        let tx: DatabaseTransaction = db.begin().await?;
+
+        Self::check_task(&tx, user_id, task_id).await?;
+
+        let models: Vec<TaskModel> = TaskEntity::find()
+            .filter(
+                TaskColumn::Id.in_subquery(
+                    TaskDependencyEntity::find()
+                        .select_only()
+                        .column(TaskDependencyColumn::BlockerId)
+                        .filter(TaskDependencyColumn::TaskId.eq(task_id))
+                        .into_query(),
+                ),
+            )
+            .filter(TaskColumn::DeletedAt.is_null())
+            .all(&tx)
+            .await?;
+
+        TaskService::schemas(&tx, models).await
+    }
+
+    /// Tasks that `task_id` blocks.
+    pub async fn list_dependents(
+        db: &DatabaseConnection,
+        user_id: Uuid,
+        task_id: Uuid,
+    ) -> ServiceResult<Vec<TaskReadDto>> {
+        let tx: DatabaseTransaction = db.begin().await?;
+
+        Self::check_task(&tx, user_id, task_id).await?;
+
+        let models: Vec<TaskModel> = TaskEntity::find()
+            .filter(
+                TaskColumn::Id.in_subquery(
+                    TaskDependencyEntity::find()
+                        .select_only()
+                        .column(TaskDependencyColumn::TaskId)
+                        .filter(TaskDependencyColumn::BlockerId.eq(task_id))
+                        .into_query(),
+                ),
+            )
+            .filter(TaskColumn::DeletedAt.is_null())
+            .all(&tx)
+            .await?;
+
+        TaskService::schemas(&tx, models).await
+    }
+
+    pub async fn delete(
+        db: &DatabaseConnection,
+        user_id: Uuid,
+        task_id: Uuid,
+        blocker_id: Uuid,
+    ) -> ServiceResult {
+        let tx: DatabaseTransaction = db.begin().await?;
+
+        match TaskDependencyEntity::find_by_id((task_id, blocker_id))
+            .one(&tx)
+            .await?
+        {
+            Some(value) => {
+                if value.user_id != user_id {
+                    return Err(ServiceError::Forbidden);
+                }
+
+                value.delete(&tx).await?;
+            }
+            None => return Err(ServiceError::NotFound(blocker_id)),
+        }
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    /// Ids of the given tasks that an unfinished task blocks.
+    pub async fn blocked<C: ConnectionTrait>(
+        conn: &C,
+        task_ids: &[Uuid],
+    ) -> ServiceResult<HashSet<Uuid>> {
+        if task_ids.is_empty() {
+            return Ok(HashSet::new());
+        }
+
+        Ok(Self::unfinished_blockers()
+            .filter(TaskDependencyColumn::TaskId.is_in(task_ids.iter().copied()))
+            .distinct()
+            .into_tuple::<Uuid>()
+            .all(conn)
+            .await?
+            .into_iter()
+            .collect::<HashSet<Uuid>>())
+    }
+
+    /// Ids of tasks with at least one blocker that is neither done nor trashed.
+    pub fn unfinished_blockers() -> Select<TaskDependencyEntity> {
+        TaskDependencyEntity::find()
+            .select_only()
+            .column(TaskDependencyColumn::TaskId)
+            .join(JoinType::InnerJoin, TaskDependencyRelation::Blocker.def())
+            .filter(TaskColumn::Status.ne(TaskStatus::Done))
+            .filter(TaskColumn::DeletedAt.is_null())
+    }
+
+    /// Fails when a blocked task moves from `from` to `InProgress` or `Done`.
+    pub async fn check_status<C: ConnectionTrait>(
+        conn: &C,
+        id: Uuid,
+        from: &TaskStatus,
+        to: &TaskStatus,
+    ) -> ServiceResult {
+        if from == to || to == &TaskStatus::ToDo {
+            return Ok(());
+        }
+
+        if Self::unfinished_blockers()
+            .filter(TaskDependencyColumn::TaskId.eq(id))
+            .into_tuple::<Uuid>()
+            .one(conn)
+            .await?
+            .is_some()
+        {
+            return Err(ServiceError::Blocked(id));
+        }
+
+        Ok(())
+    }
+
+    async fn reaches(tx: &DatabaseTransaction, from: Uuid, to: Uuid) -> ServiceResult<bool> {
+        let row: Option<QueryResult> = tx
+            .query_one(Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                REACHES_SQL,
+                [from.into(), to.into()],
+            ))
+            .await?;
+
+        match row {
+            Some(value) => Ok(value.try_get::<bool>("", "exists")?),
+            None => Ok(false),
+        }
+    }
+
+    /// Checks that the task exists, is not trashed and belongs to the user.
+    async fn check_task(tx: &DatabaseTransaction, user_id: Uuid, task_id: Uuid) -> ServiceResult {
+        match TaskEntity::find_by_id(task_id)
+            .filter(TaskColumn::DeletedAt.is_null())
+            .one(tx)
+            .await?
+        {
+            Some(value) => {
+                if value.user_id != user_id {
+                    return Err(ServiceError::Forbidden);
+                }
+
+                Ok(())
+            }
+            None => Err(ServiceError::NotFound(task_id)),
+        }
+    }
+}
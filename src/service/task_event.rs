@@ -0,0 +1,209 @@
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, DatabaseTransaction, EntityTrait,
+    QueryFilter, QueryOrder, QuerySelect, Set, TransactionTrait,
+};
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+use uuid::Uuid;
+
+use crate::{
+    dto::task::{TaskCommentReadDto, TaskEventReadDto, TaskReadDto},
+    entity::{
+        prelude::{
+            TaskCommentModel, TaskEntity, TaskEventActiveModel, TaskEventColumn, TaskEventEntity,
+            TaskEventModel, TaskModel,
+        },
+        sea_orm_active_enums::TaskEventAction,
+    },
+    error::service::{ServiceError, ServiceResult},
+};
+
+/// Fields that change on every write or are already told by the action.
+const IGNORED_FIELDS: [&str; 7] = [
+    "id",
+    "task_id",
+    "version",
+    "created_at",
+    "updated_at",
+    "deleted_at",
+    "position",
+];
+
+pub struct TaskEventService;
+
+impl TaskEventService {
+    /// Records a task change inside the transaction that makes it. `before` is
+    /// `None` for a creation and `after` is `None` for a permanent deletion.
+    /// `actor_id` is `None` for changes made by the server itself.
+    pub async fn record_task(
+        tx: &DatabaseTransaction,
+        actor_id: Option<Uuid>,
+        before: Option<&TaskModel>,
+        after: Option<&TaskModel>,
+    ) -> ServiceResult {
+        let model: &TaskModel = match after.or(before) {
+            Some(value) => value,
+            None => return Ok(()),
+        };
+
+        let action: TaskEventAction = Self::action(
+            before.map(|value| value.deleted_at.is_some()),
+            after.map(|value| value.deleted_at.is_some()),
+        );
+
+        Self::record(
+            tx,
+            TaskEventActiveModel {
+                task_id: Set(model.id),
+                comment_id: Set(None),
+                user_id: Set(model.user_id),
+                actor_id: Set(actor_id),
+                action: Set(action),
+                ..Default::default()
+            },
+            before.cloned().map(TaskReadDto::from),
+            after.cloned().map(TaskReadDto::from),
+        )
+        .await
+    }
+
+    /// Same as [`Self::record_task`] for comments, attributed to their task.
+    pub async fn record_comment(
+        tx: &DatabaseTransaction,
+        actor_id: Option<Uuid>,
+        before: Option<&TaskCommentModel>,
+        after: Option<&TaskCommentModel>,
+    ) -> ServiceResult {
+        let model: &TaskCommentModel = match after.or(before) {
+            Some(value) => value,
+            None => return Ok(()),
+        };
+
+        let action: TaskEventAction = Self::action(
+            before.map(|value| value.deleted_at.is_some()),
+            after.map(|value| value.deleted_at.is_some()),
+        );
+
+        Self::record(
+            tx,
+            TaskEventActiveModel {
+                task_id: Set(model.task_id),
+                comment_id: Set(Some(model.id)),
+                user_id: Set(model.user_id),
+                actor_id: Set(actor_id),
+                action: Set(action),
+                ..Default::default()
+            },
+            before.cloned().map(TaskCommentReadDto::from),
+            after.cloned().map(TaskCommentReadDto::from),
+        )
+        .await
+    }
+
+    pub async fn list(
+        db: &DatabaseConnection,
+        user_id: Uuid,
+        task_id: Uuid,
+        limit: u64,
+        offset: u64,
+    ) -> ServiceResult<Vec<TaskEventReadDto>> {
+        let tx: DatabaseTransaction = db.begin().await?;
+
+        // History of a deleted task stays readable by its former owner
+        let exists: bool = match TaskEntity::find_by_id(task_id).one(&tx).await? {
+            Some(value) => {
+                if value.user_id != user_id {
+                    return Err(ServiceError::Forbidden);
+                }
+
+                true
+            }
+            None => false,
+        };
+
+        let models: Vec<TaskEventModel> = TaskEventEntity::find()
+            .filter(TaskEventColumn::TaskId.eq(task_id))
+            .filter(TaskEventColumn::UserId.eq(user_id))
+            .order_by_desc(TaskEventColumn::CreatedAt)
+            .order_by_desc(TaskEventColumn::Id)
+            .limit(limit)
+            .offset(offset)
+            .all(&tx)
+            .await?;
+
+        if !exists && models.is_empty() {
+            return Err(ServiceError::NotFound(task_id));
+        }
+
+        let schemas: Vec<TaskEventReadDto> = models
+            .into_iter()
+            .map(TaskEventReadDto::from)
+            .collect::<Vec<TaskEventReadDto>>();
+
+        Ok(schemas)
+    }
+
+    /// Derives the action from whether the record existed and was trashed
+    /// before and after the change.
+    fn action(before: Option<bool>, after: Option<bool>) -> TaskEventAction {
+        match (before, after) {
+            (None, _) => TaskEventAction::Create,
+            (Some(_), None) => TaskEventAction::Purge,
+            (Some(false), Some(true)) => TaskEventAction::Delete,
+            (Some(true), Some(false)) => TaskEventAction::Restore,
+            _ => TaskEventAction::Update,
+        }
+    }
+
+    async fn record<T: Serialize>(
+        tx: &DatabaseTransaction,
+        mut active_model: TaskEventActiveModel,
+        before: Option<T>,
+        after: Option<T>,
+    ) -> ServiceResult {
+        let changes: Map<String, Value> = Self::diff(before, after)?;
+
+        // Nothing but bookkeeping fields changed
+        if changes.is_empty() && active_model.action.as_ref() == &TaskEventAction::Update {
+            return Ok(());
+        }
+
+        active_model.changes = Set(Value::Object(changes));
+        active_model.insert(tx).await?;
+
+        Ok(())
+    }
+
+    fn diff<T: Serialize>(
+        before: Option<T>,
+        after: Option<T>,
+    ) -> ServiceResult<Map<String, Value>> {
+        let to_map = |value: Option<T>| -> ServiceResult<Map<String, Value>> {
+            match value.map(serde_json::to_value).transpose() {
+                Ok(Some(Value::Object(map))) => Ok(map),
+                Ok(_) => Ok(Map::new()),
+                Err(err) => Err(ServiceError::Unknow(err.to_string())),
+            }
+        };
+
+        let before: Map<String, Value> = to_map(before)?;
+        let after: Map<String, Value> = to_map(after)?;
+
+        let mut changes: Map<String, Value> = Map::new();
+
+        for key in before.keys().chain(after.keys()) {
+            if IGNORED_FIELDS.contains(&key.as_str()) || changes.contains_key(key) {
+                continue;
+            }
+
+            let old: &Value = before.get(key).unwrap_or(&Value::Null);
+            let new: &Value = after.get(key).unwrap_or(&Value::Null);
+
+            if old != new {
+                changes.insert(key.clone(), json!({ "before": old, "after": new }));
+            }
+        }
+
+        Ok(changes)
+    }
+}
@@ -0,0 +1,376 @@
+use std::collections::HashMap;
+
+use chrono::{Local, Utc};
+use chrono_tz::Tz;
+use garde::Validate;
+use sea_orm::{
+    sea_query::LockBehavior, sea_query::LockType, ActiveModelTrait, ColumnTrait,
+    DatabaseConnection, DatabaseTransaction, EntityTrait, IntoActiveModel, ModelTrait, QueryFilter,
+    QueryOrder, QuerySelect, Set, TransactionTrait,
+};
+use uuid::Uuid;
+
+use crate::{
+    dto::{
+        task::TaskCommentCreateDto,
+        task_import::{
+            CsvMappingDto, TaskImportCreateDto, TaskImportReadDto, TaskImportRowDto,
+            TaskImportRowStatus,
+        },
+    },
+    entity::{
+        prelude::{
+            TaskActiveModel, TaskColumn, TaskCommentActiveModel, TaskCommentModel, TaskEntity,
+            TaskImportActiveModel, TaskImportColumn, TaskImportEntity, TaskImportModel, TaskModel,
+            UserEntity, UserModel, WorkflowStatusColumn, WorkflowStatusEntity,
+        },
+        sea_orm_active_enums::ImportStatus,
+    },
+    error::service::{ServiceError, ServiceResult},
+};
+
+use super::{
+    common,
+    import::{self, ImportContext, ImportRow, ImportTask},
+    task::TaskService,
+    task_event::TaskEventService,
+};
+
+pub struct TaskImportService;
+
+impl TaskImportService {
+    /// Queues an import, it runs in the background.
+    pub async fn create(
+        db: &DatabaseConnection,
+        user_id: Uuid,
+        body: TaskImportCreateDto,
+    ) -> ServiceResult<TaskImportReadDto> {
+        let tx: DatabaseTransaction = db.begin().await?;
+
+        let model: TaskImportModel = TaskImportActiveModel {
+            user_id: Set(user_id),
+            format: Set(body.format),
+            dry_run: Set(body.dry_run),
+            content: Set(Some(body.content)),
+            mapping: Set(body
+                .mapping
+                .map(|value| serde_json::to_value(value).unwrap_or_default())),
+            ..Default::default()
+        }
+        .insert(&tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(TaskImportReadDto::from(model))
+    }
+
+    /// Queues the file of a finished dry run for a real import.
+    pub async fn apply(
+        db: &DatabaseConnection,
+        user_id: Uuid,
+        id: Uuid,
+    ) -> ServiceResult<TaskImportReadDto> {
+        let tx: DatabaseTransaction = db.begin().await?;
+
+        let model: TaskImportModel = Self::find(&tx, user_id, id).await?;
+
+        let content: String = match (model.dry_run, &model.status, model.content) {
+            (true, ImportStatus::Done, Some(value)) => value,
+            _ => {
+                return Err(ServiceError::BadRequest(
+                    "Only a finished dry run can be applied".to_string(),
+                ))
+            }
+        };
+
+        let model: TaskImportModel = TaskImportActiveModel {
+            user_id: Set(user_id),
+            format: Set(model.format),
+            content: Set(Some(content)),
+            mapping: Set(model.mapping),
+            ..Default::default()
+        }
+        .insert(&tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(TaskImportReadDto::from(model))
+    }
+
+    pub async fn list(
+        db: &DatabaseConnection,
+        user_id: Uuid,
+    ) -> ServiceResult<Vec<TaskImportReadDto>> {
+        let tx: DatabaseTransaction = db.begin().await?;
+
+        let models: Vec<TaskImportModel> = TaskImportEntity::find()
+            .filter(TaskImportColumn::UserId.eq(user_id))
+            .order_by_desc(TaskImportColumn::CreatedAt)
+            .all(&tx)
+            .await?;
+
+        let schemas: Vec<TaskImportReadDto> = models
+            .into_iter()
+            .map(TaskImportReadDto::from)
+            .collect::<Vec<TaskImportReadDto>>();
+
+        Ok(schemas)
+    }
+
+    pub async fn get_by_id(
+        db: &DatabaseConnection,
+        user_id: Uuid,
+        id: Uuid,
+    ) -> ServiceResult<TaskImportReadDto> {
+        let tx: DatabaseTransaction = db.begin().await?;
+
+        Ok(TaskImportReadDto::from(Self::find(&tx, user_id, id).await?))
+    }
+
+    /// Removes the report of an import, the tasks it created stay.
+    pub async fn delete(db: &DatabaseConnection, user_id: Uuid, id: Uuid) -> ServiceResult {
+        let tx: DatabaseTransaction = db.begin().await?;
+
+        Self::find(&tx, user_id, id).await?.delete(&tx).await?;
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    /// Runs the oldest waiting import. Returns `false` when none waits.
+    /// Concurrent runs skip the import another one is running.
+    pub async fn process_next(db: &DatabaseConnection) -> ServiceResult<bool> {
+        let tx: DatabaseTransaction = db.begin().await?;
+
+        let model: TaskImportModel = match TaskImportEntity::find()
+            .filter(TaskImportColumn::Status.eq(ImportStatus::Pending))
+            .order_by_asc(TaskImportColumn::CreatedAt)
+            .lock_with_behavior(LockType::Update, LockBehavior::SkipLocked)
+            .one(&tx)
+            .await?
+        {
+            Some(value) => value,
+            None => return Ok(false),
+        };
+
+        let id: Uuid = model.id;
+
+        // `ServiceError` is not `Send`, only its message may live across awaits
+        let result: Result<Vec<TaskImportRowDto>, String> =
+            Self::run(&tx, &model).await.map_err(|err| err.to_string());
+
+        match result {
+            Ok(rows) => {
+                let dry_run: bool = model.dry_run;
+
+                let mut active_model: TaskImportActiveModel = model.into_active_model();
+                active_model.status = Set(ImportStatus::Done);
+                active_model.rows = Set(Some(serde_json::to_value(rows).unwrap_or_default()));
+                // A dry run keeps its file until it is applied or removed
+                if !dry_run {
+                    active_model.content = Set(None);
+                }
+                active_model.updated_at = Set(Local::now().fixed_offset());
+                active_model.update(&tx).await?;
+
+                tx.commit().await?;
+            }
+            Err(err) => {
+                tx.rollback().await?;
+
+                log::warn!("Import {} failed: {}", id, err);
+
+                let tx: DatabaseTransaction = db.begin().await?;
+
+                TaskImportActiveModel {
+                    id: Set(id),
+                    status: Set(ImportStatus::Failed),
+                    content: Set(None),
+                    error: Set(Some(err)),
+                    updated_at: Set(Local::now().fixed_offset()),
+                    ..Default::default()
+                }
+                .update(&tx)
+                .await?;
+
+                tx.commit().await?;
+            }
+        }
+
+        Ok(true)
+    }
+
+    async fn find(
+        tx: &DatabaseTransaction,
+        user_id: Uuid,
+        id: Uuid,
+    ) -> ServiceResult<TaskImportModel> {
+        match TaskImportEntity::find_by_id(id).one(tx).await? {
+            Some(value) => {
+                if value.user_id != user_id {
+                    return Err(ServiceError::Forbidden);
+                }
+
+                Ok(value)
+            }
+            None => Err(ServiceError::NotFound(id)),
+        }
+    }
+
+    /// Imports every row of `model` that no earlier import created. Each row
+    /// gets its own savepoint, so a failing one leaves the others in.
+    async fn run(
+        tx: &DatabaseTransaction,
+        model: &TaskImportModel,
+    ) -> ServiceResult<Vec<TaskImportRowDto>> {
+        let user: UserModel = match UserEntity::find_by_id(model.user_id).one(tx).await? {
+            Some(value) => value,
+            None => return Err(ServiceError::NotFound(model.user_id)),
+        };
+        let time_zone: Tz = common::time_zone(&user.time_zone);
+
+        let context: ImportContext = ImportContext {
+            time_zone,
+            now: Utc::now().with_timezone(&time_zone),
+            statuses: WorkflowStatusEntity::find()
+                .filter(WorkflowStatusColumn::UserId.eq(model.user_id))
+                .order_by_asc(WorkflowStatusColumn::Position)
+                .all(tx)
+                .await?,
+        };
+        let mapping: CsvMappingDto = match model.mapping.clone() {
+            Some(value) => serde_json::from_value::<CsvMappingDto>(value)
+                .map_err(|err| ServiceError::BadRequest(format!("Invalid mapping: {err}")))?,
+            None => CsvMappingDto::default(),
+        };
+
+        let rows: Vec<ImportRow> = import::parse(
+            &model.format,
+            model.content.as_deref().unwrap_or_default(),
+            &mapping,
+            &context,
+        )?;
+
+        // Keys of tasks earlier imports created, trashed ones included
+        let mut imported: HashMap<String, Option<Uuid>> = TaskEntity::find()
+            .filter(TaskColumn::UserId.eq(model.user_id))
+            .filter(TaskColumn::ImportKey.is_in(rows.iter().map(|row| row.key.clone())))
+            .all(tx)
+            .await?
+            .into_iter()
+            .filter_map(|task| task.import_key.map(|key| (key, Some(task.id))))
+            .collect::<HashMap<String, Option<Uuid>>>();
+
+        let mut schemas: Vec<TaskImportRowDto> = Vec::new();
+
+        for ImportRow { row, key, task } in rows {
+            let mut schema: TaskImportRowDto = TaskImportRowDto {
+                row,
+                key: key.clone(),
+                status: TaskImportRowStatus::Failed,
+                task_id: None,
+                task: None,
+                error: None,
+            };
+
+            let task: ImportTask = match task {
+                Ok(value) => value,
+                Err(err) => {
+                    schema.error = Some(err);
+                    schemas.push(schema);
+                    continue;
+                }
+            };
+            schema.task = Some(task.body.clone());
+
+            if let Err(err) = validate(&task) {
+                schema.error = Some(err);
+                schemas.push(schema);
+                continue;
+            }
+
+            if let Some(task_id) = imported.get(&key) {
+                schema.status = TaskImportRowStatus::Skipped;
+                schema.task_id = *task_id;
+                schemas.push(schema);
+                continue;
+            }
+
+            if model.dry_run {
+                imported.insert(key, None);
+                schema.status = TaskImportRowStatus::Valid;
+                schemas.push(schema);
+                continue;
+            }
+
+            let result: Result<Uuid, String> = Self::insert(tx, model.user_id, key.clone(), task)
+                .await
+                .map_err(|err| err.to_string());
+
+            match result {
+                Ok(task_id) => {
+                    imported.insert(key, Some(task_id));
+                    schema.status = TaskImportRowStatus::Created;
+                    schema.task_id = Some(task_id);
+                }
+                Err(err) => schema.error = Some(err),
+            }
+
+            schemas.push(schema);
+        }
+
+        Ok(schemas)
+    }
+
+    async fn insert(
+        tx: &DatabaseTransaction,
+        user_id: Uuid,
+        key: String,
+        task: ImportTask,
+    ) -> ServiceResult<Uuid> {
+        let savepoint: DatabaseTransaction = tx.begin().await?;
+
+        let model: TaskModel = TaskService::insert(&savepoint, user_id, task.body).await?;
+
+        let mut active_model: TaskActiveModel = model.into_active_model();
+        active_model.import_key = Set(Some(key));
+
+        let model: TaskModel = active_model.update(&savepoint).await?;
+
+        for text in task.comments {
+            let comment: TaskCommentModel = TaskCommentActiveModel {
+                user_id: Set(user_id),
+                task_id: Set(model.id),
+                text: Set(text),
+                ..Default::default()
+            }
+            .insert(&savepoint)
+            .await?;
+
+            TaskEventService::record_comment(&savepoint, Some(user_id), None, Some(&comment))
+                .await?;
+        }
+
+        savepoint.commit().await?;
+
+        Ok(model.id)
+    }
+}
+
+/// Checks a row against the limits of the task and comment endpoints.
+fn validate(task: &ImportTask) -> Result<(), String> {
+    task.body
+        .validate()
+        .map_err(|err| err.to_string().trim().to_string())?;
+
+    for text in task.comments.iter() {
+        TaskCommentCreateDto { text: text.clone() }
+            .validate()
+            .map_err(|err| format!("comment {}", err.to_string().trim()))?;
+    }
+
+    Ok(())
+}
@@ -1,14 +1,29 @@
+use std::collections::{HashMap, HashSet};
+
+use chrono_tz::Tz;
 use sea_orm::{
-    ActiveModelTrait, ColumnTrait, Condition, DatabaseConnection, DatabaseTransaction, EntityTrait,
-    IntoActiveModel, ModelTrait, QueryFilter, QuerySelect, Set, TransactionTrait, TryIntoModel,
+    sea_query::{
+        extension::postgres::{PgBinOper, PgExpr},
+        Expr, Func, SimpleExpr,
+    },
+    ActiveModelTrait, ColumnTrait, Condition, ConnectionTrait, DatabaseConnection,
+    DatabaseTransaction, DbErr, EntityTrait, IntoActiveModel, QueryFilter, QueryOrder, QuerySelect,
+    Set, TransactionTrait, TryIntoModel,
 };
 use uuid::Uuid;
 
-use crate::dto::user::{UserCreateDto, UserReadDto, UserUpdateDto};
-use crate::entity::prelude::{UserActiveModel, UserColumn, UserEntity, UserModel};
+use crate::dto::precondition::IfMatchDto;
+use crate::dto::user::{UserCreateDto, UserProfileReadDto, UserReadDto, UserUpdateDto};
+use crate::entity::prelude::{
+    UserActiveModel, UserColumn, UserEntity, UserModel, UserPreferenceColumn, UserPreferenceEntity,
+    UserPreferenceModel,
+};
 use crate::error::service::{ServiceError, ServiceResult};
 
-use super::common;
+use super::{
+    common, user_avatar::UserAvatarService, user_preference::UserPreferenceService,
+    workflow_status::WorkflowStatusService,
+};
 
 pub struct UserService;
 
@@ -19,24 +34,22 @@ impl UserService {
     ) -> ServiceResult<UserReadDto> {
         let tx: DatabaseTransaction = db.begin().await?;
 
-        if Self::check_name_exists(&db, body.name.clone()).await? {
-            return Err(ServiceError::Conflict {
-                field: "name".to_string(),
-                value: body.name.clone(),
-            });
-        }
-
-        if Self::check_email_exists(&db, body.email.clone()).await? {
-            return Err(ServiceError::Conflict {
-                field: "email".to_string(),
-                value: body.email.clone(),
-            });
-        }
-
+        // Taken names and emails fail on their unique indexes, whatever the
+        // case
         body.password = common::hash(body.password)?;
 
+        let name: String = body.name.clone();
+        let email: String = body.email.clone();
+
         let active_model: UserActiveModel = body.into_active_model();
-        let model: UserModel = active_model.save(&tx).await?.try_into_model()?;
+        let model: UserModel = active_model
+            .save(&tx)
+            .await
+            .map_err(|err| Self::conflict(err, Some(&name), Some(&email)))?
+            .try_into_model()?;
+
+        WorkflowStatusService::seed(&tx, model.id).await?;
+        UserPreferenceService::seed(&tx, model.id).await?;
 
         tx.commit().await?;
 
@@ -49,7 +62,25 @@ impl UserService {
         let tx: DatabaseTransaction = db.begin().await?;
 
         match UserEntity::find_by_id(id).one(&tx).await? {
-            Some(value) => Ok(UserReadDto::from(value)),
+            Some(value) => Self::schema(&tx, value).await,
+            None => Err(ServiceError::NotFound(id)),
+        }
+    }
+
+    /// Public profile of any user.
+    pub async fn get_profile_by_id(
+        db: &DatabaseConnection,
+        id: Uuid,
+    ) -> ServiceResult<UserProfileReadDto> {
+        let tx: DatabaseTransaction = db.begin().await?;
+
+        match UserEntity::find_by_id(id).one(&tx).await? {
+            Some(value) => {
+                let mut profiles: Vec<UserProfileReadDto> =
+                    Self::profiles(&tx, vec![value]).await?;
+
+                Ok(profiles.remove(0))
+            }
             None => Err(ServiceError::NotFound(id)),
         }
     }
@@ -61,11 +92,7 @@ impl UserService {
         let tx: DatabaseTransaction = db.begin().await?;
 
         match UserEntity::find()
-            .filter(
-                Condition::any()
-                    .add(UserColumn::Name.eq(login.clone()))
-                    .add(UserColumn::Email.eq(login.clone())),
-            )
+            .filter(Self::login_condition(&login))
             .one(&tx)
             .await?
         {
@@ -76,104 +103,222 @@ impl UserService {
         }
     }
 
+    /// Matches the user whose name or email is `login`, in any case.
+    pub fn login_condition(login: &str) -> Condition {
+        let lower = |column: UserColumn| {
+            Expr::expr(Func::lower(Expr::col((UserEntity, column))))
+                .eq(Func::lower(Expr::val(login)))
+        };
+
+        Condition::any()
+            .add(lower(UserColumn::Name))
+            .add(lower(UserColumn::Email))
+    }
+
+    /// Profiles of the users that let others find them whose name or display
+    /// name contains `name` or a word like it. Names starting with it come
+    /// first, the closest matches next.
     pub async fn search_by_name(
         db: &DatabaseConnection,
         name: String,
         limit: u64,
         offset: u64,
-    ) -> ServiceResult<Vec<UserReadDto>> {
+    ) -> ServiceResult<Vec<UserProfileReadDto>> {
         let tx: DatabaseTransaction = db.begin().await?;
 
+        let escaped: String = name
+            .replace('\\', "\\\\")
+            .replace('%', "\\%")
+            .replace('_', "\\_");
+        let contains: String = format!("%{escaped}%");
+        let prefix: String = format!("{escaped}%");
+
+        let user_name = || Expr::col((UserEntity, UserColumn::Name));
+        let display_name = || Expr::col((UserPreferenceEntity, UserPreferenceColumn::DisplayName));
+        // Users without a display name must not rank as unknown
+        let display_name_or_empty = || {
+            SimpleExpr::from(Func::coalesce([
+                display_name().into(),
+                Expr::val("").into(),
+            ]))
+        };
+
         let models = UserEntity::find()
-            .filter(UserColumn::Name.contains(name))
+            .inner_join(UserPreferenceEntity)
+            .filter(UserPreferenceColumn::Discoverable.eq(true))
+            .filter(
+                Condition::any()
+                    .add(user_name().ilike(&contains))
+                    .add(display_name().ilike(&contains))
+                    .add(Expr::val(&name).binary(PgBinOper::WordSimilarity, user_name()))
+                    .add(Expr::val(&name).binary(PgBinOper::WordSimilarity, display_name())),
+            )
+            .order_by_desc(
+                user_name()
+                    .ilike(&prefix)
+                    .or(Expr::expr(display_name_or_empty()).ilike(&prefix)),
+            )
+            .order_by_asc(
+                Expr::val(&name).binary(
+                    PgBinOper::WordSimilarityDistance,
+                    user_name()
+                        .concatenate(" ")
+                        .concatenate(display_name_or_empty()),
+                ),
+            )
+            .order_by_asc(UserColumn::Name)
+            .order_by_asc(UserColumn::Id)
             .limit(limit)
             .offset(offset)
             .all(&tx)
             .await?;
 
-        let schemas = models
+        Self::profiles(&tx, models).await
+    }
+
+    /// Converts users to their schemas with their profile and whether their
+    /// avatar is custom filled in.
+    pub async fn schemas<C: ConnectionTrait>(
+        conn: &C,
+        models: Vec<UserModel>,
+    ) -> ServiceResult<Vec<UserReadDto>> {
+        let ids: Vec<Uuid> = models.iter().map(|model| model.id).collect::<Vec<Uuid>>();
+        let customized: HashSet<Uuid> = UserAvatarService::customized(conn, &ids).await?;
+        let mut preferences: HashMap<Uuid, UserPreferenceModel> = UserPreferenceEntity::find()
+            .filter(UserPreferenceColumn::UserId.is_in(ids.iter().copied()))
+            .all(conn)
+            .await?
+            .into_iter()
+            .map(|model| (model.user_id, model))
+            .collect::<HashMap<Uuid, UserPreferenceModel>>();
+
+        let schemas: Vec<UserReadDto> = models
             .into_iter()
-            .map(|m| UserReadDto::from(m))
+            .map(|model| {
+                let preference: Option<UserPreferenceModel> = preferences.remove(&model.id);
+
+                UserReadDto {
+                    display_name: preference
+                        .as_ref()
+                        .and_then(|value| value.display_name.clone()),
+                    bio: preference.and_then(|value| value.bio),
+                    custom_avatar: customized.contains(&model.id),
+                    ..UserReadDto::from(model)
+                }
+            })
             .collect::<Vec<UserReadDto>>();
 
         Ok(schemas)
     }
 
-    pub async fn check_name_exists(db: &DatabaseConnection, name: String) -> ServiceResult<bool> {
-        let tx: DatabaseTransaction = db.begin().await?;
+    /// Converts users to what others see of them, uploaded avatars the user
+    /// hides are not reported.
+    pub async fn profiles<C: ConnectionTrait>(
+        conn: &C,
+        models: Vec<UserModel>,
+    ) -> ServiceResult<Vec<UserProfileReadDto>> {
+        let ids: Vec<Uuid> = models.iter().map(|model| model.id).collect::<Vec<Uuid>>();
+        let hidden: HashSet<Uuid> = UserPreferenceService::hidden_avatars(conn, &ids).await?;
 
-        Ok(UserEntity::find()
-            .filter(UserColumn::Name.eq(name))
-            .one(&tx)
+        let profiles: Vec<UserProfileReadDto> = Self::schemas(conn, models)
             .await?
-            .is_some())
+            .into_iter()
+            .map(|schema| UserProfileReadDto {
+                custom_avatar: schema.custom_avatar && !hidden.contains(&schema.id),
+                ..UserProfileReadDto::from(schema)
+            })
+            .collect::<Vec<UserProfileReadDto>>();
+
+        Ok(profiles)
     }
 
-    pub async fn check_email_exists(db: &DatabaseConnection, email: String) -> ServiceResult<bool> {
-        let tx: DatabaseTransaction = db.begin().await?;
+    /// Same as [`Self::schemas`] for a single user.
+    pub async fn schema<C: ConnectionTrait>(
+        conn: &C,
+        model: UserModel,
+    ) -> ServiceResult<UserReadDto> {
+        let mut schemas: Vec<UserReadDto> = Self::schemas(conn, vec![model]).await?;
 
-        Ok(UserEntity::find()
-            .filter(UserColumn::Email.eq(email))
-            .one(&tx)
-            .await?
-            .is_some())
+        Ok(schemas.remove(0))
+    }
+
+    /// Time zone dates and times of the user are shown and read in.
+    pub async fn time_zone<C: ConnectionTrait>(conn: &C, id: Uuid) -> ServiceResult<Tz> {
+        match UserEntity::find_by_id(id).one(conn).await? {
+            Some(value) => Ok(common::time_zone(&value.time_zone)),
+            None => Err(ServiceError::NotFound(id)),
+        }
+    }
+
+    /// Same as [`Self::time_zone`] for several users, keyed by user id.
+    pub async fn time_zones<C: ConnectionTrait>(
+        conn: &C,
+        ids: &[Uuid],
+    ) -> ServiceResult<HashMap<Uuid, Tz>> {
+        let models: Vec<UserModel> = UserEntity::find()
+            .filter(UserColumn::Id.is_in(ids.iter().copied()))
+            .all(conn)
+            .await?;
+
+        Ok(models
+            .into_iter()
+            .map(|model| (model.id, common::time_zone(&model.time_zone)))
+            .collect::<HashMap<Uuid, Tz>>())
     }
 
     pub async fn update(
         db: &DatabaseConnection,
         id: Uuid,
+        if_match: IfMatchDto,
         mut body: UserUpdateDto,
     ) -> ServiceResult<UserReadDto> {
         let tx: DatabaseTransaction = db.begin().await?;
 
-        Self::get_by_id(db, id).await?;
-
-        if let Some(name) = body.name.clone() {
-            if Self::check_name_exists(&db, name.clone()).await? {
-                return Err(ServiceError::Conflict {
-                    field: "name".to_string(),
-                    value: name.clone(),
-                });
-            }
-        }
-
-        if let Some(email) = body.email.clone() {
-            if Self::check_email_exists(&db, email.clone()).await? {
-                return Err(ServiceError::Conflict {
-                    field: "email".to_string(),
-                    value: email.clone(),
-                });
-            }
+        match UserEntity::find_by_id(id).lock_exclusive().one(&tx).await? {
+            Some(value) => if_match.check(value.version)?,
+            None => return Err(ServiceError::NotFound(id)),
         }
 
         if let Some(password) = body.password {
             body.password = Some(common::hash(password)?);
         }
 
+        let name: Option<String> = body.name.clone();
+        let email: Option<String> = body.email.clone();
+
         let mut active_model: UserActiveModel = body.into_active_model();
         active_model.id = Set(id);
 
-        let model: UserModel = active_model.save(&tx).await?.try_into_model()?;
+        let model: UserModel = active_model
+            .save(&tx)
+            .await
+            .map_err(|err| Self::conflict(err, name.as_deref(), email.as_deref()))?
+            .try_into_model()?;
 
-        tx.commit().await?;
+        let schema: UserReadDto = Self::schema(&tx, model).await?;
 
-        let schema: UserReadDto = UserReadDto::from(model);
+        tx.commit().await?;
 
         Ok(schema)
     }
 
-    pub async fn delete(db: &DatabaseConnection, id: Uuid) -> ServiceResult {
-        let tx: DatabaseTransaction = db.begin().await?;
-
-        let model: UserModel = match UserEntity::find_by_id(id).one(&tx).await? {
-            Some(value) => value,
-            None => return Err(ServiceError::NotFound(id)),
-        };
-
-        model.delete(&tx).await?;
-
-        tx.commit().await?;
-
-        Ok(())
+    /// The name and email indexes hold lowercased values, a conflict on them
+    /// reports the value as submitted instead.
+    fn conflict(err: DbErr, name: Option<&str>, email: Option<&str>) -> ServiceError {
+        match ServiceError::from(err) {
+            ServiceError::Conflict { field, value } => {
+                let submitted: Option<&str> = match field.as_str() {
+                    "name" => name,
+                    "email" => email,
+                    _ => None,
+                };
+
+                ServiceError::Conflict {
+                    value: submitted.map(str::to_string).unwrap_or(value),
+                    field,
+                }
+            }
+            err => err,
+        }
     }
 }
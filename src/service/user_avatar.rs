@@ -1,108 +1,411 @@
-use std::io::Cursor;
+use std::collections::HashSet;
 
 use actix_multipart::Multipart;
+use actix_web::rt;
+use bytes::Bytes;
 use chrono::Local;
-use futures::{StreamExt, TryStreamExt};
-use image::{imageops, RgbaImage};
+use futures::{stream, StreamExt, TryStreamExt};
 use sea_orm::{
-    ActiveModelTrait, ColumnTrait, DatabaseConnection, DatabaseTransaction, EntityTrait,
-    IntoActiveModel, ModelTrait, QueryFilter, Set, TransactionTrait,
+    sea_query::Expr, ActiveModelTrait, ColumnTrait, Condition, ConnectionTrait, DatabaseConnection,
+    DatabaseTransaction, EntityTrait, IntoActiveModel, ModelTrait, QueryFilter, QueryOrder,
+    QuerySelect, Set, TransactionTrait, TryIntoModel,
 };
+use sha2::{Digest, Sha256};
 use uuid::Uuid;
 
 use crate::entity::prelude::{
     UserAvatarActiveModel, UserAvatarColumn, UserAvatarEntity, UserAvatarModel,
+    UserAvatarVariantActiveModel, UserAvatarVariantColumn, UserAvatarVariantEntity,
+    UserAvatarVariantModel, UserColumn, UserEntity,
 };
 
 use crate::{
+    client::storage::{ByteStream, StorageClient},
     constants,
-    error::service::{ServiceError, ServiceResult},
+    dto::{precondition::IfNoneMatchTagDto, user::UserAvatarCropQuery},
+    entity::sea_orm_active_enums::AvatarFormat,
+    error::{
+        avatar::AvatarResult,
+        service::{ServiceError, ServiceResult},
+    },
 };
 
+use super::{
+    avatar_image::{self, AvatarImage},
+    storage::StorageService,
+    user_preference::UserPreferenceService,
+};
+
+/// One size of an avatar with the SHA-256 it is tagged by. There is no body
+/// when the client already holds it.
+pub struct UserAvatarContent {
+    pub format: AvatarFormat,
+    pub sha256: String,
+    pub body: Option<ByteStream>,
+}
+
 pub struct UserAvatarService;
 
 impl UserAvatarService {
+    /// Makes the avatar from the `image` field of `body`. Returns its largest
+    /// size in `format`.
     pub async fn set(
         db: &DatabaseConnection,
+        storage: &StorageClient,
         user_id: Uuid,
         mut body: Multipart,
-    ) -> ServiceResult<Vec<u8>> {
+        crop: UserAvatarCropQuery,
+        format: AvatarFormat,
+    ) -> ServiceResult<UserAvatarContent> {
         let mut file: Vec<u8> = Vec::new();
 
         while let Some(mut field) = body.try_next().await? {
-            while let Some(chunk) = field.next().await {
-                let data = match chunk {
-                    Ok(value) => value,
-                    Err(err) => return Err(ServiceError::Multipart(err)),
-                };
-                file.extend_from_slice(&data);
+            if field.name() != Some("image") {
+                continue;
+            }
+
+            while let Some(chunk) = field.try_next().await? {
+                if file.len() + chunk.len() > constants::AVATAR_MAX_SIZE {
+                    return Err(ServiceError::LargeFile);
+                }
+                file.extend_from_slice(&chunk);
             }
+
+            break;
+        }
+
+        if file.is_empty() {
+            return Err(ServiceError::BadRequest(
+                "Missing 'image' field".to_string(),
+            ));
         }
 
-        let image: RgbaImage = image::load_from_memory(&file)?.to_rgba8();
-        let resized_image: RgbaImage = imageops::resize(
-            &image,
-            constants::AVATAR_SIDE_SIZE as u32,
-            constants::AVATAR_SIDE_SIZE as u32,
-            imageops::FilterType::Lanczos3,
-        );
+        let images: Vec<AvatarImage> = Self::process(file, crop).await?;
+
+        Self::save(db, storage, user_id, &images).await?;
+
+        let image: AvatarImage = images
+            .into_iter()
+            .filter(|image| image.format == format)
+            .max_by_key(|image| image.size)
+            .ok_or(ServiceError::NotFound(user_id))?;
 
-        let mut saved_file: Vec<u8> = Vec::new();
-        let mut cursor: Cursor<&mut Vec<u8>> = Cursor::new(&mut saved_file);
-        resized_image.write_to(&mut cursor, image::ImageFormat::Png)?;
+        Ok(UserAvatarContent {
+            format: image.format,
+            sha256: digest(&image.content),
+            body: Some(stream::once(async move { Ok(Bytes::from(image.content)) }).boxed()),
+        })
+    }
 
+    /// Opens the avatar in `format` and the smallest made size not under
+    /// `size`, the largest without one. Users without an avatar, or hiding it
+    /// from the viewer, get one generated from their id.
+    pub async fn get_by_user_id(
+        db: &DatabaseConnection,
+        storage: &StorageClient,
+        user_id: Uuid,
+        viewer_id: Uuid,
+        size: Option<u32>,
+        format: AvatarFormat,
+        if_none_match: &IfNoneMatchTagDto,
+    ) -> ServiceResult<UserAvatarContent> {
         let tx: DatabaseTransaction = db.begin().await?;
 
-        let mut active_model: UserAvatarActiveModel = match UserAvatarEntity::find()
+        let avatar: Option<UserAvatarModel> = UserAvatarEntity::find()
             .filter(UserAvatarColumn::UserId.eq(user_id))
             .one(&tx)
-            .await?
-        {
-            Some(value) => value.into_active_model(),
-            None => UserAvatarActiveModel {
-                user_id: Set(user_id),
-                ..Default::default()
-            },
+            .await?;
+
+        let hidden: bool =
+            viewer_id != user_id && !UserPreferenceService::find(&tx, user_id).await?.show_avatar;
+
+        let avatar: UserAvatarModel = match avatar {
+            Some(value) if !hidden => value,
+            _ => {
+                if UserEntity::find_by_id(user_id).one(&tx).await?.is_none() {
+                    return Err(ServiceError::NotFound(user_id));
+                }
+
+                tx.commit().await?;
+
+                return Self::generate(user_id, size, format, if_none_match).await;
+            }
         };
 
-        active_model.file = Set(saved_file.clone());
-        active_model.updated_at = Set(Local::now().fixed_offset());
-        active_model.save(&tx).await?;
+        let variants: Vec<UserAvatarVariantModel> = UserAvatarVariantEntity::find()
+            .filter(UserAvatarVariantColumn::AvatarId.eq(avatar.id))
+            .filter(UserAvatarVariantColumn::Format.eq(format))
+            .order_by_asc(UserAvatarVariantColumn::Size)
+            .all(&tx)
+            .await?;
 
         tx.commit().await?;
 
-        Ok(saved_file)
+        let size: i32 = size.unwrap_or(u32::MAX).min(i32::MAX as u32) as i32;
+        let variant: UserAvatarVariantModel =
+            match variants.iter().position(|variant| variant.size >= size) {
+                Some(index) => variants[index].clone(),
+                None => variants
+                    .last()
+                    .cloned()
+                    .ok_or(ServiceError::NotFound(user_id))?,
+            };
+
+        if if_none_match.matches(&variant.sha256) {
+            return Ok(UserAvatarContent {
+                format: variant.format,
+                sha256: variant.sha256,
+                body: None,
+            });
+        }
+
+        Ok(UserAvatarContent {
+            body: Some(storage.get(&variant.storage_key, None).await?),
+            format: variant.format,
+            sha256: variant.sha256,
+        })
+    }
+
+    /// Ids of the users among `user_ids` that uploaded an avatar.
+    pub async fn customized<C: ConnectionTrait>(
+        conn: &C,
+        user_ids: &[Uuid],
+    ) -> ServiceResult<HashSet<Uuid>> {
+        if user_ids.is_empty() {
+            return Ok(HashSet::new());
+        }
+
+        Ok(UserAvatarEntity::find()
+            .select_only()
+            .column(UserAvatarColumn::UserId)
+            .filter(UserAvatarColumn::UserId.is_in(user_ids.iter().copied()))
+            .into_tuple::<Uuid>()
+            .all(conn)
+            .await?
+            .into_iter()
+            .collect::<HashSet<Uuid>>())
     }
 
-    pub async fn get_by_user_id(db: &DatabaseConnection, user_id: Uuid) -> ServiceResult<Vec<u8>> {
+    /// Removes the record, the files follow with the next storage cleanup
+    /// unless other avatars share them.
+    pub async fn delete(db: &DatabaseConnection, user_id: Uuid) -> ServiceResult {
         let tx: DatabaseTransaction = db.begin().await?;
 
+        Self::find(&tx, user_id).await?.delete(&tx).await?;
+        Self::touch(&tx, user_id).await?;
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    /// Makes the sizes of avatars stored as a single image before, a batch
+    /// at a time. Returns how many were converted.
+    pub async fn convert_legacy(
+        db: &DatabaseConnection,
+        storage: &StorageClient,
+    ) -> ServiceResult<u64> {
+        let mut count: u64 = 0;
+
+        loop {
+            let tx: DatabaseTransaction = db.begin().await?;
+
+            let models: Vec<UserAvatarModel> = UserAvatarEntity::find()
+                .filter(
+                    Condition::any()
+                        .add(UserAvatarColumn::File.is_not_null())
+                        .add(UserAvatarColumn::StorageKey.is_not_null()),
+                )
+                .order_by_asc(UserAvatarColumn::Id)
+                .limit(constants::STORAGE_CLEANUP_BATCH_SIZE)
+                .all(&tx)
+                .await?;
+
+            tx.commit().await?;
+
+            if models.is_empty() {
+                return Ok(count);
+            }
+
+            for model in models {
+                let file: Vec<u8> = match (&model.file, &model.storage_key) {
+                    (Some(file), _) => file.clone(),
+                    (None, Some(storage_key)) => storage.read(storage_key).await?,
+                    (None, None) => continue,
+                };
+
+                let images: Vec<AvatarImage> =
+                    Self::process(file, UserAvatarCropQuery::default()).await?;
+
+                Self::save(db, storage, model.user_id, &images).await?;
+
+                count += 1;
+            }
+        }
+    }
+
+    async fn find(tx: &DatabaseTransaction, user_id: Uuid) -> ServiceResult<UserAvatarModel> {
         match UserAvatarEntity::find()
             .filter(UserAvatarColumn::UserId.eq(user_id))
-            .one(&tx)
+            .one(tx)
             .await?
         {
-            Some(value) => Ok(value.file),
+            Some(value) => Ok(value),
             None => Err(ServiceError::NotFound(user_id)),
         }
     }
 
-    pub async fn delete(db: &DatabaseConnection, user_id: Uuid) -> ServiceResult {
-        let tx: DatabaseTransaction = db.begin().await?;
+    /// Renders the default avatar of the user in the made size closest to
+    /// `size` as [`Self::get_by_user_id`] picks it.
+    async fn generate(
+        user_id: Uuid,
+        size: Option<u32>,
+        format: AvatarFormat,
+        if_none_match: &IfNoneMatchTagDto,
+    ) -> ServiceResult<UserAvatarContent> {
+        let size: u32 = match size {
+            Some(size) => constants::AVATAR_SIZES
+                .into_iter()
+                .find(|value| *value >= size)
+                .unwrap_or(constants::AVATAR_SIZES[constants::AVATAR_SIZES.len() - 1]),
+            None => constants::AVATAR_SIZES[constants::AVATAR_SIZES.len() - 1],
+        };
 
-        let model: UserAvatarModel = match UserAvatarEntity::find()
-            .filter(UserAvatarColumn::UserId.eq(user_id))
-            .one(&tx)
-            .await?
-        {
-            Some(value) => value,
-            None => return Err(ServiceError::NotFound(user_id)),
+        let result: AvatarResult<AvatarImage> = rt::task::spawn_blocking(move || {
+            avatar_image::generate(user_id.as_bytes(), size, format)
+        })
+        .await
+        .map_err(|err| ServiceError::Unknow(format!("Image processing failed: {err}")))?;
+        let image: AvatarImage = result?;
+
+        let sha256: String = digest(&image.content);
+        let body: Option<ByteStream> = match if_none_match.matches(&sha256) {
+            true => None,
+            false => Some(stream::once(async move { Ok(Bytes::from(image.content)) }).boxed()),
         };
 
-        model.delete(&tx).await?;
+        Ok(UserAvatarContent {
+            format: image.format,
+            sha256,
+            body,
+        })
+    }
 
-        tx.commit().await?;
+    /// Updates the user so its version, and with it the `ETag` of the
+    /// profile telling whether the avatar is custom, changes.
+    async fn touch(tx: &DatabaseTransaction, user_id: Uuid) -> ServiceResult {
+        UserEntity::update_many()
+            .col_expr(
+                UserColumn::UpdatedAt,
+                Expr::value(Local::now().fixed_offset()),
+            )
+            .filter(UserColumn::Id.eq(user_id))
+            .exec(tx)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Runs the image work on a blocking thread.
+    async fn process(file: Vec<u8>, crop: UserAvatarCropQuery) -> ServiceResult<Vec<AvatarImage>> {
+        let result: AvatarResult<Vec<AvatarImage>> =
+            rt::task::spawn_blocking(move || avatar_image::process(&file, &crop))
+                .await
+                .map_err(|err| ServiceError::Unknow(format!("Image processing failed: {err}")))?;
+
+        Ok(result?)
+    }
+
+    /// Writes the files of `images` under the keys of their contents and
+    /// makes them the avatar of the user, replacing the ones it had.
+    async fn save(
+        db: &DatabaseConnection,
+        storage: &StorageClient,
+        user_id: Uuid,
+        images: &[AvatarImage],
+    ) -> ServiceResult {
+        let keys: Vec<String> = images
+            .iter()
+            .map(|image| key(&digest(&image.content), &image.format))
+            .collect::<Vec<String>>();
+
+        let result: ServiceResult = async {
+            let tx: DatabaseTransaction = db.begin().await?;
+
+            let mut active_model: UserAvatarActiveModel = match UserAvatarEntity::find()
+                .filter(UserAvatarColumn::UserId.eq(user_id))
+                .one(&tx)
+                .await?
+            {
+                Some(value) => value.into_active_model(),
+                None => UserAvatarActiveModel {
+                    user_id: Set(user_id),
+                    ..Default::default()
+                },
+            };
+
+            active_model.file = Set(None);
+            active_model.storage_key = Set(None);
+            active_model.sha256 = Set(None);
+            active_model.updated_at = Set(Local::now().fixed_offset());
+            let avatar: UserAvatarModel = active_model.save(&tx).await?.try_into_model()?;
+
+            UserAvatarVariantEntity::delete_many()
+                .filter(UserAvatarVariantColumn::AvatarId.eq(avatar.id))
+                .exec(&tx)
+                .await?;
+
+            for (image, storage_key) in images.iter().zip(keys.iter()) {
+                StorageService::claim(&tx, storage_key).await?;
+                storage
+                    .put_bytes(storage_key, image.content.clone())
+                    .await?;
+
+                UserAvatarVariantActiveModel {
+                    avatar_id: Set(avatar.id),
+                    size: Set(image.size as i32),
+                    format: Set(image.format.clone()),
+                    storage_key: Set(storage_key.clone()),
+                    sha256: Set(digest(&image.content)),
+                    ..Default::default()
+                }
+                .insert(&tx)
+                .await?;
+            }
+
+            Self::touch(&tx, user_id).await?;
+
+            tx.commit().await?;
+
+            Ok(())
+        }
+        .await;
+
+        // Files may be written already, the cleanup removes the unused ones
+        if let Err(err) = result {
+            for storage_key in keys {
+                if let Err(err) = StorageService::release(db, &storage_key).await {
+                    log::warn!("Releasing unrecorded file {} failed: {}", storage_key, err);
+                }
+            }
+
+            return Err(err);
+        }
 
         Ok(())
     }
 }
+
+fn digest(content: &[u8]) -> String {
+    Sha256::digest(content)
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect::<String>()
+}
+
+fn key(sha256: &str, format: &AvatarFormat) -> String {
+    match format {
+        AvatarFormat::Png => format!("avatar/{sha256}.png"),
+        AvatarFormat::Webp => format!("avatar/{sha256}.webp"),
+    }
+}
@@ -0,0 +1,127 @@
+use std::collections::HashSet;
+
+use chrono::Local;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseConnection, DatabaseTransaction,
+    EntityTrait, IntoActiveModel, QueryFilter, QuerySelect, Set, TransactionTrait, TryIntoModel,
+};
+use uuid::Uuid;
+
+use crate::dto::precondition::IfMatchDto;
+use crate::dto::user_preference::{UserPreferenceReadDto, UserPreferenceUpdateDto};
+use crate::entity::prelude::{
+    UserActiveModel, UserEntity, UserModel, UserPreferenceActiveModel, UserPreferenceColumn,
+    UserPreferenceEntity, UserPreferenceModel,
+};
+use crate::error::service::{ServiceError, ServiceResult};
+
+pub struct UserPreferenceService;
+
+impl UserPreferenceService {
+    /// Creates the default preferences of a new user.
+    pub async fn seed(tx: &DatabaseTransaction, user_id: Uuid) -> ServiceResult {
+        UserPreferenceActiveModel {
+            user_id: Set(user_id),
+            ..Default::default()
+        }
+        .insert(tx)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn get(
+        db: &DatabaseConnection,
+        user_id: Uuid,
+    ) -> ServiceResult<UserPreferenceReadDto> {
+        let tx: DatabaseTransaction = db.begin().await?;
+
+        let user: UserModel = match UserEntity::find_by_id(user_id).one(&tx).await? {
+            Some(value) => value,
+            None => return Err(ServiceError::NotFound(user_id)),
+        };
+
+        Ok(UserPreferenceReadDto::new(
+            Self::find(&tx, user_id).await?,
+            user.time_zone,
+        ))
+    }
+
+    /// Preferences of the user, read by other services to fill in what a
+    /// request leaves out.
+    pub async fn find<C: ConnectionTrait>(
+        conn: &C,
+        user_id: Uuid,
+    ) -> ServiceResult<UserPreferenceModel> {
+        match UserPreferenceEntity::find_by_id(user_id).one(conn).await? {
+            Some(value) => Ok(value),
+            None => Err(ServiceError::NotFound(user_id)),
+        }
+    }
+
+    /// Ids of the users among `user_ids` that show others a generated avatar
+    /// instead of their own.
+    pub async fn hidden_avatars<C: ConnectionTrait>(
+        conn: &C,
+        user_ids: &[Uuid],
+    ) -> ServiceResult<HashSet<Uuid>> {
+        if user_ids.is_empty() {
+            return Ok(HashSet::new());
+        }
+
+        Ok(UserPreferenceEntity::find()
+            .select_only()
+            .column(UserPreferenceColumn::UserId)
+            .filter(UserPreferenceColumn::UserId.is_in(user_ids.iter().copied()))
+            .filter(UserPreferenceColumn::ShowAvatar.eq(false))
+            .into_tuple::<Uuid>()
+            .all(conn)
+            .await?
+            .into_iter()
+            .collect::<HashSet<Uuid>>())
+    }
+
+    pub async fn update(
+        db: &DatabaseConnection,
+        user_id: Uuid,
+        if_match: IfMatchDto,
+        mut body: UserPreferenceUpdateDto,
+    ) -> ServiceResult<UserPreferenceReadDto> {
+        let tx: DatabaseTransaction = db.begin().await?;
+
+        match UserPreferenceEntity::find_by_id(user_id)
+            .lock_exclusive()
+            .one(&tx)
+            .await?
+        {
+            Some(value) => if_match.check(value.version)?,
+            None => return Err(ServiceError::NotFound(user_id)),
+        }
+
+        // Kept on the user, where the services showing dates read it
+        let mut user: UserActiveModel = match UserEntity::find_by_id(user_id).one(&tx).await? {
+            Some(value) => value.into_active_model(),
+            None => return Err(ServiceError::NotFound(user_id)),
+        };
+        if let Some(time_zone) = body.time_zone.take() {
+            user.time_zone = Set(time_zone);
+        }
+        // The profile shows these, its version must change
+        if body.display_name.is_some() || body.bio.is_some() || body.show_avatar.is_some() {
+            user.updated_at = Set(Local::now().fixed_offset());
+        }
+        let user: UserModel = match user.is_changed() {
+            true => user.update(&tx).await?,
+            false => user.try_into_model()?,
+        };
+
+        let mut active_model: UserPreferenceActiveModel = body.into_active_model();
+        active_model.user_id = Set(user_id);
+
+        let model: UserPreferenceModel = active_model.update(&tx).await?;
+
+        tx.commit().await?;
+
+        Ok(UserPreferenceReadDto::new(model, user.time_zone))
+    }
+}
@@ -0,0 +1,334 @@
+use std::collections::HashMap;
+
+use chrono::Local;
+use sea_orm::{
+    sea_query::Expr, ActiveEnum, ActiveModelTrait, ColumnTrait, ConnectionTrait,
+    DatabaseConnection, DatabaseTransaction, EntityTrait, IntoActiveModel, ModelTrait,
+    PaginatorTrait, QueryFilter, QueryOrder, QuerySelect, Set, TransactionTrait,
+};
+use uuid::Uuid;
+
+use crate::{
+    dto::workflow_status::{
+        WorkflowStatusCreateDto, WorkflowStatusReadDto, WorkflowStatusUpdateDto,
+    },
+    entity::{
+        prelude::{
+            TaskColumn, TaskEntity, TaskModel, WorkflowStatusActiveModel, WorkflowStatusColumn,
+            WorkflowStatusEntity, WorkflowStatusModel,
+        },
+        sea_orm_active_enums::TaskStatus,
+    },
+    error::service::{ServiceError, ServiceResult},
+};
+
+use super::task_event::TaskEventService;
+
+/// Statuses every user starts with, one per category.
+const DEFAULT_STATUSES: [(&str, TaskStatus); 3] = [
+    ("To do", TaskStatus::ToDo),
+    ("In progress", TaskStatus::InProgress),
+    ("Done", TaskStatus::Done),
+];
+
+pub struct WorkflowStatusService;
+
+impl WorkflowStatusService {
+    /// Creates the default statuses of a new user.
+    pub async fn seed(tx: &DatabaseTransaction, user_id: Uuid) -> ServiceResult {
+        for (position, (name, category)) in DEFAULT_STATUSES.into_iter().enumerate() {
+            WorkflowStatusActiveModel {
+                user_id: Set(user_id),
+                name: Set(name.to_string()),
+                category: Set(category),
+                position: Set(position as i32),
+                ..Default::default()
+            }
+            .insert(tx)
+            .await?;
+        }
+
+        Ok(())
+    }
+
+    pub async fn list<C: ConnectionTrait>(
+        conn: &C,
+        user_id: Uuid,
+    ) -> ServiceResult<Vec<WorkflowStatusReadDto>> {
+        let models: Vec<WorkflowStatusModel> = WorkflowStatusEntity::find()
+            .filter(WorkflowStatusColumn::UserId.eq(user_id))
+            .order_by_asc(WorkflowStatusColumn::Position)
+            .all(conn)
+            .await?;
+
+        let schemas: Vec<WorkflowStatusReadDto> = models
+            .into_iter()
+            .map(WorkflowStatusReadDto::from)
+            .collect::<Vec<WorkflowStatusReadDto>>();
+
+        Ok(schemas)
+    }
+
+    /// Adds a status at the end of the user's workflow.
+    pub async fn create(
+        db: &DatabaseConnection,
+        user_id: Uuid,
+        body: WorkflowStatusCreateDto,
+    ) -> ServiceResult<WorkflowStatusReadDto> {
+        let tx: DatabaseTransaction = db.begin().await?;
+
+        let models: Vec<WorkflowStatusModel> = Self::lock_all(&tx, user_id).await?;
+
+        if models.iter().any(|model| model.name == body.name) {
+            return Err(ServiceError::Conflict {
+                field: "name".to_string(),
+                value: body.name,
+            });
+        }
+
+        let mut active_model: WorkflowStatusActiveModel = body.into_active_model();
+        active_model.user_id = Set(user_id);
+        active_model.position = Set(models.len() as i32);
+
+        let model: WorkflowStatusModel = active_model.insert(&tx).await?;
+
+        tx.commit().await?;
+
+        Ok(WorkflowStatusReadDto::from(model))
+    }
+
+    pub async fn update(
+        db: &DatabaseConnection,
+        user_id: Uuid,
+        id: Uuid,
+        body: WorkflowStatusUpdateDto,
+    ) -> ServiceResult<WorkflowStatusReadDto> {
+        let tx: DatabaseTransaction = db.begin().await?;
+
+        let mut models: Vec<WorkflowStatusModel> = Self::lock_all(&tx, user_id).await?;
+        let index: usize = Self::find_index(&tx, &models, id).await?;
+
+        let mut active_model: WorkflowStatusActiveModel = models[index].clone().into_active_model();
+
+        if let Some(name) = body.name {
+            if models
+                .iter()
+                .any(|model| model.id != id && model.name == name)
+            {
+                return Err(ServiceError::Conflict {
+                    field: "name".to_string(),
+                    value: name,
+                });
+            }
+
+            active_model.name = Set(name);
+        }
+
+        if let Some(category) = body.category {
+            if category != models[index].category {
+                Self::check_not_last(&models, index)?;
+
+                // Tasks keep the category of their status in sync
+                Self::move_tasks(&tx, user_id, id, id, &category).await?;
+
+                active_model.category = Set(category);
+            }
+        }
+
+        active_model.updated_at = Set(Local::now().fixed_offset());
+        let model: WorkflowStatusModel = active_model.update(&tx).await?;
+
+        if let Some(position) = body.position {
+            let position: usize = (position as usize).min(models.len() - 1);
+
+            let moved: WorkflowStatusModel = models.remove(index);
+            models.insert(position, moved);
+
+            Self::renumber(&tx, models).await?;
+        }
+
+        let model: WorkflowStatusModel =
+            match WorkflowStatusEntity::find_by_id(model.id).one(&tx).await? {
+                Some(value) => value,
+                None => return Err(ServiceError::NotFound(id)),
+            };
+
+        tx.commit().await?;
+
+        Ok(WorkflowStatusReadDto::from(model))
+    }
+
+    /// Deletes a status, its tasks move to `replacement_id` which is required
+    /// when there are any.
+    pub async fn delete(
+        db: &DatabaseConnection,
+        user_id: Uuid,
+        id: Uuid,
+        replacement_id: Option<Uuid>,
+    ) -> ServiceResult {
+        let tx: DatabaseTransaction = db.begin().await?;
+
+        let mut models: Vec<WorkflowStatusModel> = Self::lock_all(&tx, user_id).await?;
+        let index: usize = Self::find_index(&tx, &models, id).await?;
+
+        Self::check_not_last(&models, index)?;
+
+        let tasks: u64 = TaskEntity::find()
+            .filter(TaskColumn::StatusId.eq(id))
+            .count(&tx)
+            .await?;
+
+        if tasks > 0 {
+            let replacement: &WorkflowStatusModel = match replacement_id {
+                Some(value) if value != id => {
+                    &models[Self::find_index(&tx, &models, value).await?]
+                }
+                _ => {
+                    return Err(ServiceError::BadRequest(
+                        "Status has tasks, a replacement status is required".to_string(),
+                    ))
+                }
+            };
+
+            Self::move_tasks(&tx, user_id, id, replacement.id, &replacement.category).await?;
+        }
+
+        models.remove(index).delete(&tx).await?;
+
+        Self::renumber(&tx, models).await?;
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    /// Finds the status a task should get: `status_id` when given, otherwise
+    /// the first status of `category`.
+    pub async fn resolve(
+        tx: &DatabaseTransaction,
+        user_id: Uuid,
+        status_id: Option<Uuid>,
+        category: Option<TaskStatus>,
+    ) -> ServiceResult<Option<WorkflowStatusModel>> {
+        match (status_id, category) {
+            (Some(id), _) => match WorkflowStatusEntity::find_by_id(id).one(tx).await? {
+                Some(value) => {
+                    if value.user_id != user_id {
+                        return Err(ServiceError::Forbidden);
+                    }
+
+                    Ok(Some(value))
+                }
+                None => Err(ServiceError::NotFound(id)),
+            },
+            (None, Some(category)) => {
+                match WorkflowStatusEntity::find()
+                    .filter(WorkflowStatusColumn::UserId.eq(user_id))
+                    .filter(WorkflowStatusColumn::Category.eq(category))
+                    .order_by_asc(WorkflowStatusColumn::Position)
+                    .one(tx)
+                    .await?
+                {
+                    Some(value) => Ok(Some(value)),
+                    None => Err(ServiceError::Unknow(
+                        "User has no status in the category".to_string(),
+                    )),
+                }
+            }
+            (None, None) => Ok(None),
+        }
+    }
+
+    /// Loads and locks all statuses of the user in workflow order.
+    async fn lock_all(
+        tx: &DatabaseTransaction,
+        user_id: Uuid,
+    ) -> ServiceResult<Vec<WorkflowStatusModel>> {
+        Ok(WorkflowStatusEntity::find()
+            .filter(WorkflowStatusColumn::UserId.eq(user_id))
+            .order_by_asc(WorkflowStatusColumn::Position)
+            .lock_exclusive()
+            .all(tx)
+            .await?)
+    }
+
+    async fn find_index(
+        tx: &DatabaseTransaction,
+        models: &[WorkflowStatusModel],
+        id: Uuid,
+    ) -> ServiceResult<usize> {
+        match models.iter().position(|model| model.id == id) {
+            Some(value) => Ok(value),
+            None => match WorkflowStatusEntity::find_by_id(id).one(tx).await? {
+                Some(_) => Err(ServiceError::Forbidden),
+                None => Err(ServiceError::NotFound(id)),
+            },
+        }
+    }
+
+    /// Points the tasks of status `id` at `status_id` and `category`, each
+    /// change lands in the task history like any other edit.
+    async fn move_tasks(
+        tx: &DatabaseTransaction,
+        user_id: Uuid,
+        id: Uuid,
+        status_id: Uuid,
+        category: &TaskStatus,
+    ) -> ServiceResult {
+        let before: Vec<TaskModel> = TaskEntity::find()
+            .filter(TaskColumn::StatusId.eq(id))
+            .order_by_asc(TaskColumn::Id)
+            .lock_exclusive()
+            .all(tx)
+            .await?;
+
+        let after: HashMap<Uuid, TaskModel> = TaskEntity::update_many()
+            .col_expr(TaskColumn::StatusId, Expr::value(status_id))
+            .col_expr(TaskColumn::Status, category.as_enum())
+            .filter(TaskColumn::StatusId.eq(id))
+            .exec_with_returning(tx)
+            .await?
+            .into_iter()
+            .map(|model| (model.id, model))
+            .collect::<HashMap<Uuid, TaskModel>>();
+
+        for model in &before {
+            TaskEventService::record_task(tx, Some(user_id), Some(model), after.get(&model.id))
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Every category keeps at least one status so it can be resolved.
+    fn check_not_last(models: &[WorkflowStatusModel], index: usize) -> ServiceResult {
+        let category: &TaskStatus = &models[index].category;
+
+        if models
+            .iter()
+            .filter(|model| &model.category == category)
+            .count()
+            == 1
+        {
+            return Err(ServiceError::BadRequest(
+                "Last status of its category can't be removed".to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+
+    async fn renumber(tx: &DatabaseTransaction, models: Vec<WorkflowStatusModel>) -> ServiceResult {
+        for (position, model) in models.into_iter().enumerate() {
+            if model.position == position as i32 {
+                continue;
+            }
+
+            let mut active_model: WorkflowStatusActiveModel = model.into_active_model();
+            active_model.position = Set(position as i32);
+            active_model.update(tx).await?;
+        }
+
+        Ok(())
+    }
+}